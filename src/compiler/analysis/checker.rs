@@ -0,0 +1,1059 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use either::{Left, Right};
+use crate::compiler::evaluator::Evaluator;
+use crate::compiler::parser::{Declaration, NodeArithmeticExpr, NodeArithmeticOperation, NodeBaseExpr, NodeField, NodeFieldAssignment, NodeIndex, NodeIndexAssignment, NodeLen, NodeProgram, NodeStmt, NodeStruct, NodeStructLiteral, NodeVariableAssignment, NodeEnum, NodeVariantLiteral, NodeMatch, NodeFor, NodeBuiltin, NodeExtern, NodeCall, ParserLogger, ResultType};
+use crate::compiler::span::Span;
+use crate::compiler::tokenizer::{Operator, Token};
+use crate::utility::LinkMode;
+
+struct Binding {
+    m_name: String,
+    m_mutable: bool,
+    m_constant: bool,
+    /// Whether the variable is the counter of a `for` loop.
+    m_counter: bool,
+    /// Value of a constant, when it could be evaluated.
+    m_value: Option<i64>,
+    /// Annotated type, or else the type of the first value, `None` when it depends on an undeclared variable.
+    /// The type of an array is the type of its elements.
+    m_type: Option<ResultType>,
+    /// Number of elements, when the variable is an array.
+    m_length: Option<usize>,
+    /// Name of its struct, when the variable is a struct.
+    m_struct: Option<String>,
+    /// Name of its enum, when the variable is an enum.
+    m_enum: Option<String>
+}
+
+/// Reports the errors of a whole program, possibly made of several files, that the parser can't see: undeclared
+/// variables, assignments, types, declarations and calls. Unlike the lints, these checks always run. The logger of
+/// each file is indexed by its file id, so every error is reported against the file it comes from.
+pub struct Checker<'a> {
+    m_loggers: &'a [Arc<Mutex<ParserLogger>>],
+    m_scopes: Vec<Vec<Binding>>,
+    /// Fields of the declared structs, with the name of their type.
+    m_structs: HashMap<String, Vec<(String, String)>>,
+    /// Variants of the declared enums, with the names of the types of their values.
+    m_enums: HashMap<String, Vec<(String, Vec<String>)>>,
+    /// Types of the parameters of the declared C functions, and whether they take more arguments.
+    m_functions: HashMap<String, (Vec<String>, bool)>,
+    m_link_mode: LinkMode
+}
+
+impl<'a> Checker<'a> {
+    pub fn new(m_loggers: &'a [Arc<Mutex<ParserLogger>>]) -> Self {
+        Checker { m_loggers, m_scopes: Vec::new(), m_structs: HashMap::new(), m_enums: HashMap::new(), m_functions: HashMap::new(), m_link_mode: LinkMode::default() }
+    }
+
+    /// C functions can only be declared when the program is linked with the C library.
+    pub fn set_link_mode(&mut self, link_mode: LinkMode) {
+        self.m_link_mode = link_mode;
+    }
+
+    pub fn check(&mut self, prog: &NodeProgram) {
+        self.check_block(&prog.stmts);
+    }
+
+    fn check_block(&mut self, stmts: &[NodeStmt]) {
+        self.m_scopes.push(Vec::new());
+        for stmt in stmts {
+            self.check_stmt(stmt);
+        }
+        self.m_scopes.pop();
+    }
+
+    fn check_stmt(&mut self, stmt: &NodeStmt) {
+        match stmt {
+            NodeStmt::Exit(exit) => self.check_expr(&exit.expr),
+            NodeStmt::ID(var) => self.check_assignment(var),
+            NodeStmt::Scope(scope) => self.check_block(&scope.stmts),
+            NodeStmt::Import(_) => {}
+            NodeStmt::Index(assignment) => self.check_index_assignment(assignment),
+            NodeStmt::Struct(declaration) => self.check_struct(declaration),
+            NodeStmt::Field(assignment) => self.check_field_assignment(assignment),
+            NodeStmt::Enum(declaration) => self.check_enum(declaration),
+            NodeStmt::For(node) => self.check_for(node),
+            NodeStmt::Free(node) => {
+                self.check_expr(&node.pointer);
+                self.check_value_type("int", &node.pointer);
+            }
+            NodeStmt::Extern(declaration) => self.check_extern(declaration),
+            NodeStmt::Call(call) => self.check_call(call),
+        }
+    }
+
+    /// The bounds of a range are integers, and its loop variable is an immutable integer that only exists in the body.
+    fn check_for(&mut self, node: &NodeFor) {
+        for bound in [&node.start, &node.end] {
+            self.check_expr(bound);
+            self.check_value_type("int", bound);
+        }
+        self.m_scopes.push(Vec::new());
+        if let Token::ID { name, .. } = &node.variable {
+            let binding = Binding {
+                m_name: name.clone(),
+                m_mutable: false,
+                m_constant: false,
+                m_counter: true,
+                m_value: None,
+                m_type: Some(ResultType::Numeric),
+                m_length: None,
+                m_struct: None,
+                m_enum: None
+            };
+            self.m_scopes.last_mut().unwrap().push(binding);
+        }
+        self.check_block(&node.body.stmts);
+        self.m_scopes.pop();
+    }
+
+    fn check_assignment(&mut self, var: &NodeVariableAssignment) {
+        if let (None, Token::ID { name, span }) = (var.declaration, &var.variable) {
+            if self.find_binding(name).is_none() {
+                // Only reported as undeclared, the variable being declared before its reads in `x += 1`
+                let result_type = var.annotation.map(|annotation| annotation.result_type).or(self.get_type(&var.value));
+                self.check_reassignment(name, *span, (result_type, Self::get_length(&var.value)), Self::get_struct(&var.value), Self::get_enum(&var.value));
+                self.check_expr(&var.value);
+                return;
+            }
+        }
+        self.check_expr(&var.value);
+        let Token::ID { name, span } = &var.variable else { return };
+        // An array, struct or enum variable given as the value is already reported, its type isn't checked again
+        let aggregate = self.get_aggregate(&var.value).map(|binding| {
+            (binding.m_type, binding.m_length, binding.m_struct.clone(), binding.m_enum.clone())
+        });
+        let (value_type, value_length, value_struct, value_enum) = aggregate.clone().unwrap_or_else(|| {
+            (self.get_type(&var.value), Self::get_length(&var.value), Self::get_struct(&var.value), Self::get_enum(&var.value))
+        });
+        if let Some(annotation) = var.annotation.filter(|_| aggregate.is_none()) {
+            match value_struct.as_ref().or(value_enum.as_ref()) {
+                Some(found) => self.error("E0046", format!("Mismatched types: expected `{annotation}`, found `{found}`."), var.value.get_span()),
+                None => self.check_type((annotation.result_type, annotation.length), (value_type, value_length), var.value.get_span()),
+            }
+        }
+        let result_type = var.annotation.map(|annotation| annotation.result_type).or(value_type);
+        let length = var.annotation.map_or(value_length, |annotation| annotation.length);
+        let Some(declaration) = var.declaration else {
+            // The type of a variable is fixed by its declaration
+            if let Some(binding) = self.find_binding(name).filter(|_| aggregate.is_none()) {
+                let span = var.annotation.map_or(var.value.get_span(), |annotation| annotation.span);
+                match (binding.m_struct.as_ref().or(binding.m_enum.as_ref()), binding.m_type, value_struct.as_ref().or(value_enum.as_ref())) {
+                    (Some(expected), _, _) => self.check_value_type(expected, &var.value),
+                    (None, Some(expected), Some(found)) => {
+                        let message = format!("Mismatched types: expected `{}`, found `{found}`.", Self::format_type((expected, binding.m_length)));
+                        self.error("E0046", message, span);
+                    }
+                    (None, Some(expected), None) => self.check_type((expected, binding.m_length), (result_type, length), span),
+                    (None, None, _) => {}
+                }
+            }
+            self.check_reassignment(name, *span, (result_type, length), value_struct, value_enum);
+            return;
+        };
+        let value = match declaration {
+            Declaration::Const { span: keyword_span } if length.is_some() => {
+                self.error("E0049", format!("Arrays can't be constants, declare `{name}` with `let` instead."), keyword_span);
+                None
+            }
+            Declaration::Const { span: keyword_span } if value_struct.is_some() => {
+                self.error("E0049", format!("Structs can't be constants, declare `{name}` with `let` instead."), keyword_span);
+                None
+            }
+            Declaration::Const { span: keyword_span } if value_enum.is_some() => {
+                self.error("E0049", format!("Enums can't be constants, declare `{name}` with `let` instead."), keyword_span);
+                None
+            }
+            Declaration::Const { span: keyword_span } => self.evaluate_constant(name, &var.value, keyword_span),
+            _ => None
+        };
+        let binding = Binding {
+            m_name: name.clone(),
+            m_mutable: declaration.is_mutable(),
+            m_constant: matches!(declaration, Declaration::Const { .. }),
+            m_counter: false,
+            m_value: value,
+            m_type: result_type,
+            m_length: length,
+            m_struct: value_struct,
+            m_enum: value_enum
+        };
+        self.m_scopes.last_mut().unwrap().push(binding);
+    }
+
+    /// Elements can only be assigned in arrays declared with `var`, with a value of the type of the elements.
+    fn check_index_assignment(&mut self, assignment: &NodeIndexAssignment) {
+        self.check_expr(&assignment.value);
+        self.check_index(&assignment.element);
+        let Token::ID { name, span } = &assignment.element.variable else { return };
+        let message = match self.find_binding(name) {
+            Some(binding) if binding.m_length.is_none() => return,
+            Some(binding) if binding.m_mutable => {
+                let expected = binding.m_type;
+                if let Some(expected) = expected {
+                    let value_type = (self.get_type(&assignment.value), Self::get_length(&assignment.value));
+                    self.check_type((expected, None), value_type, assignment.value.get_span());
+                }
+                return;
+            }
+            Some(_) => format!("Cannot assign to an element of immutable array `{name}`, declare it with `var` instead."),
+            None => format!("Variable `{name}` is assigned before being declared."),
+        };
+        let code = if self.find_binding(name).is_some() { "E0042" } else { "E0043" };
+        self.error(code, message, *span);
+    }
+
+    /// Fields can only be assigned in structs declared with `var`, with a value of the type of the field.
+    fn check_field_assignment(&mut self, assignment: &NodeFieldAssignment) {
+        self.check_expr(&assignment.value);
+        let Token::ID { name, span } = &assignment.field.variable else { return };
+        let message = match self.find_binding(name) {
+            Some(binding) if binding.m_struct.is_some() && !binding.m_mutable => {
+                format!("Cannot assign to a field of immutable struct `{name}`, declare it with `var` instead.")
+            }
+            Some(_) => {
+                match self.get_field_type(&assignment.field) {
+                    Ok(expected) => self.check_value_type(&expected, &assignment.value),
+                    Err(Some((code, message, span))) => self.error(code, message, span),
+                    Err(None) => {}
+                }
+                return;
+            }
+            None => format!("Variable `{name}` is assigned before being declared."),
+        };
+        let code = if self.find_binding(name).is_some() { "E0042" } else { "E0043" };
+        self.error(code, message, *span);
+    }
+
+    /// Structs are declared once, at the top level, and their fields have distinct names and known types.
+    fn check_struct(&mut self, declaration: &NodeStruct) {
+        let Token::ID { name, span } = &declaration.name else { return };
+        if self.m_scopes.len() > 1 {
+            self.error("E0044", "Structs can only be declared at the top level.".to_string(), *span);
+            return;
+        }
+        if self.m_structs.contains_key(name) || self.m_enums.contains_key(name) {
+            self.error("E0050", format!("Type `{name}` is already declared."), *span);
+            return;
+        }
+        let mut fields: Vec<(String, String)> = Vec::new();
+        for field in &declaration.fields {
+            let (Token::ID { name: field_name, span: field_span }, Token::ID { name: type_name, span: type_span }) = (&field.name, &field.type_name) else {
+                continue;
+            };
+            if self.m_enums.contains_key(type_name) {
+                self.error("E0051", format!("Field `{field_name}` can't hold enum `{type_name}`."), *type_span);
+            } else if !self.is_type(type_name) {
+                // A struct can't hold itself, as it is only declared once its fields are checked
+                self.error("E0050", format!("Unknown type `{type_name}`."), *type_span);
+            }
+            if fields.iter().any(|(declared, _)| declared == field_name) {
+                self.error("E0051", format!("Field `{field_name}` is declared twice in `{name}`."), *field_span);
+                continue;
+            }
+            fields.push((field_name.clone(), type_name.clone()));
+        }
+        self.m_structs.insert(name.clone(), fields);
+    }
+
+    /// C functions are declared once, at the top level, and their parameters are `int` or `bool` values.
+    fn check_extern(&mut self, declaration: &NodeExtern) {
+        let Token::ID { name, span } = &declaration.name else { return };
+        if self.m_scopes.len() > 1 {
+            self.error("E0044", "C functions can only be declared at the top level.".to_string(), *span);
+            return;
+        }
+        if self.m_link_mode != LinkMode::Libc {
+            self.error("E0056", format!("C function `{name}` can only be called from a program linked with `--link=libc`."), *span);
+        }
+        if self.m_functions.contains_key(name) {
+            self.error("E0054", format!("Function `{name}` is already declared."), *span);
+            return;
+        }
+        let mut parameters = Vec::new();
+        for parameter in &declaration.parameters {
+            let Token::ID { name: type_name, span: type_span } = parameter else { continue };
+            if ResultType::from_name(type_name).is_none() {
+                // Every argument is passed in a single register
+                self.error("E0054", "C functions only take `int` and `bool` values.".to_string(), *type_span);
+            }
+            parameters.push(type_name.clone());
+        }
+        self.m_functions.insert(name.clone(), (parameters, declaration.variadic));
+    }
+
+    /// A call names a declared C function and gives a value of its type to each parameter, and possibly more
+    /// when the function is variadic.
+    fn check_call(&mut self, call: &NodeCall) {
+        for argument in &call.arguments {
+            self.check_expr(argument);
+        }
+        let Token::ID { name, span } = &call.function else { return };
+        let Some((parameters, variadic)) = self.m_functions.get(name).cloned() else {
+            self.error("E0054", format!("Unknown function `{name}`, C functions are declared with `extern fn`."), *span);
+            return;
+        };
+        let count = call.arguments.len();
+        if count < parameters.len() || (!variadic && count > parameters.len()) {
+            let expected = if variadic { format!("at least {}", Self::format_count(parameters.len())) } else { Self::format_count(parameters.len()) };
+            self.error("E0054", format!("Function `{name}` takes {expected}, found {count}."), call.span);
+            return;
+        }
+        for (type_name, argument) in parameters.iter().zip(&call.arguments) {
+            self.check_value_type(type_name, argument);
+        }
+    }
+
+    /// Every field of a struct literal is given once, with a value of its type.
+    fn check_struct_literal(&mut self, literal: &NodeStructLiteral) {
+        for (_, value) in &literal.fields {
+            self.check_expr(value);
+        }
+        let Token::ID { name, span } = &literal.name else { return };
+        let Some(fields) = self.m_structs.get(name).cloned() else {
+            self.error("E0050", format!("Unknown struct `{name}`."), *span);
+            return;
+        };
+        let mut given: Vec<&str> = Vec::new();
+        for (field, value) in &literal.fields {
+            let Token::ID { name: field_name, span: field_span } = field else { continue };
+            let Some((_, type_name)) = fields.iter().find(|(declared, _)| declared == field_name) else {
+                self.error("E0051", format!("Struct `{name}` has no field `{field_name}`."), *field_span);
+                continue;
+            };
+            if given.contains(&field_name.as_str()) {
+                self.error("E0051", format!("Field `{field_name}` is given twice."), *field_span);
+                continue;
+            }
+            given.push(field_name);
+            self.check_value_type(type_name, value);
+        }
+        for (field_name, _) in fields.iter().filter(|(field_name, _)| !given.contains(&field_name.as_str())) {
+            self.error("E0051", format!("Missing field `{field_name}` in `{name}`."), *span);
+        }
+    }
+
+    /// Enums are declared once, at the top level, and their variants have distinct names and hold `int` or `bool` values.
+    fn check_enum(&mut self, declaration: &NodeEnum) {
+        let Token::ID { name, span } = &declaration.name else { return };
+        if self.m_scopes.len() > 1 {
+            self.error("E0044", "Enums can only be declared at the top level.".to_string(), *span);
+            return;
+        }
+        if self.m_structs.contains_key(name) || self.m_enums.contains_key(name) {
+            self.error("E0050", format!("Type `{name}` is already declared."), *span);
+            return;
+        }
+        let mut variants: Vec<(String, Vec<String>)> = Vec::new();
+        for variant in &declaration.variants {
+            let Token::ID { name: variant_name, span: variant_span } = &variant.name else { continue };
+            for type_token in &variant.types {
+                let Token::ID { name: type_name, span: type_span } = type_token else { continue };
+                if self.is_type(type_name) && ResultType::from_name(type_name).is_none() {
+                    // The values of a variant share the stack slots of the other variants
+                    self.error("E0052", "Variants can only hold `int` and `bool` values.".to_string(), *type_span);
+                } else if !self.is_type(type_name) {
+                    self.error("E0050", format!("Unknown type `{type_name}`."), *type_span);
+                }
+            }
+            if variants.iter().any(|(declared, _)| declared == variant_name) {
+                self.error("E0052", format!("Variant `{variant_name}` is declared twice in `{name}`."), *variant_span);
+                continue;
+            }
+            let types = variant.types.iter().map(|type_token| match type_token {
+                Token::ID { name, .. } => name.clone(),
+                _ => String::new()
+            }).collect();
+            variants.push((variant_name.clone(), types));
+        }
+        self.m_enums.insert(name.clone(), variants);
+    }
+
+    /// A variant literal names a variant of a declared enum, and gives every value it holds.
+    fn check_variant_literal(&mut self, literal: &NodeVariantLiteral) {
+        for value in &literal.values {
+            self.check_expr(value);
+        }
+        let (Token::ID { name, span }, Token::ID { name: variant, span: variant_span }) = (&literal.enum_name, &literal.variant) else { return };
+        let Some(variants) = self.m_enums.get(name) else {
+            self.error("E0050", format!("Unknown enum `{name}`."), *span);
+            return;
+        };
+        let Some((_, types)) = variants.iter().find(|(declared, _)| declared == variant).cloned() else {
+            self.error("E0052", format!("Enum `{name}` has no variant `{variant}`."), *variant_span);
+            return;
+        };
+        if types.len() != literal.values.len() {
+            self.error("E0052", format!("Variant `{variant}` holds {}, found {}.", Self::format_count(types.len()), literal.values.len()), literal.span);
+            return;
+        }
+        for (type_name, value) in types.iter().zip(&literal.values) {
+            self.check_value_type(type_name, value);
+        }
+    }
+
+    /// The variable of a match is an enum, and its arms name each of its variants at most once, unless a `_` arm ends
+    /// the match. The bindings of an arm are only visible in its value, and every arm gives a value of the same type.
+    fn check_match(&mut self, node: &NodeMatch) {
+        let Token::ID { name, span } = &node.variable else { return };
+        self.read_variable(name, *span);
+        let Some(binding) = self.find_binding(name) else { return };
+        let Some(enum_name) = binding.m_enum.clone() else {
+            if let Some(found) = Self::get_binding_type(binding) {
+                self.error("E0046", format!("Mismatched types: expected an enum, found `{found}`."), *span);
+            }
+            return;
+        };
+        let variants = self.m_enums.get(&enum_name).cloned().unwrap_or_default();
+        let expected = self.get_type(&NodeArithmeticExpr::Match(node.clone()));
+        let mut matched: Vec<&str> = Vec::new();
+        let mut wildcard = false;
+        for arm in &node.arms {
+            let types = match &arm.variant {
+                Token::ID { name: variant, span: variant_span } => {
+                    match variants.iter().find(|(declared, _)| declared == variant) {
+                        Some((_, types)) if types.len() != arm.bindings.len() => {
+                            let message = format!("Variant `{variant}` holds {}, found {}.", Self::format_count(types.len()), arm.bindings.len());
+                            self.error("E0052", message, *variant_span);
+                            types.clone()
+                        }
+                        Some((_, types)) => types.clone(),
+                        None => {
+                            self.error("E0052", format!("Enum `{enum_name}` has no variant `{variant}`."), *variant_span);
+                            Vec::new()
+                        }
+                    }
+                }
+                _ => {
+                    wildcard = true;
+                    Vec::new()
+                }
+            };
+            if let Token::ID { name: variant, .. } = &arm.variant {
+                matched.push(variant);
+            }
+            self.m_scopes.push(Vec::new());
+            for (index, binding) in arm.bindings.iter().enumerate() {
+                let Token::ID { name, .. } = binding else { continue };
+                let binding = Binding {
+                    m_name: name.clone(),
+                    m_mutable: false,
+                    m_constant: false,
+                    m_counter: false,
+                    m_value: None,
+                    m_type: types.get(index).and_then(|type_name| ResultType::from_name(type_name)),
+                    m_length: None,
+                    m_struct: None,
+                    m_enum: None
+                };
+                self.m_scopes.last_mut().unwrap().push(binding);
+            }
+            self.check_expr(&arm.value);
+            if let Some(expected) = expected {
+                self.check_type((expected, None), (self.get_type(&arm.value), None), arm.value.get_span());
+            }
+            self.m_scopes.pop();
+        }
+        let missing: Vec<String> = variants.iter()
+            .filter(|(variant, _)| !matched.contains(&variant.as_str()))
+            .map(|(variant, _)| format!("`{variant}`"))
+            .collect();
+        if !wildcard && !missing.is_empty() {
+            self.error("E0053", format!("Non-exhaustive match, missing {}.", missing.join(", ")), node.span);
+        }
+    }
+
+    fn format_count(count: usize) -> String {
+        if count == 1 { "1 value".to_string() } else { format!("{count} values") }
+    }
+
+    /// An assignment without declaration overwrites the nearest variable, which must have been declared with `var`.
+    fn check_reassignment(&mut self, name: &str, span: Span, (result_type, length): (Option<ResultType>, Option<usize>), r#struct: Option<String>, r#enum: Option<String>) {
+        let binding = self.find_binding(name);
+        let message = match binding {
+            Some(binding) if binding.m_mutable => return,
+            Some(binding) if binding.m_constant => format!("Cannot assign to constant `{name}`."),
+            Some(binding) if binding.m_counter => format!("Cannot assign to loop variable `{name}`."),
+            Some(_) => format!("Cannot assign twice to immutable variable `{name}`, declare it with `var` instead."),
+            None => {
+                self.error("E0043", format!("Variable `{name}` is assigned before being declared."), span);
+                // Declared from here on, so that its reads aren't reported as well
+                let binding = Binding {
+                    m_name: name.to_string(),
+                    m_mutable: true,
+                    m_constant: false,
+                    m_counter: false,
+                    m_value: None,
+                    m_type: result_type,
+                    m_length: length,
+                    m_struct: r#struct,
+                    m_enum: r#enum
+                };
+                self.m_scopes.last_mut().unwrap().push(binding);
+                return;
+            }
+        };
+        self.error("E0042", message, span);
+    }
+
+    /// Reports a value of type `found` where a value of type `expected` is required. Types are given with the
+    /// number of elements of the arrays.
+    fn check_type(&self, expected: (ResultType, Option<usize>), found: (Option<ResultType>, Option<usize>), span: Span) {
+        match found {
+            (Some(found_type), found_length) if (found_type, found_length) != expected => {
+                let message = format!("Mismatched types: expected `{}`, found `{}`.", Self::format_type(expected), Self::format_type((found_type, found_length)));
+                self.error("E0046", message, span);
+            }
+            _ => {}
+        }
+    }
+
+    /// Reports a value that doesn't have the type named `expected`, which is `int`, `bool` or the name of a struct or an enum.
+    /// Nothing is reported when the expected type is unknown, as its declaration already is.
+    fn check_value_type(&self, expected: &str, value: &NodeArithmeticExpr) {
+        if !self.is_type(expected) {
+            return;
+        }
+        let found = match Self::get_struct(value).or(Self::get_enum(value)) {
+            Some(found) => Some(found),
+            None => self.get_type(value).map(|found| Self::format_type((found, Self::get_length(value)))),
+        };
+        match found {
+            Some(found) if found != expected => {
+                self.error("E0046", format!("Mismatched types: expected `{expected}`, found `{found}`."), value.get_span());
+            }
+            _ => {}
+        }
+    }
+
+    fn is_type(&self, name: &str) -> bool {
+        ResultType::from_name(name).is_some() || self.m_structs.contains_key(name) || self.m_enums.contains_key(name)
+    }
+
+    fn format_type((result_type, length): (ResultType, Option<usize>)) -> String {
+        match length {
+            Some(length) => format!("[{result_type}; {length}]"),
+            None => result_type.to_string(),
+        }
+    }
+
+    /// Type of an expression, `None` when it reads a variable that isn't declared. The type of an array literal
+    /// is the type of its first element, and the type of a match is the type of its first arm.
+    fn get_type(&self, expr: &NodeArithmeticExpr) -> Option<ResultType> {
+        match expr {
+            NodeArithmeticExpr::Base(base) => self.get_base_type(base),
+            NodeArithmeticExpr::Operation(operation) => Some(operation.result_type),
+            NodeArithmeticExpr::Array(array) => self.get_type(array.elements.first()?),
+            NodeArithmeticExpr::Struct(_) | NodeArithmeticExpr::Variant(_) => None,
+            NodeArithmeticExpr::Match(node) => {
+                let arm = node.arms.first()?;
+                // The arm can return one of its bindings, which are out of scope here
+                let position = match &arm.value {
+                    NodeArithmeticExpr::Base(NodeBaseExpr::ID(Token::ID { name, .. })) => {
+                        arm.bindings.iter().position(|binding| matches!(binding, Token::ID { name: bound, .. } if bound == name))
+                    }
+                    _ => None
+                };
+                let Some(position) = position else {
+                    return self.get_type(&arm.value);
+                };
+                let (Token::ID { name, .. }, Token::ID { name: variant, .. }) = (&node.variable, &arm.variant) else { return None };
+                let variants = self.m_enums.get(self.find_binding(name)?.m_enum.as_ref()?)?;
+                let (_, types) = variants.iter().find(|(declared, _)| declared == variant)?;
+                ResultType::from_name(types.get(position)?)
+            }
+        }
+    }
+
+    fn get_base_type(&self, base: &NodeBaseExpr) -> Option<ResultType> {
+        match base {
+            NodeBaseExpr::Num(_) | NodeBaseExpr::Len(_) | NodeBaseExpr::Alloc(_) | NodeBaseExpr::Builtin(_)
+            | NodeBaseExpr::Call(_) | NodeBaseExpr::Str(_) => Some(ResultType::Numeric),
+            NodeBaseExpr::Bool(_) => Some(ResultType::Boolean),
+            NodeBaseExpr::ID(Token::ID { name, .. }) | NodeBaseExpr::Index(NodeIndex { variable: Token::ID { name, .. }, .. }) => {
+                self.find_binding(name)?.m_type
+            }
+            NodeBaseExpr::Field(field) => ResultType::from_name(&self.get_field_type(field).ok()?),
+            NodeBaseExpr::ID(_) | NodeBaseExpr::Index(_) => None,
+        }
+    }
+
+    /// Name of the struct of a struct literal. Struct variables can't be used as values, so they are not structs here.
+    fn get_struct(expr: &NodeArithmeticExpr) -> Option<String> {
+        match expr {
+            NodeArithmeticExpr::Struct(NodeStructLiteral { name: Token::ID { name, .. }, .. }) => Some(name.clone()),
+            _ => None
+        }
+    }
+
+    /// Name of the enum of a variant literal.
+    fn get_enum(expr: &NodeArithmeticExpr) -> Option<String> {
+        match expr {
+            NodeArithmeticExpr::Variant(NodeVariantLiteral { enum_name: Token::ID { name, .. }, .. }) => Some(name.clone()),
+            _ => None
+        }
+    }
+
+    /// Name of the type of the field, `int`, `bool` or the name of a struct. The error is `None` when the variable
+    /// is not declared or its type is unknown, which is reported elsewhere.
+    fn get_field_type(&self, field: &NodeField) -> Result<String, Option<(&'static str, String, Span)>> {
+        let Token::ID { name, span } = &field.variable else { return Err(None) };
+        let binding = self.find_binding(name).ok_or(None)?;
+        let Some(mut current) = binding.m_struct.clone() else {
+            let found = binding.m_enum.clone().or(binding.m_type.map(|found| Self::format_type((found, binding.m_length)))).ok_or(None)?;
+            return Err(Some(("E0046", format!("Mismatched types: expected a struct, found `{found}`."), *span)));
+        };
+        for field_token in &field.fields {
+            let Token::ID { name: field_name, span: field_span } = field_token else { return Err(None) };
+            let Some(fields) = self.m_structs.get(&current) else {
+                return match ResultType::from_name(&current) {
+                    Some(found) => Err(Some(("E0046", format!("Mismatched types: expected a struct, found `{found}`."), *field_span))),
+                    None => Err(None),
+                };
+            };
+            let Some((_, type_name)) = fields.iter().find(|(declared, _)| declared == field_name) else {
+                return Err(Some(("E0051", format!("Struct `{current}` has no field `{field_name}`."), *field_span)));
+            };
+            current = type_name.clone();
+        }
+        Ok(current)
+    }
+
+    /// Binding of the variable read by the expression, when it is an array, a struct or an enum.
+    fn get_aggregate(&self, expr: &NodeArithmeticExpr) -> Option<&Binding> {
+        let NodeArithmeticExpr::Base(NodeBaseExpr::ID(Token::ID { name, .. })) = expr else { return None };
+        self.find_binding(name).filter(|binding| binding.m_length.is_some() || binding.m_struct.is_some() || binding.m_enum.is_some())
+    }
+
+    /// Number of elements of an array literal. Array variables can't be used as values, so they are not arrays here.
+    fn get_length(expr: &NodeArithmeticExpr) -> Option<usize> {
+        match expr {
+            NodeArithmeticExpr::Array(array) => Some(array.length),
+            _ => None
+        }
+    }
+
+    /// Constants are declared at the top level, and their value only depends on literals and other constants.
+    fn evaluate_constant(&mut self, name: &str, value: &NodeArithmeticExpr, keyword_span: Span) -> Option<i64> {
+        if self.m_scopes.len() > 1 {
+            self.error("E0044", "Constants can only be declared at the top level.".to_string(), keyword_span);
+            return None;
+        }
+        let mut constants = HashMap::new();
+        for (variable, span) in Self::get_variables(value) {
+            match self.find_binding(&variable) {
+                Some(Binding { m_value: Some(value), .. }) => constants.insert(variable, *value),
+                Some(Binding { m_constant: false, .. }) => {
+                    self.error("E0045", format!("Constant `{name}` depends on `{variable}`, which is not a constant."), span);
+                    return None;
+                }
+                // Either read before being assigned, or a constant whose error is already reported
+                _ => return None,
+            };
+        }
+        match Evaluator::evaluate_constant(value, &constants) {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.error("E0045", format!("Constant `{name}` cannot be evaluated: {error}"), value.get_span());
+                None
+            }
+        }
+    }
+
+    fn find_binding(&self, name: &str) -> Option<&Binding> {
+        self.m_scopes.iter().flatten().rev().find(|binding| binding.m_name == name)
+    }
+
+    /// Names and spans of the variables read by an expression.
+    fn get_variables(expr: &NodeArithmeticExpr) -> Vec<(String, Span)> {
+        fn collect_base(base: &NodeBaseExpr, variables: &mut Vec<(String, Span)>) {
+            match base {
+                NodeBaseExpr::ID(Token::ID { name, span })
+                | NodeBaseExpr::Len(NodeLen { variable: Token::ID { name, span }, .. })
+                | NodeBaseExpr::Field(NodeField { variable: Token::ID { name, span }, .. }) => variables.push((name.clone(), *span)),
+                NodeBaseExpr::Index(NodeIndex { variable: Token::ID { name, span }, index, .. }) => {
+                    variables.push((name.clone(), *span));
+                    variables.extend(Checker::get_variables(index));
+                }
+                NodeBaseExpr::Alloc(node) => variables.extend(Checker::get_variables(&node.size)),
+                NodeBaseExpr::Builtin(NodeBuiltin { argument: Some(argument), .. }) => variables.extend(Checker::get_variables(argument)),
+                NodeBaseExpr::Call(call) => variables.extend(call.arguments.iter().flat_map(Checker::get_variables)),
+                _ => {}
+            }
+        }
+        fn collect_operation(operation: &NodeArithmeticOperation, variables: &mut Vec<(String, Span)>) {
+            for operand in [&operation.lhs, &operation.rhs] {
+                match operand {
+                    Left(inner) => collect_operation(inner, variables),
+                    Right(base) => collect_base(base, variables),
+                }
+            }
+        }
+        let mut variables = Vec::new();
+        match expr {
+            NodeArithmeticExpr::Base(base) => collect_base(base, &mut variables),
+            NodeArithmeticExpr::Operation(operation) => collect_operation(operation, &mut variables),
+            NodeArithmeticExpr::Array(array) => variables.extend(array.elements.iter().flat_map(Self::get_variables)),
+            NodeArithmeticExpr::Struct(literal) => variables.extend(literal.fields.iter().flat_map(|(_, value)| Self::get_variables(value))),
+            NodeArithmeticExpr::Variant(literal) => variables.extend(literal.values.iter().flat_map(Self::get_variables)),
+            NodeArithmeticExpr::Match(node) => {
+                if let Token::ID { name, span } = &node.variable {
+                    variables.push((name.clone(), *span));
+                }
+                for arm in &node.arms {
+                    // The bindings of an arm are not variables of the enclosing scope
+                    let bindings = Self::get_variables(&arm.value).into_iter()
+                        .filter(|(name, _)| !arm.bindings.iter().any(|binding| matches!(binding, Token::ID { name: bound, .. } if bound == name)));
+                    variables.extend(bindings);
+                }
+            }
+        }
+        variables
+    }
+
+    fn check_expr(&mut self, expr: &NodeArithmeticExpr) {
+        match expr {
+            NodeArithmeticExpr::Base(base) => self.check_base(base),
+            NodeArithmeticExpr::Operation(operation) => self.check_operation(operation),
+            NodeArithmeticExpr::Array(array) => {
+                // Every element has the type of the first one
+                let expected = self.get_type(&array.elements[0]);
+                for element in &array.elements {
+                    self.check_expr(element);
+                    if let Some(expected) = expected {
+                        self.check_type((expected, None), (self.get_type(element), None), element.get_span());
+                    }
+                }
+            }
+            NodeArithmeticExpr::Struct(literal) => self.check_struct_literal(literal),
+            NodeArithmeticExpr::Variant(literal) => self.check_variant_literal(literal),
+            NodeArithmeticExpr::Match(node) => self.check_match(node),
+        }
+    }
+
+    fn check_operation(&mut self, operation: &NodeArithmeticOperation) {
+        self.check_operand_types(operation);
+        if let (Operator::Division { .. } | Operator::Modulus { .. }, Right(NodeBaseExpr::Num(Token::Number { value, span }))) = (operation.op, &operation.rhs) {
+            if value.parse::<i64>() == Ok(0) {
+                self.error("E0041", "Division by zero.".to_string(), *span);
+            }
+        }
+        // Unary operations hold their only operand on both sides
+        let operands = if operation.op.is_unary() { vec![&operation.lhs] } else { vec![&operation.lhs, &operation.rhs] };
+        for operand in operands {
+            match operand {
+                Left(inner) => self.check_operation(inner),
+                Right(base) => self.check_base(base),
+            }
+        }
+    }
+
+    /// Logical operators only apply to booleans, and bitwise ones to integers. The parser already checks the literals
+    /// and the operations, so only the variables are left.
+    fn check_operand_types(&self, operation: &NodeArithmeticOperation) {
+        let expected = match operation.op {
+            Operator::And { .. } | Operator::Or { .. } | Operator::Xor { .. } | Operator::Not { .. } => ResultType::Boolean,
+            Operator::BitAnd { .. } | Operator::BitOr { .. } | Operator::BitXor { .. } | Operator::BitNot { .. }
+            | Operator::ShiftLeft { .. } | Operator::ShiftRight { .. } => ResultType::Numeric,
+            _ => return
+        };
+        let operands = if operation.op.is_unary() { vec![&operation.lhs] } else { vec![&operation.lhs, &operation.rhs] };
+        for operand in operands {
+            let Right(base) = operand else { continue };
+            match self.get_base_type(base) {
+                Some(found) if found != expected => {
+                    let message = format!("Type mismatch: `{}` expects `{expected}` operands, found `{found}`.", operation.op);
+                    self.error("E0011", message, base.get_span());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn check_base(&mut self, base: &NodeBaseExpr) {
+        match base {
+            NodeBaseExpr::ID(Token::ID { name, span }) => {
+                let length = self.read_variable(name, *span);
+                if length.is_some() {
+                    // The elements of an array don't fit in the single register holding a value
+                    self.error("E0048", format!("`{name}` is an array, it can only be indexed or given to `len`."), *span);
+                } else if self.find_binding(name).is_some_and(|binding| binding.m_struct.is_some()) {
+                    self.error("E0048", format!("`{name}` is a struct, only its fields can be used."), *span);
+                } else if self.find_binding(name).is_some_and(|binding| binding.m_enum.is_some()) {
+                    self.error("E0048", format!("`{name}` is an enum, only `match` can read its values."), *span);
+                }
+            }
+            NodeBaseExpr::Field(field) => {
+                if let Token::ID { name, span } = &field.variable {
+                    self.read_variable(name, *span);
+                }
+                match self.get_field_type(field) {
+                    Ok(found) if self.m_structs.contains_key(&found) => {
+                        self.error("E0048", format!("`{field}` is a struct, only its fields can be used."), field.span);
+                    }
+                    Err(Some((code, message, span))) => self.error(code, message, span),
+                    _ => {}
+                }
+            }
+            NodeBaseExpr::Index(element) => {
+                if let Token::ID { name, span } = &element.variable {
+                    self.read_variable(name, *span);
+                }
+                self.check_index(element);
+            }
+            NodeBaseExpr::Len(NodeLen { variable: Token::ID { name, span }, .. }) => {
+                if let (None, Some(found)) = (self.read_variable(name, *span), self.find_binding(name).and_then(Self::get_binding_type)) {
+                    self.error("E0046", format!("Mismatched types: expected an array, found `{found}`."), *span);
+                }
+            }
+            NodeBaseExpr::Alloc(node) => {
+                self.check_expr(&node.size);
+                self.check_value_type("int", &node.size);
+            }
+            NodeBaseExpr::Builtin(NodeBuiltin { argument: Some(argument), .. }) => {
+                self.check_expr(argument);
+                self.check_value_type("int", argument);
+            }
+            NodeBaseExpr::Call(call) => self.check_call(call),
+            _ => {}
+        }
+    }
+
+    /// Returns the number of elements of the variable when it is an array.
+    fn read_variable(&self, name: &str, span: Span) -> Option<usize> {
+        match self.find_binding(name) {
+            Some(binding) => binding.m_length,
+            None => {
+                // The generator has no stack slot to read the variable from
+                self.error("E0040", format!("Variable `{name}` is used before being assigned."), span);
+                None
+            }
+        }
+    }
+
+    /// The index must be an integer and, when it is a literal, smaller than the number of elements.
+    fn check_index(&mut self, element: &NodeIndex) {
+        self.check_expr(&element.index);
+        self.check_type((ResultType::Numeric, None), (self.get_type(&element.index), None), element.index.get_span());
+        let Token::ID { name, span } = &element.variable else { return };
+        let Some(binding) = self.find_binding(name) else { return };
+        match (binding.m_length, element.index.as_ref()) {
+            (Some(length), NodeArithmeticExpr::Base(NodeBaseExpr::Num(Token::Number { value, span })))
+                if value.parse::<usize>().is_ok_and(|index| index >= length) => {
+                self.error("E0047", format!("Index {value} is out of bounds for `{name}`, which has {length} elements."), *span);
+            }
+            (None, _) => {
+                if let Some(found) = Self::get_binding_type(binding) {
+                    self.error("E0046", format!("Mismatched types: expected an array, found `{found}`."), *span);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Name of the type of a variable that isn't an array, `None` when it is unknown.
+    fn get_binding_type(binding: &Binding) -> Option<String> {
+        binding.m_struct.clone().or(binding.m_enum.clone()).or(binding.m_type.map(|result_type| result_type.to_string()))
+    }
+
+    /// Errors in a file without a logger, as in a program read from JSON, go to the first logger.
+    fn error(&self, code: &'static str, message: String, span: Span) {
+        if let Some(logger) = self.m_loggers.get(span.m_file_id).or(self.m_loggers.first()) {
+            logger.lock().unwrap().log_error_message(code, message, span);
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod test_checker{
+    use crate::compiler::logger::Logger;
+    use crate::compiler::parser::Parser;
+    use crate::compiler::tokenizer::Tokenizer;
+    use super::*;
+
+    fn errors(code: &str) -> Vec<(String, Span)> {
+        link_errors(code, LinkMode::Libc)
+    }
+
+    fn link_errors(code: &str, link_mode: LinkMode) -> Vec<(String, Span)> {
+        let logger = Arc::new(Mutex::new(ParserLogger::new("".to_string(), code.to_string())));
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.tokenize(code);
+        let prog = Parser::new(tokenizer.get_tokens(), logger.clone()).parse().expect("The test program should parse");
+        let mut checker = Checker::new(std::slice::from_ref(&logger));
+        checker.set_link_mode(link_mode);
+        checker.check(&prog);
+        let errors = logger.lock().unwrap().get_errors();
+        errors
+    }
+
+    #[test]
+    fn test_undefined_variable(){
+        assert_eq!(errors("{\nlet x = 1\n}\nexit(x)"), vec![("Variable `x` is used before being assigned.".to_string(), Span::new(0, 19, 20))]);
+        assert!(!errors("let x = y\nexit(x)").is_empty());
+    }
+
+    #[test]
+    fn test_division_by_zero(){
+        assert_eq!(errors("let x = 1 // 0\nlet y = 1 % 0\nlet z = 1 // (1 - 1)\nexit(x + y + z)"), vec![
+            ("Division by zero.".to_string(), Span::new(0, 13, 14)), ("Division by zero.".to_string(), Span::new(0, 27, 28))
+        ]);
+    }
+
+    #[test]
+    fn test_assignments(){
+        assert!(errors("var x = 1\n{\nx += 1\n}\nexit(x)").is_empty());
+        assert_eq!(errors("let x = 1\nx = 2\nexit(x)"), vec![
+            ("Cannot assign twice to immutable variable `x`, declare it with `var` instead.".to_string(), Span::new(0, 10, 11))
+        ]);
+        assert_eq!(errors("const N = 1\nN += 1"), vec![("Cannot assign to constant `N`.".to_string(), Span::new(0, 12, 13))]);
+        assert_eq!(errors("x = 1\nexit(x)"), vec![("Variable `x` is assigned before being declared.".to_string(), Span::new(0, 0, 1))]);
+        assert_eq!(errors("x += 1\nexit(x)"), vec![("Variable `x` is assigned before being declared.".to_string(), Span::new(0, 0, 1))]);
+    }
+
+    #[test]
+    fn test_constants(){
+        assert!(errors("const N = 2 * 5\nconst M = N + 1\nexit(M)").is_empty());
+        assert_eq!(errors("{\nconst N = 1\nexit(N)\n}"), vec![("Constants can only be declared at the top level.".to_string(), Span::new(0, 2, 7))]);
+        assert_eq!(errors("var x = 1\nconst N = x + 1\nexit(N)"), vec![
+            ("Constant `N` depends on `x`, which is not a constant.".to_string(), Span::new(0, 20, 21))
+        ]);
+        assert_eq!(errors("const N = 1 // (1 - 1)\nexit(N)"), vec![
+            ("Constant `N` cannot be evaluated: Division by zero.".to_string(), Span::new(0, 10, 21))
+        ]);
+    }
+
+    #[test]
+    fn test_types(){
+        assert!(errors("let x: int = 5\nlet flag: bool = !!false\nexit(x + flag as int)").is_empty());
+        assert_eq!(errors("let x: int = true\nexit(x)"), vec![
+            ("Mismatched types: expected `int`, found `bool`.".to_string(), Span::new(0, 13, 17))
+        ]);
+        assert_eq!(errors("var b = true\nb = 2\nexit(b as int)"), vec![
+            ("Mismatched types: expected `bool`, found `int`.".to_string(), Span::new(0, 17, 18))
+        ]);
+        assert_eq!(errors("let x = 1\nexit((x && true) as int)"), vec![
+            ("Type mismatch: `&&` expects `bool` operands, found `int`.".to_string(), Span::new(0, 16, 17))
+        ]);
+        assert_eq!(errors("let b = true\nexit(~b)"), vec![
+            ("Type mismatch: `~` expects `int` operands, found `bool`.".to_string(), Span::new(0, 19, 20))
+        ]);
+    }
+
+    #[test]
+    fn test_arrays(){
+        assert!(errors("var a: [int; 3] = [1, 2, 3]\nlet i = 2\na[i] += len(a)\na = [0; 3]\nexit(a[0])").is_empty());
+        assert_eq!(errors("let a = [1, true]\nexit(a[2])"), vec![
+            ("Mismatched types: expected `int`, found `bool`.".to_string(), Span::new(0, 12, 16)),
+            ("Index 2 is out of bounds for `a`, which has 2 elements.".to_string(), Span::new(0, 25, 26))
+        ]);
+        assert_eq!(errors("var a = [1, 2]\na = [1, 2, 3]\nexit(a + a[true as int])"), vec![
+            ("Mismatched types: expected `[int; 2]`, found `[int; 3]`.".to_string(), Span::new(0, 19, 28)),
+            ("`a` is an array, it can only be indexed or given to `len`.".to_string(), Span::new(0, 34, 35))
+        ]);
+        assert_eq!(errors("let a = [1; 2]\na[0] = 2\nexit(a[0])"), vec![
+            ("Cannot assign to an element of immutable array `a`, declare it with `var` instead.".to_string(), Span::new(0, 15, 16))
+        ]);
+        assert_eq!(errors("let x = 1\nexit(x[0] + len(x))"), vec![
+            ("Mismatched types: expected an array, found `int`.".to_string(), Span::new(0, 15, 16)),
+            ("Mismatched types: expected an array, found `int`.".to_string(), Span::new(0, 26, 27))
+        ]);
+        // Only the array used as a value is reported, not the type of the assignment
+        assert_eq!(errors("var a = [1, 2]\nlet b = [3, 4]\na = b\nlet c: [int; 2] = b\nexit(a[0] + c[1])"), vec![
+            ("`b` is an array, it can only be indexed or given to `len`.".to_string(), Span::new(0, 34, 35)),
+            ("`b` is an array, it can only be indexed or given to `len`.".to_string(), Span::new(0, 54, 55))
+        ]);
+        assert_eq!(errors("const A = [1, 2]"), vec![
+            ("Arrays can't be constants, declare `A` with `let` instead.".to_string(), Span::new(0, 0, 5))
+        ]);
+    }
+
+    #[test]
+    fn test_structs(){
+        assert!(errors("struct P { x: int, y: bool }\nstruct L { a: P }\nvar l = L { a: P { y: true, x: 1 } }\nl.a.x += 1\nl.a = P { x: 2, y: false }\nexit(l.a.x)").is_empty());
+        assert_eq!(errors("struct P { x: int, x: Q }\nlet p = P { x: 1, z: 2 }\nexit(p.x)"), vec![
+            ("Unknown type `Q`.".to_string(), Span::new(0, 22, 23)),
+            ("Field `x` is declared twice in `P`.".to_string(), Span::new(0, 19, 20)),
+            ("Struct `P` has no field `z`.".to_string(), Span::new(0, 44, 45)),
+        ]);
+        assert_eq!(errors("struct P { x: int }\nlet p = P { x: true }\np.x = 2\nexit(p + p.y)"), vec![
+            ("Mismatched types: expected `int`, found `bool`.".to_string(), Span::new(0, 35, 39)),
+            ("Cannot assign to a field of immutable struct `p`, declare it with `var` instead.".to_string(), Span::new(0, 42, 43)),
+            ("`p` is a struct, only its fields can be used.".to_string(), Span::new(0, 55, 56)),
+            ("Struct `P` has no field `y`.".to_string(), Span::new(0, 61, 62)),
+        ]);
+    }
+
+    #[test]
+    fn test_enums(){
+        assert!(errors("enum S { C(int), R(int, bool), E }\nvar s = S::R(1, true)\ns = S::E\nlet x = match s { R(w, _) => w, C(r) => r, E => 0 }\nexit(match s { C(r) => r + x, _ => x })").is_empty());
+        assert_eq!(errors("enum S { C(int), C, D(P) }\nlet s = S::C(true)\nlet t = S::E\nexit(s + t)"), vec![
+            ("Variant `C` is declared twice in `S`.".to_string(), Span::new(0, 17, 18)),
+            ("Unknown type `P`.".to_string(), Span::new(0, 22, 23)),
+            ("Mismatched types: expected `int`, found `bool`.".to_string(), Span::new(0, 40, 44)),
+            ("Enum `S` has no variant `E`.".to_string(), Span::new(0, 57, 58)),
+            ("`s` is an enum, only `match` can read its values.".to_string(), Span::new(0, 64, 65)),
+            ("`t` is an enum, only `match` can read its values.".to_string(), Span::new(0, 68, 69)),
+        ]);
+        assert_eq!(errors("enum S { C(int), E }\nlet s = S::C(1, 2)\nexit(match s { C(r) => r })"), vec![
+            ("Variant `C` holds 1 value, found 2.".to_string(), Span::new(0, 29, 39)),
+            ("Non-exhaustive match, missing `E`.".to_string(), Span::new(0, 45, 66)),
+        ]);
+        assert_eq!(errors("enum S { C(int), E }\nlet s = S::E\nlet x = 1\nexit(match x { E => 1, _ => true })"), vec![
+            ("Mismatched types: expected an enum, found `int`.".to_string(), Span::new(0, 55, 56)),
+        ]);
+    }
+
+    #[test]
+    fn test_for(){
+        assert!(errors("var x = 0\nfor i in 0..3 {\nx = x + i\n}\nfor _ in x..=9 step 2 {\n}\nexit(x)").is_empty());
+        assert_eq!(errors("for i in 0..true {\ni = 2\n}\nexit(i)"), vec![
+            ("Mismatched types: expected `int`, found `bool`.".to_string(), Span::new(0, 12, 16)),
+            ("Cannot assign to loop variable `i`.".to_string(), Span::new(0, 19, 20)),
+            ("Variable `i` is used before being assigned.".to_string(), Span::new(0, 32, 33)),
+        ]);
+    }
+
+    #[test]
+    fn test_heap(){
+        assert!(errors("let p = alloc(4 * 2)\nfree(p)\nexit(0)").is_empty());
+        assert_eq!(errors("let b = true\nlet p = alloc(b)\nfree(b)\nconst Q = alloc(8)\nexit(p)"), vec![
+            ("Mismatched types: expected `int`, found `bool`.".to_string(), Span::new(0, 27, 28)),
+            ("Mismatched types: expected `int`, found `bool`.".to_string(), Span::new(0, 35, 36)),
+            ("Constant `Q` cannot be evaluated: `alloc` only runs with the program.".to_string(), Span::new(0, 48, 56)),
+        ]);
+    }
+
+    #[test]
+    fn test_input(){
+        assert!(errors("let n = parse_int(arg(1)) + read_int()\nexit(n)").is_empty());
+        assert_eq!(errors("let b = true\nlet s = arg(b)\nconst N = read_line()\nexit(s)"), vec![
+            ("Mismatched types: expected `int`, found `bool`.".to_string(), Span::new(0, 25, 26)),
+            ("Constant `N` cannot be evaluated: `read_line` only runs with the program.".to_string(), Span::new(0, 38, 49)),
+        ]);
+    }
+
+    #[test]
+    fn test_extern(){
+        assert!(errors("extern fn printf(int, ...)\nlet n = printf(\"%d\", 1)\nprintf(\"x\")\nexit(n)").is_empty());
+        assert_eq!(errors("extern fn f(int, bool)\nextern fn f()\nextern fn g(P)\nf(1)\nf(1, 2)\nh()\nexit(0)"), vec![
+            ("Function `f` is already declared.".to_string(), Span::new(0, 33, 34)),
+            ("C functions only take `int` and `bool` values.".to_string(), Span::new(0, 49, 50)),
+            ("Function `f` takes 2 values, found 1.".to_string(), Span::new(0, 52, 56)),
+            ("Mismatched types: expected `bool`, found `int`.".to_string(), Span::new(0, 62, 63)),
+            ("Unknown function `h`, C functions are declared with `extern fn`.".to_string(), Span::new(0, 65, 66)),
+        ]);
+        assert_eq!(errors("{\nextern fn p(int, ...)\n}\nextern fn p(int, ...)\np()\nconst S = \"a\"\nexit(0)"), vec![
+            ("C functions can only be declared at the top level.".to_string(), Span::new(0, 12, 13)),
+            ("Function `p` takes at least 1 value, found 0.".to_string(), Span::new(0, 48, 51)),
+            ("Constant `S` cannot be evaluated: The address of \"a\" is only known when the program runs.".to_string(), Span::new(0, 62, 65)),
+        ]);
+        assert_eq!(link_errors("extern fn rand()
+exit(rand())", LinkMode::Freestanding), vec![
+            ("C function `rand` can only be called from a program linked with `--link=libc`.".to_string(), Span::new(0, 10, 14)),
+        ]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use crate::compiler::parser::ParserLogger;
+use crate::compiler::span::{LineIndex, Span};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Lint {
+    UnusedVariables,
+    Shadowing,
+    UnreachableCode,
+    ConstantCondition,
+}
+
+impl Lint {
+    pub fn all() -> [Lint; 4] {
+        [Lint::UnusedVariables, Lint::Shadowing, Lint::UnreachableCode, Lint::ConstantCondition]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::UnusedVariables => "unused_variables",
+            Lint::Shadowing => "shadowing",
+            Lint::UnreachableCode => "unreachable_code",
+            Lint::ConstantCondition => "constant_condition",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Lint> {
+        Lint::all().into_iter().find(|lint| lint.name() == name)
+    }
+
+    pub fn default_level(&self) -> LintLevel {
+        LintLevel::Warn
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl LintLevel {
+    /// Maps the command line flags `-A`, `-W` and `-D` to their level.
+    pub fn from_flag(flag: &str) -> Option<LintLevel> {
+        match flag {
+            "-A" => Some(LintLevel::Allow),
+            "-W" => Some(LintLevel::Warn),
+            "-D" => Some(LintLevel::Deny),
+            _ => None
+        }
+    }
+
+    /// Maps the attribute names used in `#![allow(..)]`, `#![warn(..)]` and `#![deny(..)]`.
+    pub fn from_attribute(attribute: &str) -> Option<LintLevel> {
+        match attribute {
+            "allow" => Some(LintLevel::Allow),
+            "warn" => Some(LintLevel::Warn),
+            "deny" => Some(LintLevel::Deny),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for LintLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintLevel::Allow => write!(f, "allow"),
+            LintLevel::Warn => write!(f, "warn"),
+            LintLevel::Deny => write!(f, "deny"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LintConfig {
+    m_levels: HashMap<Lint, LintLevel>
+}
+
+impl LintConfig {
+    pub fn new() -> Self {
+        LintConfig { m_levels: HashMap::new() }
+    }
+
+    pub fn set_level(&mut self, lint: Lint, level: LintLevel) {
+        self.m_levels.insert(lint, level);
+    }
+
+    pub fn get_level(&self, lint: Lint) -> LintLevel {
+        self.m_levels.get(&lint).copied().unwrap_or(lint.default_level())
+    }
+
+    /// Applies the per-file `#![level(lint, ..)]` attributes found at the start of the lines of `input`.
+    /// Attributes take precedence over the command line flags. The returned source has every attribute
+    /// replaced by spaces, so that the tokenizer never sees them and the byte offsets stay unchanged.
+    pub fn apply_attributes(&mut self, input: &str, file_id: usize, logger: &Arc<Mutex<ParserLogger>>) -> String {
+        let mut source = Vec::new();
+        let line_index = LineIndex::new(input);
+        for (line_i, line) in input.split('\n').enumerate() {
+            let trimmed = line.trim_start();
+            if !trimmed.starts_with("#!") {
+                source.push(line.to_string());
+                continue;
+            }
+            let start = line_index.line_start(line_i).unwrap_or_default() + line.len() - trimmed.len();
+            let span = Span::new(file_id, start, start + trimmed.trim_end().len());
+            match Self::parse_attribute(trimmed.trim_end()) {
+                Some((level, names)) => {
+                    for name in names {
+                        match Lint::from_name(name) {
+                            Some(lint) => self.set_level(lint, level),
+                            None => logger.lock().unwrap().log_warning("unknown_lints", format!("Unknown lint `{name}`."), span)
+                        }
+                    }
+                }
+                None => logger.lock().unwrap().log_error_message("E0020", "Malformed lint attribute.".to_string(), span)
+            }
+            source.push(" ".repeat(line.len()));
+        }
+        source.join("\n")
+    }
+
+    fn parse_attribute(attribute: &str) -> Option<(LintLevel, Vec<&str>)> {
+        let body = attribute.strip_prefix("#![")?.strip_suffix("]")?;
+        let (level, names) = body.strip_suffix(")")?.split_once('(')?;
+        let level = LintLevel::from_attribute(level.trim())?;
+        let names: Vec<&str> = names.split(',').map(str::trim).filter(|name| !name.is_empty()).collect();
+        if names.is_empty() {
+            return None;
+        }
+        Some((level, names))
+    }
+}
+
+
+
+#[cfg(test)]
+mod test_lint{
+    use crate::compiler::logger::Logger;
+    use super::*;
+
+    fn setup_logger() -> Arc<Mutex<ParserLogger>> {
+        Arc::new(Mutex::new(ParserLogger::new("".to_string(), "".to_string())))
+    }
+
+    #[test]
+    fn test_lint_names(){
+        for lint in Lint::all(){
+            assert_eq!(Lint::from_name(lint.name()), Some(lint));
+        }
+        assert!(Lint::from_name("not_a_lint").is_none());
+    }
+
+    #[test]
+    fn test_levels(){
+        assert_eq!(LintLevel::from_flag("-A"), Some(LintLevel::Allow));
+        assert_eq!(LintLevel::from_flag("-W"), Some(LintLevel::Warn));
+        assert_eq!(LintLevel::from_flag("-D"), Some(LintLevel::Deny));
+        assert!(LintLevel::from_flag("--outdir").is_none());
+        assert_eq!(LintLevel::from_attribute("deny"), Some(LintLevel::Deny));
+        assert!(LintLevel::from_attribute("forbid").is_none());
+    }
+
+    #[test]
+    fn test_default_config(){
+        let mut config = LintConfig::new();
+        for lint in Lint::all(){
+            assert_eq!(config.get_level(lint), LintLevel::Warn);
+        }
+        config.set_level(Lint::Shadowing, LintLevel::Allow);
+        assert_eq!(config.get_level(Lint::Shadowing), LintLevel::Allow);
+    }
+
+    #[test]
+    fn test_apply_attributes(){
+        let logger = setup_logger();
+        let mut config = LintConfig::new();
+        config.set_level(Lint::Shadowing, LintLevel::Deny);
+        let source = config.apply_attributes("#![allow(shadowing, unused_variables)]\n  #![deny(unreachable_code)]\nx = 1", 0, &logger);
+        assert_eq!(source, format!("{}\n{}\nx = 1", " ".repeat(38), " ".repeat(28)));
+        assert_eq!(config.get_level(Lint::Shadowing), LintLevel::Allow);
+        assert_eq!(config.get_level(Lint::UnusedVariables), LintLevel::Allow);
+        assert_eq!(config.get_level(Lint::UnreachableCode), LintLevel::Deny);
+        assert_eq!(config.get_level(Lint::ConstantCondition), LintLevel::Warn);
+        assert!(!logger.lock().unwrap().failed_parsing());
+    }
+
+    #[test]
+    fn test_bad_attributes(){
+        let logger = setup_logger();
+        let mut config = LintConfig::new();
+        config.apply_attributes("x = 1\n  #![allow(not_a_lint)]  ", 1, &logger);
+        assert_eq!(logger.lock().unwrap().get_warnings()[0].1, Span::new(1, 8, 29));
+        assert!(!logger.lock().unwrap().failed_parsing());
+
+        config.apply_attributes("#![forbid(shadowing)]", 0, &logger);
+        assert!(logger.lock().unwrap().failed_parsing());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,370 @@
+use std::sync::{Arc, Mutex};
+use either::{Either, Left, Right};
+use crate::compiler::analysis::{Lint, LintConfig, LintLevel};
+use crate::compiler::parser::{Declaration, NodeArithmeticExpr, NodeArithmeticOperation, NodeBaseExpr, NodeIndex, NodeLen, NodeProgram, NodeStmt, NodeVariableAssignment, NodeMatch, NodeFor, NodeBuiltin, ParserLogger};
+use crate::compiler::span::Span;
+use crate::compiler::tokenizer::{Operator, Token};
+
+struct Binding {
+    m_name: String,
+    m_span: Span,
+    m_read: bool,
+    m_constant: bool
+}
+
+/// Lints a whole program, possibly made of several files. The lint configuration and the logger of each
+/// file are indexed by its file id, so every diagnostic is reported against the file it comes from.
+/// The errors are reported by the `Checker`, the lints only report what is allowed but likely a mistake.
+pub struct Linter<'a> {
+    m_configs: &'a [LintConfig],
+    m_loggers: &'a [Arc<Mutex<ParserLogger>>],
+    m_scopes: Vec<Vec<Binding>>
+}
+
+impl<'a> Linter<'a> {
+    pub fn new(m_configs: &'a [LintConfig], m_loggers: &'a [Arc<Mutex<ParserLogger>>]) -> Self {
+        Linter { m_configs, m_loggers, m_scopes: Vec::new() }
+    }
+
+    pub fn check(&mut self, prog: &NodeProgram) {
+        self.check_block(&prog.stmts);
+    }
+
+    /// Returns whether the block unconditionally reaches an `exit`.
+    fn check_block(&mut self, stmts: &[NodeStmt]) -> bool {
+        self.m_scopes.push(Vec::new());
+        let mut exited = false;
+        let mut reported = false;
+        for stmt in stmts {
+            if exited && !reported {
+                // Only the first unreachable statement of a block is reported.
+                self.emit(Lint::UnreachableCode, "Unreachable statement after `exit`.".to_string(), stmt.get_span());
+                reported = true;
+            }
+            exited |= self.check_stmt(stmt);
+        }
+        self.pop_scope();
+        exited
+    }
+
+    /// Closes the innermost scope, reporting its bindings that are never read.
+    fn pop_scope(&mut self) {
+        let scope = self.m_scopes.pop().unwrap_or_default();
+        for binding in scope.iter().filter(|binding| !binding.m_read) {
+            let kind = if binding.m_constant { "Constant" } else { "Variable" };
+            self.emit(Lint::UnusedVariables, format!("{kind} `{}` is assigned but never read.", binding.m_name), binding.m_span);
+        }
+    }
+
+    fn check_stmt(&mut self, stmt: &NodeStmt) -> bool {
+        match stmt {
+            NodeStmt::Exit(exit) => {
+                self.check_expr(&exit.expr);
+                true
+            }
+            NodeStmt::ID(var) => {
+                self.check_assignment(var);
+                false
+            }
+            NodeStmt::Scope(scope) => self.check_block(&scope.stmts),
+            NodeStmt::Index(assignment) => {
+                self.check_expr(&assignment.value);
+                self.check_expr(&assignment.element.index);
+                false
+            }
+            NodeStmt::Field(assignment) => {
+                self.check_expr(&assignment.value);
+                false
+            }
+            NodeStmt::For(node) => {
+                self.check_for(node);
+                false
+            }
+            NodeStmt::Free(node) => {
+                self.check_expr(&node.pointer);
+                false
+            }
+            NodeStmt::Call(call) => {
+                for argument in &call.arguments {
+                    self.check_expr(argument);
+                }
+                false
+            }
+            NodeStmt::Import(_) | NodeStmt::Struct(_) | NodeStmt::Enum(_) | NodeStmt::Extern(_) => false,
+        }
+    }
+
+    /// The loop variable only exists in the body. The body may run zero times, so the loop never counts as reaching
+    /// an `exit`.
+    fn check_for(&mut self, node: &NodeFor) {
+        self.check_expr(&node.start);
+        self.check_expr(&node.end);
+        self.m_scopes.push(Vec::new());
+        if let Token::ID { name, span } = &node.variable {
+            self.declare(name, *span, false);
+        }
+        self.check_block(&node.body.stmts);
+        self.pop_scope();
+    }
+
+    /// A declaration is reported when it hides a variable of an outer scope. An assignment without declaration
+    /// declares nothing.
+    fn check_assignment(&mut self, var: &NodeVariableAssignment) {
+        self.check_expr(&var.value);
+        let (Token::ID { name, span }, Some(declaration)) = (&var.variable, var.declaration) else { return };
+        let current_scope = self.m_scopes.last().expect("The linter always has an open scope");
+        let outer_scopes = &self.m_scopes[..self.m_scopes.len() - 1];
+        let outer = outer_scopes.iter().flatten().rev().find(|binding| &binding.m_name == name);
+        if let Some(outer) = outer.filter(|_| !current_scope.iter().any(|binding| &binding.m_name == name)) {
+            let message = match self.m_loggers.get(outer.m_span.m_file_id) {
+                Some(logger) if outer.m_span.m_file_id == span.m_file_id => {
+                    let (line, _) = logger.lock().unwrap().get_line_col(outer.m_span);
+                    format!("`{name}` shadows a variable declared on line {}.", line + 1)
+                }
+                _ => format!("`{name}` shadows a variable declared in an imported file.")
+            };
+            self.emit(Lint::Shadowing, message, *span);
+        }
+        self.declare(name, *span, matches!(declaration, Declaration::Const { .. }));
+    }
+
+    fn declare(&mut self, name: &str, span: Span, constant: bool) {
+        let binding = Binding { m_name: name.to_string(), m_span: span, m_read: false, m_constant: constant };
+        self.m_scopes.last_mut().expect("The linter always has an open scope").push(binding);
+    }
+
+    /// Arms naming a variant that is already matched, or following a `_` arm, are never taken. The bindings of an arm
+    /// are only visible in its value.
+    fn check_match(&mut self, node: &NodeMatch) {
+        if let Token::ID { name, .. } = &node.variable {
+            self.read_variable(name);
+        }
+        let mut matched: Vec<&str> = Vec::new();
+        let mut wildcard = false;
+        for arm in &node.arms {
+            match &arm.variant {
+                Token::ID { name: variant, span: variant_span } => {
+                    if wildcard || matched.contains(&variant.as_str()) {
+                        self.emit(Lint::UnreachableCode, format!("Unreachable arm, `{variant}` is already matched."), *variant_span);
+                    }
+                    matched.push(variant);
+                }
+                variant => {
+                    if wildcard {
+                        self.emit(Lint::UnreachableCode, "Unreachable arm, every variant is already matched.".to_string(), variant.get_span());
+                    }
+                    wildcard = true;
+                }
+            }
+            self.m_scopes.push(Vec::new());
+            for binding in &arm.bindings {
+                if let Token::ID { name, span } = binding {
+                    self.declare(name, *span, false);
+                }
+            }
+            self.check_expr(&arm.value);
+            self.pop_scope();
+        }
+    }
+
+    fn check_expr(&mut self, expr: &NodeArithmeticExpr) {
+        match expr {
+            NodeArithmeticExpr::Base(base) => self.check_base(base),
+            NodeArithmeticExpr::Operation(operation) => {
+                if let Some(value) = Self::fold_boolean(operation) {
+                    self.emit(Lint::ConstantCondition, format!("Condition always evaluates to `{value}`."), operation.get_span());
+                }
+                self.check_operation(operation);
+            }
+            NodeArithmeticExpr::Array(array) => {
+                for element in &array.elements {
+                    self.check_expr(element);
+                }
+            }
+            NodeArithmeticExpr::Struct(literal) => {
+                for (_, value) in &literal.fields {
+                    self.check_expr(value);
+                }
+            }
+            NodeArithmeticExpr::Variant(literal) => {
+                for value in &literal.values {
+                    self.check_expr(value);
+                }
+            }
+            NodeArithmeticExpr::Match(node) => self.check_match(node),
+        }
+    }
+
+    fn check_operation(&mut self, operation: &NodeArithmeticOperation) {
+        // Unary operations hold their only operand on both sides
+        let operands = if operation.op.is_unary() { vec![&operation.lhs] } else { vec![&operation.lhs, &operation.rhs] };
+        for operand in operands {
+            match operand {
+                Left(inner) => self.check_operation(inner),
+                Right(base) => self.check_base(base),
+            }
+        }
+    }
+
+    fn check_base(&mut self, base: &NodeBaseExpr) {
+        match base {
+            NodeBaseExpr::ID(Token::ID { name, .. }) | NodeBaseExpr::Len(NodeLen { variable: Token::ID { name, .. }, .. }) => {
+                self.read_variable(name);
+            }
+            NodeBaseExpr::Field(field) => {
+                if let Token::ID { name, .. } = &field.variable {
+                    self.read_variable(name);
+                }
+            }
+            NodeBaseExpr::Index(NodeIndex { variable, index, .. }) => {
+                if let Token::ID { name, .. } = variable {
+                    self.read_variable(name);
+                }
+                self.check_expr(index);
+            }
+            NodeBaseExpr::Alloc(node) => self.check_expr(&node.size),
+            NodeBaseExpr::Builtin(NodeBuiltin { argument: Some(argument), .. }) => self.check_expr(argument),
+            NodeBaseExpr::Call(call) => {
+                for argument in &call.arguments {
+                    self.check_expr(argument);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Marks the nearest variable of that name as read. Undeclared variables are reported by the `Checker`.
+    fn read_variable(&mut self, name: &str) {
+        let binding = self.m_scopes.iter_mut().rev()
+            .find_map(|scope| scope.iter_mut().find(|binding| binding.m_name == name));
+        if let Some(binding) = binding {
+            binding.m_read = true;
+        }
+    }
+
+    /// Evaluates a boolean operation made only of literals, returning `None` if it depends on a variable.
+    fn fold_boolean(operation: &NodeArithmeticOperation) -> Option<bool> {
+        fn fold_operand(operand: &Either<Box<NodeArithmeticOperation>, NodeBaseExpr>) -> Option<bool> {
+            match operand {
+                Left(inner) => Linter::fold_boolean(inner),
+                Right(NodeBaseExpr::Bool(Token::Boolean { value, .. })) => Some(*value),
+                Right(_) => None,
+            }
+        }
+        let lhs = fold_operand(&operation.lhs)?;
+        match operation.op {
+            Operator::Not { .. } => Some(!lhs),
+            Operator::And { .. } => Some(lhs & fold_operand(&operation.rhs)?),
+            Operator::Or { .. } => Some(lhs | fold_operand(&operation.rhs)?),
+            Operator::Xor { .. } => Some(lhs ^ fold_operand(&operation.rhs)?),
+            _ => None
+        }
+    }
+
+    fn emit(&self, lint: Lint, message: String, span: Span) {
+        let (Some(config), Some(logger)) = (self.m_configs.get(span.m_file_id), self.m_loggers.get(span.m_file_id)) else {
+            return;
+        };
+        let level = config.get_level(lint);
+        let message = format!("{message} [{level}({})]", lint.name());
+        let mut logger = logger.lock().unwrap();
+        match level {
+            LintLevel::Allow => {}
+            LintLevel::Warn => logger.log_warning(lint.name(), message, span),
+            LintLevel::Deny => logger.log_error_message(lint.name(), message, span),
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod test_linter{
+    use crate::compiler::logger::Logger;
+    use crate::compiler::parser::Parser;
+    use crate::compiler::tokenizer::Tokenizer;
+    use super::*;
+
+    fn lint(code: &str, config: &LintConfig) -> Arc<Mutex<ParserLogger>> {
+        let logger = Arc::new(Mutex::new(ParserLogger::new("".to_string(), code.to_string())));
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.tokenize(code);
+        let prog = Parser::new(tokenizer.get_tokens(), logger.clone()).parse().expect("The test program should parse");
+        Linter::new(std::slice::from_ref(config), std::slice::from_ref(&logger)).check(&prog);
+        logger
+    }
+
+    fn warning_count(code: &str) -> usize {
+        lint(code, &LintConfig::new()).lock().unwrap().get_warnings().len()
+    }
+
+    #[test]
+    fn test_clean_program(){
+        assert_eq!(warning_count("let x = 1\nexit(x)"), 0);
+    }
+
+    #[test]
+    fn test_unused_variable(){
+        assert_eq!(warning_count("let x = 1\nlet y = 2\nexit(x)"), 1);
+        assert_eq!(warning_count("let x = 1\n{\nlet y = x\n}\nexit(x)"), 1);
+    }
+
+    #[test]
+    fn test_shadowing(){
+        assert_eq!(warning_count("let x = 1\n{\nlet x = x + 1\nexit(x)\n}"), 1);
+        let logger = lint("\nlet x = 1\n{\nlet x = x + 1\nexit(x)\n}", &LintConfig::new());
+        let (message, span) = logger.lock().unwrap().get_warnings()[0].clone();
+        assert!(message.starts_with("`x` shadows a variable declared on line 2."));
+        assert_eq!(span, Span::new(0, 17, 18));
+        assert_eq!(warning_count("var x = 1\nx = 2\nexit(x)"), 0);
+    }
+
+    #[test]
+    fn test_unreachable_code(){
+        assert_eq!(warning_count("exit(0)\nexit(1)\nexit(2)"), 1);
+        assert_eq!(warning_count("{\nexit(0)\n}\nexit(1)"), 1);
+        assert_eq!(warning_count("{\nlet x = 0\n}\nexit(1)"), 1);
+    }
+
+    #[test]
+    fn test_constant_condition(){
+        assert_eq!(warning_count("let x = true && false\nexit(0)\n"), 2);
+        assert_eq!(warning_count("let x = 1 + 2\nexit(x)\n"), 0);
+    }
+
+    #[test]
+    fn test_constants(){
+        assert_eq!(warning_count("const N = 1"), 1);
+        assert_eq!(warning_count("const N = 1\nexit(N)"), 0);
+    }
+
+    #[test]
+    fn test_enums(){
+        assert_eq!(warning_count("enum S { C(int), E }\nlet s = S::E\nexit(match s { E => 1, _ => 2, C(r) => 3 })"), 2);
+    }
+
+    #[test]
+    fn test_for(){
+        // The body may not run, so the program can still reach the end of the loop
+        assert_eq!(warning_count("for i in 0..3 {\nexit(i)\n}\nexit(0)"), 0);
+        assert_eq!(warning_count("for i in 0..3 {\n}\nexit(0)"), 1);
+    }
+
+    #[test]
+    fn test_errors(){
+        // Errors are reported by the checker
+        assert!(lint("x = 1\nexit(y)", &LintConfig::new()).lock().unwrap().get_errors().is_empty());
+    }
+
+    #[test]
+    fn test_lint_levels(){
+        let mut config = LintConfig::new();
+        config.set_level(Lint::UnusedVariables, LintLevel::Allow);
+        let logger = lint("let x = 1", &config);
+        assert!(logger.lock().unwrap().get_warnings().is_empty());
+
+        config.set_level(Lint::UnusedVariables, LintLevel::Deny);
+        let logger = lint("let x = 1", &config);
+        assert!(logger.lock().unwrap().failed_parsing());
+    }
+}
\ No newline at end of file
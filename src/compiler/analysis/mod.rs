@@ -0,0 +1,17 @@
+mod checker;
+mod lint;
+mod linter;
+
+pub use checker::{
+    Checker
+};
+
+pub use lint::{
+    Lint,
+    LintConfig,
+    LintLevel
+};
+
+pub use linter::{
+    Linter
+};
\ No newline at end of file
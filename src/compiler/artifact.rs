@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+use crate::compiler::diagnostic::Diagnostic;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum OutputKind {
+    /// Only the assembly text is produced, nothing is written to disk.
+    Assembly,
+    /// The assembly is assembled into an object file.
+    Object,
+    /// The object file is linked into an executable.
+    #[default]
+    Executable,
+    /// The parsed program is dumped as JSON, see `AstJson`.
+    AstJson,
+    /// The parsed program is dumped as an S-expression, see `AstSexpr`.
+    AstSexpr,
+    /// The parsed program is drawn as a PlantUML mind map, see `AstPlantUml`.
+    AstPlantUml,
+    /// The parsed program is drawn as a Graphviz graph, see `AstDot`.
+    AstDot,
+}
+
+impl OutputKind {
+    /// Maps the values of `--emit=<kind>` to their output kind.
+    pub fn from_name(name: &str) -> Option<OutputKind> {
+        match name {
+            "asm" => Some(OutputKind::Assembly),
+            "obj" => Some(OutputKind::Object),
+            "exe" => Some(OutputKind::Executable),
+            "ast-json" => Some(OutputKind::AstJson),
+            "ast-sexpr" => Some(OutputKind::AstSexpr),
+            "ast-plantuml" => Some(OutputKind::AstPlantUml),
+            "ast-dot" => Some(OutputKind::AstDot),
+            _ => None
+        }
+    }
+}
+
+/// Result of a successful compilation.
+#[derive(Clone, Debug)]
+pub struct Artifact {
+    pub m_kind: OutputKind,
+    /// Generated assembly, or the AST dump for the AST outputs.
+    pub m_output: String,
+    /// File written for object and executable outputs.
+    pub m_path: Option<PathBuf>,
+    pub m_warnings: Vec<Diagnostic>
+}
\ No newline at end of file
@@ -0,0 +1,129 @@
+use std::fmt;
+use std::sync::Arc;
+use ariadne::{Color, Config, IndexType, Label, Report, ReportKind, Source};
+use crate::compiler::span::{LineIndex, Span};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// An error or a warning produced while compiling. Diagnostics about the source point at a span of the
+/// file they come from, while the ones about the toolchain (assembler, linker, ...) have no span.
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub m_severity: Severity,
+    /// Error code (`E0001`, ...) or lint name.
+    pub m_code: Option<&'static str>,
+    pub m_message: String,
+    pub m_file_name: String,
+    pub m_span: Option<Span>,
+    m_source: Arc<str>
+}
+
+impl Diagnostic {
+    pub fn new(m_severity: Severity, m_message: String, m_file_name: String, m_span: Span, m_source: Arc<str>) -> Self {
+        Diagnostic { m_severity, m_code: None, m_message, m_file_name, m_span: Some(m_span), m_source }
+    }
+
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.m_code = Some(code);
+        self
+    }
+
+    /// Error that isn't tied to a source file.
+    pub fn error(m_message: String) -> Self {
+        Diagnostic { m_severity: Severity::Error, m_code: None, m_message, m_file_name: String::new(), m_span: None, m_source: Arc::from("") }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.m_severity == Severity::Error
+    }
+
+    /// One-based line and column of the start of the span.
+    pub fn get_location(&self) -> Option<(usize, usize)> {
+        self.get_line_col(self.m_span?.m_start)
+    }
+
+    /// One-based line and column just after the end of the span.
+    pub fn get_end_location(&self) -> Option<(usize, usize)> {
+        self.get_line_col(self.m_span?.m_end)
+    }
+
+    /// Prints the diagnostic on stderr, with the source code it points at.
+    pub fn report(&self) {
+        let Some(span) = self.m_span else {
+            eprintln!("{self}");
+            return;
+        };
+        let (kind, color) = match self.m_severity {
+            Severity::Error => (ReportKind::Error, Color::Red),
+            Severity::Warning => (ReportKind::Warning, Color::Yellow),
+        };
+        let file_name = self.m_file_name.as_str();
+        let mut report = Report::build(kind, (file_name, span.range()))
+            .with_config(Config::default().with_index_type(IndexType::Byte));
+        if let Some(code) = self.m_code {
+            report = report.with_code(code);
+        }
+        report
+            .with_message(&self.m_message)
+            .with_label(
+                Label::new((file_name, span.range()))
+                    .with_message(&self.m_message)
+                    .with_color(color),
+            )
+            .finish()
+            .eprint((file_name, Source::from(self.m_source.clone())))
+            .unwrap();
+    }
+
+    fn get_line_col(&self, offset: usize) -> Option<(usize, usize)> {
+        let (line, col) = LineIndex::new(&self.m_source).line_col(offset);
+        Some((line + 1, col + 1))
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some((line, col)) = self.get_location() {
+            write!(f, "{}:{line}:{col}: ", self.m_file_name)?;
+        }
+        match self.m_code {
+            Some(code) => write!(f, "{}[{code}]: {}", self.m_severity, self.m_message),
+            None => write!(f, "{}: {}", self.m_severity, self.m_message),
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod test_diagnostic{
+    use super::*;
+
+    #[test]
+    fn test_location(){
+        let source: Arc<str> = Arc::from("x = 1\nexit(y)");
+        let diagnostic = Diagnostic::new(Severity::Error, "Unknown variable.".to_string(), "main.brs".to_string(), Span::new(0, 11, 12), source);
+        assert!(diagnostic.is_error());
+        assert_eq!(diagnostic.get_location(), Some((2, 6)));
+        assert_eq!(diagnostic.get_end_location(), Some((2, 7)));
+        assert_eq!(diagnostic.to_string(), "main.brs:2:6: error: Unknown variable.");
+        assert_eq!(diagnostic.with_code("E0100").to_string(), "main.brs:2:6: error[E0100]: Unknown variable.");
+
+        let diagnostic = Diagnostic::error("Command failed.".to_string());
+        assert!(diagnostic.get_location().is_none());
+        assert_eq!(diagnostic.to_string(), "error: Command failed.");
+    }
+}
\ No newline at end of file
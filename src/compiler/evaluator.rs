@@ -0,0 +1,581 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::rc::Rc;
+use either::{Either, Left, Right};
+use crate::compiler::parser::{NodeArithmeticExpr, NodeArithmeticOperation, NodeBaseExpr, NodeField, NodeLen, NodeProgram, NodeStmt, NodeStructLiteral, NodeMatch, NodeAlloc, NodeBuiltin, ResultType};
+use crate::compiler::tokenizer::{Builtin, Operator, Token};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum EvalError {
+    UndefinedVariable(String),
+    DivisionByZero,
+    NegativeExponent,
+    InvalidNumber(String),
+    IndexOutOfBounds { index: i64, length: usize },
+    /// An array, a struct or an enum, given by its name or its literal, used where a single value is expected.
+    NotAValue(String),
+    NotAnArray(String),
+    NotAStruct(String),
+    NotAnEnum(String),
+    /// A field, like `p.z`, that the struct doesn't have or that holds a struct.
+    UnknownField(String),
+    /// An allocation of a negative number of bytes.
+    AllocationFailed,
+    /// A `free` of an address that isn't an allocation still in use.
+    InvalidFree(i64),
+    /// A use of the heap or of the input, named by its keyword, in the value of a constant.
+    RuntimeOnly(&'static str),
+    /// An argument past the last one, the first being the name of the program.
+    ArgumentOutOfBounds { index: i64, count: usize },
+    /// A text given to `parse_int` or read by `read_int` that isn't a decimal integer fitting in 64 bits.
+    InvalidInteger(String),
+    /// An address given to `parse_int` that doesn't hold a string of the input or a string literal.
+    NotAString(i64),
+    /// A string literal in the value of a constant, whose address is only known when the program runs.
+    StringConstant(String),
+    /// A call of a C function, given by its name, which only the compiled program can make.
+    ExternalCall(String),
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::UndefinedVariable(name) => write!(f, "Variable `{name}` is used before being assigned."),
+            EvalError::DivisionByZero => write!(f, "Division by zero."),
+            EvalError::NegativeExponent => write!(f, "Negative exponent."),
+            EvalError::InvalidNumber(value) => write!(f, "Invalid number `{value}`."),
+            EvalError::IndexOutOfBounds { index, length } => write!(f, "Index {index} is out of bounds for an array of length {length}."),
+            EvalError::NotAValue(value) => write!(f, "`{value}` is not a single value."),
+            EvalError::NotAnArray(name) => write!(f, "`{name}` is not an array."),
+            EvalError::NotAStruct(name) => write!(f, "`{name}` is not a struct."),
+            EvalError::NotAnEnum(name) => write!(f, "`{name}` is not an enum."),
+            EvalError::UnknownField(field) => write!(f, "`{field}` is not a field holding a value."),
+            EvalError::AllocationFailed => write!(f, "Allocation failed."),
+            EvalError::InvalidFree(address) => write!(f, "`{address}` is not an allocated address."),
+            EvalError::RuntimeOnly(keyword) => write!(f, "`{keyword}` only runs with the program."),
+            EvalError::ArgumentOutOfBounds { index, count } => write!(f, "Argument {index} is out of bounds for {count} arguments."),
+            EvalError::InvalidInteger(text) => write!(f, "`{text}` is not an integer."),
+            EvalError::NotAString(address) => write!(f, "`{address}` is not the address of a string."),
+            EvalError::StringConstant(literal) => write!(f, "The address of {literal} is only known when the program runs."),
+            EvalError::ExternalCall(name) => write!(f, "`{name}` is a C function, which only runs with the compiled program."),
+        }
+    }
+}
+
+/// Address of the first command-line argument.
+const ARGUMENTS_ADDRESS: i64 = 1 << 47;
+
+/// Reference interpreter of a program, used to check the generated code.
+/// Values are 64 bit signed integers that wrap on overflow, and booleans are 0 or 1.
+/// The result is the value given to the first `exit` reached, or 0 when the program doesn't exit.
+pub struct Evaluator {
+    m_scopes: Vec<HashMap<String, Value>>,
+    m_runtime: Option<Rc<RefCell<Runtime>>>
+}
+
+/// Heap and input of the running program. Allocations are given distinct addresses aligned on 8 bytes, that are
+/// never reused, and the strings of the input are stored at the address returned to the program.
+/// Like the data of the generated code, string literals with the same text share one address, which can't be freed.
+#[derive(Default)]
+struct Runtime {
+    m_next: i64,
+    m_allocations: HashSet<i64>,
+    m_strings: HashMap<i64, String>,
+    m_literals: HashMap<String, i64>,
+    m_arguments: Vec<i64>,
+    m_lines: VecDeque<String>
+}
+
+impl Runtime {
+    fn allocate(&mut self, size: i64) -> i64 {
+        // Like the generated code, each allocation is preceded by a header of 8 bytes
+        let address = self.m_next + 8;
+        self.m_next = address + (size + 7) / 8 * 8;
+        self.m_allocations.insert(address);
+        address
+    }
+
+    fn store_literal(&mut self, value: &str) -> i64 {
+        if let Some(address) = self.m_literals.get(value) {
+            return *address;
+        }
+        let address = self.allocate(value.len() as i64 + 1);
+        self.m_allocations.remove(&address);
+        self.m_strings.insert(address, value.to_string());
+        self.m_literals.insert(value.to_string(), address);
+        address
+    }
+}
+
+#[derive(Clone)]
+enum Value {
+    Single(i64),
+    Array(Vec<i64>),
+    /// Values of the fields, the fields of a nested struct being named like `start.x`.
+    Struct(HashMap<String, i64>),
+    /// Name of the variant of an enum, with its values.
+    Variant(String, Vec<i64>)
+}
+
+impl Evaluator {
+    pub fn evaluate(prog: &NodeProgram) -> Result<i64, EvalError> {
+        Evaluator::evaluate_with_input(prog, &["program".to_string()], "")
+    }
+
+    /// Evaluates the program with its command-line arguments, the first being its name, and its standard input.
+    pub fn evaluate_with_input(prog: &NodeProgram, arguments: &[String], stdin: &str) -> Result<i64, EvalError> {
+        let mut runtime = Runtime::default();
+        for (i, argument) in arguments.iter().enumerate() {
+            // Like on the initial stack, the arguments are far from the heap and can't be freed
+            let address = ARGUMENTS_ADDRESS + 8 * i as i64;
+            runtime.m_strings.insert(address, argument.clone());
+            runtime.m_arguments.push(address);
+        }
+        // Like `read_line`, the line feed and the carriage return before it are left out
+        runtime.m_lines = stdin.split_inclusive('\n').map(|line| {
+            let line = line.strip_suffix('\n').unwrap_or(line);
+            line.strip_suffix('\r').unwrap_or(line).to_string()
+        }).collect();
+        let mut evaluator = Evaluator { m_scopes: vec![HashMap::new()], m_runtime: Some(Rc::new(RefCell::new(runtime))) };
+        Ok(evaluator.evaluate_stmts(&prog.stmts)?.unwrap_or(0))
+    }
+
+    /// Evaluates the value of a constant, where the only variables are the constants declared before it.
+    pub fn evaluate_constant(expr: &NodeArithmeticExpr, constants: &HashMap<String, i64>) -> Result<i64, EvalError> {
+        let constants = constants.iter().map(|(name, value)| (name.clone(), Value::Single(*value))).collect();
+        Evaluator { m_scopes: vec![constants], m_runtime: None }.evaluate_expr(expr)
+    }
+
+    /// Returns the exit code once an `exit` is reached.
+    fn evaluate_stmts(&mut self, stmts: &[NodeStmt]) -> Result<Option<i64>, EvalError> {
+        for stmt in stmts {
+            match stmt {
+                NodeStmt::Exit(exit) => return self.evaluate_expr(&exit.expr).map(Some),
+                NodeStmt::ID(assignment) => {
+                    let value = match &assignment.value {
+                        NodeArithmeticExpr::Array(array) => {
+                            let elements = array.elements.iter().map(|element| self.evaluate_expr(element)).collect::<Result<Vec<_>, _>>()?;
+                            // A single element is repeated to the length of the array
+                            Value::Array(elements.into_iter().cycle().take(array.length).collect())
+                        }
+                        NodeArithmeticExpr::Struct(literal) => Value::Struct(self.evaluate_struct(literal)?),
+                        NodeArithmeticExpr::Variant(literal) => {
+                            let values = literal.values.iter().map(|value| self.evaluate_expr(value)).collect::<Result<Vec<_>, _>>()?;
+                            let Token::ID { name: variant, .. } = &literal.variant else {
+                                unreachable!("Variants are identifiers")
+                            };
+                            Value::Variant(variant.clone(), values)
+                        }
+                        value => Value::Single(self.evaluate_expr(value)?),
+                    };
+                    if let Token::ID { name, .. } = &assignment.variable {
+                        // An assignment overwrites the nearest variable, a declaration adds one to the current scope
+                        let declared = self.m_scopes.iter_mut().rev().find(|scope| scope.contains_key(name));
+                        match declared {
+                            Some(scope) if assignment.declaration.is_none() => scope.insert(name.clone(), value),
+                            _ => self.m_scopes.last_mut().unwrap().insert(name.clone(), value),
+                        };
+                    }
+                }
+                NodeStmt::Scope(scope) => {
+                    self.m_scopes.push(HashMap::new());
+                    let exit_code = self.evaluate_stmts(&scope.stmts)?;
+                    self.m_scopes.pop();
+                    if exit_code.is_some() {
+                        return Ok(exit_code);
+                    }
+                }
+                NodeStmt::Import(_) => {}
+                NodeStmt::Index(assignment) => {
+                    let value = self.evaluate_expr(&assignment.value)?;
+                    let index = self.evaluate_expr(&assignment.element.index)?;
+                    let (name, position) = self.find_element(&assignment.element.variable, index)?;
+                    let Some(Value::Array(elements)) = self.m_scopes.iter_mut().rev().find_map(|scope| scope.get_mut(&name)) else {
+                        unreachable!("The element was found")
+                    };
+                    elements[position] = value;
+                }
+                NodeStmt::Struct(_) | NodeStmt::Enum(_) | NodeStmt::Extern(_) => {}
+                NodeStmt::Call(call) => {
+                    self.evaluate_base(&NodeBaseExpr::Call(call.clone()))?;
+                }
+                NodeStmt::Free(node) => {
+                    let address = self.evaluate_expr(&node.pointer)?;
+                    let mut runtime = self.m_runtime.as_ref().ok_or(EvalError::RuntimeOnly("free"))?.borrow_mut();
+                    if address != 0 && !runtime.m_allocations.remove(&address) {
+                        return Err(EvalError::InvalidFree(address));
+                    }
+                    runtime.m_strings.remove(&address);
+                }
+                NodeStmt::Field(assignment) => {
+                    let path = assignment.field.get_path();
+                    let values: Vec<(String, i64)> = match &assignment.value {
+                        NodeArithmeticExpr::Struct(literal) => {
+                            self.evaluate_struct(literal)?.into_iter().map(|(field, value)| (format!("{path}.{field}"), value)).collect()
+                        }
+                        value => vec![(path, self.evaluate_expr(value)?)],
+                    };
+                    let name = self.find_struct(&assignment.field)?;
+                    let Some(Value::Struct(fields)) = self.m_scopes.iter_mut().rev().find_map(|scope| scope.get_mut(&name)) else {
+                        unreachable!("The struct was found")
+                    };
+                    fields.extend(values);
+                }
+                NodeStmt::For(node) => {
+                    let (start, end) = (self.evaluate_expr(&node.start)?, self.evaluate_expr(&node.end)?);
+                    let mut counter = Some(start);
+                    // Like the generated code, the loop stops when the counter would overflow
+                    while let Some(value) = counter.filter(|value| *value < end || (node.inclusive && *value == end)) {
+                        let mut scope = HashMap::new();
+                        if let Token::ID { name, .. } = &node.variable {
+                            scope.insert(name.clone(), Value::Single(value));
+                        }
+                        self.m_scopes.push(scope);
+                        self.m_scopes.push(HashMap::new());
+                        let exit_code = self.evaluate_stmts(&node.body.stmts);
+                        self.m_scopes.truncate(self.m_scopes.len() - 2);
+                        if let Some(exit_code) = exit_code? {
+                            return Ok(Some(exit_code));
+                        }
+                        counter = value.checked_add(node.get_step());
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn evaluate_expr(&self, expr: &NodeArithmeticExpr) -> Result<i64, EvalError> {
+        match expr {
+            NodeArithmeticExpr::Base(base) => self.evaluate_base(base),
+            NodeArithmeticExpr::Operation(operation) => self.evaluate_operation(operation),
+            NodeArithmeticExpr::Array(array) => Err(EvalError::NotAValue(array.to_string())),
+            NodeArithmeticExpr::Struct(literal) => Err(EvalError::NotAValue(literal.to_string())),
+            NodeArithmeticExpr::Variant(literal) => Err(EvalError::NotAValue(literal.to_string())),
+            NodeArithmeticExpr::Match(node) => self.evaluate_match(node),
+        }
+    }
+
+    /// Value of the first arm matching the variant of the enum or, like the generated code, of the last arm when none does.
+    fn evaluate_match(&self, node: &NodeMatch) -> Result<i64, EvalError> {
+        let Token::ID { name, .. } = &node.variable else {
+            unreachable!("Enums are identifiers")
+        };
+        let Value::Variant(variant, values) = self.find_variable(name)? else {
+            return Err(EvalError::NotAnEnum(name.clone()));
+        };
+        let matching = node.arms.iter().find(|arm| match &arm.variant {
+            Token::ID { name, .. } => name == variant,
+            _ => true,
+        });
+        let Some(arm) = matching.or(node.arms.last()) else {
+            unreachable!("A match has at least one arm")
+        };
+        let bindings = arm.bindings.iter().zip(values).filter_map(|(binding, value)| match binding {
+            Token::ID { name, .. } => Some((name.clone(), Value::Single(*value))),
+            _ => None,
+        }).collect();
+        let mut scopes = self.m_scopes.clone();
+        scopes.push(bindings);
+        Evaluator { m_scopes: scopes, m_runtime: self.m_runtime.clone() }.evaluate_expr(&arm.value)
+    }
+
+    fn evaluate_operand(&self, operand: &Either<Box<NodeArithmeticOperation>, NodeBaseExpr>) -> Result<i64, EvalError> {
+        match operand {
+            Left(operation) => self.evaluate_operation(operation),
+            Right(base) => self.evaluate_base(base),
+        }
+    }
+
+    fn evaluate_operation(&self, operation: &NodeArithmeticOperation) -> Result<i64, EvalError> {
+        let lhs = self.evaluate_operand(&operation.lhs)?;
+        match operation.op {
+            Operator::Not { .. } => return Ok(lhs ^ 1),
+            Operator::BitNot { .. } => return Ok(!lhs),
+            Operator::Negate { .. } => return Ok(lhs.wrapping_neg()),
+            Operator::Cast { .. } if operation.result_type == ResultType::Boolean => return Ok((lhs != 0) as i64),
+            Operator::Cast { .. } => return Ok(lhs),
+            _ => {}
+        }
+        let rhs = self.evaluate_operand(&operation.rhs)?;
+        match operation.op {
+            Operator::Plus { .. } => Ok(lhs.wrapping_add(rhs)),
+            Operator::Minus { .. } => Ok(lhs.wrapping_sub(rhs)),
+            Operator::Multiplication { .. } => Ok(lhs.wrapping_mul(rhs)),
+            // Like the generated code, `i64::MIN / -1` wraps around
+            Operator::Division { .. } if rhs != 0 => Ok(lhs.wrapping_div(rhs)),
+            Operator::Modulus { .. } if rhs != 0 => Ok(lhs.wrapping_rem(rhs)),
+            Operator::Division { .. } | Operator::Modulus { .. } => Err(EvalError::DivisionByZero),
+            Operator::Exponent { .. } => {
+                let exponent = u32::try_from(rhs).map_err(|_| EvalError::NegativeExponent)?;
+                Ok(lhs.wrapping_pow(exponent))
+            }
+            Operator::And { .. } => Ok(lhs & rhs),
+            Operator::Or { .. } => Ok(lhs | rhs),
+            Operator::Xor { .. } | Operator::BitXor { .. } => Ok(lhs ^ rhs),
+            Operator::BitAnd { .. } => Ok(lhs & rhs),
+            Operator::BitOr { .. } => Ok(lhs | rhs),
+            // Like the generated code, only the 6 lowest bits of the count are used
+            Operator::ShiftLeft { .. } => Ok(lhs.wrapping_shl(rhs as u32)),
+            Operator::ShiftRight { .. } => Ok(lhs.wrapping_shr(rhs as u32)),
+            Operator::Not { .. } | Operator::BitNot { .. } | Operator::Negate { .. } | Operator::Cast { .. } | Operator::OpenBracket { .. } | Operator::ClosedBracket { .. }
+            | Operator::Index { .. } | Operator::Len { .. } | Operator::Field { .. } | Operator::Alloc { .. } | Operator::Builtin { .. } | Operator::Call { .. } => unreachable!("Not an operation"),
+        }
+    }
+
+    fn evaluate_base(&self, base: &NodeBaseExpr) -> Result<i64, EvalError> {
+        match base {
+            NodeBaseExpr::Num(Token::Number { value, .. }) => value.parse().map_err(|_| EvalError::InvalidNumber(value.clone())),
+            NodeBaseExpr::Bool(Token::Boolean { value, .. }) => Ok(*value as i64),
+            NodeBaseExpr::ID(Token::ID { name, .. }) => match self.find_variable(name)? {
+                Value::Single(value) => Ok(*value),
+                Value::Array(_) | Value::Struct(_) | Value::Variant(..) => Err(EvalError::NotAValue(name.clone())),
+            },
+            NodeBaseExpr::Index(element) => {
+                let index = self.evaluate_expr(&element.index)?;
+                let (name, position) = self.find_element(&element.variable, index)?;
+                match self.find_variable(&name)? {
+                    Value::Array(elements) => Ok(elements[position]),
+                    Value::Single(_) | Value::Struct(_) | Value::Variant(..) => unreachable!("The element was found"),
+                }
+            }
+            NodeBaseExpr::Len(NodeLen { variable: Token::ID { name, .. }, .. }) => match self.find_variable(name)? {
+                Value::Array(elements) => Ok(elements.len() as i64),
+                Value::Single(_) | Value::Struct(_) | Value::Variant(..) => Err(EvalError::NotAnArray(name.clone())),
+            },
+            NodeBaseExpr::Field(field) => {
+                let name = self.find_struct(field)?;
+                match self.find_variable(&name)? {
+                    Value::Struct(fields) => fields.get(&field.get_path()).copied().ok_or(EvalError::UnknownField(field.to_string())),
+                    Value::Single(_) | Value::Array(_) | Value::Variant(..) => unreachable!("The struct was found"),
+                }
+            }
+            NodeBaseExpr::Alloc(NodeAlloc { size, .. }) => {
+                let size = self.evaluate_expr(size)?;
+                if size < 0 {
+                    return Err(EvalError::AllocationFailed);
+                }
+                let mut runtime = self.m_runtime.as_ref().ok_or(EvalError::RuntimeOnly("alloc"))?.borrow_mut();
+                Ok(runtime.allocate(size))
+            }
+            NodeBaseExpr::Builtin(builtin) => self.evaluate_builtin(builtin),
+            NodeBaseExpr::Str(Token::StringLiteral { value, .. }) => {
+                let mut runtime = self.m_runtime.as_ref().ok_or(EvalError::StringConstant(base.to_string()))?.borrow_mut();
+                Ok(runtime.store_literal(value))
+            }
+            NodeBaseExpr::Call(call) => Err(EvalError::ExternalCall(NodeBaseExpr::ID(call.function.clone()).to_string())),
+            _ => unreachable!("Base expressions only hold numbers, booleans, identifiers and arrays"),
+        }
+    }
+
+    fn evaluate_builtin(&self, builtin: &NodeBuiltin) -> Result<i64, EvalError> {
+        let argument = builtin.argument.as_ref().map(|argument| self.evaluate_expr(argument)).transpose()?;
+        let mut runtime = self.m_runtime.as_ref().ok_or(EvalError::RuntimeOnly(builtin.function.name()))?.borrow_mut();
+        match (builtin.function, argument) {
+            (Builtin::Args, _) => Ok(runtime.m_arguments.len() as i64),
+            (Builtin::Arg, Some(index)) => usize::try_from(index).ok()
+                .and_then(|position| runtime.m_arguments.get(position).copied())
+                .ok_or(EvalError::ArgumentOutOfBounds { index, count: runtime.m_arguments.len() }),
+            (Builtin::ReadLine, _) => Ok(Evaluator::read_line(&mut runtime).unwrap_or(0)),
+            (Builtin::ReadInt, _) => {
+                // The line is freed once parsed, and the end of the input is an empty text
+                let line = Evaluator::read_line(&mut runtime).and_then(|address| {
+                    runtime.m_allocations.remove(&address);
+                    runtime.m_strings.remove(&address)
+                });
+                let line = line.unwrap_or_default();
+                line.parse().map_err(|_| EvalError::InvalidInteger(line))
+            }
+            (Builtin::ParseInt, Some(0)) => Err(EvalError::InvalidInteger(String::new())),
+            (Builtin::ParseInt, Some(address)) => {
+                let text = runtime.m_strings.get(&address).ok_or(EvalError::NotAString(address))?;
+                text.parse().map_err(|_| EvalError::InvalidInteger(text.clone()))
+            }
+            (Builtin::Arg | Builtin::ParseInt, None) => unreachable!("The parser checks the arguments"),
+        }
+    }
+
+    /// Address of the next line of the standard input, allocated like the buffer of the generated code, or `None`
+    /// at the end of the input.
+    fn read_line(runtime: &mut Runtime) -> Option<i64> {
+        let line = runtime.m_lines.pop_front()?;
+        let address = runtime.allocate(4096);
+        runtime.m_strings.insert(address, line);
+        Some(address)
+    }
+
+    fn find_variable(&self, name: &str) -> Result<&Value, EvalError> {
+        self.m_scopes.iter().rev()
+            .find_map(|scope| scope.get(name))
+            .ok_or(EvalError::UndefinedVariable(name.to_string()))
+    }
+
+    /// Name of the array and position of the element, checked against the length of the array.
+    fn find_element(&self, variable: &Token, index: i64) -> Result<(String, usize), EvalError> {
+        let Token::ID { name, .. } = variable else {
+            unreachable!("Arrays are identifiers")
+        };
+        match self.find_variable(name)? {
+            Value::Array(elements) => match usize::try_from(index) {
+                Ok(position) if position < elements.len() => Ok((name.clone(), position)),
+                _ => Err(EvalError::IndexOutOfBounds { index, length: elements.len() }),
+            },
+            Value::Single(_) | Value::Struct(_) | Value::Variant(..) => Err(EvalError::NotAnArray(name.clone())),
+        }
+    }
+
+    /// Name of the struct variable whose field is read or assigned.
+    fn find_struct(&self, field: &NodeField) -> Result<String, EvalError> {
+        let Token::ID { name, .. } = &field.variable else {
+            unreachable!("Structs are identifiers")
+        };
+        match self.find_variable(name)? {
+            Value::Struct(_) => Ok(name.clone()),
+            Value::Single(_) | Value::Array(_) | Value::Variant(..) => Err(EvalError::NotAStruct(name.clone())),
+        }
+    }
+
+    /// Values of the fields of a struct literal, the fields of a nested struct being named like `start.x`.
+    fn evaluate_struct(&self, literal: &NodeStructLiteral) -> Result<HashMap<String, i64>, EvalError> {
+        let mut fields = HashMap::new();
+        for (field, value) in &literal.fields {
+            let Token::ID { name, .. } = field else { continue };
+            match value {
+                NodeArithmeticExpr::Struct(nested) => {
+                    for (path, value) in self.evaluate_struct(nested)? {
+                        fields.insert(format!("{name}.{path}"), value);
+                    }
+                }
+                value => {
+                    fields.insert(name.clone(), self.evaluate_expr(value)?);
+                }
+            }
+        }
+        Ok(fields)
+    }
+}
+
+
+
+#[cfg(test)]
+mod test_evaluator{
+    use std::sync::{Arc, Mutex};
+    use crate::compiler::logger::Logger;
+    use crate::compiler::parser::{Parser, ParserLogger};
+    use crate::compiler::tokenizer::Tokenizer;
+    use super::*;
+
+    fn evaluate(code: &str) -> Result<i64, EvalError> {
+        let logger = Arc::new(Mutex::new(ParserLogger::new("".to_string(), code.to_string())));
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.tokenize(code);
+        Evaluator::evaluate(&Parser::new(tokenizer.get_tokens(), logger).parse().expect("The test program should parse"))
+    }
+
+    #[test]
+    fn test_arithmetic(){
+        assert_eq!(evaluate("exit(((3+5)*2 + (12//4))%7+(18//(6-3))*(2**3-4) + 10)"), Ok(39));
+        assert_eq!(evaluate("exit(2 - 5)"), Ok(-3));
+        assert_eq!(evaluate("exit(2 ** 64)"), Ok(0));
+        assert_eq!(evaluate("let m = 0 - 9223372036854775807 - 1\nexit(m // (0 - 1) + m % (0 - 1))"), Ok(i64::MIN));
+        assert_eq!(evaluate("exit(true ^| false && true)"), Ok(1));
+        assert_eq!(evaluate("exit(!!false)"), Ok(1));
+        assert_eq!(evaluate("exit(~5 & 0xFF | 1 << 8 ^ 0b11)"), Ok(0x1FB));
+        assert_eq!(evaluate("let m = -9223372036854775808\nexit(-m - -(2 * 3) + -2 ** 2)"), Ok(i64::MIN + 10));
+        assert_eq!(evaluate("exit((0 - 16) >> 2 + 1 << 65)"), Ok(-4));
+        assert_eq!(evaluate("let b: bool = 7 as bool\nexit(b as int + (0 as bool) as int)"), Ok(1));
+    }
+
+    #[test]
+    fn test_scopes(){
+        assert_eq!(evaluate("let x = 1\n{\nlet x = 2\nlet y = x\n}\nexit(x)"), Ok(1));
+        assert_eq!(evaluate("var x = 1\n{\nx = 2\nlet y = x\n}\nexit(x)"), Ok(2));
+        assert_eq!(evaluate("let x = 1\n{\nexit(x + 1)\n}\nexit(x)"), Ok(2));
+        assert_eq!(evaluate("let x = 1"), Ok(0));
+    }
+
+    #[test]
+    fn test_arrays(){
+        assert_eq!(evaluate("var a = [1, 2, 3]\na[1] = 5\na[2] += a[1]\nexit(a[0] + a[2] * len(a))"), Ok(25));
+        assert_eq!(evaluate("var a: [int; 4] = [7; 4]\na = [1, 2, 3, 4]\nexit(a[3] + len(a))"), Ok(8));
+    }
+
+    #[test]
+    fn test_structs(){
+        let code = "struct P { x: int, y: int }\nstruct L { a: P, b: P }\nvar l = L { b: P { y: 4, x: 3 }, a: P { x: 1, y: 2 } }\nl.a.x += 10\nl.b = P { x: 5, y: 6 }\nexit(l.a.x + l.a.y * l.b.y)";
+        assert_eq!(evaluate(code), Ok(23));
+        assert_eq!(evaluate("struct P { x: int }\nlet p = P { x: 1 }\nexit(p)"), Err(EvalError::NotAValue("p".to_string())));
+        assert_eq!(evaluate("let x = 1\nexit(x.y)"), Err(EvalError::NotAStruct("x".to_string())));
+    }
+
+    #[test]
+    fn test_enums(){
+        let code = "enum S { C(int), R(int, int), E }\nvar s = S::R(3, 4)\nlet x = match s { R(w, h) => w * h, C(r) => r, E => 0 }\ns = S::E\nexit(match s { C(r) => r, _ => x + 1 })";
+        assert_eq!(evaluate(code), Ok(13));
+        assert_eq!(evaluate("let x = 1\nexit(match x { _ => 0 })"), Err(EvalError::NotAnEnum("x".to_string())));
+    }
+
+    #[test]
+    fn test_for(){
+        assert_eq!(evaluate("var x = 0\nfor i in 0..4 {\nx = x + i\n}\nfor i in 1..=9 step 4 {\nlet y = i\nx = x + y\n}\nexit(x)"), Ok(21));
+        assert_eq!(evaluate("for i in 3..3 {\nexit(1)\n}\nexit(0)"), Ok(0));
+        assert_eq!(evaluate("for i in 0..9 {\nexit(i + 5)\n}\nexit(0)"), Ok(5));
+        assert_eq!(evaluate("for i in 0..2 {\n}\nexit(i)"), Err(EvalError::UndefinedVariable("i".to_string())));
+    }
+
+    #[test]
+    fn test_heap(){
+        assert_eq!(evaluate("let p = alloc(12)
+let q = alloc(0)
+free(p)
+free(q)
+free(0)
+exit((q - p) + p % 8)"), Ok(24));
+        assert_eq!(evaluate("let p = alloc(0 - 1)
+exit(0)"), Err(EvalError::AllocationFailed));
+        assert_eq!(evaluate("let p = alloc(8)
+free(p)
+free(p)
+exit(0)"), Err(EvalError::InvalidFree(8)));
+    }
+
+    #[test]
+    fn test_input(){
+        let logger = Arc::new(Mutex::new(ParserLogger::new("".to_string(), "".to_string())));
+        let evaluate = |code: &str, arguments: &[&str], stdin: &str| {
+            let mut tokenizer = Tokenizer::new();
+            tokenizer.tokenize(code);
+            let prog = Parser::new(tokenizer.get_tokens(), logger.clone()).parse().expect("The test program should parse");
+            Evaluator::evaluate_with_input(&prog, &arguments.iter().map(|argument| argument.to_string()).collect::<Vec<_>>(), stdin)
+        };
+        let code = "let n = read_int()\nlet s = read_line()\nexit(n * parse_int(s) + parse_int(arg(args() - 1)) + read_line())";
+        assert_eq!(evaluate(code, &["program", "-40"], "7\r\n+6\n"), Ok(2));
+        assert_eq!(evaluate("exit(parse_int(arg(2)))", &["program", "1"], ""), Err(EvalError::ArgumentOutOfBounds { index: 2, count: 2 }));
+        assert_eq!(evaluate("exit(read_int())", &["program"], "1 2\n"), Err(EvalError::InvalidInteger("1 2".to_string())));
+        assert_eq!(evaluate("exit(read_int())", &["program"], ""), Err(EvalError::InvalidInteger(String::new())));
+        assert_eq!(evaluate("exit(parse_int(alloc(8)))", &["program"], ""), Err(EvalError::NotAString(8)));
+        assert_eq!(Evaluator::evaluate_constant(&NodeArithmeticExpr::Base(NodeBaseExpr::Builtin(NodeBuiltin {
+            function: Builtin::Args, argument: None, span: Default::default()
+        })), &HashMap::new()), Err(EvalError::RuntimeOnly("args")));
+    }
+
+    #[test]
+    fn test_strings(){
+        assert_eq!(evaluate("let s = \"12\"\nexit(parse_int(s) + (\"12\" - s))"), Ok(12));
+        assert_eq!(evaluate("let s = \"a\"\nfree(s)\nexit(0)"), Err(EvalError::InvalidFree(8)));
+        assert_eq!(evaluate("extern fn rand()\nexit(rand() % 6)"), Err(EvalError::ExternalCall("rand".to_string())));
+        assert_eq!(Evaluator::evaluate_constant(&NodeArithmeticExpr::Base(NodeBaseExpr::Str(Token::StringLiteral {
+            value: "a\n".to_string(), span: Default::default()
+        })), &HashMap::new()), Err(EvalError::StringConstant("\"a\\n\"".to_string())));
+    }
+
+    #[test]
+    fn test_errors(){
+        assert_eq!(evaluate("{\nlet x = 1\n}\nexit(x)"), Err(EvalError::UndefinedVariable("x".to_string())));
+        assert_eq!(evaluate("exit(1 // (2 - 2))"), Err(EvalError::DivisionByZero));
+        assert_eq!(evaluate("exit(2 ** (0 - 1))"), Err(EvalError::NegativeExponent));
+        assert_eq!(evaluate("let a = [1, 2]\nexit(a[2])"), Err(EvalError::IndexOutOfBounds { index: 2, length: 2 }));
+        assert_eq!(evaluate("let a = [1, 2]\nexit(a)"), Err(EvalError::NotAValue("a".to_string())));
+    }
+}
\ No newline at end of file
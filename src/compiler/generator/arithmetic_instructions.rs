@@ -1,12 +1,12 @@
 use std::collections::HashMap;
-use crate::utility::TARGET_ARCH;
-use super::instruction_factory::INSTRUCTION_FACTORY;
+use super::instruction_factory::InstructionFactory;
 
 pub struct ArithmeticInstructions {
     instrs: HashMap<String, ((String, String), String, Vec<String>)>
 }
 impl ArithmeticInstructions {
-    pub fn new() -> Self {
+    /// With `overflow_checks`, the operations that can overflow jump to the overflow trap instead of wrapping around.
+    pub fn new(factory: &InstructionFactory, overflow_checks: bool) -> Self {
         fn operation(
             reg_lhs: &str,
             reg_rhs: &str,
@@ -20,31 +20,57 @@ impl ArithmeticInstructions {
                 )
         }
 
-        let (arith_reg_lhs, arith_reg_rhs, arith_result_reg) = TARGET_ARCH.get_arithmetic_regs();
-        let (exp_reg_lhs, exp_reg_rhs, exp_result_reg) = TARGET_ARCH.get_exponentiation_regs();
-        let modulo_result_reg = TARGET_ARCH.get_modulo_reg();
+        let (arith_reg_lhs, arith_reg_rhs, arith_result_reg) = factory.get_arch().get_arithmetic_regs();
+        let (exp_reg_lhs, exp_reg_rhs, exp_result_reg) = factory.get_arch().get_exponentiation_regs();
+        let modulo_result_reg = factory.get_arch().get_modulo_reg();
+        let (shift_reg_lhs, shift_reg_rhs, shift_result_reg) = factory.get_arch().get_shift_regs();
+        let (addition, subtraction, multiplication, division, exponentiation, negation) = if overflow_checks {
+            (factory.get_checked_addition_instr(), factory.get_checked_subtraction_instr(),
+             factory.get_checked_multiplication_instr(), factory.get_checked_division_instr(),
+             factory.get_checked_exponentiation_instr(), factory.get_checked_negation_instr())
+        } else {
+            (factory.get_addition_instr(), factory.get_subtraction_instr(),
+             factory.get_multiplication_instr(), factory.get_division_instr(),
+             factory.get_exponentiation_instr(), factory.get_negation_instr())
+        };
 
         let map = HashMap::from([
             ("Addition".to_string(),
-            operation(arith_reg_lhs, arith_reg_rhs, arith_result_reg, vec![INSTRUCTION_FACTORY.get_addition_instr()])),
+            operation(arith_reg_lhs, arith_reg_rhs, arith_result_reg, vec![addition])),
             ("Subtraction".to_string(),
-            operation(arith_reg_lhs, arith_reg_rhs, arith_result_reg, vec![INSTRUCTION_FACTORY.get_subtraction_instr()])),
+            operation(arith_reg_lhs, arith_reg_rhs, arith_result_reg, vec![subtraction])),
             ("Multiplication".to_string(),
-            operation(arith_reg_lhs, arith_reg_rhs, arith_result_reg, vec![INSTRUCTION_FACTORY.get_multiplication_instr()])),
+            operation(arith_reg_lhs, arith_reg_rhs, arith_result_reg, vec![multiplication])),
             ("Division".to_string(),
-            operation(arith_reg_lhs, arith_reg_rhs, arith_result_reg, vec![INSTRUCTION_FACTORY.get_division_instr()])),
+            operation(arith_reg_lhs, arith_reg_rhs, arith_result_reg, vec![division])),
             ("Exponentiation".to_string(),
-            operation(exp_reg_lhs, exp_reg_rhs, exp_result_reg, vec![INSTRUCTION_FACTORY.get_exponentiation_instr()])),
+            operation(exp_reg_lhs, exp_reg_rhs, exp_result_reg, vec![exponentiation])),
             ("Modulo".to_string(),
-            operation(arith_reg_lhs, arith_reg_rhs, modulo_result_reg, vec![INSTRUCTION_FACTORY.get_modulo_instr()])),
+            operation(arith_reg_lhs, arith_reg_rhs, modulo_result_reg, vec![factory.get_modulo_instr()])),
             ("And".to_string(),
-            operation(arith_reg_lhs, arith_reg_rhs, arith_result_reg, vec![INSTRUCTION_FACTORY.get_and_instr()])),
+            operation(arith_reg_lhs, arith_reg_rhs, arith_result_reg, vec![factory.get_and_instr()])),
             ("Or".to_string(),
-            operation(arith_reg_lhs, arith_reg_rhs, arith_result_reg, vec![INSTRUCTION_FACTORY.get_or_instr()])),
+            operation(arith_reg_lhs, arith_reg_rhs, arith_result_reg, vec![factory.get_or_instr()])),
             ("Xor".to_string(),
-            operation(arith_reg_lhs, arith_reg_rhs, arith_result_reg, vec![INSTRUCTION_FACTORY.get_xor_instr()])),
+            operation(arith_reg_lhs, arith_reg_rhs, arith_result_reg, vec![factory.get_xor_instr()])),
             ("Not".to_string(),
-            operation(arith_reg_lhs, arith_reg_rhs, arith_result_reg, vec![INSTRUCTION_FACTORY.get_not_instr()])),
+            operation(arith_reg_lhs, arith_reg_rhs, arith_result_reg, vec![factory.get_not_instr()])),
+            ("BitAnd".to_string(),
+            operation(arith_reg_lhs, arith_reg_rhs, arith_result_reg, vec![factory.get_and_instr()])),
+            ("BitOr".to_string(),
+            operation(arith_reg_lhs, arith_reg_rhs, arith_result_reg, vec![factory.get_or_instr()])),
+            ("BitXor".to_string(),
+            operation(arith_reg_lhs, arith_reg_rhs, arith_result_reg, vec![factory.get_xor_instr()])),
+            ("BitNot".to_string(),
+            operation(arith_reg_lhs, arith_reg_rhs, arith_result_reg, vec![factory.get_bitwise_not_instr()])),
+            ("Negation".to_string(),
+            operation(arith_reg_lhs, arith_reg_rhs, arith_result_reg, vec![negation])),
+            ("ShiftLeft".to_string(),
+            operation(shift_reg_lhs, shift_reg_rhs, shift_result_reg, vec![factory.get_shift_left_instr()])),
+            ("ShiftRight".to_string(),
+            operation(shift_reg_lhs, shift_reg_rhs, shift_result_reg, vec![factory.get_shift_right_instr()])),
+            ("ToBool".to_string(),
+            operation(arith_reg_lhs, arith_reg_rhs, arith_result_reg, vec![factory.get_to_bool_instr()])),
             ]
         );
         ArithmeticInstructions{instrs: map}
@@ -61,16 +87,18 @@ impl ArithmeticInstructions {
 #[cfg(test)]
 mod test_arithmetic_instructions {
     use super::*;
-    use crate::utility::Arch;
+    use crate::compiler::generator::INSTRUCTION_FACTORY;
+    use crate::utility::{Arch, TARGET_ARCH};
 
     #[test]
     fn test_init() {
-        let obj = ArithmeticInstructions::new();
+        let obj = ArithmeticInstructions::new(&INSTRUCTION_FACTORY, false);
 
         // Ensure all expected operations exist in the hashmap
         let expected_keys = [
             "Addition", "Subtraction", "Multiplication", "Division",
-            "Exponentiation", "Modulo", "And", "Or", "Xor", "Not"
+            "Exponentiation", "Modulo", "And", "Or", "Xor", "Not",
+            "BitAnd", "BitOr", "BitXor", "BitNot", "Negation", "ShiftLeft", "ShiftRight", "ToBool"
         ];
 
         for key in expected_keys.iter() {
@@ -80,7 +108,7 @@ mod test_arithmetic_instructions {
     
     #[test]
     fn test_get_all_operations() {
-        let obj = ArithmeticInstructions::new();
+        let obj = ArithmeticInstructions::new(&INSTRUCTION_FACTORY, false);
 
         let operations = vec![
             ("Addition", INSTRUCTION_FACTORY.get_addition_instr()),
@@ -93,6 +121,14 @@ mod test_arithmetic_instructions {
             ("Or", INSTRUCTION_FACTORY.get_or_instr()),
             ("Xor", INSTRUCTION_FACTORY.get_xor_instr()),
             ("Not", INSTRUCTION_FACTORY.get_not_instr()),
+            ("BitAnd", INSTRUCTION_FACTORY.get_and_instr()),
+            ("BitOr", INSTRUCTION_FACTORY.get_or_instr()),
+            ("BitXor", INSTRUCTION_FACTORY.get_xor_instr()),
+            ("BitNot", INSTRUCTION_FACTORY.get_bitwise_not_instr()),
+            ("Negation", INSTRUCTION_FACTORY.get_negation_instr()),
+            ("ShiftLeft", INSTRUCTION_FACTORY.get_shift_left_instr()),
+            ("ShiftRight", INSTRUCTION_FACTORY.get_shift_right_instr()),
+            ("ToBool", INSTRUCTION_FACTORY.get_to_bool_instr()),
         ];
 
         for (key, expected_instr) in operations {
@@ -104,11 +140,15 @@ mod test_arithmetic_instructions {
                     },
                     "Modulo" => match TARGET_ARCH {
                         Arch::X86_64 => ("rax", "rbx", "rdx"),
-                        Arch::AArch64 => ("x0", "x1", "x0"),
+                        Arch::AArch64 => ("x1", "x2", "x0"),
+                    },
+                    "ShiftLeft" | "ShiftRight" => match TARGET_ARCH {
+                        Arch::X86_64 => ("rax", "rcx", "rax"),
+                        Arch::AArch64 => ("x1", "x2", "x0"),
                     },
                     _ => match TARGET_ARCH {
                         Arch::X86_64 => ("rax", "rbx", "rax"),
-                        Arch::AArch64 => ("x0", "x1", "x0"),
+                        Arch::AArch64 => ("x1", "x2", "x0"),
                     },
                 };
 
@@ -125,9 +165,20 @@ mod test_arithmetic_instructions {
 
     #[test]
     fn test_get_unknown_operation() {
-        let obj = ArithmeticInstructions::new();
+        let obj = ArithmeticInstructions::new(&INSTRUCTION_FACTORY, false);
         let key = "UnknownOperation".to_string();
 
         assert!(obj.get(&key).is_none(), "Expected None for an unknown operation");
     }
-}
+
+    #[test]
+    fn test_overflow_checks() {
+        let obj = ArithmeticInstructions::new(&INSTRUCTION_FACTORY, true);
+        for key in ["Addition", "Subtraction", "Multiplication", "Division", "Exponentiation", "Negation"] {
+            let (_, _, instructions) = obj.get(&key.to_string()).unwrap();
+            assert!(instructions[0].contains("overflow_trap"), "{key} is not checked");
+        }
+        let (_, _, instructions) = obj.get(&"Modulo".to_string()).unwrap();
+        assert_eq!(instructions[0], INSTRUCTION_FACTORY.get_modulo_instr());
+    }
+}
\ No newline at end of file
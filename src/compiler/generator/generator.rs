@@ -1,9 +1,11 @@
+use std::collections::HashMap;
 use either::Either;
 use either::Either::{Left, Right};
-use crate::compiler::parser::{NodeProgram, NodeStmt, NodeExit, NodeBaseExpr, NodeVariableAssignment, NodeArithmeticExpr, NodeArithmeticOperation, NodeScope};
-use crate::compiler::tokenizer::{Operator, Token};
-use crate::compiler::generator::{ArithmeticInstructions, StackHandler, INSTRUCTION_FACTORY};
-use crate::utility::{Arch, OS, TARGET_ARCH, TARGET_OS};
+use crate::compiler::parser::{NodeProgram, NodeStmt, NodeExit, NodeBaseExpr, NodeVariableAssignment, NodeArithmeticExpr, NodeArithmeticOperation, NodeScope, NodeIndex, NodeIndexAssignment, NodeLen, NodeArrayLiteral, NodeStruct, NodeStructLiteral, NodeFieldAssignment, NodeEnum, NodeVariantLiteral, NodeMatch, NodeFor, NodeFree, NodeBuiltin, NodeExtern, NodeCall, ResultType};
+use crate::compiler::span::{LineIndex, Span};
+use crate::compiler::tokenizer::{Builtin, Operator, Token};
+use crate::compiler::generator::{ArithmeticInstructions, InstructionFactory, StackHandler, INSTRUCTION_FACTORY};
+use crate::utility::{Arch, LinkMode, Target};
 
 pub struct Generator {
     m_prog: NodeProgram,
@@ -11,11 +13,62 @@ pub struct Generator {
     m_stack: StackHandler,
     m_stack_size: usize,
     m_num_exponentials: usize,
+    m_num_divisions: usize,
+    m_num_matches: usize,
+    m_num_loops: usize,
+    m_instructions: InstructionFactory,
+    m_sources: Vec<(String, String, LineIndex)>,
+    m_last_marked_line: Option<(usize, usize)>,
+    m_overflow_checks: bool,
+    m_bounds_checks: bool,
+    m_panic_sites: Vec<(String, String)>,
+    m_reads_arguments: bool,
+    /// Whether the routines are called, by the program or by another routine.
+    m_allocates: bool,
+    m_frees: bool,
+    m_reads_ints: bool,
+    m_reads_lines: bool,
+    m_parses_ints: bool,
+    m_indexes_arguments: bool,
+    /// Number of declared parameters of the C functions, the other arguments of a call are variadic.
+    m_functions: HashMap<String, usize>,
+    /// String literals, stored once each after the code.
+    m_strings: Vec<String>,
 }
 
 impl Generator {
     pub fn new(prog : NodeProgram) -> Self {
-        Generator {m_prog: prog, m_output: "".to_string(), m_stack: StackHandler::new(), m_stack_size: 0, m_num_exponentials: 0}
+        Generator {m_prog: prog, m_output: "".to_string(), m_stack: StackHandler::new(), m_stack_size: 0, m_num_exponentials: 0, m_num_divisions: 0, m_num_matches: 0, m_num_loops: 0, m_instructions: INSTRUCTION_FACTORY, m_sources: Vec::new(), m_last_marked_line: None, m_overflow_checks: false, m_bounds_checks: true, m_panic_sites: Vec::new(), m_reads_arguments: false, m_allocates: false, m_frees: false, m_reads_ints: false, m_reads_lines: false, m_parses_ints: false, m_indexes_arguments: false, m_functions: HashMap::new(), m_strings: Vec::new()}
+    }
+
+    /// Generates code for `target` instead of the platform the compiler is running on.
+    pub fn set_target(&mut self, target: Target) {
+        let link_mode = self.m_instructions.get_link_mode();
+        self.m_instructions = InstructionFactory::new(target.m_arch, target.m_os);
+        self.m_instructions.set_link_mode(link_mode);
+    }
+
+    /// Generates a C `main` function, to be linked with the C library, instead of the `_start` entry point.
+    pub fn set_link_mode(&mut self, link_mode: LinkMode) {
+        self.m_instructions.set_link_mode(link_mode);
+    }
+
+    /// Makes `+`, `-`, `*` and `**` stop the program with an error message when their result overflows,
+    /// instead of wrapping around.
+    pub fn set_overflow_checks(&mut self, overflow_checks: bool) {
+        self.m_overflow_checks = overflow_checks;
+    }
+
+    /// Makes the indexing of an array stop the program with an error message when the index is out of bounds.
+    /// On by default.
+    pub fn set_bounds_checks(&mut self, bounds_checks: bool) {
+        self.m_bounds_checks = bounds_checks;
+    }
+
+    /// Makes the generator annotate the assembly of every statement with the source line it comes from.
+    /// Sources must be added in the order of their file id.
+    pub fn add_line_marker_source(&mut self, file_name: &str, source: &str) {
+        self.m_sources.push((file_name.to_string(), source.to_string(), LineIndex::new(source)));
     }
 
     pub fn get_out_assembly(& self) -> String {
@@ -24,48 +77,473 @@ impl Generator {
     
     pub fn generate(&mut self){
         self.m_output.clear();
-        self.m_output.push_str(INSTRUCTION_FACTORY.get_program_header());
+        self.m_last_marked_line = None;
+        self.m_panic_sites.clear();
+        self.m_reads_arguments = false;
+        self.m_allocates = false;
+        self.m_frees = false;
+        self.m_reads_ints = false;
+        self.m_reads_lines = false;
+        self.m_parses_ints = false;
+        self.m_indexes_arguments = false;
+        self.m_functions.clear();
+        self.m_strings.clear();
+        self.m_output.push_str(self.m_instructions.get_program_header());
         let stmts = self.m_prog.get_stmts();
         for stmt in stmts {
             self.generate_stmt(&stmt);
         }
-        if !self.m_output.contains(INSTRUCTION_FACTORY.get_exit_marker()){
+        if !self.m_output.contains(self.m_instructions.get_exit_marker()){
             // TODO This boilerplate is also for a script that doesn't exit
-            self.m_output.push_str(INSTRUCTION_FACTORY.generate_comment("Boiler plate for empty script").as_str());
+            self.m_output.push_str(self.m_instructions.generate_comment("Boiler plate for empty script").as_str());
             self.m_output.push_str("\t");
-            self.m_output.push_str(INSTRUCTION_FACTORY.get_exit_instr());
+            self.m_output.push_str(&self.m_instructions.get_mov_exit_code_instr(0));
+            self.m_output.push_str("\n\t");
+            self.m_output.push_str(self.m_instructions.get_exit_instr());
             self.m_output.push_str("\n");
         }
+        if self.m_reads_arguments {
+            // The arguments are kept before the initial stack is used
+            let setup = format!("{}\t{}\n", self.m_instructions.generate_comment("Command-line arguments"), self.m_instructions.get_arguments_setup());
+            self.m_output.insert_str(self.m_instructions.get_program_header().len(), &setup);
+        }
+        // Routines are added after the routines calling them
+        let splits_command_line = self.m_reads_arguments && self.m_instructions.splits_command_line();
+        self.m_reads_lines |= self.m_reads_ints;
+        self.m_parses_ints |= self.m_reads_ints;
+        self.m_allocates |= self.m_reads_lines || splits_command_line;
+        self.m_frees |= self.m_reads_lines;
+        if self.m_reads_ints {
+            self.m_output.push_str(self.m_instructions.generate_comment("Integer input").as_str());
+            self.m_output.push_str(self.m_instructions.get_read_int_routine());
+        }
+        if self.m_reads_lines {
+            self.m_output.push_str(self.m_instructions.generate_comment("Line input").as_str());
+            self.m_output.push_str(&self.m_instructions.get_read_line_routine());
+        }
+        if splits_command_line {
+            self.m_output.push_str(self.m_instructions.generate_comment("Command line splitting").as_str());
+            self.m_output.push_str(self.m_instructions.get_command_line_routine());
+        }
+        if self.m_indexes_arguments {
+            self.m_output.push_str(self.m_instructions.generate_comment("Command-line argument").as_str());
+            self.m_output.push_str(self.m_instructions.get_arg_routine());
+            self.m_panic_sites.push(("arg_out_of_bounds".to_string(), "Runtime error: argument index out of bounds".to_string()));
+        }
+        if self.m_parses_ints {
+            self.m_output.push_str(self.m_instructions.generate_comment("Integer parsing").as_str());
+            self.m_output.push_str(self.m_instructions.get_parse_int_routine());
+            self.m_panic_sites.push(("invalid_integer".to_string(), "Runtime error: invalid integer".to_string()));
+        }
+        if self.m_allocates {
+            self.m_output.push_str(self.m_instructions.generate_comment("Heap allocation").as_str());
+            self.m_output.push_str(self.m_instructions.get_alloc_routine());
+            self.m_panic_sites.push(("alloc_failed".to_string(), "Runtime error: allocation failed".to_string()));
+        }
+        if self.m_frees {
+            self.m_output.push_str(self.m_instructions.generate_comment("Heap release").as_str());
+            self.m_output.push_str(self.m_instructions.get_free_routine());
+        }
+        if self.m_output.contains("overflow_trap") {
+            self.m_panic_sites.push(("overflow_trap".to_string(), "Runtime error: integer overflow".to_string()));
+        }
+        self.generate_runtime_errors();
+        self.generate_strings();
+    }
+
+    fn generate_strings(&mut self) {
+        if self.m_strings.is_empty() {
+            return;
+        }
+        self.m_output.push_str(self.m_instructions.generate_comment("String literals").as_str());
+        for (i, string) in self.m_strings.iter().enumerate() {
+            self.m_output.push_str(&self.m_instructions.get_string_data(&format!("string{i}"), string));
+            self.m_output.push('\n');
+        }
+    }
+
+    /// Generates the code reached when a runtime error happens: every site loads its own message and
+    /// jumps to the routine that prints it and stops the program.
+    fn generate_runtime_errors(&mut self) {
+        if self.m_panic_sites.is_empty() {
+            return;
+        }
+        self.m_output.push_str(self.m_instructions.generate_comment("Runtime errors").as_str());
+        let mut messages = String::new();
+        for (i, (label, message)) in self.m_panic_sites.iter().enumerate() {
+            let message_label = format!("panic_message{i}");
+            self.m_output.push_str(&format!("{label}:\n\t{}\n", self.m_instructions.get_panic_call(&message_label, message.len() + 1)));
+            messages.push_str(&self.m_instructions.get_message_data(&message_label, message));
+            messages.push('\n');
+        }
+        self.m_output.push_str(&self.m_instructions.get_panic_routine());
+        self.m_output.push_str(&messages);
+    }
+
+    /// Position of `span` in the source, like `main.brs:3:10`, if the source is known.
+    fn get_source_location(&self, span: Span) -> Option<String> {
+        let (file_name, _, line_index) = self.m_sources.get(span.m_file_id)?;
+        let (line, col) = line_index.line_col(span.m_start);
+        Some(format!("{file_name}:{}:{}", line + 1, col + 1))
     }
     
     fn generate_stmt(&mut self, stmt: &NodeStmt) {
+        if !matches!(stmt, NodeStmt::Scope(_) | NodeStmt::Import(_)) {
+            self.generate_line_marker(stmt.get_span());
+        }
         match stmt {
             NodeStmt::Exit(exit) => self.generate_exit(exit),
             NodeStmt::ID(var) => self.generate_id(var),
             NodeStmt::Scope(scope) => self.generate_scope(scope),
+            // Imports are resolved by the module loader before generating code
+            NodeStmt::Import(_) => {}
+            NodeStmt::Index(assignment) => self.generate_index_assignment(assignment),
+            NodeStmt::Struct(declaration) => self.add_struct(declaration),
+            NodeStmt::Field(assignment) => self.generate_field_assignment(assignment),
+            NodeStmt::Enum(declaration) => self.add_enum(declaration),
+            NodeStmt::For(node) => self.generate_for(node),
+            NodeStmt::Free(node) => self.generate_free(node),
+            NodeStmt::Extern(declaration) => self.add_extern(declaration),
+            NodeStmt::Call(call) => {
+                // The result of a call made for its effects is dropped
+                self.generate_call(call);
+                self.release(1);
+            }
         }
     }
     
+    fn generate_line_marker(&mut self, span: Span) {
+        let Some((file_name, source, line_index)) = self.m_sources.get(span.m_file_id) else { return };
+        let (line, _) = line_index.line_col(span.m_start);
+        if self.m_last_marked_line == Some((span.m_file_id, line)) {
+            return;
+        }
+        let start = line_index.line_start(line).unwrap_or_default();
+        let end = line_index.line_start(line + 1).unwrap_or(source.len());
+        let marker = format!("{file_name}:{}: {}", line + 1, source[start..end].trim());
+        self.m_output.push_str(self.m_instructions.generate_comment(&marker).as_str());
+        self.m_last_marked_line = Some((span.m_file_id, line));
+    }
+
     fn generate_exit(&mut self, exit: &NodeExit){
-        self.m_output.push_str(INSTRUCTION_FACTORY.generate_comment("Exit call").as_str());
-        self.m_output.push_str(INSTRUCTION_FACTORY.generate_comment(&format!("Exit Code = {}", exit.expr)).as_str());
+        self.m_output.push_str(self.m_instructions.generate_comment("Exit call").as_str());
+        self.m_output.push_str(self.m_instructions.generate_comment(&format!("Exit Code = {}", exit.expr)).as_str());
         self.generate_arithmetic_expr(&exit.expr);
-        self.pop(INSTRUCTION_FACTORY.get_exit_reg());
+        self.pop(self.m_instructions.get_exit_reg());
         self.m_output.push_str("\n\t");
-        self.m_output.push_str(INSTRUCTION_FACTORY.get_exit_instr());
+        self.m_output.push_str(self.m_instructions.get_exit_instr());
         self.m_output.push_str("\n");
-        self.m_output.push_str(INSTRUCTION_FACTORY.generate_comment("Exit end call").as_str());
+        self.m_output.push_str(self.m_instructions.generate_comment("Exit end call").as_str());
     }
     
     fn generate_id(&mut self, var: &NodeVariableAssignment) {
-        self.m_output.push_str(INSTRUCTION_FACTORY.generate_comment("VarAssignment").as_str());
+        self.m_output.push_str(self.m_instructions.generate_comment("VarAssignment").as_str());
         if let Token::ID {name, ..}  = &var.variable{
-            self.m_output.push_str(INSTRUCTION_FACTORY.generate_comment(&format!("{var}")).as_str());
+            self.m_output.push_str(self.m_instructions.generate_comment(&format!("{var}")).as_str());
+            if let NodeArithmeticExpr::Array(array) = &var.value {
+                self.generate_array(name, var, array);
+                return;
+            }
+            if let NodeArithmeticExpr::Struct(literal) = &var.value {
+                self.generate_struct(name, var, literal);
+                return;
+            }
+            if let NodeArithmeticExpr::Variant(literal) = &var.value {
+                self.generate_variant(name, var, literal);
+                return;
+            }
             self.generate_arithmetic_expr(&var.value);
-            self.m_stack.add_variable(name.clone(), self.infer_type(&var.value).to_string());
+            // Programs read from JSON aren't linted, so an assignment to an undeclared variable declares it
+            if var.declaration.is_none() && self.m_stack.is_declared(name) {
+                // An assignment stores the value in the slot of the variable it refers to, so that the stack doesn't grow
+                let base_reg = self.m_instructions.get_arch().get_base_reg();
+                self.pop(base_reg);
+                let offset = self.m_stack.get_offset(name.clone());
+                self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_store_variable_instr(offset)));
+            } else {
+                // Programs read from JSON aren't type checked either, their variables of unknown type hold integers
+                let result_type = var.annotation.map(|annotation| annotation.result_type).or(self.infer_type(&var.value));
+                self.m_stack.add_variable(name.clone(), result_type.unwrap_or(ResultType::Numeric));
+            }
         }
     }
     
+    /// Pushes the elements of the array, the first one being the deepest. Assigning a whole array stores
+    /// its elements in the slots of the array it refers to.
+    fn generate_array(&mut self, name: &str, var: &NodeVariableAssignment, array: &NodeArrayLiteral) {
+        let base_reg = self.m_instructions.get_arch().get_base_reg();
+        if let [element] = array.elements.as_slice() {
+            // A repeated element is computed once, and pushed by a loop
+            self.generate_arithmetic_expr(element);
+            self.pop(base_reg);
+            let label = format!("array_fill{}", self.m_num_loops);
+            self.m_num_loops += 1;
+            self.m_output.push_str(&self.m_instructions.get_repeated_push_instr(array.length, &label));
+            for _ in 0..array.length {
+                self.m_stack.push();
+            }
+            self.m_stack_size += array.length * self.slot_size();
+        } else {
+            for element in &array.elements {
+                self.generate_arithmetic_expr(element);
+            }
+        }
+        if var.declaration.is_none() && self.m_stack.get_length(name) == Some(array.length) {
+            for i in (0..array.length).rev() {
+                self.pop(base_reg);
+                let offset = self.m_stack.get_offset(name.to_string()) - 8 * i;
+                self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_store_variable_instr(offset)));
+            }
+        } else {
+            let result_type = var.annotation.map(|annotation| annotation.result_type).or(self.infer_type(&var.value));
+            self.m_stack.add_array(name.to_string(), result_type.unwrap_or(ResultType::Numeric), array.length);
+        }
+    }
+
+    /// Structs only exist when compiling, declaring one only records the slots of its fields.
+    fn add_struct(&mut self, declaration: &NodeStruct) {
+        let Token::ID { name, .. } = &declaration.name else { return };
+        let fields: Vec<(String, String)> = declaration.fields.iter().filter_map(|field| match (&field.name, &field.type_name) {
+            (Token::ID { name, .. }, Token::ID { name: type_name, .. }) => Some((name.clone(), type_name.clone())),
+            _ => None,
+        }).collect();
+        self.m_stack.add_struct(name.clone(), &fields);
+    }
+
+    /// Pushes the fields of the struct in the order of its declaration, the first one being the deepest. Like an array,
+    /// assigning a whole struct stores its fields in the slots of the struct it refers to.
+    fn generate_struct(&mut self, name: &str, var: &NodeVariableAssignment, literal: &NodeStructLiteral) {
+        let Token::ID { name: struct_name, .. } = &literal.name else { return };
+        let size = self.push_struct_literal(literal);
+        self.assign_slots(name, var, struct_name, size);
+    }
+
+    /// Stores the `size` values on top of the stack in the slots of the variable when it is reassigned a value of the
+    /// same struct or enum, or else names them as a new variable.
+    fn assign_slots(&mut self, name: &str, var: &NodeVariableAssignment, type_name: &str, size: usize) {
+        if var.declaration.is_none() && self.m_stack.get_struct(name).as_deref() == Some(type_name) {
+            for i in (0..size).rev() {
+                self.pop(self.m_instructions.get_arch().get_base_reg());
+                let offset = self.m_stack.get_offset(name.to_string()) - 8 * i;
+                self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_store_variable_instr(offset)));
+            }
+        } else {
+            self.m_stack.add_struct_variable(name.to_string(), type_name.to_string());
+        }
+    }
+
+    /// Enums only exist when compiling, declaring one only records the tags of its variants.
+    fn add_enum(&mut self, declaration: &NodeEnum) {
+        let Token::ID { name, .. } = &declaration.name else { return };
+        let variants: Vec<(String, Vec<String>)> = declaration.variants.iter().filter_map(|variant| {
+            let Token::ID { name, .. } = &variant.name else { return None };
+            let types = variant.types.iter().filter_map(|type_name| match type_name {
+                Token::ID { name, .. } => Some(name.clone()),
+                _ => None,
+            }).collect();
+            Some((name.clone(), types))
+        }).collect();
+        self.m_stack.add_enum(name.clone(), &variants);
+    }
+
+    /// Pushes the tag of the variant, then its values padded with 0 up to the slots of the largest variant.
+    fn generate_variant(&mut self, name: &str, var: &NodeVariableAssignment, literal: &NodeVariantLiteral) {
+        let (Token::ID { name: enum_name, .. }, Token::ID { name: variant, .. }) = (&literal.enum_name, &literal.variant) else { return };
+        let base_reg = self.m_instructions.get_arch().get_base_reg();
+        let tag = self.m_stack.get_variant(enum_name, variant).map_or(0, |(tag, _)| tag);
+        self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_mov_number_instr(&tag.to_string())));
+        self.push(base_reg);
+        for value in &literal.values {
+            self.generate_arithmetic_expr(value);
+        }
+        let size = self.m_stack.get_fields(enum_name).len().max(literal.values.len() + 1);
+        for _ in literal.values.len() + 1..size {
+            self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_mov_number_instr("0")));
+            self.push(base_reg);
+        }
+        self.assign_slots(name, var, enum_name, size);
+    }
+
+    /// Pushes the value of the first arm matching the variant of the enum variable. Every arm but the last one compares
+    /// the tag of the variable with the tag of its variant, and the values of the variant are read from their slots.
+    fn generate_match(&mut self, node: &NodeMatch) {
+        let Token::ID { name, .. } = &node.variable else { return };
+        let base_reg = self.m_instructions.get_arch().get_base_reg();
+        let (arm_label, end_label) = (format!("match_arm{}_", self.m_num_matches), format!("match_end{}", self.m_num_matches));
+        self.m_num_matches += 1;
+        let enum_name = self.m_stack.get_struct(name).unwrap_or_default();
+        for (i, arm) in node.arms.iter().enumerate() {
+            let is_last = i + 1 == node.arms.len();
+            let variant = match &arm.variant {
+                Token::ID { name: variant, .. } => self.m_stack.get_variant(&enum_name, variant),
+                _ => None,
+            };
+            if let (Some((tag, _)), Some((offset, _)), false) = (&variant, self.m_stack.get_field(name, "tag"), is_last) {
+                self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_load_variable_instr(offset)));
+                self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_tag_check(*tag, &format!("{arm_label}{}", i + 1))));
+            }
+            self.m_stack.increase_scope_depth();
+            let types = variant.map(|(_, types)| types).unwrap_or_default();
+            for (slot, binding) in arm.bindings.iter().enumerate() {
+                if let Token::ID { name: binding, .. } = binding {
+                    let r#type = types.get(slot).copied().unwrap_or(ResultType::Numeric);
+                    self.m_stack.add_alias(binding.clone(), r#type, name, &slot.to_string());
+                }
+            }
+            self.generate_arithmetic_expr(&arm.value);
+            self.pop(base_reg);
+            self.m_stack.decrease_scope_depth();
+            if !is_last {
+                self.m_output.push_str(&format!("\t{}\n{arm_label}{}:\n", self.m_instructions.get_jump_instr(&end_label), i + 1));
+            }
+        }
+        self.m_output.push_str(&format!("{end_label}:\n"));
+        self.push(base_reg);
+    }
+
+    /// Pushes the values of the fields of a struct literal in the order of their slots and returns how many there are.
+    /// Fields missing from a literal that wasn't linted hold 0.
+    fn push_struct_literal(&mut self, literal: &NodeStructLiteral) -> usize {
+        let Token::ID { name: struct_name, .. } = &literal.name else { return 0 };
+        let fields = self.m_stack.get_fields(struct_name);
+        for (path, _) in &fields {
+            match Self::find_field(literal, path) {
+                Some(value) => self.generate_arithmetic_expr(value),
+                None => {
+                    self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_mov_number_instr("0")));
+                    self.push(self.m_instructions.get_arch().get_base_reg());
+                }
+            }
+        }
+        fields.len()
+    }
+
+    /// Value given to the field `path`, like `start.x`, in a struct literal and the literals nested in it.
+    fn find_field<'a>(literal: &'a NodeStructLiteral, path: &str) -> Option<&'a NodeArithmeticExpr> {
+        let (field, rest) = match path.split_once('.') {
+            Some((field, rest)) => (field, Some(rest)),
+            None => (path, None),
+        };
+        let (_, value) = literal.fields.iter().find(|(name, _)| matches!(name, Token::ID { name, .. } if name == field))?;
+        match (value, rest) {
+            (NodeArithmeticExpr::Struct(nested), Some(rest)) => Self::find_field(nested, rest),
+            (_, None) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn generate_field_assignment(&mut self, assignment: &NodeFieldAssignment) {
+        self.m_output.push_str(self.m_instructions.generate_comment("FieldAssignment").as_str());
+        self.m_output.push_str(self.m_instructions.generate_comment(&format!("{}", NodeStmt::Field(assignment.clone()))).as_str());
+        let Token::ID { name, .. } = &assignment.field.variable else { return };
+        let base_reg = self.m_instructions.get_arch().get_base_reg();
+        let path = assignment.field.get_path();
+        let paths = match &assignment.value {
+            NodeArithmeticExpr::Struct(literal) => {
+                self.push_struct_literal(literal);
+                let Token::ID { name: struct_name, .. } = &literal.name else { return };
+                self.m_stack.get_fields(struct_name).into_iter().map(|(field, _)| format!("{path}.{field}")).collect()
+            }
+            value => {
+                self.generate_arithmetic_expr(value);
+                vec![path]
+            }
+        };
+        for path in paths.iter().rev() {
+            self.pop(base_reg);
+            let Some((offset, _)) = self.m_stack.get_field(name, path) else { continue };
+            self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_store_variable_instr(offset)));
+        }
+    }
+
+    fn generate_index_assignment(&mut self, assignment: &NodeIndexAssignment) {
+        self.m_output.push_str(self.m_instructions.generate_comment("IndexAssignment").as_str());
+        self.m_output.push_str(self.m_instructions.generate_comment(&format!("{}", NodeStmt::Index(assignment.clone()))).as_str());
+        let Token::ID { name, .. } = &assignment.element.variable else { return };
+        self.generate_arithmetic_expr(&assignment.value);
+        self.generate_arithmetic_expr(&assignment.element.index);
+        self.pop(self.m_instructions.get_arch().get_base_reg());
+        self.pop(self.m_instructions.get_element_value_reg());
+        self.generate_bounds_check(name, assignment.element.span);
+        let offset = self.m_stack.get_offset(name.clone());
+        self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_store_element_instr(offset)));
+    }
+
+    /// Makes the program stop when the index in the base register is out of the bounds of the array,
+    /// reporting the position of the element.
+    fn generate_bounds_check(&mut self, name: &str, span: Span) {
+        if !self.m_bounds_checks {
+            return;
+        }
+        let label = format!("index_out_of_bounds{}", self.m_panic_sites.len());
+        let message = match self.get_source_location(span) {
+            Some(location) => format!("Runtime error: index out of bounds at {location}"),
+            None => "Runtime error: index out of bounds".to_string(),
+        };
+        self.m_panic_sites.push((label.clone(), message));
+        let length = self.m_stack.get_length(name).unwrap_or(1);
+        self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_bounds_check(length, &label)));
+    }
+
+    fn generate_free(&mut self, node: &NodeFree) {
+        self.m_output.push_str(self.m_instructions.generate_comment(&format!("{node}")).as_str());
+        self.generate_arithmetic_expr(&node.pointer);
+        self.pop(self.m_instructions.get_arch().get_base_reg());
+        self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_call_instr("runtime_free")));
+        self.m_frees = true;
+    }
+
+    fn add_extern(&mut self, declaration: &NodeExtern) {
+        let Token::ID { name, .. } = &declaration.name else { return eprintln!("Wrong Tokenization") };
+        self.m_functions.insert(name.clone(), declaration.parameters.len());
+        if let Some(instr) = self.m_instructions.get_extern_declaration(name) {
+            self.m_output.push_str(&format!("{instr}\n"));
+        }
+    }
+
+    /// The arguments are pushed in order, then given to the C function as its calling convention expects.
+    fn generate_call(&mut self, call: &NodeCall) {
+        let Token::ID { name, .. } = &call.function else { return eprintln!("Wrong Tokenization") };
+        self.m_output.push_str(self.m_instructions.generate_comment(&format!("{call}")).as_str());
+        for argument in &call.arguments {
+            self.generate_arithmetic_expr(argument);
+        }
+        let arguments = call.arguments.len();
+        // Programs read from JSON aren't linted, so an undeclared function takes all of its arguments as parameters
+        let named = self.m_functions.get(name).copied().unwrap_or(arguments);
+        self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_c_call_instr(name, arguments, named)));
+        self.release(arguments);
+        self.push(self.m_instructions.get_arch().get_base_reg());
+    }
+
+    /// Keeps the counter and the end of the range on the stack for the whole loop, the counter being named by the loop
+    /// variable in the body. The slots pushed by the body are dropped at the end of every iteration.
+    fn generate_for(&mut self, node: &NodeFor) {
+        let (start_label, end_label) = (format!("for_start{}", self.m_num_loops), format!("for_end{}", self.m_num_loops));
+        self.m_num_loops += 1;
+        self.m_output.push_str(self.m_instructions.generate_comment("ForLoop").as_str());
+        self.m_output.push_str(self.m_instructions.generate_comment(&format!("Range = {}{}{}", node.start, if node.inclusive { "..=" } else { ".." }, node.end)).as_str());
+        self.m_stack.increase_scope_depth();
+        self.generate_arithmetic_expr(&node.start);
+        self.generate_arithmetic_expr(&node.end);
+        if let Token::ID { name, .. } = &node.variable {
+            self.m_stack.add_variable_below_top(name.clone(), ResultType::Numeric);
+        }
+        self.m_output.push_str(&format!("{start_label}:\n"));
+        self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_load_variable_instr(8)));
+        self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_range_check(0, node.inclusive, &end_label)));
+        let stack_size = self.m_stack_size;
+        self.generate_scope(&node.body);
+        self.release((self.m_stack_size - stack_size) / self.slot_size());
+        self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_load_variable_instr(8)));
+        self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_step_instr(node.get_step(), &end_label)));
+        self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_store_variable_instr(8)));
+        self.m_output.push_str(&format!("\t{}\n{end_label}:\n", self.m_instructions.get_jump_instr(&start_label)));
+        self.release(2);
+        self.m_stack.decrease_scope_depth();
+        self.m_output.push_str(self.m_instructions.generate_comment("ForLoop end").as_str());
+    }
+
     fn generate_scope(&mut self, scope: &NodeScope){
         self.m_stack.increase_scope_depth();
         let stmts = scope.stmts.clone();
@@ -77,8 +555,12 @@ impl Generator {
     
     fn generate_arithmetic_expr(&mut self, expr: &NodeArithmeticExpr){
         match expr {
-            NodeArithmeticExpr::Base(base) => self.generate_base_expr(&base),
-            NodeArithmeticExpr::Operation(operation) => self.generate_arithmetic_op(operation)
+            NodeArithmeticExpr::Base(base) => self.generate_base_expr(base),
+            NodeArithmeticExpr::Operation(operation) => self.generate_arithmetic_op(operation),
+            NodeArithmeticExpr::Array(_) => unreachable!("Array literals are only assigned"),
+            NodeArithmeticExpr::Struct(_) => unreachable!("Struct literals are only assigned"),
+            NodeArithmeticExpr::Variant(_) => unreachable!("Variant literals are only assigned"),
+            NodeArithmeticExpr::Match(node) => self.generate_match(node),
         }
     }
     
@@ -86,8 +568,8 @@ impl Generator {
         match p_expr {
             NodeBaseExpr::Num(token) => {
                 if let Token::Number { value, .. } = token {
-                    self.m_output.push_str(&format!("\t{}\n", INSTRUCTION_FACTORY.get_mov_number_instr(value)));
-                    self.push(TARGET_ARCH.get_base_reg());
+                    self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_mov_number_instr(value)));
+                    self.push(self.m_instructions.get_arch().get_base_reg());
                 } else {
                     eprintln!("Wrong Tokenization");
                 }
@@ -95,26 +577,87 @@ impl Generator {
             NodeBaseExpr::ID(token) => {
                 if let Token::ID { name, .. } = token {
                     let offset = self.m_stack.get_offset(name.clone());
-                    self.m_output.push_str(INSTRUCTION_FACTORY.generate_comment(&format!("Recuperate {name}'s value from stack\n\t{}", INSTRUCTION_FACTORY.get_load_variable_instr(offset))).as_str());
-                    self.push(TARGET_ARCH.get_base_reg());
+                    self.m_output.push_str(self.m_instructions.generate_comment(&format!("Recuperate {name}'s value from stack\n\t{}", self.m_instructions.get_load_variable_instr(offset))).as_str());
+                    self.push(self.m_instructions.get_arch().get_base_reg());
                 } else {
                     eprintln!("Wrong Tokenization");
                 }
             }
             NodeBaseExpr::Bool(token) => {
                 if let Token::Boolean { value, .. } = token {
-                    self.m_output.push_str(&format!("\t{}\n", INSTRUCTION_FACTORY.get_mov_boolean_instr(*value)));
-                    self.push(TARGET_ARCH.get_base_reg());
+                    self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_mov_boolean_instr(*value)));
+                    self.push(self.m_instructions.get_arch().get_base_reg());
                 } else {
                     eprintln!("Wrong Tokenization");
                 }
             }
+            NodeBaseExpr::Index(NodeIndex { variable: Token::ID { name, .. }, index, span }) => {
+                self.generate_arithmetic_expr(index);
+                self.pop(self.m_instructions.get_arch().get_base_reg());
+                self.generate_bounds_check(name, *span);
+                let offset = self.m_stack.get_offset(name.clone());
+                self.m_output.push_str(self.m_instructions.generate_comment(&format!("Recuperate an element of {name} from stack\n\t{}", self.m_instructions.get_load_element_instr(offset))).as_str());
+                self.push(self.m_instructions.get_arch().get_base_reg());
+            }
+            NodeBaseExpr::Len(NodeLen { variable: Token::ID { name, .. }, .. }) => {
+                // The length of an array is known when compiling
+                let length = self.m_stack.get_length(name).unwrap_or(1);
+                self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_mov_number_instr(&length.to_string())));
+                self.push(self.m_instructions.get_arch().get_base_reg());
+            }
+            NodeBaseExpr::Field(field) => {
+                let Token::ID { name, .. } = &field.variable else { return eprintln!("Wrong Tokenization") };
+                let Some((offset, _)) = self.m_stack.get_field(name, &field.get_path()) else { return eprintln!("Unknown field {field}") };
+                self.m_output.push_str(self.m_instructions.generate_comment(&format!("Recuperate {field}'s value from stack\n\t{}", self.m_instructions.get_load_variable_instr(offset))).as_str());
+                self.push(self.m_instructions.get_arch().get_base_reg());
+            }
+            NodeBaseExpr::Alloc(node) => {
+                self.generate_arithmetic_expr(&node.size);
+                self.pop(self.m_instructions.get_arch().get_base_reg());
+                self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_call_instr("runtime_alloc")));
+                self.m_allocates = true;
+                self.push(self.m_instructions.get_arch().get_base_reg());
+            }
+            NodeBaseExpr::Builtin(node) => self.generate_builtin(node),
+            NodeBaseExpr::Call(call) => self.generate_call(call),
+            NodeBaseExpr::Str(Token::StringLiteral { value, .. }) => {
+                let index = self.m_strings.iter().position(|string| string == value).unwrap_or_else(|| {
+                    self.m_strings.push(value.clone());
+                    self.m_strings.len() - 1
+                });
+                self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_load_address_instr(&format!("string{index}"))));
+                self.push(self.m_instructions.get_arch().get_base_reg());
+            }
+            NodeBaseExpr::Index(_) | NodeBaseExpr::Len(_) | NodeBaseExpr::Str(_) => eprintln!("Wrong Tokenization"),
         }
     }
 
+    /// `args()` reads the number of arguments kept since the start of the program, the other builtins call their
+    /// routine with their argument in the base register.
+    fn generate_builtin(&mut self, node: &NodeBuiltin) {
+        if let Some(argument) = &node.argument {
+            self.generate_arithmetic_expr(argument);
+            self.pop(self.m_instructions.get_arch().get_base_reg());
+        }
+        let instr = match node.function {
+            Builtin::Args => self.m_instructions.get_argument_count_instr().to_string(),
+            Builtin::Arg => self.m_instructions.get_call_instr("runtime_arg"),
+            Builtin::ReadInt => self.m_instructions.get_call_instr("runtime_read_int"),
+            Builtin::ReadLine => self.m_instructions.get_call_instr("runtime_read_line"),
+            Builtin::ParseInt => self.m_instructions.get_call_instr("runtime_parse_int"),
+        };
+        self.m_reads_arguments |= matches!(node.function, Builtin::Args | Builtin::Arg);
+        self.m_indexes_arguments |= node.function == Builtin::Arg;
+        self.m_reads_ints |= node.function == Builtin::ReadInt;
+        self.m_reads_lines |= node.function == Builtin::ReadLine;
+        self.m_parses_ints |= node.function == Builtin::ParseInt;
+        self.m_output.push_str(&format!("\t{instr}\n"));
+        self.push(self.m_instructions.get_arch().get_base_reg());
+    }
+
     //TODO: The multiple similar lines in this method can be refactored by calling a single function that handles everything by accessing the expression
     fn generate_arithmetic_op(&mut self, expr: &NodeArithmeticOperation) {
-        let map = ArithmeticInstructions::new();
+        let map = ArithmeticInstructions::new(&self.m_instructions, self.m_overflow_checks);
         match expr.clone().op{
             Operator::Plus { .. } => {
                 let instr_data = map.get(&"Addition".to_string()).unwrap();
@@ -128,22 +671,48 @@ impl Generator {
                 let instr_data = map.get(&"Multiplication".to_string()).unwrap();
                 self.process_binary_operation(expr.clone().lhs, expr.clone().rhs, "Multiplication" , instr_data);
             }
-            Operator::Division { .. } => {
-                let instr_data = map.get(&"Division".to_string()).unwrap();
-                self.process_binary_operation(expr.clone().lhs, expr.clone().rhs, "Division" , instr_data);
+            Operator::Division { span } => {
+                let instr_data = self.add_zero_divisor_check(map.get(&"Division".to_string()).unwrap(), span);
+                self.process_binary_operation(expr.clone().lhs, expr.clone().rhs, "Division" , &instr_data);
             },
-            Operator::Exponent { .. } => {
-                let instr_data = map.get(&"Exponentiation".to_string()).unwrap();
-                self.process_binary_operation(expr.clone().rhs, expr.clone().lhs, "Exponentiation" , instr_data);
+            Operator::Exponent { span } => {
+                let instr_data = self.add_negative_exponent_check(map.get(&"Exponentiation".to_string()).unwrap(), span);
+                self.process_binary_operation(expr.clone().rhs, expr.clone().lhs, "Exponentiation" , &instr_data);
             }
-            Operator::Modulus { .. } => {
-                let instr_data = map.get(&"Modulo".to_string()).unwrap();
-                self.process_binary_operation(expr.clone().lhs, expr.clone().rhs, "Modulo" , instr_data);
+            Operator::Modulus { span } => {
+                let instr_data = self.add_zero_divisor_check(map.get(&"Modulo".to_string()).unwrap(), span);
+                self.process_binary_operation(expr.clone().lhs, expr.clone().rhs, "Modulo" , &instr_data);
             }
             Operator::Not { .. } => {
                 let instr_data = map.get(&"Not".to_string()).unwrap();
                 self.process_unary_operation(expr.clone().lhs, instr_data);
             }
+            Operator::BitNot { .. } => {
+                let instr_data = map.get(&"BitNot".to_string()).unwrap();
+                self.process_unary_operation(expr.clone().lhs, instr_data);
+            }
+            Operator::Negate { .. } => {
+                let instr_data = map.get(&"Negation".to_string()).unwrap();
+                self.process_unary_operation(expr.clone().lhs, instr_data);
+            }
+            // Booleans are stored as 0 or 1, so only the casts to `bool` need an instruction
+            Operator::Cast { .. } if expr.result_type == ResultType::Boolean => {
+                let instr_data = map.get(&"ToBool".to_string()).unwrap();
+                self.process_unary_operation(expr.clone().lhs, instr_data);
+            }
+            Operator::Cast { .. } => self.process_operand(expr.clone().lhs),
+            Operator::BitAnd { .. } | Operator::BitOr { .. } | Operator::BitXor { .. } | Operator::ShiftLeft { .. } | Operator::ShiftRight { .. } => {
+                let op_str = match expr.op {
+                    Operator::BitAnd { .. } => "BitAnd",
+                    Operator::BitOr { .. } => "BitOr",
+                    Operator::BitXor { .. } => "BitXor",
+                    Operator::ShiftLeft { .. } => "ShiftLeft",
+                    Operator::ShiftRight { .. } => "ShiftRight",
+                    _ => unreachable!(),
+                };
+                let instr_data = map.get(&op_str.to_string()).unwrap();
+                self.process_binary_operation(expr.clone().lhs, expr.clone().rhs, op_str, instr_data);
+            }
             Operator::And { .. } | Operator::Or { .. } | Operator::Xor { .. } => {
                 if let (Some(lhs_expr), Some(rhs_expr)) = (Self::extract_expr(&expr.lhs), Self::extract_expr(&expr.rhs)) {
                     if let Err(err) = self.type_check_logical_operands(&lhs_expr, &rhs_expr) {
@@ -169,6 +738,32 @@ impl Generator {
         }
     }
 
+    /// Makes the division stop the program when its divisor is 0, reporting the position of the operator.
+    fn add_zero_divisor_check(&mut self, instruction_data: &((String, String), String, Vec<String>), span: Span) -> ((String, String), String, Vec<String>) {
+        let label = format!("division_by_zero{}", self.m_panic_sites.len());
+        let message = match self.get_source_location(span) {
+            Some(location) => format!("Runtime error: division by zero at {location}"),
+            None => "Runtime error: division by zero".to_string(),
+        };
+        self.m_panic_sites.push((label.clone(), message));
+        let (regs, res_reg, mut instructions) = instruction_data.clone();
+        instructions.insert(0, self.m_instructions.get_zero_divisor_check(&label));
+        (regs, res_reg, instructions)
+    }
+
+    /// Makes the program stop when the exponent is negative, as the multiplication loop would never end.
+    fn add_negative_exponent_check(&mut self, instruction_data: &((String, String), String, Vec<String>), span: Span) -> ((String, String), String, Vec<String>) {
+        let label = format!("negative_exponent{}", self.m_panic_sites.len());
+        let message = match self.get_source_location(span) {
+            Some(location) => format!("Runtime error: negative exponent at {location}"),
+            None => "Runtime error: negative exponent".to_string(),
+        };
+        self.m_panic_sites.push((label.clone(), message));
+        let (regs, res_reg, mut instructions) = instruction_data.clone();
+        instructions.insert(0, self.m_instructions.get_negative_exponent_check(&label));
+        (regs, res_reg, instructions)
+    }
+
     fn process_unary_operation(
         &mut self,
         operand: Either<Box<NodeArithmeticOperation>, NodeBaseExpr>,
@@ -176,7 +771,7 @@ impl Generator {
     ) {
         self.process_operand(operand);
 
-        let acc_reg = TARGET_ARCH.get_base_reg();
+        let acc_reg = self.m_instructions.get_arch().get_base_reg();
 
         self.pop(acc_reg);
 
@@ -197,26 +792,24 @@ impl Generator {
     ) {
         self.process_operand(lhs);
         self.process_operand(rhs);
-        let (exp_label, done_label) : (Option<String>, Option<String>) = if instruction == "Exponentiation"{
-            let (exp, done) = self.generate_exponential_labels();
-            (Some(exp), Some(done))
-        } else{
-            (None, None)
+        // The loop of the exponentiation and the branches of the division need their own labels
+        let labels = match instruction {
+            "Exponentiation" => Some(("{exp_label}", self.generate_exponential_labels())),
+            "Division" | "Modulo" => Some(("{div_label}", self.generate_division_labels())),
+            _ => None,
         };
-        fn process_instruction(exp_label: Option<String>, done_label: Option<String>, lines: &Vec<String>) -> String {
+        fn process_instruction(labels: Option<(&str, (String, String))>, lines: &[String]) -> String {
             let mut res = lines.join("\n\t");
             res.insert_str(0, "\t");
             res.push_str("\n");
             res = res.replace("\t{", "{");
-            if let Some(exp) = exp_label{
-                if let Some(done) = done_label{
-                    res = res.replace("{exp_label}", &exp).replace("{done_label}", &done);
-                }
+            if let Some((placeholder, (label, done))) = labels {
+                res = res.replace(placeholder, &label).replace("{done_label}", &done);
             }
             res
         }
         let ((reg1, reg2), res_reg, instr) = instruction_data;
-        self.push_pop((reg1.clone(), reg2.clone()), res_reg, &*process_instruction(exp_label, done_label, instr));
+        self.push_pop((reg1.clone(), reg2.clone()), res_reg, &process_instruction(labels, instr));
     }
 
     fn process_operand(
@@ -241,20 +834,34 @@ impl Generator {
     }
     
     fn push(&mut self, reg: &str) {
-        self.m_output.push_str(&INSTRUCTION_FACTORY.get_push_instr(reg));
-        self.m_stack_size += match (TARGET_ARCH, TARGET_OS){
-            (Arch::AArch64, OS::MacOS) => 2,
-            (Arch::AArch64, OS::Windows) => 2,
-            _ => 1
-        }
+        self.m_output.push_str(&self.m_instructions.get_push_instr(reg));
+        self.m_stack.push();
+        self.m_stack_size += self.slot_size();
     }
     
     fn pop(&mut self, reg: &str) {
-        self.m_output.push_str(&INSTRUCTION_FACTORY.get_pop_instr(reg));
-        self.m_stack_size -= match (TARGET_ARCH, TARGET_OS) {
-            (Arch::AArch64, OS::MacOS) => 2,
-            (Arch::AArch64, OS::Windows) => 2,
-            _ => 1
+        self.m_output.push_str(&self.m_instructions.get_pop_instr(reg));
+        self.m_stack.pop();
+        self.m_stack_size -= self.slot_size();
+    }
+
+    /// Drops the `slots` values on top of the stack without reading them.
+    fn release(&mut self, slots: usize) {
+        if slots == 0 {
+            return;
+        }
+        self.m_output.push_str(&format!("\t{}\n", self.m_instructions.get_release_instr(slots)));
+        for _ in 0..slots {
+            self.m_stack.pop();
+        }
+        self.m_stack_size -= slots * self.slot_size();
+    }
+
+    /// Units of `m_stack_size` taken by a pushed value.
+    fn slot_size(&self) -> usize {
+        match self.m_instructions.get_arch() {
+            Arch::X86_64 => 1,
+            Arch::AArch64 => 2,
         }
     }
     
@@ -265,6 +872,12 @@ impl Generator {
         result
     }
 
+    fn generate_division_labels(&mut self) -> (String, String){
+        let result = (format!("division{}", self.m_num_divisions), format!("div_done{}", self.m_num_divisions));
+        self.m_num_divisions += 1;
+        result
+    }
+
     fn extract_expr(e: &Either<Box<NodeArithmeticOperation>, NodeBaseExpr>) -> Option<NodeArithmeticExpr> {
         match e {
             Right(base) => Some(NodeArithmeticExpr::Base(base.clone())),
@@ -273,19 +886,47 @@ impl Generator {
     }
 
     fn type_check_logical_operands(&self, lhs: &NodeArithmeticExpr, rhs: &NodeArithmeticExpr) -> Result<(), String> {
-        if self.infer_type(lhs) == "bool" && self.infer_type(rhs) == "bool" {
+        if self.infer_type(lhs) == Some(ResultType::Boolean) && self.infer_type(rhs) == Some(ResultType::Boolean) {
             Ok(())
         } else {
             Err("Logical operators can only be applied to booleans".to_string())
         }
     }
 
-    fn infer_type<'a>(&'a self, expr: &'a NodeArithmeticExpr) -> &'a str {
+    /// Type of an expression, `None` when it reads a variable that isn't declared.
+    fn infer_type(&self, expr: &NodeArithmeticExpr) -> Option<ResultType> {
         match expr {
-            NodeArithmeticExpr::Base(NodeBaseExpr::Bool(_)) => "bool",
-            NodeArithmeticExpr::Base(NodeBaseExpr::Num(_)) => "num",
-            NodeArithmeticExpr::Base(NodeBaseExpr::ID(_)) => "unknown",
-            NodeArithmeticExpr::Operation(NodeArithmeticOperation { result_type, .. }) => result_type.as_str(),
+            NodeArithmeticExpr::Base(NodeBaseExpr::Bool(_)) => Some(ResultType::Boolean),
+            NodeArithmeticExpr::Base(NodeBaseExpr::Num(_) | NodeBaseExpr::Len(_) | NodeBaseExpr::Alloc(_) | NodeBaseExpr::Builtin(_)
+                | NodeBaseExpr::Call(_) | NodeBaseExpr::Str(_)) => Some(ResultType::Numeric),
+            NodeArithmeticExpr::Base(NodeBaseExpr::ID(Token::ID { name, .. }) | NodeBaseExpr::Index(NodeIndex { variable: Token::ID { name, .. }, .. })) => {
+                self.m_stack.get_type(name)
+            }
+            NodeArithmeticExpr::Base(NodeBaseExpr::Field(field)) => {
+                let Token::ID { name, .. } = &field.variable else { return None };
+                let fields = self.m_stack.get_fields(&self.m_stack.get_struct(name)?);
+                fields.into_iter().find(|(path, _)| *path == field.get_path()).map(|(_, r#type)| r#type)
+            }
+            NodeArithmeticExpr::Base(NodeBaseExpr::ID(_) | NodeBaseExpr::Index(_)) => None,
+            NodeArithmeticExpr::Struct(_) | NodeArithmeticExpr::Variant(_) => None,
+            NodeArithmeticExpr::Match(node) => {
+                // The first arm can return one of its bindings, which are out of scope here
+                let arm = node.arms.first()?;
+                let position = match &arm.value {
+                    NodeArithmeticExpr::Base(NodeBaseExpr::ID(Token::ID { name, .. })) => {
+                        arm.bindings.iter().position(|binding| matches!(binding, Token::ID { name: bound, .. } if bound == name))
+                    }
+                    _ => None
+                };
+                let Some(position) = position else {
+                    return self.infer_type(&arm.value);
+                };
+                let (Token::ID { name, .. }, Token::ID { name: variant, .. }) = (&node.variable, &arm.variant) else { return None };
+                let (_, types) = self.m_stack.get_variant(&self.m_stack.get_struct(name)?, variant)?;
+                types.get(position).copied()
+            }
+            NodeArithmeticExpr::Operation(NodeArithmeticOperation { result_type, .. }) => Some(*result_type),
+            NodeArithmeticExpr::Array(array) => self.infer_type(array.elements.first()?),
         }
     }
 }
@@ -297,8 +938,9 @@ mod test_generator{
     use std::iter::{zip, Zip};
     use std::panic;
     use std::vec::IntoIter;
-    use crate::compiler::parser::ResultType;
+    use crate::compiler::parser::{Declaration, NodeAlloc, ResultType};
     use crate::compiler::span::Span;
+    use crate::utility::{OS, TARGET_ARCH, TARGET_OS};
     use super::*;
     
     fn assert_str_in_out_assembly(gen : &Generator, strs: Vec<&str>) {
@@ -322,12 +964,31 @@ mod test_generator{
             let mut result_type = ResultType::Numeric;
             let mut comment = format!("1 {} 2", op);
             match op{
-                Operator::And {..} | Operator::Or {..} | Operator::Not {..} | Operator::Xor {..} => {
+                Operator::And {..} | Operator::Or {..} | Operator::Xor {..} => {
                     lhs = Right(expr3.clone());
                     rhs = Right(expr4.clone());
                     result_type = ResultType::Boolean;
                     comment = format!("true {} false", op);
                 }
+                Operator::Not {..} => {
+                    lhs = Right(expr3.clone());
+                    rhs = Right(expr3.clone());
+                    result_type = ResultType::Boolean;
+                    comment = format!("{}true", op);
+                }
+                Operator::BitNot {..} => {
+                    rhs = Right(expr1.clone());
+                    comment = format!("{}1", op);
+                }
+                Operator::Negate {..} => {
+                    rhs = Right(expr1.clone());
+                    comment = "-1".to_string();
+                }
+                Operator::Cast {..} => {
+                    rhs = Right(expr1.clone());
+                    result_type = ResultType::Boolean;
+                    comment = "1 as bool".to_string();
+                }
                 _ => {}
             }
             let operation = NodeArithmeticExpr::Operation(NodeArithmeticOperation{
@@ -335,9 +996,10 @@ mod test_generator{
                 rhs,
                 op,
                 result_type,
+                span: Span::default(),
             });
             let var = Token::ID { name: "x".to_string(), span: dummy_span };
-            let id_assignment_stmt = NodeStmt::ID(NodeVariableAssignment{ variable: var, value: operation.clone() });
+            let id_assignment_stmt = NodeStmt::ID(NodeVariableAssignment{ declaration: None, variable: var, annotation: None, value: operation.clone() });
             res_stmts.push(id_assignment_stmt);
             res_exp_out.push(comment);
             res_exp_out.push(instr);
@@ -357,24 +1019,46 @@ mod test_generator{
             Operator::And { span: dummy_span },
             Operator::Or { span: dummy_span },
             Operator::Xor { span: dummy_span },
-            Operator::Not {span: dummy_span}
+            Operator::Not {span: dummy_span},
+            Operator::BitAnd { span: dummy_span },
+            Operator::BitOr { span: dummy_span },
+            Operator::BitXor { span: dummy_span },
+            Operator::BitNot { span: dummy_span },
+            Operator::Negate { span: dummy_span },
+            Operator::ShiftLeft { span: dummy_span },
+            Operator::ShiftRight { span: dummy_span },
+            Operator::Cast { span: dummy_span }
         ];
-        let mut gen = Generator::new(NodeProgram{ stmts: vec![] });
+        let mut gen = Generator::new(NodeProgram{ stmts: vec![], span: Span::default() });
         let exp_labels = gen.generate_exponential_labels();
         let exp_instr = INSTRUCTION_FACTORY.get_exponentiation_instr();
         let exp_instr = exp_instr.replace("{exp_label}", &*exp_labels.0);
         let exp_instr = exp_instr.replace("{done_label}", &*exp_labels.1);
+        let mut division_instr = |instr: &str| {
+            let (div_label, done_label) = gen.generate_division_labels();
+            instr.replace("{div_label}", &div_label).replace("{done_label}", &done_label)
+        };
+        let div_instr = division_instr(INSTRUCTION_FACTORY.get_division_instr());
+        let mod_instr = division_instr(INSTRUCTION_FACTORY.get_modulo_instr());
         let instrs = vec![
             INSTRUCTION_FACTORY.get_addition_instr().to_string(),
             INSTRUCTION_FACTORY.get_subtraction_instr().to_string(),
             INSTRUCTION_FACTORY.get_multiplication_instr().to_string(),
-            INSTRUCTION_FACTORY.get_division_instr().to_string(),
-            INSTRUCTION_FACTORY.get_modulo_instr().to_string(),
+            div_instr,
+            mod_instr,
             exp_instr,
             INSTRUCTION_FACTORY.get_and_instr().to_string(),
             INSTRUCTION_FACTORY.get_or_instr().to_string(),
             INSTRUCTION_FACTORY.get_xor_instr().to_string(),
             INSTRUCTION_FACTORY.get_not_instr().to_string(),
+            INSTRUCTION_FACTORY.get_and_instr().to_string(),
+            INSTRUCTION_FACTORY.get_or_instr().to_string(),
+            INSTRUCTION_FACTORY.get_xor_instr().to_string(),
+            INSTRUCTION_FACTORY.get_bitwise_not_instr().to_string(),
+            INSTRUCTION_FACTORY.get_negation_instr().to_string(),
+            INSTRUCTION_FACTORY.get_shift_left_instr().to_string(),
+            INSTRUCTION_FACTORY.get_shift_right_instr().to_string(),
+            INSTRUCTION_FACTORY.get_to_bool_instr().to_string(),
         ];
         zip(ops, instrs)
     }
@@ -390,13 +1074,12 @@ mod test_generator{
 
     #[test]
     fn test_push_pop() {
-        let mut gen = Generator::new(NodeProgram { stmts: Vec::new() });
+        let mut gen = Generator::new(NodeProgram { stmts: Vec::new(), span: Span::default() });
         
         gen.push(TARGET_ARCH.get_base_reg());
-        match (TARGET_ARCH, TARGET_OS) {
-            (Arch::AArch64, OS::MacOS) => {assert_eq!(gen.m_stack_size, 2);}
-            (Arch::AArch64, OS::Windows) => {assert_eq!(gen.m_stack_size, 2);}
-            _ => {assert_eq!(gen.m_stack_size, 1);}
+        match TARGET_ARCH {
+            Arch::AArch64 => {assert_eq!(gen.m_stack_size, 2);}
+            Arch::X86_64 => {assert_eq!(gen.m_stack_size, 1);}
         }
         gen.pop(TARGET_ARCH.get_base_reg());
         assert_eq!(gen.m_stack_size, 0);
@@ -408,8 +1091,8 @@ mod test_generator{
     fn test_generate_exit() {
         let dummy_span = Span::new(0, 0, 0);
         let expr = NodeArithmeticExpr::Base(NodeBaseExpr::Num(Token::Number { value: "42".to_string(), span: dummy_span }));
-        let exit_stmt = NodeStmt::Exit(NodeExit { expr });
-        let mut gen = Generator::new(NodeProgram { stmts: vec![exit_stmt] });
+        let exit_stmt = NodeStmt::Exit(NodeExit { expr, span: Span::default() });
+        let mut gen = Generator::new(NodeProgram { stmts: vec![exit_stmt], span: Span::default() });
 
         gen.generate();
         let should_contain = vec![
@@ -420,12 +1103,27 @@ mod test_generator{
         assert_str_in_out_assembly(&gen, should_contain);
     }
     
+    #[test]
+    fn test_line_markers() {
+        let expr = NodeArithmeticExpr::Base(NodeBaseExpr::Num(Token::Number { value: "42".to_string(), span: Span::new(0, 14, 16) }));
+        let exit_stmt = NodeStmt::Exit(NodeExit { expr, span: Span::new(0, 9, 17) });
+        let mut gen = Generator::new(NodeProgram { stmts: vec![exit_stmt], span: Span::new(0, 9, 17) });
+        gen.generate();
+        assert!(!gen.get_out_assembly().contains("main.brs"));
+
+        gen.add_line_marker_source("main.brs", "// empty\nexit(42)");
+        gen.generate();
+        assert_str_in_out_assembly(&gen, vec![&INSTRUCTION_FACTORY.generate_comment("main.brs:2: exit(42)")]);
+    }
+
     #[test]
     fn test_no_exit(){
-        let mut gen = Generator::new(NodeProgram { stmts: Vec::new() });
+        let mut gen = Generator::new(NodeProgram { stmts: Vec::new(), span: Span::default() });
         gen.generate();
+        let mov_exit_code = INSTRUCTION_FACTORY.get_mov_exit_code_instr(0);
         let should_contain = vec![
             "Boiler plate for empty script",
+            &mov_exit_code,
             INSTRUCTION_FACTORY.get_exit_instr()
         ];
         assert_str_in_out_assembly(&gen, should_contain);
@@ -436,8 +1134,8 @@ mod test_generator{
         let dummy_span = Span::new(0, 0, 0);
         let expr = NodeArithmeticExpr::Base(NodeBaseExpr::Num(Token::Number { value: "42".to_string(), span: dummy_span }));
         let var = Token::ID { name: "x".to_string(), span: dummy_span };
-        let id_assignment_stmt = NodeStmt::ID(NodeVariableAssignment{ variable: var, value: expr });
-        let mut gen = Generator::new(NodeProgram { stmts: vec![id_assignment_stmt] });
+        let id_assignment_stmt = NodeStmt::ID(NodeVariableAssignment{ declaration: None, variable: var, annotation: None, value: expr });
+        let mut gen = Generator::new(NodeProgram { stmts: vec![id_assignment_stmt], span: Span::default() });
 
         gen.generate();
         let push_reg = TARGET_ARCH.get_base_reg();
@@ -457,17 +1155,33 @@ mod test_generator{
         assert_str_in_out_assembly(&gen, should_contain);
     }
 
+
+    #[test]
+    fn test_generate_reassignment(){
+        let dummy_span = Span::new(0, 0, 0);
+        let expr = NodeArithmeticExpr::Base(NodeBaseExpr::Num(Token::Number { value: "42".to_string(), span: dummy_span }));
+        let var = Token::ID { name: "x".to_string(), span: dummy_span };
+        let declaration = NodeVariableAssignment{ declaration: Some(Declaration::Var { span: dummy_span }), variable: var, annotation: None, value: expr };
+        let assignment = NodeVariableAssignment{ declaration: None, ..declaration.clone() };
+        let mut gen = Generator::new(NodeProgram { stmts: vec![NodeStmt::ID(declaration), NodeStmt::ID(assignment)], span: Span::default() });
+
+        gen.generate();
+        // The assignment pops its value and stores it in the slot of the declared variable
+        assert_eq!(gen.m_stack_size, gen.slot_size());
+        assert_str_in_out_assembly(&gen, vec![INSTRUCTION_FACTORY.get_store_variable_instr(0).as_str()]);
+        assert!(gen.m_stack.is_declared("x"));
+    }
     
     #[test]
     fn test_generate_scope(){
         let dummy_span = Span::new(0, 0, 0);
         let expr = NodeArithmeticExpr::Base(NodeBaseExpr::Num(Token::Number { value: "42".to_string(), span: dummy_span }));
         let var = Token::ID { name: "x".to_string(), span: dummy_span };
-        let id_assignment_stmt = NodeStmt::ID(NodeVariableAssignment{ variable: var, value: expr.clone() });
-        let exit_stmt = NodeStmt::Exit(NodeExit { expr });
-        let scope_stmt = NodeStmt::Scope(NodeScope{stmts: vec![id_assignment_stmt, exit_stmt]});
+        let id_assignment_stmt = NodeStmt::ID(NodeVariableAssignment{ declaration: None, variable: var, annotation: None, value: expr.clone() });
+        let exit_stmt = NodeStmt::Exit(NodeExit { expr, span: Span::default() });
+        let scope_stmt = NodeStmt::Scope(NodeScope{stmts: vec![id_assignment_stmt, exit_stmt], span: Span::default()});
 
-        let mut gen = Generator::new(NodeProgram { stmts: vec![scope_stmt] });
+        let mut gen = Generator::new(NodeProgram { stmts: vec![scope_stmt], span: Span::default() });
 
         gen.generate();
         let should_contain = vec![
@@ -479,11 +1193,141 @@ mod test_generator{
         assert_str_in_out_assembly(&gen, should_contain);
     }
     
+    #[test]
+    fn test_generate_for(){
+        let dummy_span = Span::new(0, 0, 0);
+        let number = |value: &str| NodeArithmeticExpr::Base(NodeBaseExpr::Num(Token::Number { value: value.to_string(), span: dummy_span }));
+        let i = Token::ID { name: "i".to_string(), span: dummy_span };
+        let declaration = NodeVariableAssignment{ declaration: Some(Declaration::Let { span: dummy_span }), variable: Token::ID { name: "x".to_string(), span: dummy_span }, annotation: None, value: NodeArithmeticExpr::Base(NodeBaseExpr::ID(i.clone())) };
+        let body = NodeScope { stmts: vec![NodeStmt::ID(declaration)], span: dummy_span };
+        let node = NodeFor { variable: i, start: number("0"), end: number("10"), inclusive: true, step: Some(Token::Number { value: "2".to_string(), span: dummy_span }), body, span: dummy_span };
+        let mut gen = Generator::new(NodeProgram { stmts: vec![NodeStmt::For(Box::new(node))], span: Span::default() });
+
+        gen.generate();
+        // The slot of `x` is dropped at the end of every iteration, the counter and the end of the range after the loop
+        let range_check = INSTRUCTION_FACTORY.get_range_check(0, true, "for_end0");
+        let step = INSTRUCTION_FACTORY.get_step_instr(2, "for_end0");
+        let (release_body, release_loop) = (INSTRUCTION_FACTORY.get_release_instr(1), INSTRUCTION_FACTORY.get_release_instr(2));
+        let should_contain = vec![
+            "ForLoop",
+            "for_start0:",
+            range_check.as_str(),
+            release_body.as_str(),
+            step.as_str(),
+            "for_end0:",
+            release_loop.as_str(),
+        ];
+        assert_str_in_out_assembly(&gen, should_contain);
+        assert_eq!(gen.m_stack_size, 0);
+        assert!(!gen.m_stack.is_declared("i"));
+    }
+
+    #[test]
+    fn test_generate_repeated_array(){
+        let dummy_span = Span::new(0, 0, 0);
+        let element = NodeArithmeticExpr::Base(NodeBaseExpr::Num(Token::Number { value: "7".to_string(), span: dummy_span }));
+        let array = NodeArrayLiteral { elements: vec![element], length: NodeArrayLiteral::MAX_LENGTH, span: dummy_span };
+        let declaration = NodeVariableAssignment{ declaration: Some(Declaration::Let { span: dummy_span }), variable: Token::ID { name: "a".to_string(), span: dummy_span }, annotation: None, value: NodeArithmeticExpr::Array(array) };
+        let mut gen = Generator::new(NodeProgram { stmts: vec![NodeStmt::ID(declaration)], span: Span::default() });
+
+        gen.generate();
+        // The elements are pushed by a loop rather than one by one
+        let fill = INSTRUCTION_FACTORY.get_repeated_push_instr(NodeArrayLiteral::MAX_LENGTH, "array_fill0");
+        assert_str_in_out_assembly(&gen, vec![fill.as_str()]);
+        assert_eq!(gen.get_out_assembly().matches(&INSTRUCTION_FACTORY.get_push_instr("rax")).count(), 2);
+        assert_eq!(gen.m_stack_size, NodeArrayLiteral::MAX_LENGTH * gen.slot_size());
+        assert_eq!(gen.m_stack.get_length("a"), Some(NodeArrayLiteral::MAX_LENGTH));
+    }
+
+    #[test]
+    fn test_generate_heap(){
+        let dummy_span = Span::new(0, 0, 0);
+        let size = NodeArithmeticExpr::Base(NodeBaseExpr::Num(Token::Number { value: "16".to_string(), span: dummy_span }));
+        let p = Token::ID { name: "p".to_string(), span: dummy_span };
+        let alloc = NodeBaseExpr::Alloc(NodeAlloc { size: Box::new(size), span: dummy_span });
+        let declaration = NodeVariableAssignment{ declaration: Some(Declaration::Let { span: dummy_span }), variable: p.clone(), annotation: None, value: NodeArithmeticExpr::Base(alloc) };
+        let free = NodeFree { pointer: NodeArithmeticExpr::Base(NodeBaseExpr::ID(p)), span: dummy_span };
+        let mut gen = Generator::new(NodeProgram { stmts: vec![NodeStmt::ID(declaration), NodeStmt::Free(free)], span: Span::default() });
+
+        gen.generate();
+        let (call_alloc, call_free) = (INSTRUCTION_FACTORY.get_call_instr("runtime_alloc"), INSTRUCTION_FACTORY.get_call_instr("runtime_free"));
+        let should_contain = vec![
+            call_alloc.as_str(),
+            call_free.as_str(),
+            "runtime_alloc:",
+            "runtime_free:",
+            "alloc_failed:",
+            "Runtime error: allocation failed",
+        ];
+        assert_str_in_out_assembly(&gen, should_contain);
+        assert_eq!(gen.m_stack_size, gen.slot_size());
+
+        // A variable named like a routine doesn't pull it in
+        let variable = Token::ID { name: "runtime_alloc".to_string(), span: dummy_span };
+        let value = NodeArithmeticExpr::Base(NodeBaseExpr::Num(Token::Number { value: "1".to_string(), span: dummy_span }));
+        let declaration = NodeVariableAssignment{ declaration: Some(Declaration::Let { span: dummy_span }), variable: variable.clone(), annotation: None, value };
+        let exit = NodeExit { expr: NodeArithmeticExpr::Base(NodeBaseExpr::ID(variable)), span: dummy_span };
+        let mut gen = Generator::new(NodeProgram { stmts: vec![NodeStmt::ID(declaration), NodeStmt::Exit(exit)], span: Span::default() });
+        gen.generate();
+        assert!(gen.get_out_assembly().contains("runtime_alloc"));
+        assert!(!gen.get_out_assembly().contains("runtime_alloc:") && !gen.get_out_assembly().contains("alloc_failed"));
+    }
+
+    #[test]
+    fn test_generate_input(){
+        let dummy_span = Span::new(0, 0, 0);
+        let builtin = |function: Builtin, argument: Option<NodeBaseExpr>| NodeBaseExpr::Builtin(NodeBuiltin {
+            function, argument: argument.map(|argument| Box::new(NodeArithmeticExpr::Base(argument))), span: dummy_span
+        });
+        let index = NodeBaseExpr::Num(Token::Number { value: "1".to_string(), span: dummy_span });
+        let value = builtin(Builtin::ParseInt, Some(builtin(Builtin::Arg, Some(index))));
+        let exit = NodeExit { expr: NodeArithmeticExpr::Base(value), span: dummy_span };
+        let mut gen = Generator::new(NodeProgram { stmts: vec![NodeStmt::Exit(exit)], span: Span::default() });
+
+        gen.generate();
+        let output = gen.get_out_assembly();
+        // The arguments are kept right at the start of the program
+        let start = INSTRUCTION_FACTORY.get_program_header().len();
+        assert!(output[start..].contains(INSTRUCTION_FACTORY.get_arguments_setup()));
+        let should_contain = vec![
+            "runtime_arg:",
+            "runtime_parse_int:",
+            "arg_out_of_bounds:",
+            "invalid_integer:",
+            "Runtime error: invalid integer",
+        ];
+        assert_str_in_out_assembly(&gen, should_contain);
+        assert!(!output.contains("runtime_read_line"));
+
+        // Windows splits the command line into a heap allocation
+        let count = NodeExit { expr: NodeArithmeticExpr::Base(builtin(Builtin::Args, None)), span: dummy_span };
+        let mut gen = Generator::new(NodeProgram { stmts: vec![NodeStmt::Exit(count)], span: Span::default() });
+        gen.set_target(Target::from_name("x86_64-windows").unwrap());
+        gen.generate();
+        assert_str_in_out_assembly(&gen, vec!["runtime_command_line:", "runtime_alloc:"]);
+        assert!(!gen.get_out_assembly().contains("runtime_arg:"));
+
+        // A variable named like a routine doesn't pull it in
+        let variable = Token::ID { name: "runtime_read_line".to_string(), span: dummy_span };
+        let value = NodeArithmeticExpr::Base(NodeBaseExpr::Num(Token::Number { value: "1".to_string(), span: dummy_span }));
+        let declaration = NodeVariableAssignment{ declaration: Some(Declaration::Let { span: dummy_span }), variable: variable.clone(), annotation: None, value };
+        let exit = NodeExit { expr: NodeArithmeticExpr::Base(NodeBaseExpr::ID(variable)), span: dummy_span };
+        let mut gen = Generator::new(NodeProgram { stmts: vec![NodeStmt::ID(declaration), NodeStmt::Exit(exit)], span: Span::default() });
+        gen.generate();
+        assert!(!gen.get_out_assembly().contains("runtime_read_line:") && !gen.get_out_assembly().contains("runtime_alloc:"));
+
+        let read = NodeVariableAssignment{ declaration: Some(Declaration::Let { span: dummy_span }), variable: Token::ID { name: "n".to_string(), span: dummy_span }, annotation: None, value: NodeArithmeticExpr::Base(builtin(Builtin::ReadInt, None)) };
+        let mut gen = Generator::new(NodeProgram { stmts: vec![NodeStmt::ID(read)], span: Span::default() });
+        gen.generate();
+        assert_str_in_out_assembly(&gen, vec!["runtime_read_int:", "runtime_read_line:", "runtime_parse_int:", "runtime_alloc:", "runtime_free:"]);
+        assert!(!gen.get_out_assembly().contains(INSTRUCTION_FACTORY.get_arguments_setup()));
+    }
+
     #[test]
     fn test_generate_operation_id(){
         let (stmts, should_contain_strs) = create_operations();
         let should_contain = should_contain_strs.iter().map(|string| string.as_str()).collect();
-        let mut gen = Generator::new(NodeProgram { stmts });
+        let mut gen = Generator::new(NodeProgram { stmts, span: Span::default() });
 
         gen.generate();
         assert_str_in_out_assembly(&gen, should_contain);
@@ -494,20 +1338,22 @@ mod test_generator{
         let dummy_span = Span::new(0, 0, 0);
         let expr = Token::Number { value: "42".to_string(), span: dummy_span };
         let var = Token::ID { name: "x".to_string(), span: dummy_span };
-        let id_assignment_stmt = NodeStmt::ID(NodeVariableAssignment{ variable: var.clone(), value: NodeArithmeticExpr::Base(NodeBaseExpr::Num(expr.clone()))});
+        let id_assignment_stmt = NodeStmt::ID(NodeVariableAssignment{ declaration: None, variable: var.clone(), annotation: None, value: NodeArithmeticExpr::Base(NodeBaseExpr::Num(expr.clone()))});
         let nested_expr = NodeArithmeticExpr::Operation(NodeArithmeticOperation{
             lhs: Left(Box::new(NodeArithmeticOperation{
                 lhs: Right(NodeBaseExpr::Num(expr.clone())),
                 rhs: Right(NodeBaseExpr::ID(var.clone())),
                 op: Operator::Plus {span : dummy_span},
                 result_type: ResultType::Numeric,
+                span: Span::default(),
             })),
             rhs: Right(NodeBaseExpr::Num(expr.clone())),
             op: Operator::Plus {span: dummy_span},
             result_type: ResultType::Numeric,
+            span: Span::default(),
         });
-        let id_second_stmt = NodeStmt::ID(NodeVariableAssignment{ variable: var, value: nested_expr });
-        let mut gen = Generator::new(NodeProgram { stmts: vec![id_assignment_stmt, id_second_stmt] });
+        let id_second_stmt = NodeStmt::ID(NodeVariableAssignment{ declaration: None, variable: var, annotation: None, value: nested_expr });
+        let mut gen = Generator::new(NodeProgram { stmts: vec![id_assignment_stmt, id_second_stmt], span: Span::default() });
         
         gen.generate();
         let should_contain = vec![
@@ -517,9 +1363,37 @@ mod test_generator{
         assert_str_in_out_assembly(&gen, should_contain);
     }
 
+    #[test]
+    fn test_aarch64_operands(){
+        let dummy_span = Span::new(0, 0, 0);
+        let number = |value: &str| NodeBaseExpr::Num(Token::Number { value: value.to_string(), span: dummy_span });
+        let declaration = |name: &str, value: &str| NodeStmt::ID(NodeVariableAssignment{
+            declaration: Some(Declaration::Let { span: dummy_span }),
+            variable: Token::ID { name: name.to_string(), span: dummy_span },
+            annotation: None,
+            value: NodeArithmeticExpr::Base(number(value)),
+        });
+        let exit = NodeStmt::Exit(NodeExit { expr: NodeArithmeticExpr::Operation(NodeArithmeticOperation{
+            lhs: Right(NodeBaseExpr::ID(Token::ID { name: "a".to_string(), span: dummy_span })),
+            rhs: Right(number("2")),
+            op: Operator::Minus { span: dummy_span },
+            result_type: ResultType::Numeric,
+            span: Span::default(),
+        }), span: dummy_span });
+        let mut gen = Generator::new(NodeProgram { stmts: vec![declaration("a", "7"), declaration("b", "5"), exit], span: Span::default() });
+        gen.set_target(Target::new(Arch::AArch64, OS::Linux));
+
+        gen.generate();
+        // Every value takes 16 bytes, `a` being below `b`, and the operands are popped into the registers read by `sub`
+        assert_str_in_out_assembly(&gen, vec![
+            "ldr x0, [sp, #24]",
+            "\tldr x2, [sp, #8]\n\tadd sp, sp, #16\n\tldr x1, [sp, #8]\n\tadd sp, sp, #16\n\tsub x0, x1, x2\n",
+        ]);
+    }
+
     #[test]
     fn test_exp_labels(){
-        let mut gen = Generator::new(NodeProgram{stmts: Vec::new()});
+        let mut gen = Generator::new(NodeProgram{stmts: Vec::new(), span: Span::default()});
         assert_eq!(gen.generate_exponential_labels(), ("exponential0".to_string(), "exp_done0".to_string()));
         assert_eq!(gen.m_num_exponentials, 1);
         assert_eq!(gen.generate_exponential_labels(), ("exponential1".to_string(), "exp_done1".to_string()));
@@ -528,23 +1402,21 @@ mod test_generator{
 
     #[test]
     fn test_push(){
-        let mut gen = Generator::new(NodeProgram { stmts: Vec::new() });
+        let mut gen = Generator::new(NodeProgram { stmts: Vec::new(), span: Span::default() });
         let reg = TARGET_ARCH.get_base_reg();
         
         // First push
         gen.push(reg);
-        match (TARGET_ARCH, TARGET_OS) {
-            (Arch::AArch64, OS::MacOS) => {assert_eq!(gen.m_stack_size, 2);}
-            (Arch::AArch64, OS::Windows) => {assert_eq!(gen.m_stack_size, 2);}
-            _ => {assert_eq!(gen.m_stack_size, 1);}
+        match TARGET_ARCH {
+            Arch::AArch64 => {assert_eq!(gen.m_stack_size, 2);}
+            Arch::X86_64 => {assert_eq!(gen.m_stack_size, 1);}
         }
         
         // Second push
         gen.push(reg);
-        match (TARGET_ARCH, TARGET_OS) {
-            (Arch::AArch64, OS::MacOS) => {assert_eq!(gen.m_stack_size, 4);}
-            (Arch::AArch64, OS::Windows) => {assert_eq!(gen.m_stack_size, 4);}
-            _ => {assert_eq!(gen.m_stack_size, 2);}
+        match TARGET_ARCH {
+            Arch::AArch64 => {assert_eq!(gen.m_stack_size, 4);}
+            Arch::X86_64 => {assert_eq!(gen.m_stack_size, 2);}
         }
         
         let x86_expected = format!("\tpush {reg}\n");
@@ -559,13 +1431,12 @@ mod test_generator{
 
     #[test]
     fn test_pop(){
-        let mut gen = Generator::new(NodeProgram { stmts: Vec::new() });
+        let mut gen = Generator::new(NodeProgram { stmts: Vec::new(), span: Span::default() });
         let reg = TARGET_ARCH.get_base_reg();
         gen.push(reg);
-        match (TARGET_ARCH, TARGET_OS) {
-            (Arch::AArch64, OS::MacOS) => {assert_eq!(gen.m_stack_size, 2);}
-            (Arch::AArch64, OS::Windows) => {assert_eq!(gen.m_stack_size, 2);}
-            _ => {assert_eq!(gen.m_stack_size, 1);}
+        match TARGET_ARCH {
+            Arch::AArch64 => {assert_eq!(gen.m_stack_size, 2);}
+            Arch::X86_64 => {assert_eq!(gen.m_stack_size, 1);}
         }
         gen.pop(reg);
         assert_eq!(gen.m_stack_size, 0);
@@ -597,6 +1468,7 @@ mod test_generator{
             rhs: Right(var),
             op: Operator::Plus { span: dummy_span },
             result_type: ResultType::Numeric,
+            span: Span::default(),
         };
         let extracted_operation = Generator::extract_expr(&Left(Box::new(operation.clone())));
         assert_eq!(extracted_operation, Some(NodeArithmeticExpr::Operation(operation)));
@@ -607,27 +1479,30 @@ mod test_generator{
     
     #[test]
     fn test_infer_type(){
-        let gen = Generator::new(NodeProgram{stmts: Vec::new()});
+        let mut gen = Generator::new(NodeProgram{stmts: Vec::new(), span: Span::default()});
         let dummy_span = Span::new(0, 0, 0);
         let num = NodeBaseExpr::Num(Token::Number { value: 1.to_string(), span: dummy_span });
         let bool = NodeBaseExpr::Bool(Token::Boolean { value: true, span: dummy_span });
         let var = NodeBaseExpr::ID(Token::ID { name: "x".to_string(), span: dummy_span });
-        assert_eq!(gen.infer_type(&NodeArithmeticExpr::Base(num.clone())), "num");
-        assert_eq!(gen.infer_type(&NodeArithmeticExpr::Base(bool.clone())), "bool");
-        assert_eq!(gen.infer_type(&NodeArithmeticExpr::Base(var.clone())), "unknown");
+        assert_eq!(gen.infer_type(&NodeArithmeticExpr::Base(num.clone())), Some(ResultType::Numeric));
+        assert_eq!(gen.infer_type(&NodeArithmeticExpr::Base(bool.clone())), Some(ResultType::Boolean));
+        assert_eq!(gen.infer_type(&NodeArithmeticExpr::Base(var.clone())), None);
+        gen.m_stack.add_variable("x".to_string(), ResultType::Boolean);
+        assert_eq!(gen.infer_type(&NodeArithmeticExpr::Base(var.clone())), Some(ResultType::Boolean));
         
         let operation = NodeArithmeticExpr::Operation(NodeArithmeticOperation{
             lhs: Right(num),
             rhs: Right(var),
             op: Operator::Plus { span: dummy_span },
             result_type: ResultType::Numeric,
+            span: Span::default(),
         });
-        assert_eq!(gen.infer_type(&operation), "num");
+        assert_eq!(gen.infer_type(&operation), Some(ResultType::Numeric));
     }
 
     #[test]
     fn test_type_check_logical_operands(){
-        let gen = Generator::new(NodeProgram{stmts: Vec::new()});
+        let gen = Generator::new(NodeProgram{stmts: Vec::new(), span: Span::default()});
         let dummy_span = Span::new(0, 0, 0);
         let num_base = NodeBaseExpr::Num(Token::Number { value: 1.to_string(), span: dummy_span });
         let bool_base = NodeBaseExpr::Bool(Token::Boolean { value: true, span: dummy_span });
@@ -640,12 +1515,14 @@ mod test_generator{
             rhs: Right(var_base),
             op: Operator::Plus { span: dummy_span },
             result_type: ResultType::Numeric,
+            span: Span::default(),
         });
         let bool_operation = NodeArithmeticExpr::Operation(NodeArithmeticOperation{
             lhs: Right(bool_base.clone()),
             rhs: Right(bool_base),
             op: Operator::And {span: dummy_span},
             result_type: ResultType::Boolean,
+            span: Span::default(),
         });
 
         let valid1 = gen.type_check_logical_operands(&bool, &bool);
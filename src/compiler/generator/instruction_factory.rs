@@ -1,58 +1,84 @@
-use crate::utility::{Arch, OS, TARGET_ARCH, TARGET_OS};
+use crate::utility::{Arch, LinkMode, OS, TARGET_ARCH, TARGET_OS};
 
-pub const INSTRUCTION_FACTORY: InstructionFactory = InstructionFactory{};
+/// Exit code of a program stopped by a runtime error, such as an overflow or a division by zero.
+const PANIC_EXIT_CODE: u8 = 101;
 
+/// Instruction factory of the platform the compiler is running on.
+pub const INSTRUCTION_FACTORY: InstructionFactory = InstructionFactory::new(TARGET_ARCH, TARGET_OS);
+
+#[derive(Clone, Copy, Debug)]
 pub struct InstructionFactory{
-    
+    m_arch: Arch,
+    m_os: OS,
+    m_link_mode: LinkMode
 }
 
 impl InstructionFactory {
+    pub const fn new(m_arch: Arch, m_os: OS) -> Self {
+        InstructionFactory { m_arch, m_os, m_link_mode: LinkMode::Freestanding }
+    }
+
+    pub fn set_link_mode(&mut self, link_mode: LinkMode) {
+        self.m_link_mode = link_mode;
+    }
+
+    pub fn get_link_mode(&self) -> LinkMode {
+        self.m_link_mode
+    }
+
+    pub fn get_arch(&self) -> Arch {
+        self.m_arch
+    }
+
     // Comments
     pub fn generate_comment(&self, comment: &str) -> String {
-        match (TARGET_ARCH, TARGET_OS) {
+        match (self.m_arch, self.m_os) {
             (Arch::AArch64, OS::Linux) => format!("\t// {}\n", comment),
             _ => format!("\t; {}\n", comment)
         }
     }
     
     // Arithmetic operations
-    pub fn get_addition_instr(&self) -> &str {
-        match TARGET_ARCH {
+    pub fn get_addition_instr(&self) -> &'static str {
+        match self.m_arch {
             Arch::X86_64 => "add rax, rbx",
             Arch::AArch64 => "add x0, x1, x2",
         }
     }
 
-    pub fn get_subtraction_instr(&self) -> &str {
-        match TARGET_ARCH {
+    pub fn get_subtraction_instr(&self) -> &'static str {
+        match self.m_arch {
             Arch::X86_64 => "sub rax, rbx",
             Arch::AArch64 => "sub x0, x1, x2",
         }
     }
 
-    pub fn get_multiplication_instr(&self) -> &str {
-        match TARGET_ARCH {
-            Arch::X86_64 => "mul rbx",
+    pub fn get_multiplication_instr(&self) -> &'static str {
+        match self.m_arch {
+            Arch::X86_64 => "imul rax, rbx",
             Arch::AArch64 => "mul x0, x1, x2",
         }
     }
 
-    pub fn get_division_instr(&self) -> &str {
-        match TARGET_ARCH {
-            Arch::X86_64 => "xor rdx, rdx\n\tdiv rbx",
+    /// `idiv` faults on the overflowing `i64::MIN / -1`, so a divisor of -1 negates the dividend instead, wrapping
+    /// around like `sdiv`.
+    pub fn get_division_instr(&self) -> &'static str {
+        match self.m_arch {
+            Arch::X86_64 => "cmp rbx, -1\n\tjne {div_label}\n\tneg rax\n\tjmp {done_label}\n{div_label}:\n\tcqo\n\tidiv rbx\n{done_label}:",
             Arch::AArch64 => "sdiv x0, x1, x2",
         }
     }
 
-    pub fn get_modulo_instr(&self) -> &str {
-        match TARGET_ARCH {
-            Arch::X86_64 => "xor rdx, rdx\n\tdiv rbx",
+    /// Like the division, a divisor of -1 skips `idiv`, the remainder being 0.
+    pub fn get_modulo_instr(&self) -> &'static str {
+        match self.m_arch {
+            Arch::X86_64 => "cmp rbx, -1\n\tjne {div_label}\n\tmov rdx, 0\n\tjmp {done_label}\n{div_label}:\n\tcqo\n\tidiv rbx\n{done_label}:",
             Arch::AArch64 => "sdiv x3, x1, x2\n\tmsub x0, x3, x2, x1",
         }
     }
 
-    pub fn get_exponentiation_instr(&self) -> &str {
-        match (TARGET_ARCH, TARGET_OS) {
+    pub fn get_exponentiation_instr(&self) -> &'static str {
+        match (self.m_arch, self.m_os) {
             (Arch::X86_64, _) => "mov rax, 1\n{exp_label}:\n\tcmp rcx, 0\n\tje {done_label}\n\timul rax, rdx\n\tdec rcx\n\tjmp {exp_label}\n{done_label}:",
             (Arch::AArch64, OS::Linux) => "mov x0, #1\n{exp_label}:\n\tcmp x1, #0\n\tbeq {done_label}\n\tmul x0, x0, x2\n\tsub x1, x1, #1\n\tb {exp_label}\n{done_label}:",
             (Arch::AArch64, OS::Windows) => "mov x0, #1\n{exp_label}:\n\tcmp x1, #0\n\tbeq {done_label}\n\tmul x0, x0, x2\n\tsub x1, x1, #1\n\tb {exp_label}\n{done_label}:",
@@ -60,8 +86,413 @@ impl InstructionFactory {
         }
     }
 
+    // Arithmetic operations jumping to the overflow trap when the result doesn't fit in 64 bits
+    pub fn get_checked_addition_instr(&self) -> &'static str {
+        match self.m_arch {
+            Arch::X86_64 => "add rax, rbx\n\tjo overflow_trap",
+            Arch::AArch64 => "adds x0, x1, x2\n\tb.vs overflow_trap",
+        }
+    }
+
+    pub fn get_checked_subtraction_instr(&self) -> &'static str {
+        match self.m_arch {
+            Arch::X86_64 => "sub rax, rbx\n\tjo overflow_trap",
+            Arch::AArch64 => "subs x0, x1, x2\n\tb.vs overflow_trap",
+        }
+    }
+
+    pub fn get_checked_multiplication_instr(&self) -> &'static str {
+        match self.m_arch {
+            Arch::X86_64 => "imul rax, rbx\n\tjo overflow_trap",
+            // The high half of the product must only hold the sign of the low half
+            Arch::AArch64 => "mul x0, x1, x2\n\tsmulh x3, x1, x2\n\tcmp x3, x0, asr #63\n\tb.ne overflow_trap",
+        }
+    }
+
+    /// Only the negation of `i64::MIN` overflows.
+    pub fn get_checked_negation_instr(&self) -> &'static str {
+        match self.m_arch {
+            Arch::X86_64 => "neg rax\n\tjo overflow_trap",
+            Arch::AArch64 => "negs x0, x0\n\tb.vs overflow_trap",
+        }
+    }
+
+    /// Only `i64::MIN / -1` overflows.
+    pub fn get_checked_division_instr(&self) -> &'static str {
+        match self.m_arch {
+            Arch::X86_64 => "cmp rbx, -1\n\tjne {div_label}\n\tneg rax\n\tjo overflow_trap\n\tjmp {done_label}\n{div_label}:\n\tcqo\n\tidiv rbx\n{done_label}:",
+            // Negating the dividend only overflows for `i64::MIN`, the divisor is then compared to -1
+            Arch::AArch64 => "negs x3, x1\n\tccmn x2, #1, #0, vs\n\tb.eq overflow_trap\n\tsdiv x0, x1, x2",
+        }
+    }
+
+    pub fn get_checked_exponentiation_instr(&self) -> &'static str {
+        match (self.m_arch, self.m_os) {
+            (Arch::X86_64, _) => "mov rax, 1\n{exp_label}:\n\tcmp rcx, 0\n\tje {done_label}\n\timul rax, rdx\n\tjo overflow_trap\n\tdec rcx\n\tjmp {exp_label}\n{done_label}:",
+            (Arch::AArch64, _) => "mov x0, #1\n{exp_label}:\n\tcmp x1, #0\n\tbeq {done_label}\n\tsmulh x3, x0, x2\n\tmul x0, x0, x2\n\tcmp x3, x0, asr #63\n\tb.ne overflow_trap\n\tsub x1, x1, #1\n\tb {exp_label}\n{done_label}:",
+        }
+    }
+
+    /// Jumps to `label` when the divisor of the next division is 0.
+    pub fn get_zero_divisor_check(&self, label: &str) -> String {
+        let (_, divisor_reg, _) = self.m_arch.get_arithmetic_regs();
+        match self.m_arch {
+            Arch::X86_64 => format!("test {divisor_reg}, {divisor_reg}\n\tjz {label}"),
+            Arch::AArch64 => format!("cbz {divisor_reg}, {label}"),
+        }
+    }
+
+    /// Jumps to `label` when the exponent of the next exponentiation is negative.
+    pub fn get_negative_exponent_check(&self, label: &str) -> String {
+        let (exponent_reg, _, _) = self.m_arch.get_exponentiation_regs();
+        match self.m_arch {
+            Arch::X86_64 => format!("test {exponent_reg}, {exponent_reg}\n\tjs {label}"),
+            Arch::AArch64 => format!("tbnz {exponent_reg}, #63, {label}"),
+        }
+    }
+
+    /// Stops the program with the message stored at `message_label`, see `get_panic_routine`.
+    pub fn get_panic_call(&self, message_label: &str, message_length: usize) -> String {
+        match self.m_arch {
+            Arch::X86_64 => format!("lea rsi, [rel {message_label}]\n\tmov rdx, {message_length}\n\tjmp runtime_panic"),
+            Arch::AArch64 => format!("adr x1, {message_label}\n\tmov x2, #{message_length}\n\tb runtime_panic"),
+        }
+    }
+
+    /// Routine printing a message on the standard error and exiting with `PANIC_EXIT_CODE`. The message
+    /// is given by its address and length, in `rsi` and `rdx` on x86_64 and in `x1` and `x2` on AArch64.
+    pub fn get_panic_routine(&self) -> String {
+        let code = PANIC_EXIT_CODE;
+        match (self.m_arch, self.m_os) {
+            (Arch::X86_64, OS::Windows) => format!(
+                "extern GetStdHandle\nextern WriteFile\nruntime_panic:\n\tmov rdi, rdx\n\tand rsp, -16\n\tsub rsp, 48\n\tmov rcx, -12\n\tcall GetStdHandle\n\tmov rcx, rax\n\tmov rdx, rsi\n\tmov r8, rdi\n\tlea r9, [rsp + 40]\n\tmov qword [rsp + 32], 0\n\tcall WriteFile\n\tmov rcx, {code}\n\tcall ExitProcess\n"
+            ),
+            (Arch::X86_64, os) => {
+                let (write, exit) = if os == OS::MacOS { ("0x2000004", "0x2000001") } else { ("1", "60") };
+                format!("runtime_panic:\n\tmov rax, {write}\n\tmov rdi, 2\n\tsyscall\n\tmov rdi, {code}\n\tmov rax, {exit}\n\tsyscall\n")
+            }
+            (Arch::AArch64, OS::Windows) => format!(
+                "extern GetStdHandle\nextern WriteFile\nruntime_panic:\n\tmov x19, x1\n\tmov x20, x2\n\tmov x0, #-12\n\tbl GetStdHandle\n\tmov x1, x19\n\tmov x2, x20\n\tmov x3, #0\n\tmov x4, #0\n\tbl WriteFile\n\tmov x0, #{code}\n\tbl ExitProcess\n"
+            ),
+            (Arch::AArch64, os) => {
+                let (write, exit) = if os == OS::MacOS {
+                    ("ldr x16, =0x2000004\n\tsvc #0x80", "ldr x16, =0x2000001\n\tsvc #0x80")
+                } else {
+                    ("mov x8, #64\n\tsvc #0", "mov x8, #93\n\tsvc #0")
+                };
+                format!("runtime_panic:\n\tmov x0, #2\n\t{write}\n\tmov x0, #{code}\n\t{exit}\n")
+            }
+        }
+    }
+
+    /// Routine mapping the number of bytes in the base register, plus a header of 8 bytes holding the size of the
+    /// mapping, and returning the address following the header in the base register. Negative sizes and failed
+    /// mappings jump to `alloc_failed`.
+    pub fn get_alloc_routine(&self) -> &'static str {
+        match (self.m_arch, self.m_os) {
+            (Arch::X86_64, OS::Linux) => "runtime_alloc:\n\tcmp rax, 0\n\tjl alloc_failed\n\tadd rax, 8\n\tjo alloc_failed\n\tmov rsi, rax\n\tmov rax, 9\n\tmov rdi, 0\n\tmov rdx, 3\n\tmov r10, 0x22\n\tmov r8, -1\n\tmov r9, 0\n\tsyscall\n\tcmp rax, -4096\n\tja alloc_failed\n\tmov [rax], rsi\n\tadd rax, 8\n\tret\n",
+            (Arch::X86_64, OS::Windows) => "extern VirtualAlloc\nruntime_alloc:\n\tcmp rax, 0\n\tjl alloc_failed\n\tadd rax, 8\n\tjo alloc_failed\n\tmov rbx, rax\n\tpush rbp\n\tmov rbp, rsp\n\tand rsp, -16\n\tsub rsp, 32\n\tmov rcx, 0\n\tmov rdx, rbx\n\tmov r8, 0x3000\n\tmov r9, 4\n\tcall VirtualAlloc\n\tmov rsp, rbp\n\tpop rbp\n\ttest rax, rax\n\tjz alloc_failed\n\tmov [rax], rbx\n\tadd rax, 8\n\tret\n",
+            (Arch::X86_64, _) => "runtime_alloc:\n\tcmp rax, 0\n\tjl alloc_failed\n\tadd rax, 8\n\tjo alloc_failed\n\tmov rsi, rax\n\tmov rax, 0x20000C5\n\tmov rdi, 0\n\tmov rdx, 3\n\tmov r10, 0x1002\n\tmov r8, -1\n\tmov r9, 0\n\tsyscall\n\tjc alloc_failed\n\tmov [rax], rsi\n\tadd rax, 8\n\tret\n",
+            (Arch::AArch64, OS::Linux) => "runtime_alloc:\n\tcmp x0, #0\n\tb.lt alloc_failed\n\tadds x19, x0, #8\n\tb.vs alloc_failed\n\tmov x0, #0\n\tmov x1, x19\n\tmov x2, #3\n\tmov x3, #0x22\n\tmov x4, #-1\n\tmov x5, #0\n\tmov x8, #222\n\tsvc #0\n\tcmn x0, #4095\n\tb.hi alloc_failed\n\tstr x19, [x0]\n\tadd x0, x0, #8\n\tret\n",
+            (Arch::AArch64, OS::Windows) => "extern VirtualAlloc\nruntime_alloc:\n\tcmp x0, #0\n\tb.lt alloc_failed\n\tadds x19, x0, #8\n\tb.vs alloc_failed\n\tstp x29, x30, [sp, #-16]!\n\tmov x0, #0\n\tmov x1, x19\n\tmov x2, #0x3000\n\tmov x3, #4\n\tbl VirtualAlloc\n\tldp x29, x30, [sp], #16\n\tcbz x0, alloc_failed\n\tstr x19, [x0]\n\tadd x0, x0, #8\n\tret\n",
+            (Arch::AArch64, _) => "runtime_alloc:\n\tcmp x0, #0\n\tb.lt alloc_failed\n\tadds x19, x0, #8\n\tb.vs alloc_failed\n\tmov x0, #0\n\tmov x1, x19\n\tmov x2, #3\n\tmov x3, #0x1002\n\tmov x4, #-1\n\tmov x5, #0\n\tldr x16, =0x20000C5\n\tsvc #0x80\n\tb.cs alloc_failed\n\tstr x19, [x0]\n\tadd x0, x0, #8\n\tret\n",
+        }
+    }
+
+    /// Routine unmapping the allocation whose address is in the base register, using the size stored in its header.
+    /// The address 0 is ignored.
+    pub fn get_free_routine(&self) -> &'static str {
+        match (self.m_arch, self.m_os) {
+            (Arch::X86_64, OS::Linux) => "runtime_free:\n\ttest rax, rax\n\tjz runtime_free_done\n\tlea rdi, [rax - 8]\n\tmov rsi, [rdi]\n\tmov rax, 11\n\tsyscall\nruntime_free_done:\n\tret\n",
+            (Arch::X86_64, OS::Windows) => "extern VirtualFree\nruntime_free:\n\ttest rax, rax\n\tjz runtime_free_done\n\tpush rbp\n\tmov rbp, rsp\n\tand rsp, -16\n\tsub rsp, 32\n\tlea rcx, [rax - 8]\n\tmov rdx, 0\n\tmov r8, 0x8000\n\tcall VirtualFree\n\tmov rsp, rbp\n\tpop rbp\nruntime_free_done:\n\tret\n",
+            (Arch::X86_64, _) => "runtime_free:\n\ttest rax, rax\n\tjz runtime_free_done\n\tlea rdi, [rax - 8]\n\tmov rsi, [rdi]\n\tmov rax, 0x2000049\n\tsyscall\nruntime_free_done:\n\tret\n",
+            (Arch::AArch64, OS::Linux) => "runtime_free:\n\tcbz x0, runtime_free_done\n\tsub x0, x0, #8\n\tldr x1, [x0]\n\tmov x8, #215\n\tsvc #0\nruntime_free_done:\n\tret\n",
+            (Arch::AArch64, OS::Windows) => "extern VirtualFree\nruntime_free:\n\tcbz x0, runtime_free_done\n\tstp x29, x30, [sp, #-16]!\n\tsub x0, x0, #8\n\tmov x1, #0\n\tmov x2, #0x8000\n\tbl VirtualFree\n\tldp x29, x30, [sp], #16\nruntime_free_done:\n\tret\n",
+            (Arch::AArch64, _) => "runtime_free:\n\tcbz x0, runtime_free_done\n\tsub x0, x0, #8\n\tldr x1, [x0]\n\tldr x16, =0x2000049\n\tsvc #0x80\nruntime_free_done:\n\tret\n",
+        }
+    }
+
+    /// Loads the number of command-line arguments into the base register, see `get_arguments_setup`.
+    pub fn get_argument_count_instr(&self) -> &'static str {
+        match self.m_arch {
+            Arch::X86_64 => "mov rax, r12",
+            Arch::AArch64 => "mov x0, x27",
+        }
+    }
+
+    /// Keeps the number of command-line arguments and the address of their table for the whole program, in `r12`
+    /// and `r13` on x86_64 and in `x27` and `x28` on AArch64. They are on the initial stack, except on macOS AArch64
+    /// where they are given like the arguments of `main`, and on Windows where the command line is split by
+    /// `runtime_command_line`. A program linked with the C library gets them as the arguments of `main`.
+    pub fn get_arguments_setup(&self) -> &'static str {
+        if self.m_link_mode == LinkMode::Libc {
+            return match (self.m_arch, self.m_os) {
+                (Arch::X86_64, OS::Windows) => "mov r12, rcx\n\tmov r13, rdx",
+                (Arch::X86_64, _) => "mov r12, rdi\n\tmov r13, rsi",
+                (Arch::AArch64, _) => "mov x27, x0\n\tmov x28, x1",
+            };
+        }
+        match (self.m_arch, self.m_os) {
+            (Arch::X86_64, OS::Windows) => "call runtime_command_line",
+            (Arch::X86_64, _) => "mov r12, [rsp]\n\tlea r13, [rsp + 8]",
+            (Arch::AArch64, OS::Linux) => "ldr x27, [sp]\n\tadd x28, sp, #8",
+            (Arch::AArch64, OS::Windows) => "bl runtime_command_line",
+            (Arch::AArch64, _) => "mov x27, x0\n\tmov x28, x1",
+        }
+    }
+
+    /// Whether `get_arguments_setup` calls `runtime_command_line`.
+    pub fn splits_command_line(&self) -> bool {
+        self.m_os == OS::Windows && self.m_link_mode == LinkMode::Freestanding
+    }
+
+    /// Routine replacing the index in the base register by the address of that command-line argument. Indexes out of
+    /// bounds, including negative ones, jump to `arg_out_of_bounds`.
+    pub fn get_arg_routine(&self) -> &'static str {
+        match self.m_arch {
+            Arch::X86_64 => "runtime_arg:\n\tcmp rax, r12\n\tjae arg_out_of_bounds\n\tmov rax, [r13 + rax * 8]\n\tret\n",
+            Arch::AArch64 => "runtime_arg:\n\tcmp x0, x27\n\tb.hs arg_out_of_bounds\n\tldr x0, [x28, x0, lsl #3]\n\tret\n",
+        }
+    }
+
+    /// Routine splitting the command line of a Windows program into arguments, at spaces and tabs outside of double
+    /// quotes. The arguments are copied as bytes, one per UTF-16 unit, into a single allocation.
+    pub fn get_command_line_routine(&self) -> &'static str {
+        match self.m_arch {
+            Arch::X86_64 => concat!(
+                "extern GetCommandLineW\nruntime_command_line:\n\tpush rbp\n\tmov rbp, rsp\n\tand rsp, -16\n\tsub rsp, 32\n",
+                "\tcall GetCommandLineW\n\tmov rsi, rax\n\tmov rcx, 0\n",
+                "command_line_length:\n\tcmp word [rsi + rcx * 2], 0\n\tje command_line_allocate\n\tinc rcx\n\tjmp command_line_length\n",
+                "command_line_allocate:\n\tlea rdi, [rcx + 1]\n\timul rax, rdi, 9\n\tcall runtime_alloc\n",
+                "\tmov r13, rax\n\tlea rdx, [rax + rdi * 8]\n\tmov r12, 0\n\tmov r8, 0\n",
+                "command_line_skip:\n\tmovzx eax, word [rsi]\n\ttest eax, eax\n\tjz command_line_done\n\tcmp eax, 32\n\tje command_line_blank\n\tcmp eax, 9\n\tjne command_line_argument\n",
+                "command_line_blank:\n\tadd rsi, 2\n\tjmp command_line_skip\n",
+                "command_line_argument:\n\tmov [r13 + r12 * 8], rdx\n\tinc r12\n",
+                "command_line_char:\n\tmovzx eax, word [rsi]\n\ttest eax, eax\n\tjz command_line_end\n\tadd rsi, 2\n\tcmp eax, 34\n\tjne command_line_plain\n\txor r8, 1\n\tjmp command_line_char\n",
+                "command_line_plain:\n\ttest r8, r8\n\tjnz command_line_store\n\tcmp eax, 32\n\tje command_line_end\n\tcmp eax, 9\n\tje command_line_end\n",
+                "command_line_store:\n\tmov [rdx], al\n\tinc rdx\n\tjmp command_line_char\n",
+                "command_line_end:\n\tmov byte [rdx], 0\n\tinc rdx\n\tjmp command_line_skip\n",
+                "command_line_done:\n\tmov rsp, rbp\n\tpop rbp\n\tret\n"
+            ),
+            Arch::AArch64 => concat!(
+                "extern GetCommandLineW\nruntime_command_line:\n\tstp x29, x30, [sp, #-16]!\n",
+                "\tbl GetCommandLineW\n\tmov x20, x0\n\tmov x1, #0\n",
+                "command_line_length:\n\tldrh w2, [x20, x1, lsl #1]\n\tcbz w2, command_line_allocate\n\tadd x1, x1, #1\n\tb command_line_length\n",
+                "command_line_allocate:\n\tadd x21, x1, #1\n\tmov x0, #9\n\tmul x0, x0, x21\n\tbl runtime_alloc\n",
+                "\tmov x28, x0\n\tadd x1, x0, x21, lsl #3\n\tmov x27, #0\n\tmov x3, #0\n",
+                "command_line_skip:\n\tldrh w2, [x20]\n\tcbz w2, command_line_done\n\tcmp w2, #32\n\tb.eq command_line_blank\n\tcmp w2, #9\n\tb.ne command_line_argument\n",
+                "command_line_blank:\n\tadd x20, x20, #2\n\tb command_line_skip\n",
+                "command_line_argument:\n\tstr x1, [x28, x27, lsl #3]\n\tadd x27, x27, #1\n",
+                "command_line_char:\n\tldrh w2, [x20]\n\tcbz w2, command_line_end\n\tadd x20, x20, #2\n\tcmp w2, #34\n\tb.ne command_line_plain\n\teor x3, x3, #1\n\tb command_line_char\n",
+                "command_line_plain:\n\tcbnz x3, command_line_store\n\tcmp w2, #32\n\tb.eq command_line_end\n\tcmp w2, #9\n\tb.eq command_line_end\n",
+                "command_line_store:\n\tstrb w2, [x1], #1\n\tb command_line_char\n",
+                "command_line_end:\n\tstrb wzr, [x1], #1\n\tb command_line_skip\n",
+                "command_line_done:\n\tldp x29, x30, [sp], #16\n\tret\n"
+            ),
+        }
+    }
+
+    /// Routine reading a line of the standard input, one byte at a time, into a buffer of 4096 bytes from
+    /// `runtime_alloc`. The line feed and the carriage return before it are left out, longer lines are cut after
+    /// 4095 bytes, and the text is followed by a zero byte. Its address is returned in the base register, or 0
+    /// when the input has ended.
+    pub fn get_read_line_routine(&self) -> String {
+        match self.m_arch {
+            Arch::X86_64 => {
+                let (externs, prologue, handle, epilogue, read) = match self.m_os {
+                    // The handle is kept in `rbx` once `runtime_alloc` is done with it
+                    OS::Windows => (
+                        "extern GetStdHandle\nextern ReadFile\n",
+                        "push rbp\n\tmov rbp, rsp\n\tand rsp, -16\n\tsub rsp, 48\n\t",
+                        "\tmov rcx, -10\n\tcall GetStdHandle\n\tmov rbx, rax\n",
+                        "mov rsp, rbp\n\tpop rbp\n\t",
+                        "mov rcx, rbx\n\tlea rdx, [r14 + r15]\n\tmov r8, 1\n\tlea r9, [rsp + 40]\n\tmov qword [rsp + 32], 0\n\tcall ReadFile\n\ttest rax, rax\n\tjz read_line_eof\n\tcmp dword [rsp + 40], 1"
+                    ),
+                    OS::Linux => ("", "", "", "", "mov rax, 0\n\tmov rdi, 0\n\tlea rsi, [r14 + r15]\n\tmov rdx, 1\n\tsyscall\n\tcmp rax, 1"),
+                    OS::MacOS => ("", "", "", "", "mov rax, 0x2000003\n\tmov rdi, 0\n\tlea rsi, [r14 + r15]\n\tmov rdx, 1\n\tsyscall\n\tjc read_line_eof\n\tcmp rax, 1"),
+                };
+                format!(concat!(
+                    "{}runtime_read_line:\n\t{}mov rax, 4096\n\tcall runtime_alloc\n\tmov r14, rax\n\tmov r15, 0\n{}",
+                    "read_line_next:\n\tcmp r15, 4095\n\tje read_line_end\n\t{}\n\tjne read_line_eof\n",
+                    "\tcmp byte [r14 + r15], 10\n\tje read_line_end\n\tinc r15\n\tjmp read_line_next\n",
+                    "read_line_eof:\n\ttest r15, r15\n\tjnz read_line_end\n\tmov rax, r14\n\tcall runtime_free\n\tmov rax, 0\n\tjmp read_line_return\n",
+                    "read_line_end:\n\ttest r15, r15\n\tjz read_line_done\n\tcmp byte [r14 + r15 - 1], 13\n\tjne read_line_done\n\tdec r15\n",
+                    "read_line_done:\n\tmov byte [r14 + r15], 0\n\tmov rax, r14\n",
+                    "read_line_return:\n\t{}ret\n"
+                ), externs, prologue, handle, read, epilogue)
+            }
+            Arch::AArch64 => {
+                let (externs, handle, read) = match self.m_os {
+                    OS::Windows => (
+                        "extern GetStdHandle\nextern ReadFile\n",
+                        "mov x0, #-10\n\tbl GetStdHandle\n\tmov x22, x0\n\t",
+                        "mov x0, x22\n\tadd x1, x20, x21\n\tmov x2, #1\n\tadd x3, sp, #16\n\tmov x4, #0\n\tbl ReadFile\n\tcbz x0, read_line_eof\n\tldr w0, [sp, #16]\n\tcmp x0, #1"
+                    ),
+                    OS::Linux => ("", "", "mov x0, #0\n\tadd x1, x20, x21\n\tmov x2, #1\n\tmov x8, #63\n\tsvc #0\n\tcmp x0, #1"),
+                    OS::MacOS => ("", "", "mov x0, #0\n\tadd x1, x20, x21\n\tmov x2, #1\n\tldr x16, =0x2000003\n\tsvc #0x80\n\tb.cs read_line_eof\n\tcmp x0, #1"),
+                };
+                format!(concat!(
+                    "{}runtime_read_line:\n\tstp x29, x30, [sp, #-32]!\n\t{}mov x0, #4096\n\tbl runtime_alloc\n\tmov x20, x0\n\tmov x21, #0\n",
+                    "read_line_next:\n\tcmp x21, #4095\n\tb.eq read_line_end\n\t{}\n\tb.ne read_line_eof\n",
+                    "\tldrb w3, [x20, x21]\n\tcmp w3, #10\n\tb.eq read_line_end\n\tadd x21, x21, #1\n\tb read_line_next\n",
+                    "read_line_eof:\n\tcbnz x21, read_line_end\n\tmov x0, x20\n\tbl runtime_free\n\tmov x0, #0\n\tb read_line_return\n",
+                    "read_line_end:\n\tcbz x21, read_line_done\n\tsub x3, x21, #1\n\tldrb w4, [x20, x3]\n\tcmp w4, #13\n\tb.ne read_line_done\n\tmov x21, x3\n",
+                    "read_line_done:\n\tstrb wzr, [x20, x21]\n\tmov x0, x20\n",
+                    "read_line_return:\n\tldp x29, x30, [sp], #32\n\tret\n"
+                ), externs, handle, read)
+            }
+        }
+    }
+
+    /// Routine replacing the address of a string in the base register by the integer it is written as: an optional
+    /// sign followed by decimal digits. Other strings, integers overflowing 64 bits and the address 0 jump to
+    /// `invalid_integer`. The value is accumulated as a negative number, so that the smallest integer can be read.
+    pub fn get_parse_int_routine(&self) -> &'static str {
+        match self.m_arch {
+            Arch::X86_64 => concat!(
+                "runtime_parse_int:\n\ttest rax, rax\n\tjz invalid_integer\n\tmov rsi, rax\n\tmov rcx, 0\n\tmovzx rdx, byte [rsi]\n",
+                "\tcmp rdx, 45\n\tjne parse_int_plus\n\tmov rcx, 1\n\tinc rsi\n\tjmp parse_int_first\n",
+                "parse_int_plus:\n\tcmp rdx, 43\n\tjne parse_int_first\n\tinc rsi\n",
+                "parse_int_first:\n\tcmp byte [rsi], 0\n\tje invalid_integer\n\tmov rax, 0\n",
+                "parse_int_next:\n\tmovzx rdx, byte [rsi]\n\ttest rdx, rdx\n\tjz parse_int_sign\n\tsub rdx, 48\n\tcmp rdx, 9\n\tja invalid_integer\n",
+                "\timul rax, rax, 10\n\tjo invalid_integer\n\tsub rax, rdx\n\tjo invalid_integer\n\tinc rsi\n\tjmp parse_int_next\n",
+                "parse_int_sign:\n\ttest rcx, rcx\n\tjnz parse_int_done\n\tneg rax\n\tjo invalid_integer\n",
+                "parse_int_done:\n\tret\n"
+            ),
+            Arch::AArch64 => concat!(
+                "runtime_parse_int:\n\tcbz x0, invalid_integer\n\tmov x1, x0\n\tmov x2, #0\n\tldrb w3, [x1]\n",
+                "\tcmp w3, #45\n\tb.ne parse_int_plus\n\tmov x2, #1\n\tadd x1, x1, #1\n\tb parse_int_first\n",
+                "parse_int_plus:\n\tcmp w3, #43\n\tb.ne parse_int_first\n\tadd x1, x1, #1\n",
+                "parse_int_first:\n\tldrb w3, [x1]\n\tcbz w3, invalid_integer\n\tmov x0, #0\n\tmov x4, #10\n",
+                "parse_int_next:\n\tldrb w3, [x1], #1\n\tcbz w3, parse_int_sign\n\tsub w3, w3, #48\n\tcmp w3, #9\n\tb.hi invalid_integer\n",
+                "\tsmulh x5, x0, x4\n\tmul x0, x0, x4\n\tcmp x5, x0, asr #63\n\tb.ne invalid_integer\n\tsubs x0, x0, x3\n\tb.vs invalid_integer\n\tb parse_int_next\n",
+                "parse_int_sign:\n\tcbnz x2, parse_int_done\n\tnegs x0, x0\n\tb.vs invalid_integer\n",
+                "parse_int_done:\n\tret\n"
+            ),
+        }
+    }
+
+    /// Routine reading a line of the standard input and returning the integer it holds in the base register. The
+    /// line is freed once parsed, and the end of the input jumps to `invalid_integer` like an empty line.
+    pub fn get_read_int_routine(&self) -> &'static str {
+        match self.m_arch {
+            Arch::X86_64 => concat!(
+                "runtime_read_int:\n\tcall runtime_read_line\n\tpush rax\n\tcall runtime_parse_int\n",
+                "\tpop rbx\n\tpush rax\n\tmov rax, rbx\n\tcall runtime_free\n\tpop rax\n\tret\n"
+            ),
+            Arch::AArch64 => concat!(
+                "runtime_read_int:\n\tstp x29, x30, [sp, #-16]!\n\tbl runtime_read_line\n\tmov x23, x0\n\tbl runtime_parse_int\n",
+                "\tmov x24, x0\n\tmov x0, x23\n\tbl runtime_free\n\tmov x0, x24\n\tldp x29, x30, [sp], #16\n\tret\n"
+            ),
+        }
+    }
+
+    /// A line of text, followed by a line feed. Its length is `text.len() + 1`.
+    pub fn get_message_data(&self, label: &str, text: &str) -> String {
+        let text = text.replace('"', "'");
+        match self.m_arch {
+            Arch::X86_64 => format!("{label}: db \"{text}\", 10"),
+            Arch::AArch64 => format!("{label}: .ascii \"{text}\\n\""),
+        }
+    }
+
+    /// Address of a symbol of the C library, which starts with an underscore on macOS.
+    pub fn get_c_symbol(&self, name: &str) -> String {
+        match self.m_os {
+            OS::MacOS => format!("_{name}"),
+            _ => name.to_string()
+        }
+    }
+
+    /// Declares a C function before it is called, only needed by nasm and yasm.
+    pub fn get_extern_declaration(&self, name: &str) -> Option<String> {
+        match self.m_arch {
+            Arch::X86_64 => Some(format!("extern {}", self.get_c_symbol(name))),
+            Arch::AArch64 => None,
+        }
+    }
+
+    /// Calls a C function with the `arguments` values on top of the stack, the last one on top, and leaves its result
+    /// in the base register. The first `named` arguments are its declared parameters, the others are variadic.
+    /// The stack is aligned on 16 bytes for the call and restored afterwards from a callee-saved register.
+    pub fn get_c_call_instr(&self, name: &str, arguments: usize, named: usize) -> String {
+        let symbol = self.get_c_symbol(name);
+        let mut instrs = Vec::new();
+        match self.m_arch {
+            Arch::X86_64 => {
+                let regs: &[&str] = if self.m_os == OS::Windows { &["rcx", "rdx", "r8", "r9"] } else { &["rdi", "rsi", "rdx", "rcx", "r8", "r9"] };
+                let offset = |i: usize| 8 * (arguments - 1 - i);
+                instrs.push("mov rbx, rsp".to_string());
+                instrs.push("and rsp, -16".to_string());
+                if arguments.saturating_sub(regs.len()) % 2 == 1 {
+                    instrs.push("sub rsp, 8".to_string());
+                }
+                for i in (regs.len()..arguments).rev() {
+                    instrs.push(format!("push qword [rbx + {}]", offset(i)));
+                }
+                if self.m_os == OS::Windows {
+                    // Shadow space of the 4 register arguments
+                    instrs.push("sub rsp, 32".to_string());
+                }
+                for (i, reg) in regs.iter().enumerate().take(arguments) {
+                    instrs.push(format!("mov {reg}, [rbx + {}]", offset(i)));
+                }
+                match self.m_os {
+                    OS::Windows => instrs.push(format!("call {symbol}")),
+                    os => {
+                        // `al` holds the number of vector registers given to a variadic function
+                        instrs.push("mov rax, 0".to_string());
+                        // Executables linked by the C compiler are position independent on Linux
+                        instrs.push(if os == OS::Linux { format!("call {symbol} wrt ..plt") } else { format!("call {symbol}") });
+                    }
+                }
+                instrs.push("mov rsp, rbx".to_string());
+            }
+            Arch::AArch64 => {
+                let offset = |i: usize| 16 * (arguments - 1 - i) + 8;
+                // Variadic arguments are always passed on the stack on macOS
+                let on_stack = |i: usize| i >= 8 || (self.m_os == OS::MacOS && i >= named);
+                let stacked: Vec<usize> = (0..arguments).filter(|&i| on_stack(i)).collect();
+                instrs.push("mov x19, sp".to_string());
+                if !stacked.is_empty() {
+                    instrs.push(format!("sub sp, sp, #{}", 16 * stacked.len().div_ceil(2)));
+                }
+                for (slot, i) in stacked.iter().enumerate() {
+                    instrs.push(format!("ldr x9, [x19, #{}]", offset(*i)));
+                    instrs.push(format!("str x9, [sp, #{}]", 8 * slot));
+                }
+                for i in (0..arguments).filter(|&i| !on_stack(i)) {
+                    instrs.push(format!("ldr x{i}, [x19, #{}]", offset(i)));
+                }
+                instrs.push(format!("bl {symbol}"));
+                instrs.push("mov sp, x19".to_string());
+            }
+        }
+        instrs.join("\n\t")
+    }
+
+    /// Loads the address of the data at `label` into the base register.
+    pub fn get_load_address_instr(&self, label: &str) -> String {
+        match self.m_arch {
+            Arch::X86_64 => format!("lea rax, [rel {label}]"),
+            Arch::AArch64 => format!("adr x0, {label}"),
+        }
+    }
+
+    /// The bytes of a string literal, followed by a zero byte.
+    pub fn get_string_data(&self, label: &str, text: &str) -> String {
+        let bytes: Vec<String> = text.bytes().chain([0]).map(|byte| byte.to_string()).collect();
+        match self.m_arch {
+            Arch::X86_64 => format!("{label}: db {}", bytes.join(", ")),
+            Arch::AArch64 => format!("{label}: .byte {}", bytes.join(", ")),
+        }
+    }
+
     pub fn get_mov_number_instr(&self, value: &str) -> String {
-        match (TARGET_ARCH, TARGET_OS) {
+        // Immediates of AArch64 moves only have 16 bits, bigger values are loaded from a literal pool
+        let is_wide = value.parse::<i64>().map_or(true, |value| !(0..=0xFFFF).contains(&value));
+        match (self.m_arch, self.m_os) {
+            (Arch::AArch64, _) if is_wide => format!("ldr x0, ={}", value),
             (Arch::X86_64, _) => format!("mov rax, {}", value),
             (Arch::AArch64, OS::Linux) => format!("mov x0, #{}", value),
             (Arch::AArch64, OS::Windows) => format!("mov x0, #{}", value),
@@ -71,51 +502,198 @@ impl InstructionFactory {
 
     pub fn get_mov_boolean_instr(&self, value: bool) -> String {
         let bool_as_int = if value {1} else {0};
-        match TARGET_ARCH {
+        match self.m_arch {
             Arch::X86_64 => format!("mov rax, {}", bool_as_int),
             Arch::AArch64 => format!("mov x0, {}", bool_as_int)
         }
     }
 
+    /// Address, from the stack pointer, of the value `offset` bytes below the top of the stack when every value takes
+    /// 8 bytes. A value pushed on AArch64 takes 16 bytes and is stored in the upper half of its slot.
+    fn get_stack_address(&self, offset: usize) -> usize {
+        match self.m_arch {
+            Arch::X86_64 => offset,
+            Arch::AArch64 => 2 * offset + 8,
+        }
+    }
+
     pub fn get_load_variable_instr(&self, offset: usize) -> String {
-        match TARGET_ARCH {
+        match self.m_arch {
             Arch::X86_64 => format!("mov rax, [rsp + {}]", offset),
-            Arch::AArch64 => format!("ldr x0, [sp, #{}]", offset),
+            Arch::AArch64 => format!("ldr x0, [sp, #{}]", self.get_stack_address(offset)),
+        }
+    }
+
+    pub fn get_store_variable_instr(&self, offset: usize) -> String {
+        match self.m_arch {
+            Arch::X86_64 => format!("mov [rsp + {}], rax", offset),
+            Arch::AArch64 => format!("str x0, [sp, #{}]", self.get_stack_address(offset)),
         }
     }
 
     // Logical operations
-    pub fn get_and_instr(&self) -> &str {
-        match TARGET_ARCH {
+    /// Jumps to `label` when the index, in the base register, isn't below `length`. Negative indexes are huge unsigned numbers.
+    pub fn get_bounds_check(&self, length: usize, label: &str) -> String {
+        match self.m_arch {
+            Arch::X86_64 => format!("cmp rax, {length}\n\tjae {label}"),
+            Arch::AArch64 => format!("ldr x3, ={length}\n\tcmp x0, x3\n\tb.hs {label}"),
+        }
+    }
+
+    /// Jumps to `label` when the base register doesn't hold `tag`, the tag of a variant.
+    pub fn get_tag_check(&self, tag: usize, label: &str) -> String {
+        match self.m_arch {
+            Arch::X86_64 => format!("cmp rax, {tag}\n\tjne {label}"),
+            Arch::AArch64 => format!("cmp x0, #{tag}\n\tb.ne {label}"),
+        }
+    }
+
+    /// Jumps to `label` when the counter of a loop, in the base register, reaches the end of its range at `offset`.
+    pub fn get_range_check(&self, offset: usize, inclusive: bool, label: &str) -> String {
+        match (self.m_arch, inclusive) {
+            (Arch::X86_64, false) => format!("cmp rax, [rsp + {offset}]\n\tjge {label}"),
+            (Arch::X86_64, true) => format!("cmp rax, [rsp + {offset}]\n\tjg {label}"),
+            (Arch::AArch64, false) => format!("ldr x1, [sp, #{}]\n\tcmp x0, x1\n\tb.ge {label}", self.get_stack_address(offset)),
+            (Arch::AArch64, true) => format!("ldr x1, [sp, #{}]\n\tcmp x0, x1\n\tb.gt {label}", self.get_stack_address(offset)),
+        }
+    }
+
+    /// Adds `step` to the counter of a loop, in the base register, jumping to `label` when it overflows.
+    pub fn get_step_instr(&self, step: i64, label: &str) -> String {
+        match self.m_arch {
+            Arch::X86_64 => format!("mov rbx, {step}\n\tadd rax, rbx\n\tjo {label}"),
+            Arch::AArch64 => format!("ldr x1, ={step}\n\tadds x0, x0, x1\n\tb.vs {label}"),
+        }
+    }
+
+    /// Drops the `slots` values on top of the stack at once.
+    pub fn get_release_instr(&self, slots: usize) -> String {
+        match self.m_arch {
+            Arch::X86_64 => format!("add rsp, {}", 8 * slots),
+            Arch::AArch64 => format!("add sp, sp, #{}", 16 * slots),
+        }
+    }
+
+    pub fn get_jump_instr(&self, label: &str) -> String {
+        match self.m_arch {
+            Arch::X86_64 => format!("jmp {label}"),
+            Arch::AArch64 => format!("b {label}"),
+        }
+    }
+
+    /// Calls a routine of the runtime, which returns to the next instruction.
+    pub fn get_call_instr(&self, label: &str) -> String {
+        match self.m_arch {
+            Arch::X86_64 => format!("call {label}"),
+            Arch::AArch64 => format!("bl {label}"),
+        }
+    }
+
+    /// Loads the element whose index is in the base register, from an array whose first element is at `offset`.
+    pub fn get_load_element_instr(&self, offset: usize) -> String {
+        match self.m_arch {
+            Arch::X86_64 => format!("neg rax\n\tmov rax, [rsp + rax * 8 + {offset}]"),
+            Arch::AArch64 => format!("mov x1, sp\n\tsub x1, x1, x0, lsl #4\n\tldr x0, [x1, #{}]", self.get_stack_address(offset)),
+        }
+    }
+
+    /// Stores the value of the second register at the element whose index is in the base register.
+    pub fn get_store_element_instr(&self, offset: usize) -> String {
+        match self.m_arch {
+            Arch::X86_64 => format!("neg rax\n\tmov [rsp + rax * 8 + {offset}], rbx"),
+            Arch::AArch64 => format!("mov x1, sp\n\tsub x1, x1, x0, lsl #4\n\tstr x2, [x1, #{}]", self.get_stack_address(offset)),
+        }
+    }
+
+    /// Register holding the value stored by `get_store_element_instr`.
+    pub fn get_element_value_reg(&self) -> &'static str {
+        match self.m_arch {
+            Arch::X86_64 => "rbx",
+            Arch::AArch64 => "x2",
+        }
+    }
+
+    pub fn get_and_instr(&self) -> &'static str {
+        match self.m_arch {
             Arch::X86_64 => "and rax, rbx",
             Arch::AArch64 => "and x0, x1, x2",
         }
     }
 
-    pub fn get_or_instr(&self) -> &str {
-        match TARGET_ARCH {
+    pub fn get_or_instr(&self) -> &'static str {
+        match self.m_arch {
             Arch::X86_64 => "or rax, rbx",
             Arch::AArch64 => "orr x0, x1, x2",
         }
     }
 
-    pub fn get_xor_instr(&self) -> &str {
-        match TARGET_ARCH {
+    pub fn get_xor_instr(&self) -> &'static str {
+        match self.m_arch {
             Arch::X86_64 => "xor rax, rbx",
             Arch::AArch64 => "eor x0, x1, x2",
         }
     }
 
-    pub fn get_not_instr(&self) -> &str {
-        match TARGET_ARCH {
+    pub fn get_not_instr(&self) -> &'static str {
+        match self.m_arch {
             Arch::X86_64 => "xor rax, 1",
             Arch::AArch64 => "eor x0, x0, #1",
         }
     }
 
+    /// Turns any integer into the boolean telling whether it isn't 0.
+    pub fn get_to_bool_instr(&self) -> &'static str {
+        match self.m_arch {
+            Arch::X86_64 => "cmp rax, 0\n\tsetne al\n\tmovzx rax, al",
+            Arch::AArch64 => "cmp x0, #0\n\tcset x0, ne",
+        }
+    }
+
+    // Bitwise operations, `&`, `|` and `^` use the same instructions as the logical ones
+    pub fn get_bitwise_not_instr(&self) -> &'static str {
+        match self.m_arch {
+            Arch::X86_64 => "not rax",
+            Arch::AArch64 => "mvn x0, x0",
+        }
+    }
+
+    pub fn get_negation_instr(&self) -> &'static str {
+        match self.m_arch {
+            Arch::X86_64 => "neg rax",
+            Arch::AArch64 => "neg x0, x0",
+        }
+    }
+
+    /// Shifts are arithmetic, and only the 6 lowest bits of the count are used on both architectures.
+    pub fn get_shift_left_instr(&self) -> &'static str {
+        match self.m_arch {
+            Arch::X86_64 => "sal rax, cl",
+            Arch::AArch64 => "lsl x0, x1, x2",
+        }
+    }
+
+    pub fn get_shift_right_instr(&self) -> &'static str {
+        match self.m_arch {
+            Arch::X86_64 => "sar rax, cl",
+            Arch::AArch64 => "asr x0, x1, x2",
+        }
+    }
+
     // System operations
-    pub fn get_program_header(&self) -> &str {
-        match (TARGET_ARCH, TARGET_OS) {
+    /// Start of the program, up to its first statement. A program linked with the C library is a `main` function
+    /// saving the callee-saved registers, and the frame pointer that `get_exit_instr` restores them from.
+    pub fn get_program_header(&self) -> &'static str {
+        if self.m_link_mode == LinkMode::Libc {
+            return match (self.m_arch, self.m_os) {
+                (Arch::X86_64, OS::Windows) => "extern ExitProcess\nglobal main\nmain:\n\tpush rbp\n\tmov rbp, rsp\n\tpush rbx\n\tpush rsi\n\tpush rdi\n\tpush r12\n\tpush r13\n\tpush r14\n\tpush r15\n",
+                (Arch::X86_64, OS::Linux) => "global main\nmain:\n\tpush rbp\n\tmov rbp, rsp\n\tpush rbx\n\tpush rsi\n\tpush rdi\n\tpush r12\n\tpush r13\n\tpush r14\n\tpush r15\n",
+                (Arch::X86_64, OS::MacOS) => "global _main\n_main:\n\tpush rbp\n\tmov rbp, rsp\n\tpush rbx\n\tpush rsi\n\tpush rdi\n\tpush r12\n\tpush r13\n\tpush r14\n\tpush r15\n",
+                (Arch::AArch64, OS::Windows) => "extern ExitProcess\nglobal main\nmain:\n\tstp x29, x30, [sp, #-96]!\n\tmov x29, sp\n\tstp x19, x20, [sp, #16]\n\tstp x21, x22, [sp, #32]\n\tstp x23, x24, [sp, #48]\n\tstp x25, x26, [sp, #64]\n\tstp x27, x28, [sp, #80]\n",
+                (Arch::AArch64, OS::Linux) => ".global main\nmain:\n\tstp x29, x30, [sp, #-96]!\n\tmov x29, sp\n\tstp x19, x20, [sp, #16]\n\tstp x21, x22, [sp, #32]\n\tstp x23, x24, [sp, #48]\n\tstp x25, x26, [sp, #64]\n\tstp x27, x28, [sp, #80]\n",
+                (Arch::AArch64, OS::MacOS) => ".global _main\n_main:\n\tstp x29, x30, [sp, #-96]!\n\tmov x29, sp\n\tstp x19, x20, [sp, #16]\n\tstp x21, x22, [sp, #32]\n\tstp x23, x24, [sp, #48]\n\tstp x25, x26, [sp, #64]\n\tstp x27, x28, [sp, #80]\n",
+            };
+        }
+        match (self.m_arch, self.m_os) {
             (Arch::X86_64, OS::Windows) => "extern ExitProcess\nglobal _start\n_start:\n",
             (Arch::AArch64, OS::Windows) => "extern ExitProcess\nglobal _start\n_start:\n",
             (Arch::X86_64, _) => "global _start\n_start:\n",
@@ -123,40 +701,66 @@ impl InstructionFactory {
         }
     }
 
-    pub fn get_exit_marker(&self) -> &str {
-        match TARGET_ARCH {
-            Arch::X86_64 => "syscall",
-            Arch::AArch64 => "svc #0",
+    pub fn get_exit_marker(&self) -> &'static str {
+        match (self.m_arch, self.m_link_mode) {
+            (Arch::X86_64, LinkMode::Freestanding) => "syscall",
+            (Arch::AArch64, LinkMode::Freestanding) => "svc #0",
+            (Arch::X86_64, LinkMode::Libc) => "lea rsp, [rbp - 56]",
+            (Arch::AArch64, LinkMode::Libc) => "ldp x29, x30, [sp], #96",
         }
     }
 
-    pub fn get_exit_reg(&self) -> &str {
-        match TARGET_ARCH {
+    pub fn get_exit_reg(&self) -> &'static str {
+        match self.m_arch {
             Arch::X86_64 => "rdi",
             Arch::AArch64 => "x0",
         }
     }
 
-    pub fn get_exit_instr(&self) -> &str {
-        match (TARGET_ARCH, TARGET_OS) {
-            (Arch::X86_64, OS::Linux) => "mov rax, 60\n\tmov rdi, 0\n\tsyscall",
-            (Arch::X86_64, OS::Windows) => "mov rcx, 0\n\tcall ExitProcess",
-            (Arch::X86_64, _) => "mov rax, 0x2000001\n\tmov rdi, 0\n\tsyscall",
-            (Arch::AArch64, OS::Linux) => "mov x8, #93\n\tmov x0, #0\n\tsvc #0",
-            (Arch::AArch64, OS::Windows) => "mov x0, 0\n\tbl ExitProcess",
-            (Arch::AArch64, _) => "ldr x16, =0x2000001\n\tmov x0, 0\n\tsvc #0x80"
+    /// Exits with the code held by the exit register. A program linked with the C library returns it from `main`
+    /// instead, so that the C library flushes its buffers before exiting.
+    pub fn get_exit_instr(&self) -> &'static str {
+        if self.m_link_mode == LinkMode::Libc {
+            return match self.m_arch {
+                Arch::X86_64 => "mov rax, rdi\n\tlea rsp, [rbp - 56]\n\tpop r15\n\tpop r14\n\tpop r13\n\tpop r12\n\tpop rdi\n\tpop rsi\n\tpop rbx\n\tpop rbp\n\tret",
+                Arch::AArch64 => "mov sp, x29\n\tldp x19, x20, [sp, #16]\n\tldp x21, x22, [sp, #32]\n\tldp x23, x24, [sp, #48]\n\tldp x25, x26, [sp, #64]\n\tldp x27, x28, [sp, #80]\n\tldp x29, x30, [sp], #96\n\tret",
+            };
+        }
+        match (self.m_arch, self.m_os) {
+            (Arch::X86_64, OS::Linux) => "mov rax, 60\n\tsyscall",
+            (Arch::X86_64, OS::Windows) => "mov rcx, rdi\n\tcall ExitProcess",
+            (Arch::X86_64, _) => "mov rax, 0x2000001\n\tsyscall",
+            (Arch::AArch64, OS::Linux) => "mov x8, #93\n\tsvc #0",
+            (Arch::AArch64, OS::Windows) => "bl ExitProcess",
+            (Arch::AArch64, _) => "ldr x16, =0x2000001\n\tsvc #0x80"
+        }
+    }
+
+    pub fn get_mov_exit_code_instr(&self, code: u8) -> String {
+        match self.m_arch {
+            Arch::X86_64 => format!("mov rdi, {code}"),
+            Arch::AArch64 => format!("mov x0, #{code}"),
         }
     }
 
     pub fn get_push_instr(&self, reg: &str) -> String {
-        match TARGET_ARCH {
+        match self.m_arch {
             Arch::X86_64 => {format!("\tpush {}\n", reg)}
             Arch::AArch64 => {format!("\tsub sp, sp, #16\n\tstr {}, [sp, #8]\n", reg)}
         }
     }
     
+    /// Pushes the base register `count` times, counting down in a scratch register.
+    pub fn get_repeated_push_instr(&self, count: usize, label: &str) -> String {
+        let push = self.get_push_instr(self.m_arch.get_base_reg());
+        match self.m_arch {
+            Arch::X86_64 => format!("\tmov rcx, {count}\n{label}:\n{push}\tdec rcx\n\tjnz {label}\n"),
+            Arch::AArch64 => format!("\tldr x3, ={count}\n{label}:\n{push}\tsubs x3, x3, #1\n\tb.ne {label}\n"),
+        }
+    }
+
     pub fn get_pop_instr(&self, reg: &str) -> String {
-        match TARGET_ARCH {
+        match self.m_arch {
             Arch::X86_64 => {format!("\tpop {}\n", reg)}
             Arch::AArch64 => {format!("\tldr {}, [sp, #8]\n\tadd sp, sp, #16\n", reg)}
         }
@@ -171,7 +775,7 @@ mod test_architecture{
     
     #[test]
     fn test_add(){
-        let instr_factory = InstructionFactory{};
+        let instr_factory = INSTRUCTION_FACTORY;
         match TARGET_ARCH {
             Arch::X86_64 => assert_eq!(instr_factory.get_addition_instr(), "add rax, rbx"),
             Arch::AArch64 => assert_eq!(instr_factory.get_addition_instr(), "add x0, x1, x2"),
@@ -180,7 +784,7 @@ mod test_architecture{
 
     #[test]
     fn test_sub(){
-        let instr_factory = InstructionFactory{};
+        let instr_factory = INSTRUCTION_FACTORY;
         match TARGET_ARCH {
             Arch::X86_64 => assert_eq!(instr_factory.get_subtraction_instr(), "sub rax, rbx"),
             Arch::AArch64 => assert_eq!(instr_factory.get_subtraction_instr(), "sub x0, x1, x2"),
@@ -189,34 +793,34 @@ mod test_architecture{
 
     #[test]
     fn test_mul(){
-        let instr_factory = InstructionFactory{};
+        let instr_factory = INSTRUCTION_FACTORY;
         match TARGET_ARCH {
-            Arch::X86_64 => assert_eq!(instr_factory.get_multiplication_instr(), "mul rbx"),
+            Arch::X86_64 => assert_eq!(instr_factory.get_multiplication_instr(), "imul rax, rbx"),
             Arch::AArch64 => assert_eq!(instr_factory.get_multiplication_instr(), "mul x0, x1, x2"),
         }
     }
 
     #[test]
     fn test_div(){
-        let instr_factory = InstructionFactory{};
+        let instr_factory = INSTRUCTION_FACTORY;
         match TARGET_ARCH {
-            Arch::X86_64 => assert_eq!(instr_factory.get_division_instr(), "xor rdx, rdx\n\tdiv rbx"),
+            Arch::X86_64 => assert_eq!(instr_factory.get_division_instr(), "cmp rbx, -1\n\tjne {div_label}\n\tneg rax\n\tjmp {done_label}\n{div_label}:\n\tcqo\n\tidiv rbx\n{done_label}:"),
             Arch::AArch64 => assert_eq!(instr_factory.get_division_instr(), "sdiv x0, x1, x2"),
         }
     }
     
     #[test]
     fn test_mod(){
-        let instr_factory = InstructionFactory{};
+        let instr_factory = INSTRUCTION_FACTORY;
         match TARGET_ARCH {
-            Arch::X86_64 => assert_eq!(instr_factory.get_modulo_instr(), "xor rdx, rdx\n\tdiv rbx"),
+            Arch::X86_64 => assert_eq!(instr_factory.get_modulo_instr(), "cmp rbx, -1\n\tjne {div_label}\n\tmov rdx, 0\n\tjmp {done_label}\n{div_label}:\n\tcqo\n\tidiv rbx\n{done_label}:"),
             Arch::AArch64 => assert_eq!(instr_factory.get_modulo_instr(), "sdiv x3, x1, x2\n\tmsub x0, x3, x2, x1"),
         }
     }
     
     #[test]
     fn test_exp(){
-        let instr_factory = InstructionFactory{};
+        let instr_factory = INSTRUCTION_FACTORY;
         let exp_instr = instr_factory.get_exponentiation_instr();
         let expected_instr = match (TARGET_ARCH, TARGET_OS) {
             (Arch::X86_64, _) => concat!(
@@ -255,7 +859,7 @@ mod test_architecture{
     
     #[test]
     fn test_mov_num(){
-        let instr_factory = InstructionFactory{};
+        let instr_factory = INSTRUCTION_FACTORY;
         match (TARGET_ARCH, TARGET_OS) {
             (Arch::X86_64, _) => {
                 assert_eq!(instr_factory.get_mov_number_instr("0"), "mov rax, 0");
@@ -274,7 +878,7 @@ mod test_architecture{
     
     #[test]
     fn test_mov_bool(){
-        let instr_factory = InstructionFactory{};
+        let instr_factory = INSTRUCTION_FACTORY;
         match TARGET_ARCH {
             Arch::X86_64 => {
                 assert_eq!(instr_factory.get_mov_boolean_instr(true), "mov rax, 1");
@@ -289,22 +893,62 @@ mod test_architecture{
     
     #[test]
     fn test_load(){
-        let instr_factory = InstructionFactory{};
+        let instr_factory = INSTRUCTION_FACTORY;
         match TARGET_ARCH {
             Arch::X86_64 => {
                 assert_eq!(instr_factory.get_load_variable_instr(0), "mov rax, [rsp + 0]");
                 assert_eq!(instr_factory.get_load_variable_instr(8), "mov rax, [rsp + 8]");
             },
             Arch::AArch64 => {
-                assert_eq!(instr_factory.get_load_variable_instr(0), "ldr x0, [sp, #0]");
-                assert_eq!(instr_factory.get_load_variable_instr(8), "ldr x0, [sp, #8]");
+                assert_eq!(instr_factory.get_load_variable_instr(0), "ldr x0, [sp, #8]");
+                assert_eq!(instr_factory.get_load_variable_instr(8), "ldr x0, [sp, #24]");
             }
         }
     }
     
+    #[test]
+    fn test_store(){
+        let instr_factory = INSTRUCTION_FACTORY;
+        match TARGET_ARCH {
+            Arch::X86_64 => assert_eq!(instr_factory.get_store_variable_instr(8), "mov [rsp + 8], rax"),
+            Arch::AArch64 => assert_eq!(instr_factory.get_store_variable_instr(8), "str x0, [sp, #24]"),
+        }
+    }
+    
+    #[test]
+    fn test_elements(){
+        let instr_factory = INSTRUCTION_FACTORY;
+        match TARGET_ARCH {
+            Arch::X86_64 => {
+                assert_eq!(instr_factory.get_bounds_check(3, "index_out_of_bounds0"), "cmp rax, 3\n\tjae index_out_of_bounds0");
+                assert_eq!(instr_factory.get_repeated_push_instr(8, "array_fill0"), "\tmov rcx, 8\narray_fill0:\n\tpush rax\n\tdec rcx\n\tjnz array_fill0\n");
+                assert_eq!(instr_factory.get_load_element_instr(16), "neg rax\n\tmov rax, [rsp + rax * 8 + 16]");
+                assert_eq!(instr_factory.get_store_element_instr(16), "neg rax\n\tmov [rsp + rax * 8 + 16], rbx");
+                assert_eq!(instr_factory.get_tag_check(2, "match_arm0_1"), "cmp rax, 2\n\tjne match_arm0_1");
+                assert_eq!(instr_factory.get_jump_instr("match_end0"), "jmp match_end0");
+                assert_eq!(instr_factory.get_range_check(8, false, "for_end0"), "cmp rax, [rsp + 8]\n\tjge for_end0");
+                assert_eq!(instr_factory.get_range_check(8, true, "for_end0"), "cmp rax, [rsp + 8]\n\tjg for_end0");
+                assert_eq!(instr_factory.get_step_instr(2, "for_end0"), "mov rbx, 2\n\tadd rax, rbx\n\tjo for_end0");
+                assert_eq!(instr_factory.get_release_instr(2), "add rsp, 16");
+            },
+            Arch::AArch64 => {
+                assert_eq!(instr_factory.get_bounds_check(3, "index_out_of_bounds0"), "ldr x3, =3\n\tcmp x0, x3\n\tb.hs index_out_of_bounds0");
+                assert!(instr_factory.get_repeated_push_instr(8, "array_fill0").ends_with("\tsubs x3, x3, #1\n\tb.ne array_fill0\n"));
+                assert_eq!(instr_factory.get_load_element_instr(16), "mov x1, sp\n\tsub x1, x1, x0, lsl #4\n\tldr x0, [x1, #40]");
+                assert_eq!(instr_factory.get_store_element_instr(16), "mov x1, sp\n\tsub x1, x1, x0, lsl #4\n\tstr x2, [x1, #40]");
+                assert_eq!(instr_factory.get_tag_check(2, "match_arm0_1"), "cmp x0, #2\n\tb.ne match_arm0_1");
+                assert_eq!(instr_factory.get_jump_instr("match_end0"), "b match_end0");
+                assert_eq!(instr_factory.get_range_check(8, false, "for_end0"), "ldr x1, [sp, #24]\n\tcmp x0, x1\n\tb.ge for_end0");
+                assert_eq!(instr_factory.get_range_check(8, true, "for_end0"), "ldr x1, [sp, #24]\n\tcmp x0, x1\n\tb.gt for_end0");
+                assert_eq!(instr_factory.get_step_instr(2, "for_end0"), "ldr x1, =2\n\tadds x0, x0, x1\n\tb.vs for_end0");
+                assert_eq!(instr_factory.get_release_instr(2), "add sp, sp, #32");
+            }
+        }
+    }
+
     #[test]
     fn test_and(){
-        let instr_factory = InstructionFactory{};
+        let instr_factory = INSTRUCTION_FACTORY;
         match TARGET_ARCH {
             Arch::X86_64 => assert_eq!(instr_factory.get_and_instr(), "and rax, rbx"),
             Arch::AArch64 => assert_eq!(instr_factory.get_and_instr(), "and x0, x1, x2")
@@ -313,7 +957,7 @@ mod test_architecture{
     
     #[test]
     fn test_or(){
-        let instr_factory = InstructionFactory{};
+        let instr_factory = INSTRUCTION_FACTORY;
         match TARGET_ARCH {
             Arch::X86_64 => assert_eq!(instr_factory.get_or_instr(), "or rax, rbx"),
             Arch::AArch64 => assert_eq!(instr_factory.get_or_instr(), "orr x0, x1, x2")
@@ -322,7 +966,7 @@ mod test_architecture{
     
     #[test]
     fn test_xor(){
-        let instr_factory = InstructionFactory{};
+        let instr_factory = INSTRUCTION_FACTORY;
         match TARGET_ARCH {
             Arch::X86_64 => assert_eq!(instr_factory.get_xor_instr(), "xor rax, rbx"),
             Arch::AArch64 => assert_eq!(instr_factory.get_xor_instr(), "eor x0, x1, x2")
@@ -331,16 +975,33 @@ mod test_architecture{
     
     #[test]
     fn test_not(){
-        let instr_factory = InstructionFactory{};
+        let instr_factory = INSTRUCTION_FACTORY;
         match TARGET_ARCH {
             Arch::X86_64 => assert_eq!(instr_factory.get_not_instr(), "xor rax, 1"),
             Arch::AArch64 => assert_eq!(instr_factory.get_not_instr(), "eor x0, x0, #1")
         }
     }
     
+    #[test]
+    fn test_bitwise(){
+        let instr_factory = INSTRUCTION_FACTORY;
+        match TARGET_ARCH {
+            Arch::X86_64 => {
+                assert_eq!(instr_factory.get_bitwise_not_instr(), "not rax");
+                assert_eq!(instr_factory.get_shift_left_instr(), "sal rax, cl");
+                assert_eq!(instr_factory.get_shift_right_instr(), "sar rax, cl");
+            },
+            Arch::AArch64 => {
+                assert_eq!(instr_factory.get_bitwise_not_instr(), "mvn x0, x0");
+                assert_eq!(instr_factory.get_shift_left_instr(), "lsl x0, x1, x2");
+                assert_eq!(instr_factory.get_shift_right_instr(), "asr x0, x1, x2");
+            }
+        }
+    }
+    
     #[test]
     fn test_prog_header(){
-        let instr_factory = InstructionFactory{};
+        let instr_factory = INSTRUCTION_FACTORY;
         match (TARGET_ARCH, TARGET_OS) {
             (Arch::X86_64, OS::Windows) => assert_eq!(instr_factory.get_program_header(), "extern ExitProcess\nglobal _start\n_start:\n"),
             (Arch::AArch64, OS::Windows) => assert_eq!(instr_factory.get_program_header(), "extern ExitProcess\nglobal _start\n_start:\n"),
@@ -351,7 +1012,7 @@ mod test_architecture{
     
     #[test]
     fn test_get_exit_marker(){
-        let instr_factory = InstructionFactory{};
+        let instr_factory = INSTRUCTION_FACTORY;
         match TARGET_ARCH {
             Arch::X86_64 => assert_eq!(instr_factory.get_exit_marker(),"syscall"),
             Arch::AArch64 => assert_eq!(instr_factory.get_exit_marker(), "svc #0")
@@ -360,7 +1021,7 @@ mod test_architecture{
     
     #[test]
     fn test_get_exit_reg(){
-        let instr_factory = InstructionFactory{};
+        let instr_factory = INSTRUCTION_FACTORY;
         match TARGET_ARCH {
             Arch::X86_64 => assert_eq!(instr_factory.get_exit_reg(), "rdi"),
             Arch::AArch64 => assert_eq!(instr_factory.get_exit_reg(), "x0")
@@ -369,26 +1030,117 @@ mod test_architecture{
     
     #[test]
     fn test_exit(){
-        let instr_factory = InstructionFactory{};
+        let instr_factory = INSTRUCTION_FACTORY;
         let exit_instr = instr_factory.get_exit_instr();
         let expected_instr = match (TARGET_ARCH, TARGET_OS){
             (Arch::X86_64, OS::Linux) => concat!("mov rax, 60\n",
-                                                "\tmov rdi, 0\n",
                                                 "\tsyscall"),
-            (Arch::X86_64, OS::Windows) => concat!("mov rcx, 0\n",
+            (Arch::X86_64, OS::Windows) => concat!("mov rcx, rdi\n",
                                                 "\tcall ExitProcess"),
             (Arch::X86_64, _) => concat!("mov rax, 0x2000001\n",
-                                        "\tmov rdi, 0\n",
                                         "\tsyscall"),
             (Arch::AArch64, OS::Linux) => concat!("mov x8, #93\n",
-                                                "\tmov x0, #0\n",
                                                 "\tsvc #0"),
-            (Arch::AArch64, OS::Windows) => concat!("mov x0, 0\n",
-                                                "\tbl ExitProcess"),
+            (Arch::AArch64, OS::Windows) => "bl ExitProcess",
             (Arch::AArch64, _) => concat!("ldr x16, =0x2000001\n",
-                                        "\tmov x0, 0\n",
                                         "\tsvc #0x80")
         };
         assert_eq!(exit_instr, expected_instr);
+        // The exit code is never overwritten before exiting
+        assert!(!exit_instr.contains(&instr_factory.get_mov_exit_code_instr(0)));
+    }
+
+    #[test]
+    fn test_runtime_panic(){
+        let x86 = InstructionFactory::new(Arch::X86_64, OS::Linux);
+        // The divisor is checked in the register the division reads it from
+        let (_, divisor_reg, _) = Arch::X86_64.get_arithmetic_regs();
+        assert_eq!(x86.get_zero_divisor_check("division_by_zero0"), format!("test {divisor_reg}, {divisor_reg}\n\tjz division_by_zero0"));
+        assert!(x86.get_division_instr().contains(&format!("idiv {divisor_reg}")));
+        assert_eq!(x86.get_panic_call("panic_message0", 32), "lea rsi, [rel panic_message0]\n\tmov rdx, 32\n\tjmp runtime_panic");
+        assert_eq!(x86.get_message_data("panic_message0", "a \"b\""), "panic_message0: db \"a 'b'\", 10");
+        assert!(x86.get_panic_routine().starts_with("runtime_panic:\n\tmov rax, 1\n\tmov rdi, 2\n\tsyscall\n\tmov rdi, 101\n"));
+
+        let aarch64 = InstructionFactory::new(Arch::AArch64, OS::MacOS);
+        let (dividend_reg, divisor_reg, _) = Arch::AArch64.get_arithmetic_regs();
+        assert_eq!(aarch64.get_zero_divisor_check("division_by_zero0"), format!("cbz {divisor_reg}, division_by_zero0"));
+        assert!(aarch64.get_division_instr().ends_with(&format!("{dividend_reg}, {divisor_reg}")));
+        assert_eq!(aarch64.get_message_data("panic_message0", "a"), "panic_message0: .ascii \"a\\n\"");
+        for os in [OS::Linux, OS::Windows, OS::MacOS] {
+            for arch in [Arch::X86_64, Arch::AArch64] {
+                assert!(InstructionFactory::new(arch, os).get_panic_routine().contains("runtime_panic:"));
+            }
+        }
+    }
+
+    #[test]
+    fn test_heap_routines(){
+        let x86 = InstructionFactory::new(Arch::X86_64, OS::Linux);
+        assert_eq!(x86.get_call_instr("runtime_alloc"), "call runtime_alloc");
+        assert!(x86.get_alloc_routine().contains("mov rax, 9\n"));
+        assert!(x86.get_free_routine().contains("mov rax, 11\n"));
+        assert_eq!(InstructionFactory::new(Arch::AArch64, OS::Linux).get_call_instr("runtime_free"), "bl runtime_free");
+        for os in [OS::Linux, OS::Windows, OS::MacOS] {
+            for arch in [Arch::X86_64, Arch::AArch64] {
+                let factory = InstructionFactory::new(arch, os);
+                assert!(factory.get_alloc_routine().contains("runtime_alloc:") && factory.get_alloc_routine().contains("alloc_failed"));
+                assert!(factory.get_free_routine().contains("runtime_free:"));
+            }
+        }
+    }
+
+
+    #[test]
+    fn test_input_routines(){
+        let x86 = InstructionFactory::new(Arch::X86_64, OS::Linux);
+        assert_eq!(x86.get_arguments_setup(), "mov r12, [rsp]\n\tlea r13, [rsp + 8]");
+        assert_eq!(x86.get_argument_count_instr(), "mov rax, r12");
+        assert!(x86.get_read_line_routine().contains("mov rax, 0\n\tmov rdi, 0\n"));
+        assert_eq!(InstructionFactory::new(Arch::AArch64, OS::MacOS).get_arguments_setup(), "mov x27, x0\n\tmov x28, x1");
+        assert_eq!(InstructionFactory::new(Arch::X86_64, OS::Windows).get_arguments_setup(), "call runtime_command_line");
+        for os in [OS::Linux, OS::Windows, OS::MacOS] {
+            for arch in [Arch::X86_64, Arch::AArch64] {
+                let factory = InstructionFactory::new(arch, os);
+                assert!(factory.get_arg_routine().starts_with("runtime_arg:") && factory.get_arg_routine().contains("arg_out_of_bounds"));
+                assert!(factory.get_parse_int_routine().contains("invalid_integer"));
+                assert!(factory.get_read_int_routine().contains(&factory.get_call_instr("runtime_read_line")));
+                assert!(factory.get_read_line_routine().contains(&factory.get_call_instr("runtime_alloc")));
+                assert!(factory.get_command_line_routine().contains("runtime_command_line:"));
+                assert_eq!(factory.splits_command_line(), factory.get_arguments_setup().contains("runtime_command_line"));
+            }
+        }
+    }
+
+    #[test]
+    fn test_libc_mode(){
+        let mut x86 = InstructionFactory::new(Arch::X86_64, OS::Linux);
+        x86.set_link_mode(LinkMode::Libc);
+        assert!(x86.get_program_header().starts_with("global main\nmain:\n\tpush rbp\n\tmov rbp, rsp\n"));
+        assert_eq!(x86.get_arguments_setup(), "mov r12, rdi\n\tmov r13, rsi");
+        assert_eq!(x86.get_extern_declaration("printf"), Some("extern printf".to_string()));
+        assert_eq!(x86.get_c_call_instr("printf", 2, 1), "mov rbx, rsp\n\tand rsp, -16\n\tmov rdi, [rbx + 8]\n\tmov rsi, [rbx + 0]\n\tmov rax, 0\n\tcall printf wrt ..plt\n\tmov rsp, rbx");
+        // The 7th argument is on the stack, which stays aligned on 16 bytes
+        assert!(x86.get_c_call_instr("f", 7, 7).starts_with("mov rbx, rsp\n\tand rsp, -16\n\tsub rsp, 8\n\tpush qword [rbx + 0]\n\tmov rdi, [rbx + 48]\n"));
+        assert_eq!(x86.get_load_address_instr("string0"), "lea rax, [rel string0]");
+        assert_eq!(x86.get_string_data("string0", "a\n"), "string0: db 97, 10, 0");
+
+        // Variadic arguments are on the stack on macOS AArch64
+        let mut aarch64 = InstructionFactory::new(Arch::AArch64, OS::MacOS);
+        aarch64.set_link_mode(LinkMode::Libc);
+        assert!(aarch64.get_program_header().starts_with(".global _main\n_main:\n"));
+        assert_eq!(aarch64.get_extern_declaration("printf"), None);
+        assert_eq!(aarch64.get_c_call_instr("printf", 2, 1), "mov x19, sp\n\tsub sp, sp, #16\n\tldr x9, [x19, #8]\n\tstr x9, [sp, #0]\n\tldr x0, [x19, #24]\n\tbl _printf\n\tmov sp, x19");
+        aarch64 = InstructionFactory::new(Arch::AArch64, OS::Linux);
+        aarch64.set_link_mode(LinkMode::Libc);
+        assert_eq!(aarch64.get_c_call_instr("printf", 2, 1), "mov x19, sp\n\tldr x0, [x19, #24]\n\tldr x1, [x19, #8]\n\tbl printf\n\tmov sp, x19");
+        for os in [OS::Linux, OS::Windows, OS::MacOS] {
+            for arch in [Arch::X86_64, Arch::AArch64] {
+                let mut factory = InstructionFactory::new(arch, os);
+                factory.set_link_mode(LinkMode::Libc);
+                assert!(factory.get_program_header().contains("main:\n"));
+                // Returning from `main` is what tells a program that exits from one that doesn't
+                assert!(factory.get_exit_instr().contains(factory.get_exit_marker()) && factory.get_exit_instr().ends_with("ret"));
+            }
+        }
     }
 }
\ No newline at end of file
@@ -7,4 +7,4 @@ pub use generator::Generator;
 
 use arithmetic_instructions::ArithmeticInstructions;
 use stack_handler::StackHandler;
-use instruction_factory::INSTRUCTION_FACTORY;
\ No newline at end of file
+use instruction_factory::{InstructionFactory, INSTRUCTION_FACTORY};
\ No newline at end of file
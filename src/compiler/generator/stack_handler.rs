@@ -1,27 +1,37 @@
 use std::collections::HashMap;
+use crate::compiler::parser::ResultType;
 
 #[derive(Debug)]
 #[derive(PartialEq)]
 struct Variable{
     m_name: String,
-    m_type: String,
+    m_type: ResultType,
     m_scope_depth: usize,
-    m_stack_loc: usize
+    /// Location of the value or, for an array, of its first element.
+    m_stack_loc: usize,
+    m_length: Option<usize>,
+    m_struct: Option<String>
 }
 
 impl Variable{
-    fn new(name: String, r#type: String, m_scope_depth: usize,  m_stack_loc: usize) -> Self{
+    fn new(name: String, r#type: ResultType, m_scope_depth: usize,  m_stack_loc: usize) -> Self{
         Variable{
             m_name: name,
             m_type: r#type,
             m_scope_depth,
             m_stack_loc,
+            m_length: None,
+            m_struct: None,
         }
     }
 }
 
 pub struct StackHandler {
     m_variables: HashMap<String, Vec<Variable>>,
+    /// Fields of every struct in the order of their slots, the fields of a nested struct being named like `start.x`.
+    m_structs: HashMap<String, Vec<(String, ResultType)>>,
+    /// Variants of every enum in the order of their tags, with the types of their values.
+    m_enums: HashMap<String, Vec<(String, Vec<ResultType>)>>,
     m_stack_size: usize,
     m_scope_depth: usize
 }
@@ -30,17 +40,124 @@ impl StackHandler{
     pub fn new() -> Self{
         StackHandler {
             m_variables: HashMap::new(),
+            m_structs: HashMap::new(),
+            m_enums: HashMap::new(),
             m_stack_size: 0,
             m_scope_depth: 0
         }
     }
     
-    pub fn add_variable(&mut self, name: String, r#type: String){
+    /// Tracks a value pushed on the stack, so that the offsets of the variables below it stay correct.
+    pub fn push(&mut self){
         self.m_stack_size += 8;
+    }
+
+    pub fn pop(&mut self){
+        self.m_stack_size -= 8;
+    }
+
+    /// Names the value on top of the stack.
+    pub fn add_variable(&mut self, name: String, r#type: ResultType){
         let variable = Variable::new(name.clone(), r#type, self.m_scope_depth, self.m_stack_size);
         self.m_variables.entry(name).or_insert(vec![]).push(variable);
     }
     
+    /// Names the value just below the top of the stack, like the counter of a loop below the end of its range.
+    pub fn add_variable_below_top(&mut self, name: String, r#type: ResultType){
+        let variable = Variable::new(name.clone(), r#type, self.m_scope_depth, self.m_stack_size - 8);
+        self.m_variables.entry(name).or_insert(vec![]).push(variable);
+    }
+
+    /// Names the `length` values on top of the stack as the elements of an array, the first one being the deepest.
+    pub fn add_array(&mut self, name: String, r#type: ResultType, length: usize){
+        let mut variable = Variable::new(name.clone(), r#type, self.m_scope_depth, self.m_stack_size - 8 * (length - 1));
+        variable.m_length = Some(length);
+        self.m_variables.entry(name).or_insert(vec![]).push(variable);
+    }
+
+    /// Declares the layout of a struct from the type names of its fields, which are `int`, `bool` or a struct declared before.
+    pub fn add_struct(&mut self, name: String, fields: &[(String, String)]){
+        let mut layout = Vec::new();
+        for (field, type_name) in fields {
+            match (type_name.as_str(), self.m_structs.get(type_name)) {
+                ("bool", _) => layout.push((field.clone(), ResultType::Boolean)),
+                (_, Some(nested)) => layout.extend(nested.iter().map(|(path, r#type)| (format!("{field}.{path}"), *r#type))),
+                _ => layout.push((field.clone(), ResultType::Numeric)),
+            }
+        }
+        self.m_structs.insert(name, layout);
+    }
+
+    /// Names the values on top of the stack as the fields of a struct, the first field being the deepest.
+    pub fn add_struct_variable(&mut self, name: String, struct_name: String){
+        let size = self.get_fields(&struct_name).len().max(1);
+        let mut variable = Variable::new(name.clone(), ResultType::Numeric, self.m_scope_depth, self.m_stack_size - 8 * (size - 1));
+        variable.m_struct = Some(struct_name);
+        self.m_variables.entry(name).or_insert(vec![]).push(variable);
+    }
+
+    /// Declares the variants of an enum from the type names of their values. An enum variable is laid out like a struct
+    /// whose fields are its tag, followed by the values of its largest variant, named `0`, `1`...
+    pub fn add_enum(&mut self, name: String, variants: &[(String, Vec<String>)]){
+        let variants: Vec<(String, Vec<ResultType>)> = variants.iter().map(|(variant, types)| {
+            (variant.clone(), types.iter().map(|type_name| ResultType::from_name(type_name).unwrap_or(ResultType::Numeric)).collect())
+        }).collect();
+        let size = variants.iter().map(|(_, types)| types.len()).max().unwrap_or(0);
+        let mut layout = vec![("tag".to_string(), ResultType::Numeric)];
+        layout.extend((0..size).map(|slot| (slot.to_string(), ResultType::Numeric)));
+        self.m_structs.insert(name.clone(), layout);
+        self.m_enums.insert(name, variants);
+    }
+
+    /// Tag of a variant of an enum, with the types of its values.
+    pub fn get_variant(&self, enum_name: &str, variant: &str) -> Option<(usize, Vec<ResultType>)>{
+        let variants = self.m_enums.get(enum_name)?;
+        let tag = variants.iter().position(|(name, _)| name == variant)?;
+        Some((tag, variants[tag].1.clone()))
+    }
+
+    /// Names the field `path` of the struct variable `variable` as a variable of type `r#type`, without pushing anything.
+    pub fn add_alias(&mut self, name: String, r#type: ResultType, variable: &str, path: &str){
+        let Some(struct_name) = self.get_struct(variable) else { return };
+        let Some(position) = self.get_fields(&struct_name).iter().position(|(field, _)| field == path) else { return };
+        let Some(stack_loc) = self.m_variables.get(variable).and_then(|variables| variables.last()).map(|variable| variable.m_stack_loc) else { return };
+        let alias = Variable::new(name.clone(), r#type, self.m_scope_depth, stack_loc + 8 * position);
+        self.m_variables.entry(name).or_insert(vec![]).push(alias);
+    }
+
+    /// Struct of the variable `name` refers to, `None` when it isn't a struct.
+    pub fn get_struct(&self, name: &str) -> Option<String>{
+        self.m_variables.get(name)?.last()?.m_struct.clone()
+    }
+
+    /// Fields of a struct in the order of their slots, none when the struct isn't declared.
+    pub fn get_fields(&self, struct_name: &str) -> Vec<(String, ResultType)>{
+        self.m_structs.get(struct_name).cloned().unwrap_or_default()
+    }
+
+    /// Offset from the top of the stack and type of the field `path`, like `start.x`, of the struct variable `name`.
+    pub fn get_field(&mut self, name: &str, path: &str) -> Option<(usize, ResultType)>{
+        let fields = self.get_fields(&self.get_struct(name)?);
+        let position = fields.iter().position(|(field, _)| field == path)?;
+        Some((self.get_offset(name.to_string()) - 8 * position, fields[position].1))
+    }
+
+    /// Number of elements of the array `name` refers to, `None` when it isn't an array.
+    pub fn get_length(&self, name: &str) -> Option<usize>{
+        self.m_variables.get(name)?.last()?.m_length
+    }
+
+    /// Whether `name` is a variable of the current scope or of one around it, that an assignment overwrites.
+    pub fn is_declared(&self, name: &str) -> bool{
+        self.m_variables.get(name).is_some_and(|variable| !variable.is_empty())
+    }
+    
+    /// Type of the variable `name` refers to, `None` when it isn't declared.
+    pub fn get_type(&self, name: &str) -> Option<ResultType>{
+        self.m_variables.get(name)?.last().map(|variable| variable.m_type)
+    }
+
+    /// Offset of the variable from the top of the stack. Element `i` of an array is `8 * i` bytes above its first one.
     pub fn get_offset(&mut self, name: String) -> usize{
         let variable = self.m_variables.get(&name).expect("No variable found");
         let variable_pos = variable.last().expect("The Stack Handler should have deleted this entry").m_stack_loc;
@@ -76,24 +193,126 @@ mod test_stack_handler{
     #[test]
     fn test_add_variable(){
         let mut stack = StackHandler::new();
-        stack.add_variable("Test".to_string(), "Test".to_string());
+        stack.push();
+        stack.add_variable("Test".to_string(), ResultType::Numeric);
         assert_eq!(stack.m_stack_size, 8);
         assert_eq!(stack.m_scope_depth, 0);
 
         assert!(stack.m_variables.get(&"Test".to_string()).is_some());
         let test_variables = stack.m_variables.get(&"Test".to_string()).expect("There should have been a check that there was a variable in the map");
-        assert_eq!(test_variables, &vec![Variable::new("Test".to_string(), "Test".to_string(), 0, 8)])
+        assert_eq!(test_variables, &vec![Variable::new("Test".to_string(), ResultType::Numeric, 0, 8)])
     }
 
     #[test]
     fn test_get_offset(){
         let mut stack = StackHandler::new();
-        stack.add_variable("Test".to_string(), "Test".to_string());
+        stack.push();
+        stack.add_variable("Test".to_string(), ResultType::Numeric);
         assert_eq!(stack.get_offset("Test".to_string()), 0);
 
-        stack.add_variable("Test2".to_string(), "Test2".to_string());
+        stack.push();
+        stack.add_variable("Test2".to_string(), ResultType::Numeric);
         assert_eq!(stack.get_offset("Test2".to_string()), 0);
         assert_eq!(stack.get_offset("Test".to_string()), 8);
+
+        // Temporaries pushed while evaluating an expression move the variables away from the top
+        stack.push();
+        assert_eq!(stack.get_offset("Test".to_string()), 16);
+        stack.pop();
+        assert_eq!(stack.get_offset("Test".to_string()), 8);
+    }
+
+    #[test]
+    fn test_add_variable_below_top(){
+        let mut stack = StackHandler::new();
+        stack.push();
+        stack.push();
+        stack.increase_scope_depth();
+        stack.add_variable_below_top("i".to_string(), ResultType::Numeric);
+        assert_eq!(stack.get_offset("i".to_string()), 8);
+        stack.push();
+        assert_eq!(stack.get_offset("i".to_string()), 16);
+        stack.pop();
+        stack.decrease_scope_depth();
+        assert!(!stack.is_declared("i"));
+    }
+
+    #[test]
+    fn test_add_array(){
+        let mut stack = StackHandler::new();
+        stack.push();
+        stack.push();
+        stack.push();
+        stack.add_array("a".to_string(), ResultType::Boolean, 3);
+        assert_eq!(stack.get_offset("a".to_string()), 16);
+        assert_eq!(stack.get_length("a"), Some(3));
+        assert_eq!(stack.get_type("a"), Some(ResultType::Boolean));
+        stack.push();
+        stack.add_variable("x".to_string(), ResultType::Numeric);
+        assert_eq!(stack.get_offset("a".to_string()), 24);
+        assert_eq!(stack.get_length("x"), None);
+    }
+
+    #[test]
+    fn test_add_struct(){
+        let mut stack = StackHandler::new();
+        stack.add_struct("Point".to_string(), &[("x".to_string(), "int".to_string()), ("y".to_string(), "bool".to_string())]);
+        stack.add_struct("Line".to_string(), &[("start".to_string(), "Point".to_string()), ("end".to_string(), "Point".to_string())]);
+        assert_eq!(stack.get_fields("Line").len(), 4);
+        for _ in 0..4 {
+            stack.push();
+        }
+        stack.add_struct_variable("l".to_string(), "Line".to_string());
+        assert_eq!(stack.get_struct("l"), Some("Line".to_string()));
+        assert_eq!(stack.get_field("l", "start.x"), Some((24, ResultType::Numeric)));
+        assert_eq!(stack.get_field("l", "end.y"), Some((0, ResultType::Boolean)));
+        assert_eq!(stack.get_field("l", "start"), None);
+        stack.push();
+        stack.add_variable("x".to_string(), ResultType::Numeric);
+        assert_eq!(stack.get_field("l", "start.y"), Some((24, ResultType::Boolean)));
+        assert_eq!(stack.get_struct("x"), None);
+    }
+
+    #[test]
+    fn test_add_enum(){
+        let mut stack = StackHandler::new();
+        stack.add_enum("Shape".to_string(), &[
+            ("Circle".to_string(), vec!["int".to_string()]),
+            ("Square".to_string(), vec!["bool".to_string(), "int".to_string()]),
+            ("Empty".to_string(), Vec::new()),
+        ]);
+        assert_eq!(stack.get_fields("Shape").len(), 3);
+        assert_eq!(stack.get_variant("Shape", "Square"), Some((1, vec![ResultType::Boolean, ResultType::Numeric])));
+        assert_eq!(stack.get_variant("Shape", "Line"), None);
+        for _ in 0..3 {
+            stack.push();
+        }
+        stack.add_struct_variable("s".to_string(), "Shape".to_string());
+        assert_eq!(stack.get_field("s", "tag"), Some((16, ResultType::Numeric)));
+        stack.increase_scope_depth();
+        stack.add_alias("side".to_string(), ResultType::Numeric, "s", "1");
+        stack.push();
+        assert_eq!(stack.get_offset("side".to_string()), 8);
+        assert_eq!(stack.get_type("side"), Some(ResultType::Numeric));
+        stack.decrease_scope_depth();
+        assert!(!stack.is_declared("side"));
+    }
+
+    #[test]
+    fn test_is_declared(){
+        let mut stack = StackHandler::new();
+        assert!(!stack.is_declared("x"));
+        stack.increase_scope_depth();
+        stack.push();
+        stack.add_variable("x".to_string(), ResultType::Numeric);
+        assert!(stack.is_declared("x"));
+        assert_eq!(stack.get_type("x"), Some(ResultType::Numeric));
+        stack.increase_scope_depth();
+        assert!(stack.is_declared("x"));
+        stack.decrease_scope_depth();
+        stack.decrease_scope_depth();
+        assert!(!stack.is_declared("x"));
+        assert_eq!(stack.get_type("x"), None);
     }
 
     #[test]
@@ -106,10 +325,10 @@ mod test_stack_handler{
     #[test]
     fn test_decrease_scope_depth(){
         let mut stack = StackHandler::new();
-        stack.add_variable("Scope".to_string(), "Scope".to_string());
+        stack.add_variable("Scope".to_string(), ResultType::Numeric);
         stack.increase_scope_depth();
-        stack.add_variable("Scope".to_string(), "Scope".to_string());
-        stack.add_variable("Scope".to_string(), "Scope".to_string());
+        stack.add_variable("Scope".to_string(), ResultType::Numeric);
+        stack.add_variable("Scope".to_string(), ResultType::Numeric);
         stack.increase_scope_depth();
 
         stack.decrease_scope_depth();
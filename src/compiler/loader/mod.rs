@@ -0,0 +1,5 @@
+mod module_loader;
+
+pub use module_loader::{
+    ModuleLoader
+};
\ No newline at end of file
@@ -0,0 +1,249 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use crate::compiler::analysis::LintConfig;
+use crate::compiler::logger::Logger;
+use crate::compiler::parser::{NodeImport, NodeProgram, NodeStmt, Parser, ParserLogger};
+use crate::compiler::tokenizer::Tokenizer;
+
+/// A parsed source file. Its index in the loader is the file id carried by all of its spans.
+pub struct Module {
+    pub m_name: String,
+    pub m_path: PathBuf,
+    pub m_source: String,
+    pub m_logger: Arc<Mutex<ParserLogger>>,
+    pub m_lint_config: LintConfig,
+    pub m_prog: Option<NodeProgram>
+}
+
+/// Loads a root file and every file it imports, each one exactly once.
+pub struct ModuleLoader {
+    m_lint_config: LintConfig,
+    m_modules: Vec<Module>,
+    m_loading: Vec<usize>,
+    m_order: Vec<usize>
+}
+
+impl ModuleLoader {
+    pub fn new(m_lint_config: LintConfig) -> Self {
+        ModuleLoader { m_lint_config, m_modules: Vec::new(), m_loading: Vec::new(), m_order: Vec::new() }
+    }
+
+    /// Loads the root file, which always gets the file id 0, then recursively the files it imports.
+    pub fn load(&mut self, file_name: &str, source: &str) {
+        let path = Self::normalize(Path::new(file_name));
+        self.load_module(file_name.to_string(), path, source.to_string());
+    }
+
+    pub fn get_modules(&self) -> &[Module] {
+        &self.m_modules
+    }
+
+    pub fn failed(&self) -> bool {
+        self.m_modules.iter().any(|module| module.m_prog.is_none() || module.m_logger.lock().unwrap().failed_parsing())
+    }
+
+    /// Combines every module into a single program. Imported files come before the files importing them,
+    /// so that their top level variables are defined before being used.
+    pub fn get_program(&self) -> Option<NodeProgram> {
+        let mut stmts = Vec::new();
+        for &file_id in &self.m_order {
+            let prog = self.m_modules[file_id].m_prog.as_ref()?;
+            stmts.extend(prog.stmts.iter().filter(|stmt| !matches!(stmt, NodeStmt::Import(_))).cloned());
+        }
+        let span = self.m_modules.first()?.m_prog.as_ref()?.span;
+        Some(NodeProgram { stmts, span })
+    }
+
+    fn load_module(&mut self, name: String, path: PathBuf, source: String) -> usize {
+        let file_id = self.m_modules.len();
+        let logger = Arc::new(Mutex::new(ParserLogger::new(name.clone(), source.clone())));
+        let mut lint_config = self.m_lint_config.clone();
+        let code = lint_config.apply_attributes(&source, file_id, &logger);
+        let tokens = {
+            let mut tokenizer = Tokenizer::new();
+            tokenizer.set_file_id(file_id);
+            tokenizer.tokenize(&code);
+            tokenizer.get_tokens()
+        };
+        let prog = Parser::new(tokens, logger.clone()).parse();
+
+        let mut imports = Vec::new();
+        if let Some(prog) = &prog {
+            Self::collect_imports(&prog.stmts, true, &mut imports);
+            // The root file has the id 0, every other file is imported
+            if file_id > 0 {
+                Self::check_declarations(&prog.stmts, &logger);
+            }
+        }
+        self.m_modules.push(Module { m_name: name, m_path: path, m_source: source, m_logger: logger, m_lint_config: lint_config, m_prog: prog });
+
+        self.m_loading.push(file_id);
+        for (import, top_level) in imports {
+            self.resolve_import(file_id, &import, top_level);
+        }
+        self.m_loading.pop();
+        self.m_order.push(file_id);
+        file_id
+    }
+
+    fn collect_imports(stmts: &[NodeStmt], top_level: bool, imports: &mut Vec<(NodeImport, bool)>) {
+        for stmt in stmts {
+            match stmt {
+                NodeStmt::Import(import) => imports.push((import.clone(), top_level)),
+                NodeStmt::Scope(scope) => Self::collect_imports(&scope.stmts, false, imports),
+                NodeStmt::For(node) => Self::collect_imports(&node.body.stmts, false, imports),
+                _ => {}
+            }
+        }
+    }
+
+    /// An imported file only declares what the files importing it can use, so that importing it never runs
+    /// statements such as `exit`. Its top level declarations are all visible, without a namespace.
+    fn check_declarations(stmts: &[NodeStmt], logger: &Arc<Mutex<ParserLogger>>) {
+        for stmt in stmts {
+            let is_declaration = match stmt {
+                NodeStmt::ID(var) => var.declaration.is_some(),
+                NodeStmt::Import(_) | NodeStmt::Struct(_) | NodeStmt::Enum(_) | NodeStmt::Extern(_) => true,
+                _ => false
+            };
+            if !is_declaration {
+                let message = "Imported files can only declare variables, constants, structs, enums and C functions.".to_string();
+                logger.lock().unwrap().log_error_message("E0055", message, stmt.get_span());
+            }
+        }
+    }
+
+    fn resolve_import(&mut self, importer: usize, import: &NodeImport, top_level: bool) {
+        let logger = self.m_modules[importer].m_logger.clone();
+        if !top_level {
+            logger.lock().unwrap().log_error_message("E0030", "Imports are only allowed at the top level of a file.".to_string(), import.span);
+            return;
+        }
+        // Paths are relative to the directory of the importing file
+        let name = Path::new(&self.m_modules[importer].m_name).parent().unwrap_or(Path::new("")).join(&import.path);
+        let path = Self::normalize(&name);
+        match self.m_modules.iter().position(|module| module.m_path == path) {
+            Some(file_id) if self.m_loading.contains(&file_id) => {
+                let cycle_start = self.m_loading.iter().position(|&id| id == file_id).unwrap();
+                let cycle: Vec<&str> = self.m_loading[cycle_start..].iter()
+                    .chain(std::iter::once(&file_id))
+                    .map(|&id| self.m_modules[id].m_name.as_str())
+                    .collect();
+                logger.lock().unwrap().log_error_message("E0031", format!("Import cycle detected: {}.", cycle.join(" -> ")), import.span);
+            }
+            // Already loaded through another import
+            Some(_) => {}
+            None => match fs::read_to_string(&path) {
+                Ok(source) => {
+                    self.load_module(name.display().to_string(), path, source);
+                }
+                Err(err) => {
+                    logger.lock().unwrap().log_error_message("E0032", format!("Cannot read imported file `{}`: {err}.", name.display()), import.span);
+                }
+            }
+        }
+    }
+
+    fn normalize(path: &Path) -> PathBuf {
+        fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+    }
+}
+
+
+
+#[cfg(test)]
+mod test_module_loader{
+    use super::*;
+    use crate::compiler::span::Span;
+
+    fn setup_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("brs_loader_{name}_{}", std::process::id()));
+        fs::remove_dir_all(&dir).ok();
+        for (file, source) in files {
+            let path = dir.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, source).unwrap();
+        }
+        dir
+    }
+
+    fn load(dir: &Path, root: &str) -> ModuleLoader {
+        let root = dir.join(root);
+        let mut loader = ModuleLoader::new(LintConfig::new());
+        loader.load(root.to_str().unwrap(), &fs::read_to_string(&root).unwrap());
+        loader
+    }
+
+    fn get_errors(loader: &ModuleLoader, file_id: usize) -> Vec<String> {
+        let logger = loader.get_modules()[file_id].m_logger.lock().unwrap();
+        logger.get_errors().iter().map(|(message, _)| message.clone()).collect()
+    }
+
+    #[test]
+    fn test_import(){
+        let dir = setup_dir("import", &[
+            ("main.brs", "import \"lib/math.brs\"\nimport \"lib/consts.brs\"\nexit(x + y)"),
+            ("lib/math.brs", "import \"consts.brs\"\nlet x = y * 2"),
+            ("lib/consts.brs", "var y = 3"),
+        ]);
+        let loader = load(&dir, "main.brs");
+        assert!(!loader.failed());
+        assert_eq!(loader.get_modules().len(), 3);
+        // Every file has its own id, and the shared import is only loaded once
+        let prog = loader.get_program().unwrap();
+        assert_eq!(format!("{prog}"), "var y = 3\nlet x = y * 2\nexit(x + y)");
+        let file_ids: Vec<usize> = prog.stmts.iter().map(|stmt| stmt.get_span().m_file_id).collect();
+        assert_eq!(file_ids, vec![2, 1, 0]);
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_import_cycle(){
+        let dir = setup_dir("cycle", &[
+            ("a.brs", "import \"b.brs\"\nexit(0)"),
+            ("b.brs", "import \"a.brs\"\nlet x = 1"),
+        ]);
+        let loader = load(&dir, "a.brs");
+        assert!(loader.failed());
+        let errors = get_errors(&loader, 1);
+        assert_eq!(errors.len(), 1);
+        let error = errors[0].replace(dir.to_str().unwrap(), "").replace(std::path::MAIN_SEPARATOR, "");
+        assert_eq!(error, "Import cycle detected: a.brs -> b.brs -> a.brs.");
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_bad_imports(){
+        let dir = setup_dir("bad", &[
+            ("main.brs", "import \"missing.brs\"\n{\nimport \"lib.brs\"\n}\nexit(0)"),
+            ("lib.brs", "let x = 1"),
+        ]);
+        let loader = load(&dir, "main.brs");
+        assert!(loader.failed());
+        let errors = get_errors(&loader, 0);
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("Cannot read imported file"));
+        assert_eq!(errors[1], "Imports are only allowed at the top level of a file.");
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_import_statements(){
+        let dir = setup_dir("statements", &[
+            ("main.brs", "import \"lib.brs\"\nexit(x)"),
+            ("lib.brs", "var x = 1\nstruct P { x: int }\nx = 2\n{\nlet y = 3\n}\nexit(x)"),
+        ]);
+        let loader = load(&dir, "main.brs");
+        assert!(loader.failed());
+        let message = "Imported files can only declare variables, constants, structs, enums and C functions.";
+        let logger = loader.get_modules()[1].m_logger.lock().unwrap();
+        let spans: Vec<Span> = logger.get_errors().iter().map(|(error, span)| {
+            assert_eq!(error, message);
+            *span
+        }).collect();
+        assert_eq!(spans, vec![Span::new(1, 30, 35), Span::new(1, 36, 49), Span::new(1, 50, 57)]);
+        drop(logger);
+        fs::remove_dir_all(dir).ok();
+    }
+}
\ No newline at end of file
@@ -3,4 +3,5 @@ use crate::compiler::span::Span;
 pub trait Logger {
     fn new(file_name: String, code: String) -> Self;
     fn report_error(&self, message: &str, span: Span);
+    fn report_warning(&self, message: &str, span: Span);
 }
\ No newline at end of file
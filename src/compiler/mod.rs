@@ -1,49 +1,246 @@
-mod tokenizer;
-mod generator;
-mod logger;
-mod parser;
-mod span;
+pub mod analysis;
+pub mod tokenizer;
+pub mod generator;
+pub mod loader;
+pub mod logger;
+pub mod parser;
+pub mod span;
+pub mod optimizer;
+pub mod toolchain;
+pub mod evaluator;
+mod artifact;
+mod diagnostic;
 
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
-use self::logger::Logger;
-use self::parser::ParserLogger;
-use self::tokenizer::{Token, Tokenizer};
-use self::parser::{NodeProgram, Parser};
+use self::parser::{AstDot, AstJson, AstPlantUml, AstSexpr, NodeProgram, ParserLogger};
 use self::generator::Generator;
+use self::analysis::{Checker, Linter};
+use self::loader::ModuleLoader;
+use self::logger::Logger;
+use self::optimizer::{ConstantFolder, ConstantInliner};
+use self::toolchain::Toolchain;
+use crate::utility::{LinkMode, Target, OS};
+
+pub use self::analysis::{Lint, LintConfig, LintLevel};
+pub use self::artifact::{Artifact, OutputKind};
+pub use self::diagnostic::{Diagnostic, Severity};
+pub use self::optimizer::OptLevel;
 
 
 pub struct Compiler {
+    m_lint_config: LintConfig,
+    m_target: Target,
+    m_link_mode: LinkMode,
+    m_opt_level: OptLevel,
+    m_output_kind: OutputKind,
+    m_out_dir: PathBuf,
+    m_overflow_checks: bool,
+    m_bounds_checks: bool
 }
 
 impl Compiler {
     pub fn new() -> Self {
         Compiler {
+            m_lint_config: LintConfig::new(),
+            m_target: Target::host(),
+            m_link_mode: LinkMode::default(),
+            m_opt_level: OptLevel::default(),
+            m_output_kind: OutputKind::default(),
+            m_out_dir: PathBuf::from("./"),
+            m_overflow_checks: false,
+            m_bounds_checks: true
         }
     }
 
-    pub fn compile(&mut self, file: &str, input: &str) -> Option<String> {
-        // Tokenize
-        let tokens: Vec<Token> = {
-            let mut tokenizer = Tokenizer::new();
-            tokenizer.tokenize(input);
-            tokenizer.get_tokens()
+    pub fn set_lint_config(&mut self, lint_config: LintConfig) {
+        self.m_lint_config = lint_config;
+    }
+
+    pub fn set_target(&mut self, target: Target) {
+        self.m_target = target;
+    }
+
+    /// Makes the program a C `main` function linked with the C library, so that it can call C functions.
+    pub fn set_link_mode(&mut self, link_mode: LinkMode) {
+        self.m_link_mode = link_mode;
+    }
+
+    pub fn set_opt_level(&mut self, opt_level: OptLevel) {
+        self.m_opt_level = opt_level;
+    }
+
+    /// Makes the compiled program stop with an error message when `+`, `-`, `*` or `**` overflows.
+    pub fn set_overflow_checks(&mut self, overflow_checks: bool) {
+        self.m_overflow_checks = overflow_checks;
+    }
+
+    /// Makes the compiled program stop with an error message when an array is indexed out of its bounds. On by default.
+    pub fn set_bounds_checks(&mut self, bounds_checks: bool) {
+        self.m_bounds_checks = bounds_checks;
+    }
+
+    pub fn set_output_kind(&mut self, output_kind: OutputKind) {
+        self.m_output_kind = output_kind;
+    }
+
+    /// Directory where the object file and the executable are written.
+    pub fn set_out_dir(&mut self, out_dir: &Path) {
+        self.m_out_dir = out_dir.to_path_buf();
+    }
+
+    pub fn get_out_dir(&self) -> &Path {
+        &self.m_out_dir
+    }
+
+    /// Applies the command line options following the input file.
+    pub fn apply_args(&mut self, args: &[String]) -> Result<(), String> {
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if let Some(level) = LintLevel::from_flag(arg) {
+                let lint = args.next().and_then(|name| Lint::from_name(name)).ok_or_else(|| {
+                    format!("{arg} expects one of the following lints: {}", Lint::all().map(|lint| lint.name()).join(", "))
+                })?;
+                self.m_lint_config.set_level(lint, level);
+            } else if let Some(opt_level) = OptLevel::from_flag(arg) {
+                self.m_opt_level = opt_level;
+            } else if let Some(kind) = arg.strip_prefix("--emit=") {
+                self.m_output_kind = OutputKind::from_name(kind)
+                    .ok_or("--emit expects one of asm, obj, exe, ast-json, ast-sexpr, ast-plantuml or ast-dot")?;
+            } else if arg == "--target" {
+                self.m_target = args.next().and_then(|name| Target::from_name(name))
+                    .ok_or("--target expects <arch>-<os>, for example x86_64-linux")?;
+            } else if let Some(mode) = arg.strip_prefix("--link=") {
+                self.m_link_mode = LinkMode::from_name(mode).ok_or("--link expects one of freestanding or libc")?;
+            } else if arg == "--overflow-checks" {
+                self.m_overflow_checks = true;
+            } else if arg == "--no-bounds-checks" {
+                self.m_bounds_checks = false;
+            } else if arg == "--outdir" {
+                self.m_out_dir = PathBuf::from(args.next().ok_or("--outdir expects a directory")?);
+            } else {
+                return Err(format!("Unknown option `{arg}`."));
+            }
+        }
+        Ok(())
+    }
+
+    /// Compiles `file` and every file it imports into a single program. Nothing is printed: the warnings are
+    /// returned with the artifact, and on failure every diagnostic is returned, errors first.
+    /// The AST outputs dump the program as parsed, before any optimization.
+    pub fn compile(&mut self, file: &str, input: &str) -> Result<Artifact, Vec<Diagnostic>> {
+        // Load, tokenize and parse every file
+        let mut loader = ModuleLoader::new(self.m_lint_config.clone());
+        loader.load(file, input);
+        let modules = loader.get_modules();
+        let lint_configs: Vec<LintConfig> = modules.iter().map(|module| module.m_lint_config.clone()).collect();
+        let loggers: Vec<Arc<Mutex<ParserLogger>>> = modules.iter().map(|module| module.m_logger.clone()).collect();
+        let prog = loader.get_program().filter(|_| !loader.failed());
+
+        // Check and lint
+        if let Some(prog) = &prog {
+            let mut checker = Checker::new(&loggers);
+            checker.set_link_mode(self.m_link_mode);
+            checker.check(prog);
+            Linter::new(&lint_configs, &loggers).check(prog);
+        }
+        let (errors, warnings): (Vec<Diagnostic>, Vec<Diagnostic>) = loggers.iter()
+            .flat_map(|logger| logger.lock().unwrap().get_diagnostics())
+            .partition(Diagnostic::is_error);
+        let Some(prog) = prog.filter(|_| errors.is_empty()) else {
+            return Err(errors.into_iter().chain(warnings).collect());
         };
+        let sources: Vec<(&str, &str)> = modules.iter().map(|module| (module.m_name.as_str(), module.m_source.as_str())).collect();
+        self.generate(file, prog, &sources, warnings)
+    }
 
-        // Parse
-        let prog : Option<NodeProgram> = {
-            let logger = Arc::new(Mutex::new(ParserLogger::new(file.to_string(), input.to_string())));
-            let mut parser = Parser::new(tokens, logger);
-            parser.parse()
+    /// Compiles a program read from its JSON dump, see `AstJson`. The program is checked but not linted, and
+    /// since its source isn't available, the errors have no span.
+    pub fn compile_ast(&mut self, file: &str, json: &str) -> Result<Artifact, Vec<Diagnostic>> {
+        let prog = AstJson::read(json).map_err(|error| vec![Diagnostic::error(format!("{file}: {error}"))])?;
+        let logger = Arc::new(Mutex::new(ParserLogger::new(file.to_string(), String::new())));
+        let mut checker = Checker::new(std::slice::from_ref(&logger));
+        checker.set_link_mode(self.m_link_mode);
+        checker.check(&prog);
+        let errors: Vec<Diagnostic> = logger.lock().unwrap().get_diagnostics().into_iter()
+            .filter(Diagnostic::is_error)
+            .map(|mut error| {
+                error.m_message = format!("{file}: {}", error.m_message);
+                error.m_span = None;
+                error
+            })
+            .collect();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        self.generate(file, prog, &[], Vec::new())
+    }
+
+    fn generate(&self, file: &str, mut prog: NodeProgram, sources: &[(&str, &str)], warnings: Vec<Diagnostic>) -> Result<Artifact, Vec<Diagnostic>> {
+        // Dump the parsed program
+        let dump = match self.m_output_kind {
+            OutputKind::AstJson => Some(AstJson::write(&prog)),
+            OutputKind::AstSexpr => Some(AstSexpr::write(&prog)),
+            OutputKind::AstPlantUml => Some(AstPlantUml::write(&prog)),
+            OutputKind::AstDot => Some(AstDot::write(&prog)),
+            _ => None
         };
+        if let Some(dump) = dump {
+            return Ok(Artifact { m_kind: self.m_output_kind, m_output: dump, m_path: None, m_warnings: warnings });
+        }
+
+        // Optimize, constants being replaced by their value at every level
+        prog = ConstantInliner::inline(prog).map_err(|error| vec![Diagnostic::error(format!("{file}: {error}"))])?;
+        if self.m_opt_level == OptLevel::O1 {
+            prog = ConstantFolder::fold(prog);
+        }
 
         // Generate
-        let out: String = if let Some(prog) = prog {
-            let mut generator = Generator::new(prog);
-            generator.generate();
-            generator.get_out_assembly()
-        } else { return None };
-        
-        // Return the generated assembly
-        Some(out)
+        let mut generator = Generator::new(prog);
+        generator.set_target(self.m_target);
+        generator.set_link_mode(self.m_link_mode);
+        generator.set_overflow_checks(self.m_overflow_checks);
+        generator.set_bounds_checks(self.m_bounds_checks);
+        for (name, source) in sources {
+            generator.add_line_marker_source(name, source);
+        }
+        generator.generate();
+        let assembly = generator.get_out_assembly();
+
+        // Assemble and link
+        let path = match self.m_output_kind {
+            OutputKind::Object | OutputKind::Executable => {
+                Some(self.build(file, &assembly, self.m_output_kind).map_err(|error| vec![Diagnostic::error(error)])?)
+            }
+            _ => None
+        };
+        Ok(Artifact { m_kind: self.m_output_kind, m_output: assembly, m_path: path, m_warnings: warnings })
+    }
+
+    fn build(&self, file: &str, assembly: &str, kind: OutputKind) -> Result<PathBuf, String> {
+        let stem = Path::new(file).file_stem().and_then(|stem| stem.to_str()).unwrap_or("out");
+        let asm_file = self.m_out_dir.join(format!("{stem}.asm"));
+        let o_file = self.m_out_dir.join(format!("{stem}.o"));
+        fs::write(&asm_file, assembly).map_err(|err| format!("Unable to write `{}`: {err}", asm_file.display()))?;
+
+        let mut toolchain = Toolchain::new(self.m_target);
+        toolchain.set_link_mode(self.m_link_mode);
+        toolchain.assemble(&asm_file, &o_file)?;
+        if kind == OutputKind::Object {
+            return Ok(o_file);
+        }
+        let final_file = match self.m_target.m_os {
+            OS::Windows => self.m_out_dir.join(format!("{stem}.exe")),
+            _ => self.m_out_dir.join(stem)
+        };
+        toolchain.link(&o_file, &final_file)?;
+        Ok(final_file)
     }
 }
+
+impl Default for Compiler {
+    fn default() -> Self {
+        Compiler::new()
+    }
+}
\ No newline at end of file
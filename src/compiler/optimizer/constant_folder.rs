@@ -0,0 +1,239 @@
+use either::{Either, Left, Right};
+use crate::compiler::parser::{NodeArithmeticExpr, NodeArithmeticOperation, NodeBaseExpr, NodeProgram, NodeStmt, ResultType};
+use crate::compiler::tokenizer::{Operator, Token};
+
+enum Constant {
+    Num(i64),
+    Bool(bool)
+}
+
+/// Replaces the operations whose operands are all literals by their result.
+/// Operations are only folded when the generated code would compute the same value, so divisions by zero,
+/// overflows and negative results are left to the runtime.
+pub struct ConstantFolder;
+
+impl ConstantFolder {
+    pub fn fold(prog: NodeProgram) -> NodeProgram {
+        NodeProgram { stmts: Self::fold_stmts(prog.stmts), span: prog.span }
+    }
+
+    fn fold_stmts(stmts: Vec<NodeStmt>) -> Vec<NodeStmt> {
+        stmts.into_iter().map(Self::fold_stmt).collect()
+    }
+
+    fn fold_stmt(stmt: NodeStmt) -> NodeStmt {
+        match stmt {
+            NodeStmt::Exit(mut exit) => {
+                exit.expr = Self::fold_expr(exit.expr);
+                NodeStmt::Exit(exit)
+            }
+            NodeStmt::ID(mut var) => {
+                var.value = Self::fold_expr(var.value);
+                NodeStmt::ID(var)
+            }
+            NodeStmt::Scope(mut scope) => {
+                scope.stmts = Self::fold_stmts(scope.stmts);
+                NodeStmt::Scope(scope)
+            }
+            NodeStmt::Import(import) => NodeStmt::Import(import),
+            NodeStmt::Index(mut assignment) => {
+                assignment.element.index = Box::new(Self::fold_expr(*assignment.element.index));
+                assignment.value = Self::fold_expr(assignment.value);
+                NodeStmt::Index(assignment)
+            }
+            NodeStmt::Struct(declaration) => NodeStmt::Struct(declaration),
+            NodeStmt::Enum(declaration) => NodeStmt::Enum(declaration),
+            NodeStmt::Field(mut assignment) => {
+                assignment.value = Self::fold_expr(assignment.value);
+                NodeStmt::Field(assignment)
+            }
+            NodeStmt::For(mut node) => {
+                node.start = Self::fold_expr(node.start);
+                node.end = Self::fold_expr(node.end);
+                node.body.stmts = Self::fold_stmts(node.body.stmts);
+                NodeStmt::For(node)
+            }
+            NodeStmt::Free(mut node) => {
+                node.pointer = Self::fold_expr(node.pointer);
+                NodeStmt::Free(node)
+            }
+            NodeStmt::Extern(declaration) => NodeStmt::Extern(declaration),
+            NodeStmt::Call(mut call) => {
+                call.arguments = call.arguments.into_iter().map(Self::fold_expr).collect();
+                NodeStmt::Call(call)
+            }
+        }
+    }
+
+    fn fold_expr(expr: NodeArithmeticExpr) -> NodeArithmeticExpr {
+        match expr {
+            NodeArithmeticExpr::Base(base) => NodeArithmeticExpr::Base(Self::fold_base(base)),
+            NodeArithmeticExpr::Operation(operation) => match Self::fold_operation(operation) {
+                Left(operation) => NodeArithmeticExpr::Operation(*operation),
+                Right(base) => NodeArithmeticExpr::Base(base),
+            }
+            NodeArithmeticExpr::Array(mut array) => {
+                array.elements = array.elements.into_iter().map(Self::fold_expr).collect();
+                NodeArithmeticExpr::Array(array)
+            }
+            NodeArithmeticExpr::Struct(mut literal) => {
+                literal.fields = literal.fields.into_iter().map(|(field, value)| (field, Self::fold_expr(value))).collect();
+                NodeArithmeticExpr::Struct(literal)
+            }
+            NodeArithmeticExpr::Variant(mut literal) => {
+                literal.values = literal.values.into_iter().map(Self::fold_expr).collect();
+                NodeArithmeticExpr::Variant(literal)
+            }
+            NodeArithmeticExpr::Match(mut node) => {
+                for arm in &mut node.arms {
+                    arm.value = Self::fold_expr(arm.value.clone());
+                }
+                NodeArithmeticExpr::Match(node)
+            }
+        }
+    }
+
+    fn fold_operand(operand: Either<Box<NodeArithmeticOperation>, NodeBaseExpr>) -> Either<Box<NodeArithmeticOperation>, NodeBaseExpr> {
+        match operand {
+            Left(operation) => Self::fold_operation(*operation),
+            Right(base) => Right(Self::fold_base(base)),
+        }
+    }
+
+    /// Only the index of an element, the size of an allocation and the arguments of a function can be folded, the
+    /// element, the address and the result of the function are only known at runtime.
+    fn fold_base(base: NodeBaseExpr) -> NodeBaseExpr {
+        match base {
+            NodeBaseExpr::Index(mut element) => {
+                element.index = Box::new(Self::fold_expr(*element.index));
+                NodeBaseExpr::Index(element)
+            }
+            NodeBaseExpr::Alloc(mut node) => {
+                node.size = Box::new(Self::fold_expr(*node.size));
+                NodeBaseExpr::Alloc(node)
+            }
+            NodeBaseExpr::Builtin(mut node) => {
+                node.argument = node.argument.map(|argument| Box::new(Self::fold_expr(*argument)));
+                NodeBaseExpr::Builtin(node)
+            }
+            NodeBaseExpr::Call(mut call) => {
+                call.arguments = call.arguments.into_iter().map(Self::fold_expr).collect();
+                NodeBaseExpr::Call(call)
+            }
+            base => base,
+        }
+    }
+
+    fn fold_operation(mut operation: NodeArithmeticOperation) -> Either<Box<NodeArithmeticOperation>, NodeBaseExpr> {
+        operation.lhs = Self::fold_operand(operation.lhs);
+        operation.rhs = Self::fold_operand(operation.rhs);
+        let span = operation.span;
+        let lhs = Self::get_constant(&operation.lhs);
+        let rhs = Self::get_constant(&operation.rhs);
+        let result = match (operation.op, lhs, rhs) {
+            (Operator::Not { .. }, Some(Constant::Bool(lhs)), _) => Some(Constant::Bool(!lhs)),
+            (Operator::Cast { .. }, Some(constant), _) => Some(Self::cast(constant, operation.result_type)),
+            (op, Some(Constant::Num(lhs)), Some(Constant::Num(rhs))) => Self::fold_numeric(op, lhs, rhs).map(Constant::Num),
+            (op, Some(Constant::Bool(lhs)), Some(Constant::Bool(rhs))) => Self::fold_boolean(op, lhs, rhs).map(Constant::Bool),
+            _ => None
+        };
+        match result {
+            Some(Constant::Num(value)) => Right(NodeBaseExpr::Num(Token::Number { value: value.to_string(), span })),
+            Some(Constant::Bool(value)) => Right(NodeBaseExpr::Bool(Token::Boolean { value, span })),
+            None => Left(Box::new(operation)),
+        }
+    }
+
+    fn fold_numeric(op: Operator, lhs: i64, rhs: i64) -> Option<i64> {
+        let result = match op {
+            Operator::Plus { .. } => lhs.checked_add(rhs),
+            Operator::Minus { .. } => lhs.checked_sub(rhs),
+            Operator::Multiplication { .. } => lhs.checked_mul(rhs),
+            Operator::Division { .. } => lhs.checked_div(rhs),
+            Operator::Modulus { .. } => lhs.checked_rem(rhs),
+            Operator::Exponent { .. } => lhs.checked_pow(u32::try_from(rhs).ok()?),
+            Operator::BitAnd { .. } => Some(lhs & rhs),
+            Operator::BitOr { .. } => Some(lhs | rhs),
+            Operator::BitXor { .. } => Some(lhs ^ rhs),
+            Operator::ShiftLeft { .. } => lhs.checked_shl(u32::try_from(rhs).ok()?),
+            Operator::ShiftRight { .. } => lhs.checked_shr(u32::try_from(rhs).ok()?),
+            _ => None
+        }?;
+        (result >= 0).then_some(result)
+    }
+
+    fn fold_boolean(op: Operator, lhs: bool, rhs: bool) -> Option<bool> {
+        match op {
+            Operator::And { .. } => Some(lhs & rhs),
+            Operator::Or { .. } => Some(lhs | rhs),
+            Operator::Xor { .. } => Some(lhs ^ rhs),
+            _ => None
+        }
+    }
+
+    fn cast(constant: Constant, result_type: ResultType) -> Constant {
+        match (constant, result_type) {
+            (Constant::Num(value), ResultType::Boolean) => Constant::Bool(value != 0),
+            (Constant::Bool(value), ResultType::Numeric) => Constant::Num(value as i64),
+            (constant, _) => constant,
+        }
+    }
+
+    fn get_constant(operand: &Either<Box<NodeArithmeticOperation>, NodeBaseExpr>) -> Option<Constant> {
+        match operand {
+            Right(NodeBaseExpr::Num(Token::Number { value, .. })) => value.parse().ok().map(Constant::Num),
+            Right(NodeBaseExpr::Bool(Token::Boolean { value, .. })) => Some(Constant::Bool(*value)),
+            _ => None
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod test_constant_folder{
+    use std::sync::{Arc, Mutex};
+    use crate::compiler::logger::Logger;
+    use crate::compiler::parser::{Parser, ParserLogger};
+    use crate::compiler::tokenizer::Tokenizer;
+    use super::*;
+
+    fn fold(code: &str) -> String {
+        let logger = Arc::new(Mutex::new(ParserLogger::new("".to_string(), code.to_string())));
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.tokenize(code);
+        let prog = Parser::new(tokenizer.get_tokens(), logger).parse().expect("The test program should parse");
+        format!("{}", ConstantFolder::fold(prog))
+    }
+
+    #[test]
+    fn test_fold_numeric(){
+        assert_eq!(fold("x = ((3+5)*2 + (12//4))%7 + 2**3"), "x = 13");
+        assert_eq!(fold("{\nexit(1 + 2)\n}"), "{exit(3)}");
+        assert_eq!(fold("x = 0xF0 & 0x3C | 1 << 2 ^ 3 >> 1"), "x = 53");
+    }
+
+    #[test]
+    fn test_fold_boolean(){
+        assert_eq!(fold("x = true && false ^| true"), "x = true");
+        assert_eq!(fold("x = !!(2 as bool) as int + 1"), "x = 1");
+    }
+
+    #[test]
+    fn test_partial_fold(){
+        assert_eq!(fold("x = 1\ny = x * (2 + 3)"), "x = 1\ny = x * 5");
+        assert_eq!(fold("a = [1 + 1, 2]\na[3 - 2] = a[0 * 1] + 2"), "a = [2, 2]\na[1] = a[0] + 2");
+        assert_eq!(fold("p = alloc(2 * 8) + 1\nfree(p - (0 + 1))"), "p = alloc(16) + 1\nfree(p - 1)");
+        assert_eq!(fold("exit(parse_int(arg(3 - 2)) + 2 * 2)"), "exit(parse_int(arg(1)) + 4)");
+    }
+
+    #[test]
+    fn test_runtime_semantics_kept(){
+        // Division by zero and negative results are left to the generated code
+        assert_eq!(fold("x = 1 // 0"), "x = 1 / 0");
+        assert_eq!(fold("x = 1 - 2"), "x = 1 - 2");
+        assert_eq!(fold("x = 2 ** 64"), "x = 2 ** 64");
+        assert_eq!(fold("x = 1 << 64"), "x = 1 << 64");
+        assert_eq!(fold("x = ~0"), "x = ~0");
+    }
+}
\ No newline at end of file
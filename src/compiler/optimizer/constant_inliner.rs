@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use either::{Either, Left, Right};
+use crate::compiler::evaluator::{EvalError, Evaluator};
+use crate::compiler::parser::{Declaration, NodeArithmeticExpr, NodeArithmeticOperation, NodeBaseExpr, NodeProgram, NodeStmt, ResultType};
+use crate::compiler::tokenizer::Token;
+
+/// Replaces the reads of the constants by their value and removes their declarations, so that constants
+/// never get a stack slot. Unlike the constant folding, this is done whatever the optimization level.
+pub struct ConstantInliner {
+    /// Value of the constants visible in each scope, `None` for the variables that shadow a constant.
+    m_scopes: Vec<HashMap<String, Option<NodeBaseExpr>>>
+}
+
+impl ConstantInliner {
+    pub fn inline(prog: NodeProgram) -> Result<NodeProgram, EvalError> {
+        let mut inliner = ConstantInliner { m_scopes: vec![HashMap::new()] };
+        Ok(NodeProgram { stmts: inliner.inline_stmts(prog.stmts)?, span: prog.span })
+    }
+
+    fn inline_stmts(&mut self, stmts: Vec<NodeStmt>) -> Result<Vec<NodeStmt>, EvalError> {
+        let mut inlined = Vec::new();
+        for stmt in stmts {
+            match stmt {
+                NodeStmt::Exit(mut exit) => {
+                    exit.expr = self.inline_expr(exit.expr);
+                    inlined.push(NodeStmt::Exit(exit));
+                }
+                NodeStmt::ID(mut var) => {
+                    var.value = self.inline_expr(var.value);
+                    let (Some(declaration), Token::ID { name, .. }) = (var.declaration, &var.variable) else {
+                        inlined.push(NodeStmt::ID(var));
+                        continue;
+                    };
+                    let value = match declaration {
+                        Declaration::Const { .. } => Some(Self::evaluate(&var.value)?),
+                        _ => None
+                    };
+                    self.m_scopes.last_mut().unwrap().insert(name.clone(), value.clone());
+                    if value.is_none() {
+                        inlined.push(NodeStmt::ID(var));
+                    }
+                }
+                NodeStmt::Scope(mut scope) => {
+                    self.m_scopes.push(HashMap::new());
+                    scope.stmts = self.inline_stmts(scope.stmts)?;
+                    self.m_scopes.pop();
+                    inlined.push(NodeStmt::Scope(scope));
+                }
+                NodeStmt::Import(import) => inlined.push(NodeStmt::Import(import)),
+                NodeStmt::Index(mut assignment) => {
+                    assignment.element.index = Box::new(self.inline_expr(*assignment.element.index));
+                    assignment.value = self.inline_expr(assignment.value);
+                    inlined.push(NodeStmt::Index(assignment));
+                }
+                NodeStmt::Struct(declaration) => inlined.push(NodeStmt::Struct(declaration)),
+                NodeStmt::Enum(declaration) => inlined.push(NodeStmt::Enum(declaration)),
+                NodeStmt::Field(mut assignment) => {
+                    assignment.value = self.inline_expr(assignment.value);
+                    inlined.push(NodeStmt::Field(assignment));
+                }
+                NodeStmt::For(mut node) => {
+                    node.start = self.inline_expr(node.start);
+                    node.end = self.inline_expr(node.end);
+                    // The loop variable shadows the constant of the same name in the body
+                    let counter = match &node.variable {
+                        Token::ID { name, .. } => HashMap::from([(name.clone(), None)]),
+                        _ => HashMap::new(),
+                    };
+                    self.m_scopes.push(counter);
+                    self.m_scopes.push(HashMap::new());
+                    node.body.stmts = self.inline_stmts(node.body.stmts)?;
+                    self.m_scopes.truncate(self.m_scopes.len() - 2);
+                    inlined.push(NodeStmt::For(node));
+                }
+                NodeStmt::Free(mut node) => {
+                    node.pointer = self.inline_expr(node.pointer);
+                    inlined.push(NodeStmt::Free(node));
+                }
+                NodeStmt::Extern(declaration) => inlined.push(NodeStmt::Extern(declaration)),
+                NodeStmt::Call(mut call) => {
+                    call.arguments = call.arguments.into_iter().map(|argument| self.inline_expr(argument)).collect();
+                    inlined.push(NodeStmt::Call(call));
+                }
+            }
+        }
+        Ok(inlined)
+    }
+
+    /// Computes the value of a constant whose reads are already inlined, as a literal of its type.
+    fn evaluate(expr: &NodeArithmeticExpr) -> Result<NodeBaseExpr, EvalError> {
+        let value = Evaluator::evaluate_constant(expr, &HashMap::new())?;
+        let span = expr.get_span();
+        Ok(match expr {
+            NodeArithmeticExpr::Base(NodeBaseExpr::Bool(_))
+            | NodeArithmeticExpr::Operation(NodeArithmeticOperation { result_type: ResultType::Boolean, .. }) => {
+                NodeBaseExpr::Bool(Token::Boolean { value: value != 0, span })
+            }
+            _ => NodeBaseExpr::Num(Token::Number { value: value.to_string(), span }),
+        })
+    }
+
+    fn inline_expr(&self, expr: NodeArithmeticExpr) -> NodeArithmeticExpr {
+        match expr {
+            NodeArithmeticExpr::Base(base) => NodeArithmeticExpr::Base(self.inline_base(base)),
+            NodeArithmeticExpr::Operation(operation) => NodeArithmeticExpr::Operation(self.inline_operation(operation)),
+            NodeArithmeticExpr::Array(mut array) => {
+                array.elements = array.elements.into_iter().map(|element| self.inline_expr(element)).collect();
+                NodeArithmeticExpr::Array(array)
+            }
+            NodeArithmeticExpr::Struct(mut literal) => {
+                literal.fields = literal.fields.into_iter().map(|(field, value)| (field, self.inline_expr(value))).collect();
+                NodeArithmeticExpr::Struct(literal)
+            }
+            NodeArithmeticExpr::Variant(mut literal) => {
+                literal.values = literal.values.into_iter().map(|value| self.inline_expr(value)).collect();
+                NodeArithmeticExpr::Variant(literal)
+            }
+            NodeArithmeticExpr::Match(mut node) => {
+                for arm in &mut node.arms {
+                    // The bindings of the arm shadow the constants of the same name
+                    let bindings = arm.bindings.iter().filter_map(|binding| match binding {
+                        Token::ID { name, .. } => Some((name.clone(), None)),
+                        _ => None,
+                    }).collect();
+                    let mut scopes = self.m_scopes.clone();
+                    scopes.push(bindings);
+                    arm.value = ConstantInliner { m_scopes: scopes }.inline_expr(arm.value.clone());
+                }
+                NodeArithmeticExpr::Match(node)
+            }
+        }
+    }
+
+    fn inline_operand(&self, operand: Either<Box<NodeArithmeticOperation>, NodeBaseExpr>) -> Either<Box<NodeArithmeticOperation>, NodeBaseExpr> {
+        match operand {
+            Left(operation) => Left(Box::new(self.inline_operation(*operation))),
+            Right(base) => Right(self.inline_base(base)),
+        }
+    }
+
+    fn inline_operation(&self, mut operation: NodeArithmeticOperation) -> NodeArithmeticOperation {
+        operation.lhs = self.inline_operand(operation.lhs);
+        operation.rhs = self.inline_operand(operation.rhs);
+        operation
+    }
+
+    /// The literal keeps the span of the read it replaces.
+    fn inline_base(&self, base: NodeBaseExpr) -> NodeBaseExpr {
+        let NodeBaseExpr::ID(Token::ID { name, span }) = &base else {
+            return match base {
+                NodeBaseExpr::Index(mut element) => {
+                    element.index = Box::new(self.inline_expr(*element.index));
+                    NodeBaseExpr::Index(element)
+                }
+                NodeBaseExpr::Alloc(mut node) => {
+                    node.size = Box::new(self.inline_expr(*node.size));
+                    NodeBaseExpr::Alloc(node)
+                }
+                NodeBaseExpr::Builtin(mut node) => {
+                    node.argument = node.argument.map(|argument| Box::new(self.inline_expr(*argument)));
+                    NodeBaseExpr::Builtin(node)
+                }
+                NodeBaseExpr::Call(mut call) => {
+                    call.arguments = call.arguments.into_iter().map(|argument| self.inline_expr(argument)).collect();
+                    NodeBaseExpr::Call(call)
+                }
+                base => base,
+            };
+        };
+        let constant = self.m_scopes.iter().rev().find_map(|scope| scope.get(name));
+        match constant {
+            Some(Some(NodeBaseExpr::Num(Token::Number { value, .. }))) => NodeBaseExpr::Num(Token::Number { value: value.clone(), span: *span }),
+            Some(Some(NodeBaseExpr::Bool(Token::Boolean { value, .. }))) => NodeBaseExpr::Bool(Token::Boolean { value: *value, span: *span }),
+            _ => base,
+        }
+    }
+}
+
+
+
+#[cfg(test)]
+mod test_constant_inliner{
+    use std::sync::{Arc, Mutex};
+    use crate::compiler::logger::Logger;
+    use crate::compiler::parser::{Parser, ParserLogger};
+    use crate::compiler::tokenizer::Tokenizer;
+    use super::*;
+
+    fn inline(code: &str) -> Result<String, EvalError> {
+        let logger = Arc::new(Mutex::new(ParserLogger::new("".to_string(), code.to_string())));
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.tokenize(code);
+        let prog = Parser::new(tokenizer.get_tokens(), logger).parse().expect("The test program should parse");
+        ConstantInliner::inline(prog).map(|prog| format!("{prog}"))
+    }
+
+    #[test]
+    fn test_inline(){
+        assert_eq!(inline("const N = 2 * 5\nconst M = N - 12\nlet x = N + M\nexit(x)"), Ok("let x = 10 + -2\nexit(x)".to_string()));
+        assert_eq!(inline("const B = !!false\n{\nexit(B)\n}"), Ok("{exit(true)}".to_string()));
+        assert_eq!(inline("const N = 2\nvar a = [N; 3]\na[N] = a[N - 1]\nexit(len(a))"), Ok("var a = [2; 3]\na[2] = a[2 - 1]\nexit(len(a))".to_string()));
+        assert_eq!(inline("const N = 3\nstruct P { x: int }\nvar p = P { x: N }\np.x = N\nexit(p.x)"), Ok("struct P { x: int }\nvar p = P { x: 3 }\np.x = 3\nexit(p.x)".to_string()));
+    }
+
+    #[test]
+    fn test_shadowed_constant(){
+        assert_eq!(inline("const N = 1\n{\nlet N = 2\nexit(N)\n}\nexit(N)"), Ok("{let N = 2exit(N)}\nexit(1)".to_string()));
+        assert_eq!(inline("const N = 1\nfor N in 0..=N {\nexit(N)\n}\nexit(N)"), Ok("for N in 0..=1 {exit(N)}\nexit(1)".to_string()));
+    }
+
+    #[test]
+    fn test_evaluation_error(){
+        assert_eq!(inline("const N = 1 // (1 - 1)"), Err(EvalError::DivisionByZero));
+        assert_eq!(inline("let x = 1\nconst N = x"), Err(EvalError::UndefinedVariable("x".to_string())));
+        assert_eq!(inline("const A = [1, 2]"), Err(EvalError::NotAValue("[1, 2]".to_string())));
+    }
+}
\ No newline at end of file
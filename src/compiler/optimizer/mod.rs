@@ -0,0 +1,30 @@
+mod constant_folder;
+mod constant_inliner;
+
+pub use constant_folder::{
+    ConstantFolder
+};
+
+pub use constant_inliner::{
+    ConstantInliner
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum OptLevel {
+    /// Code is generated exactly as written.
+    #[default]
+    O0,
+    /// Constant expressions are folded before generating code.
+    O1,
+}
+
+impl OptLevel {
+    /// Maps the command line flags `-O0` and `-O1` to their level.
+    pub fn from_flag(flag: &str) -> Option<OptLevel> {
+        match flag {
+            "-O0" => Some(OptLevel::O0),
+            "-O1" => Some(OptLevel::O1),
+            _ => None
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,78 @@
+use crate::compiler::parser::NodeProgram;
+use super::DiagramNode;
+
+/// Writes a program as a Graphviz graph, with the same colors as the PlantUML mind map.
+pub struct AstDot {
+    m_output: String,
+    m_node_count: usize
+}
+
+impl AstDot {
+    pub fn write(prog: &NodeProgram) -> String {
+        let mut writer = AstDot { m_output: String::from("digraph AST {\n\tnode [shape=box];\n"), m_node_count: 0 };
+        writer.write_node(&DiagramNode::from_program(prog));
+        writer.m_output.push('}');
+        writer.m_output
+    }
+
+    /// Writes the node and its children, returning the id of the node.
+    fn write_node(&mut self, node: &DiagramNode) -> usize {
+        let id = self.m_node_count;
+        self.m_node_count += 1;
+        let style = match node.m_color {
+            Some(color) => format!(", style=filled, fillcolor={color}"),
+            None => String::new(),
+        };
+        self.m_output.push_str(&format!("\tn{id} [label=\"{}\"{style}];\n", Self::escape(&node.m_label)));
+        for child in &node.m_children {
+            let child_id = self.write_node(child);
+            self.m_output.push_str(&format!("\tn{id} -> n{child_id};\n"));
+        }
+        id
+    }
+
+    fn escape(label: &str) -> String {
+        label.replace('\\', "\\\\").replace('"', "\\\"")
+    }
+}
+
+
+
+#[cfg(test)]
+mod test_ast_dot{
+    use std::sync::{Arc, Mutex};
+    use crate::compiler::logger::Logger;
+    use crate::compiler::parser::{Parser, ParserLogger};
+    use crate::compiler::tokenizer::Tokenizer;
+    use super::*;
+
+    fn write(code: &str) -> String {
+        let logger = Arc::new(Mutex::new(ParserLogger::new("".to_string(), code.to_string())));
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.tokenize(code);
+        AstDot::write(&Parser::new(tokenizer.get_tokens(), logger).parse().expect("The test program should parse"))
+    }
+
+    #[test]
+    fn test_write(){
+        assert_eq!(write("exit(x + 1)"), concat!(
+            "digraph AST {\n",
+            "\tnode [shape=box];\n",
+            "\tn0 [label=\"program\", style=filled, fillcolor=lightblue];\n",
+            "\tn1 [label=\"exit\", style=filled, fillcolor=lightblue];\n",
+            "\tn2 [label=\"+\", style=filled, fillcolor=lightgreen];\n",
+            "\tn3 [label=\"x\"];\n",
+            "\tn2 -> n3;\n",
+            "\tn4 [label=\"1\"];\n",
+            "\tn2 -> n4;\n",
+            "\tn1 -> n2;\n",
+            "\tn0 -> n1;\n",
+            "}"
+        ));
+    }
+
+    #[test]
+    fn test_escape(){
+        assert_eq!(AstDot::escape("import \"lib\\a.brs\""), "import \\\"lib\\\\a.brs\\\"");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,687 @@
+use either::{Either, Left, Right};
+use serde_json::{json, Map, Value};
+use crate::compiler::parser::{Declaration, NodeArithmeticExpr, NodeArithmeticOperation, NodeArrayLiteral, NodeBaseExpr, NodeExit, NodeField, NodeFieldAssignment, NodeFieldDeclaration, NodeImport, NodeIndex, NodeIndexAssignment, NodeLen, NodeProgram, NodeScope, NodeStmt, NodeStruct, NodeStructLiteral, NodeVariableAssignment, NodeEnum, NodeVariant, NodeVariantLiteral, NodeMatch, NodeMatchArm, NodeFor, NodeAlloc, NodeFree, NodeBuiltin, NodeExtern, NodeCall, ResultType, TypeAnnotation};
+use crate::compiler::span::Span;
+use crate::compiler::tokenizer::{Builtin, Operator, Token, Tokenizer};
+use super::VariableTypes;
+
+/// Converts a program to JSON and back. Every node is an object with a `kind`, its `span` and,
+/// for the expressions, the inferred `type` (`null` for variables that are never assigned).
+/// When reading, missing spans default to an empty span and missing types are inferred again.
+/// Arrays have the type of their elements, and their `length` is written apart. Struct literals have no type,
+/// and the names of structs, of their fields and of the types of the fields are written as variables, like the names of
+/// enums and of their variants. The wildcard arm of a match and its unused bindings are `Wildcard` nodes, like an unused
+/// loop variable. The body of a loop is a `Scope` node. A call whose result is discarded is a `Call` statement.
+pub struct AstJson {
+    m_types: VariableTypes
+}
+
+impl AstJson {
+    pub fn write(prog: &NodeProgram) -> String {
+        let mut writer = AstJson { m_types: VariableTypes::new() };
+        let value = json!({
+            "kind": "Program",
+            "span": Self::write_span(prog.span),
+            "stmts": writer.write_stmts(&prog.stmts),
+        });
+        serde_json::to_string_pretty(&value).unwrap()
+    }
+
+    pub fn read(json: &str) -> Result<NodeProgram, String> {
+        let value: Value = serde_json::from_str(json).map_err(|err| format!("Invalid JSON: {err}"))?;
+        Self::expect_kind(&value, "Program")?;
+        Ok(NodeProgram { stmts: Self::read_stmts(&value)?, span: Self::read_span(&value)? })
+    }
+
+    fn write_stmts(&mut self, stmts: &[NodeStmt]) -> Vec<Value> {
+        stmts.iter().map(|stmt| self.write_stmt(stmt)).collect()
+    }
+
+    fn write_stmt(&mut self, stmt: &NodeStmt) -> Value {
+        match stmt {
+            NodeStmt::Exit(exit) => json!({
+                "kind": "Exit",
+                "span": Self::write_span(exit.span),
+                "expr": self.write_expr(&exit.expr),
+            }),
+            NodeStmt::ID(assignment) => {
+                let value = self.write_expr(&assignment.value);
+                let annotation = assignment.annotation.map(|annotation| annotation.result_type);
+                self.m_types.declare_assignment(assignment);
+                json!({
+                    "kind": "Assignment",
+                    "span": Self::write_span(assignment.get_span()),
+                    "declaration": assignment.declaration.as_ref().map(Declaration::as_str),
+                    "declaration_span": assignment.declaration.map(|declaration| Self::write_span(declaration.get_span())),
+                    "variable": Self::write_variable(&assignment.variable),
+                    "annotation": annotation.as_ref().map(ResultType::as_str),
+                    "annotation_length": assignment.annotation.and_then(|annotation| annotation.length),
+                    "annotation_span": assignment.annotation.map(|annotation| Self::write_span(annotation.span)),
+                    "value": value,
+                })
+            }
+            NodeStmt::Scope(scope) => {
+                self.m_types.enter_scope();
+                let stmts = self.write_stmts(&scope.stmts);
+                self.m_types.exit_scope();
+                json!({
+                    "kind": "Scope",
+                    "span": Self::write_span(scope.span),
+                    "stmts": stmts,
+                })
+            }
+            NodeStmt::Import(import) => json!({
+                "kind": "Import",
+                "span": Self::write_span(import.span),
+                "path": import.path,
+            }),
+            NodeStmt::Index(assignment) => json!({
+                "kind": "IndexAssignment",
+                "span": Self::write_span(stmt.get_span()),
+                "element": self.write_base(&NodeBaseExpr::Index(assignment.element.clone())),
+                "value": self.write_expr(&assignment.value),
+            }),
+            NodeStmt::Struct(declaration) => {
+                self.m_types.declare_struct(declaration);
+                json!({
+                    "kind": "Struct",
+                    "span": Self::write_span(declaration.span),
+                    "name": Self::write_variable(&declaration.name),
+                    "fields": declaration.fields.iter().map(|field| json!({
+                        "kind": "FieldDeclaration",
+                        "name": Self::write_variable(&field.name),
+                        "type": Self::write_variable(&field.type_name),
+                    })).collect::<Vec<_>>(),
+                })
+            }
+            NodeStmt::Field(assignment) => json!({
+                "kind": "FieldAssignment",
+                "span": Self::write_span(stmt.get_span()),
+                "field": self.write_base(&NodeBaseExpr::Field(assignment.field.clone())),
+                "value": self.write_expr(&assignment.value),
+            }),
+            NodeStmt::Enum(declaration) => {
+                self.m_types.declare_enum(declaration);
+                json!({
+                    "kind": "Enum",
+                    "span": Self::write_span(declaration.span),
+                    "name": Self::write_variable(&declaration.name),
+                    "variants": declaration.variants.iter().map(|variant| json!({
+                        "kind": "VariantDeclaration",
+                        "name": Self::write_variable(&variant.name),
+                        "types": variant.types.iter().map(Self::write_variable).collect::<Vec<_>>(),
+                    })).collect::<Vec<_>>(),
+                })
+            }
+            NodeStmt::For(node) => {
+                let (start, end) = (self.write_expr(&node.start), self.write_expr(&node.end));
+                self.m_types.enter_scope();
+                self.m_types.declare(&node.variable, Some(ResultType::Numeric));
+                let body = self.write_stmt(&NodeStmt::Scope(node.body.clone()));
+                self.m_types.exit_scope();
+                json!({
+                    "kind": "For",
+                    "span": Self::write_span(node.span),
+                    "variable": Self::write_variable(&node.variable),
+                    "start": start,
+                    "end": end,
+                    "inclusive": node.inclusive,
+                    "step": node.step.as_ref().map(|step| self.write_base(&NodeBaseExpr::Num(step.clone()))),
+                    "body": body,
+                })
+            }
+            NodeStmt::Free(node) => json!({
+                "kind": "Free",
+                "span": Self::write_span(node.span),
+                "pointer": self.write_expr(&node.pointer),
+            }),
+            NodeStmt::Extern(declaration) => json!({
+                "kind": "Extern",
+                "span": Self::write_span(declaration.span),
+                "name": Self::write_variable(&declaration.name),
+                "parameters": declaration.parameters.iter().map(Self::write_variable).collect::<Vec<_>>(),
+                "variadic": declaration.variadic,
+            }),
+            NodeStmt::Call(call) => self.write_base(&NodeBaseExpr::Call(call.clone())),
+        }
+    }
+
+    fn write_expr(&self, expr: &NodeArithmeticExpr) -> Value {
+        match expr {
+            NodeArithmeticExpr::Base(base) => self.write_base(base),
+            NodeArithmeticExpr::Operation(operation) => self.write_operation(operation),
+            NodeArithmeticExpr::Array(array) => json!({
+                "kind": "Array",
+                "span": Self::write_span(array.span),
+                "type": self.m_types.get_expr_type(expr).as_ref().map(ResultType::as_str),
+                "elements": array.elements.iter().map(|element| self.write_expr(element)).collect::<Vec<_>>(),
+                "length": array.length,
+            }),
+            NodeArithmeticExpr::Struct(literal) => json!({
+                "kind": "StructLiteral",
+                "span": Self::write_span(literal.span),
+                "type": null,
+                "name": Self::write_variable(&literal.name),
+                "fields": literal.fields.iter().map(|(field, value)| json!({
+                    "kind": "FieldValue",
+                    "name": Self::write_variable(field),
+                    "value": self.write_expr(value),
+                })).collect::<Vec<_>>(),
+            }),
+            NodeArithmeticExpr::Variant(literal) => json!({
+                "kind": "VariantLiteral",
+                "span": Self::write_span(literal.span),
+                "type": null,
+                "enum": Self::write_variable(&literal.enum_name),
+                "variant": Self::write_variable(&literal.variant),
+                "values": literal.values.iter().map(|value| self.write_expr(value)).collect::<Vec<_>>(),
+            }),
+            NodeArithmeticExpr::Match(node) => json!({
+                "kind": "Match",
+                "span": Self::write_span(node.span),
+                "type": self.m_types.get_expr_type(expr).as_ref().map(ResultType::as_str),
+                "variable": self.write_base(&NodeBaseExpr::ID(node.variable.clone())),
+                "arms": node.arms.iter().map(|arm| json!({
+                    "kind": "Arm",
+                    "variant": Self::write_variable(&arm.variant),
+                    "bindings": arm.bindings.iter().map(Self::write_variable).collect::<Vec<_>>(),
+                    "value": AstJson { m_types: self.m_types.with_arm(&node.variable, arm) }.write_expr(&arm.value),
+                })).collect::<Vec<_>>(),
+            }),
+        }
+    }
+
+    fn write_operand(&self, operand: &Either<Box<NodeArithmeticOperation>, NodeBaseExpr>) -> Value {
+        match operand {
+            Left(operation) => self.write_operation(operation),
+            Right(base) => self.write_base(base),
+        }
+    }
+
+    fn write_operation(&self, operation: &NodeArithmeticOperation) -> Value {
+        json!({
+            "kind": "Operation",
+            "span": Self::write_span(operation.span),
+            "type": operation.result_type.as_str(),
+            "op": operation.op.to_string(),
+            "op_span": Self::write_span(operation.op.get_span()),
+            "lhs": self.write_operand(&operation.lhs),
+            "rhs": self.write_operand(&operation.rhs),
+        })
+    }
+
+    fn write_base(&self, base: &NodeBaseExpr) -> Value {
+        let (kind, field, value) = match base {
+            NodeBaseExpr::Num(Token::Number { value, .. }) => ("Number", "value", json!(value)),
+            NodeBaseExpr::ID(Token::ID { name, .. }) => ("Variable", "name", json!(name)),
+            NodeBaseExpr::Bool(Token::Boolean { value, .. }) => ("Boolean", "value", json!(value)),
+            NodeBaseExpr::Index(element) => ("Index", "variable", self.write_array(&element.variable)),
+            NodeBaseExpr::Len(len) => ("Len", "variable", self.write_array(&len.variable)),
+            NodeBaseExpr::Field(field) => ("Field", "variable", Self::write_variable(&field.variable)),
+            NodeBaseExpr::Alloc(node) => ("Alloc", "size", self.write_expr(&node.size)),
+            NodeBaseExpr::Builtin(node) => ("Builtin", "function", json!(node.function.name())),
+            NodeBaseExpr::Call(call) => ("Call", "function", Self::write_variable(&call.function)),
+            NodeBaseExpr::Str(Token::StringLiteral { value, .. }) => ("String", "value", json!(value)),
+            _ => ("Invalid", "value", Value::Null),
+        };
+        let mut node = Map::new();
+        node.insert("kind".to_string(), json!(kind));
+        node.insert("span".to_string(), Self::write_span(base.get_span()));
+        node.insert("type".to_string(), json!(self.m_types.get_base_type(base).as_ref().map(ResultType::as_str)));
+        node.insert(field.to_string(), value);
+        if let NodeBaseExpr::Index(element) = base {
+            node.insert("index".to_string(), self.write_expr(&element.index));
+        }
+        if let NodeBaseExpr::Builtin(NodeBuiltin { argument: Some(argument), .. }) = base {
+            node.insert("argument".to_string(), self.write_expr(argument));
+        }
+        if let NodeBaseExpr::Call(call) = base {
+            node.insert("arguments".to_string(), json!(call.arguments.iter().map(|argument| self.write_expr(argument)).collect::<Vec<_>>()));
+        }
+        if let NodeBaseExpr::Field(field) = base {
+            node.insert("fields".to_string(), json!(field.fields.iter().map(Self::write_variable).collect::<Vec<_>>()));
+        }
+        Value::Object(node)
+    }
+
+    /// The array of an element or of `len`, which has the type of its elements.
+    fn write_array(&self, variable: &Token) -> Value {
+        self.write_base(&NodeBaseExpr::ID(variable.clone()))
+    }
+
+    /// The assigned variable or a name, which have no type.
+    fn write_variable(variable: &Token) -> Value {
+        match variable {
+            Token::ID { name, span } => json!({ "kind": "Variable", "span": Self::write_span(*span), "type": null, "name": name }),
+            Token::Underscore { span } => json!({ "kind": "Wildcard", "span": Self::write_span(*span), "type": null }),
+            token => json!({ "kind": "Invalid", "span": Self::write_span(token.get_span()), "type": null, "value": null }),
+        }
+    }
+
+    fn write_span(span: Span) -> Value {
+        json!({ "file": span.m_file_id, "start": span.m_start, "end": span.m_end })
+    }
+
+    fn read_stmts(value: &Value) -> Result<Vec<NodeStmt>, String> {
+        Self::get_field(value, "stmts")?.as_array()
+            .ok_or("Expected `stmts` to be an array.")?
+            .iter()
+            .map(Self::read_stmt)
+            .collect()
+    }
+
+    fn read_stmt(value: &Value) -> Result<NodeStmt, String> {
+        let span = Self::read_span(value)?;
+        match Self::get_kind(value)? {
+            "Exit" => Ok(NodeStmt::Exit(NodeExit { expr: Self::read_expr(Self::get_field(value, "expr")?)?, span })),
+            "Assignment" => {
+                let variable = Self::get_field(value, "variable")?;
+                Self::expect_kind(variable, "Variable")?;
+                let name = Self::get_string(variable, "name")?;
+                let variable = Token::ID { name, span: Self::read_span(variable)? };
+                let declaration = match value.get("declaration").and_then(Value::as_str) {
+                    Some(keyword) => {
+                        let keyword_span = match value.get("declaration_span") {
+                            Some(keyword_span) if !keyword_span.is_null() => Self::read_span_value(keyword_span)?,
+                            _ => span,
+                        };
+                        Some(Declaration::from_name(keyword, keyword_span).ok_or(format!("Unknown declaration `{keyword}`."))?)
+                    }
+                    None => None,
+                };
+                let annotation = match value.get("annotation").and_then(Value::as_str) {
+                    Some(name) => {
+                        let annotation_span = match value.get("annotation_span") {
+                            Some(annotation_span) if !annotation_span.is_null() => Self::read_span_value(annotation_span)?,
+                            _ => span,
+                        };
+                        let result_type = ResultType::from_name(name).ok_or(format!("Unknown type `{name}`."))?;
+                        let length = match value.get("annotation_length") {
+                            Some(length) if !length.is_null() => Some(Self::read_length(length)?),
+                            _ => None,
+                        };
+                        Some(TypeAnnotation { result_type, length, span: annotation_span })
+                    }
+                    None => None,
+                };
+                let value = Self::read_expr(Self::get_field(value, "value")?)?;
+                Ok(NodeStmt::ID(NodeVariableAssignment { declaration, variable, annotation, value }))
+            }
+            "Scope" => Ok(NodeStmt::Scope(NodeScope { stmts: Self::read_stmts(value)?, span })),
+            "Import" => Ok(NodeStmt::Import(NodeImport { path: Self::get_string(value, "path")?, span })),
+            "IndexAssignment" => {
+                let Right(NodeBaseExpr::Index(element)) = Self::read_operand(Self::get_field(value, "element")?)? else {
+                    return Err("Expected `element` to be an `Index` node.".to_string());
+                };
+                let value = Self::read_expr(Self::get_field(value, "value")?)?;
+                Ok(NodeStmt::Index(NodeIndexAssignment { element, value }))
+            }
+            "Struct" => {
+                let fields = Self::get_array(value, "fields")?.iter().map(|field| {
+                    Self::expect_kind(field, "FieldDeclaration")?;
+                    Ok(NodeFieldDeclaration { name: Self::read_variable(Self::get_field(field, "name")?)?, type_name: Self::read_variable(Self::get_field(field, "type")?)? })
+                }).collect::<Result<Vec<_>, String>>()?;
+                Ok(NodeStmt::Struct(NodeStruct { name: Self::read_variable(Self::get_field(value, "name")?)?, fields, span }))
+            }
+            "FieldAssignment" => {
+                let Right(NodeBaseExpr::Field(field)) = Self::read_operand(Self::get_field(value, "field")?)? else {
+                    return Err("Expected `field` to be a `Field` node.".to_string());
+                };
+                let value = Self::read_expr(Self::get_field(value, "value")?)?;
+                Ok(NodeStmt::Field(NodeFieldAssignment { field, value }))
+            }
+            "Enum" => {
+                let variants = Self::get_array(value, "variants")?.iter().map(|variant| {
+                    Self::expect_kind(variant, "VariantDeclaration")?;
+                    let types = Self::get_array(variant, "types")?.iter().map(Self::read_variable).collect::<Result<Vec<_>, _>>()?;
+                    Ok(NodeVariant { name: Self::read_variable(Self::get_field(variant, "name")?)?, types })
+                }).collect::<Result<Vec<_>, String>>()?;
+                Ok(NodeStmt::Enum(NodeEnum { name: Self::read_variable(Self::get_field(value, "name")?)?, variants, span }))
+            }
+            "For" => {
+                let step = match value.get("step") {
+                    Some(step) if !step.is_null() => match Self::read_operand(step)? {
+                        Right(NodeBaseExpr::Num(step)) => Some(step),
+                        _ => return Err("Expected `step` to be a `Number` node.".to_string()),
+                    },
+                    _ => None,
+                };
+                let NodeStmt::Scope(body) = Self::read_stmt(Self::get_field(value, "body")?)? else {
+                    return Err("Expected `body` to be a `Scope` node.".to_string());
+                };
+                let inclusive = Self::get_field(value, "inclusive")?.as_bool().ok_or("Expected `inclusive` to be a boolean.")?;
+                Ok(NodeStmt::For(Box::new(NodeFor {
+                    variable: Self::read_pattern(Self::get_field(value, "variable")?)?,
+                    start: Self::read_expr(Self::get_field(value, "start")?)?,
+                    end: Self::read_expr(Self::get_field(value, "end")?)?,
+                    inclusive,
+                    step,
+                    body,
+                    span,
+                })))
+            }
+            "Free" => Ok(NodeStmt::Free(NodeFree { pointer: Self::read_expr(Self::get_field(value, "pointer")?)?, span })),
+            "Extern" => {
+                let parameters = Self::get_array(value, "parameters")?.iter().map(Self::read_variable).collect::<Result<Vec<_>, _>>()?;
+                let variadic = Self::get_field(value, "variadic")?.as_bool().ok_or("Expected `variadic` to be a boolean.")?;
+                Ok(NodeStmt::Extern(NodeExtern { name: Self::read_variable(Self::get_field(value, "name")?)?, parameters, variadic, span }))
+            }
+            "Call" => {
+                let Right(NodeBaseExpr::Call(call)) = Self::read_operand(value)? else {
+                    unreachable!("A `Call` node is read as a call")
+                };
+                Ok(NodeStmt::Call(call))
+            }
+            kind => Err(format!("Unknown statement kind `{kind}`.")),
+        }
+    }
+
+    fn read_expr(value: &Value) -> Result<NodeArithmeticExpr, String> {
+        if Self::get_kind(value)? == "Array" {
+            let elements = Self::get_field(value, "elements")?.as_array()
+                .ok_or("Expected `elements` to be an array.")?
+                .iter()
+                .map(Self::read_expr)
+                .collect::<Result<Vec<_>, _>>()?;
+            let length = Self::read_length(Self::get_field(value, "length")?)?;
+            if elements.is_empty() || (elements.len() != length && elements.len() != 1) {
+                return Err(format!("Expected {length} elements or a repeated one."));
+            }
+            return Ok(NodeArithmeticExpr::Array(NodeArrayLiteral { elements, length, span: Self::read_span(value)? }));
+        }
+        if Self::get_kind(value)? == "StructLiteral" {
+            let fields = Self::get_array(value, "fields")?.iter().map(|field| {
+                Self::expect_kind(field, "FieldValue")?;
+                Ok((Self::read_variable(Self::get_field(field, "name")?)?, Self::read_expr(Self::get_field(field, "value")?)?))
+            }).collect::<Result<Vec<_>, String>>()?;
+            let name = Self::read_variable(Self::get_field(value, "name")?)?;
+            return Ok(NodeArithmeticExpr::Struct(NodeStructLiteral { name, fields, span: Self::read_span(value)? }));
+        }
+        if Self::get_kind(value)? == "VariantLiteral" {
+            let values = Self::get_array(value, "values")?.iter().map(Self::read_expr).collect::<Result<Vec<_>, _>>()?;
+            let enum_name = Self::read_variable(Self::get_field(value, "enum")?)?;
+            let variant = Self::read_variable(Self::get_field(value, "variant")?)?;
+            return Ok(NodeArithmeticExpr::Variant(NodeVariantLiteral { enum_name, variant, values, span: Self::read_span(value)? }));
+        }
+        if Self::get_kind(value)? == "Match" {
+            let arms = Self::get_array(value, "arms")?.iter().map(|arm| {
+                Self::expect_kind(arm, "Arm")?;
+                let bindings = Self::get_array(arm, "bindings")?.iter().map(Self::read_pattern).collect::<Result<Vec<_>, _>>()?;
+                Ok(NodeMatchArm { variant: Self::read_pattern(Self::get_field(arm, "variant")?)?, bindings, value: Self::read_expr(Self::get_field(arm, "value")?)? })
+            }).collect::<Result<Vec<_>, String>>()?;
+            if arms.is_empty() {
+                return Err("Expected `arms` to hold at least one arm.".to_string());
+            }
+            let variable = Self::read_variable(Self::get_field(value, "variable")?)?;
+            return Ok(NodeArithmeticExpr::Match(NodeMatch { variable, arms, span: Self::read_span(value)? }));
+        }
+        match Self::read_operand(value)? {
+            Left(operation) => Ok(NodeArithmeticExpr::Operation(*operation)),
+            Right(base) => Ok(NodeArithmeticExpr::Base(base)),
+        }
+    }
+
+    fn read_operand(value: &Value) -> Result<Either<Box<NodeArithmeticOperation>, NodeBaseExpr>, String> {
+        let span = Self::read_span(value)?;
+        match Self::get_kind(value)? {
+            "Number" => {
+                let number = Self::get_string(value, "value")?;
+                if !number.bytes().all(|byte| byte.is_ascii_digit()) || number.parse::<i64>().is_err() {
+                    return Err(format!("Invalid number `{number}`."));
+                }
+                Ok(Right(NodeBaseExpr::Num(Token::Number { value: number, span })))
+            }
+            "Boolean" => {
+                let boolean = Self::get_field(value, "value")?.as_bool().ok_or("Expected `value` to be a boolean.")?;
+                Ok(Right(NodeBaseExpr::Bool(Token::Boolean { value: boolean, span })))
+            }
+            "Variable" => Ok(Right(NodeBaseExpr::ID(Token::ID { name: Self::get_name(value)?, span }))),
+            "Index" => {
+                let variable = Self::read_array(value)?;
+                let index = Box::new(Self::read_expr(Self::get_field(value, "index")?)?);
+                Ok(Right(NodeBaseExpr::Index(NodeIndex { variable, index, span })))
+            }
+            "Len" => Ok(Right(NodeBaseExpr::Len(NodeLen { variable: Self::read_array(value)?, span }))),
+            "Alloc" => Ok(Right(NodeBaseExpr::Alloc(NodeAlloc { size: Box::new(Self::read_expr(Self::get_field(value, "size")?)?), span }))),
+            "Builtin" => {
+                let name = Self::get_string(value, "function")?;
+                let function = Builtin::from_name(&name).ok_or(format!("Unknown function `{name}`."))?;
+                let argument = value.get("argument").map(Self::read_expr).transpose()?.map(Box::new);
+                match (function.arity(), &argument) {
+                    (1, None) => return Err(format!("Expected the `argument` of `{name}`.")),
+                    (0, Some(_)) => return Err(format!("`{name}` takes no argument.")),
+                    _ => {}
+                }
+                Ok(Right(NodeBaseExpr::Builtin(NodeBuiltin { function, argument, span })))
+            }
+            "Call" => {
+                let function = Self::read_variable(Self::get_field(value, "function")?)?;
+                let arguments = Self::get_array(value, "arguments")?.iter().map(Self::read_expr).collect::<Result<Vec<_>, _>>()?;
+                Ok(Right(NodeBaseExpr::Call(NodeCall { function, arguments, span })))
+            }
+            "String" => Ok(Right(NodeBaseExpr::Str(Token::StringLiteral { value: Self::get_string(value, "value")?, span }))),
+            "Field" => {
+                let variable = Self::read_variable(Self::get_field(value, "variable")?)?;
+                let fields = Self::get_array(value, "fields")?.iter().map(Self::read_variable).collect::<Result<Vec<_>, _>>()?;
+                if fields.is_empty() {
+                    return Err("Expected `fields` to name at least one field.".to_string());
+                }
+                Ok(Right(NodeBaseExpr::Field(NodeField { variable, fields, span })))
+            }
+            "Operation" => {
+                let symbol = Self::get_string(value, "op")?;
+                let op_span = match value.get("op_span") {
+                    Some(op_span) => Self::read_span_value(op_span)?,
+                    None => span,
+                };
+                let op = Operator::from_symbol(&symbol, op_span).ok_or(format!("Unknown operator `{symbol}`."))?;
+                let result_type = match op {
+                    // The type of a cast is its target, the type of the other operations only depends on their operator
+                    Operator::Cast { .. } => {
+                        let name = Self::get_string(value, "type")?;
+                        ResultType::from_name(&name).ok_or(format!("Unknown type `{name}`."))?
+                    }
+                    _ => ResultType::from_operator(&op),
+                };
+                Ok(Left(Box::new(NodeArithmeticOperation {
+                    lhs: Self::read_operand(Self::get_field(value, "lhs")?)?,
+                    rhs: Self::read_operand(Self::get_field(value, "rhs")?)?,
+                    op,
+                    result_type,
+                    span,
+                })))
+            }
+            kind => Err(format!("Unknown expression kind `{kind}`.")),
+        }
+    }
+
+    fn read_array(value: &Value) -> Result<Token, String> {
+        Self::read_variable(Self::get_field(value, "variable")?)
+    }
+
+    fn read_variable(variable: &Value) -> Result<Token, String> {
+        Self::expect_kind(variable, "Variable")?;
+        Ok(Token::ID { name: Self::get_name(variable)?, span: Self::read_span(variable)? })
+    }
+
+    /// The variant of an arm or one of its bindings, which can be a `Wildcard` node.
+    fn read_pattern(pattern: &Value) -> Result<Token, String> {
+        match Self::get_kind(pattern)? {
+            "Wildcard" => Ok(Token::Underscore { span: Self::read_span(pattern)? }),
+            _ => Self::read_variable(pattern),
+        }
+    }
+
+    fn read_length(value: &Value) -> Result<usize, String> {
+        match value.as_u64() {
+            Some(length) if (1..=NodeArrayLiteral::MAX_LENGTH as u64).contains(&length) => Ok(length as usize),
+            _ => Err(format!("Expected the length of the array to be a positive integer up to {}.", NodeArrayLiteral::MAX_LENGTH)),
+        }
+    }
+
+    fn read_span(value: &Value) -> Result<Span, String> {
+        match value.get("span") {
+            Some(span) => Self::read_span_value(span),
+            None => Ok(Span::default()),
+        }
+    }
+
+    fn read_span_value(value: &Value) -> Result<Span, String> {
+        let get = |field: &str| -> Result<usize, String> {
+            let number = Self::get_field(value, field)?.as_u64().ok_or(format!("Expected span `{field}` to be a positive integer."))?;
+            Ok(number as usize)
+        };
+        Ok(Span::new(get("file")?, get("start")?, get("end")?))
+    }
+
+    fn expect_kind(value: &Value, kind: &str) -> Result<(), String> {
+        match Self::get_kind(value)? {
+            found if found == kind => Ok(()),
+            found => Err(format!("Expected a `{kind}` node, found `{found}`.")),
+        }
+    }
+
+    fn get_kind(value: &Value) -> Result<&str, String> {
+        Self::get_field(value, "kind")?.as_str().ok_or("Expected `kind` to be a string.".to_string())
+    }
+
+    fn get_string(value: &Value, field: &str) -> Result<String, String> {
+        Self::get_field(value, field)?.as_str().map(str::to_string).ok_or(format!("Expected `{field}` to be a string."))
+    }
+
+    /// The name of a variable, type, field, variant or function, which must be read as an identifier, as some
+    /// end up in the assembly.
+    fn get_name(value: &Value) -> Result<String, String> {
+        let name = Self::get_string(value, "name")?;
+        if !Tokenizer::is_identifier(&name) {
+            return Err(format!("Invalid name `{name}`."));
+        }
+        Ok(name)
+    }
+
+    fn get_array<'v>(value: &'v Value, field: &str) -> Result<&'v Vec<Value>, String> {
+        Self::get_field(value, field)?.as_array().ok_or(format!("Expected `{field}` to be an array."))
+    }
+
+    fn get_field<'v>(value: &'v Value, field: &str) -> Result<&'v Value, String> {
+        value.get(field).ok_or(format!("Missing field `{field}`."))
+    }
+}
+
+
+
+#[cfg(test)]
+mod test_ast_json{
+    use std::sync::{Arc, Mutex};
+    use crate::compiler::logger::Logger;
+    use crate::compiler::parser::{Parser, ParserLogger};
+    use crate::compiler::tokenizer::Tokenizer;
+    use super::*;
+
+    fn parse(code: &str) -> NodeProgram {
+        let logger = Arc::new(Mutex::new(ParserLogger::new("".to_string(), code.to_string())));
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.tokenize(code);
+        Parser::new(tokenizer.get_tokens(), logger).parse().expect("The test program should parse")
+    }
+
+    #[test]
+    fn test_round_trip(){
+        let prog = parse("let x = (1 + 2) * 3\nconst y: bool = true && false\n{\nvar z = x\nz += 1\nexit(z % 2 as bool as int)\n}\nexit(x)");
+        let json = AstJson::write(&prog);
+        assert_eq!(AstJson::read(&json).unwrap(), prog);
+        let prog = parse("var a: [int; 3] = [1, 2, 3]\nlet b = [a[0]; 2]\na[b[1]] -= len(a)\nexit(a[2])");
+        let json = AstJson::write(&prog);
+        assert_eq!(AstJson::read(&json).unwrap(), prog);
+        let prog = parse("struct P { x: int, y: bool }\nstruct L { a: P }\nvar l = L { a: P { x: 1, y: true } }\nl.a.x *= 2\nl.a = P { x: 3, y: l.a.y }\nexit(l.a.x)");
+        let json = AstJson::write(&prog);
+        assert_eq!(AstJson::read(&json).unwrap(), prog);
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["stmts"][5]["expr"]["type"], "int");
+        let prog = parse("enum S { C(int, bool), E }\nvar s = S::C(1, false)\ns = S::E\nexit(match s { C(x, _) => x, E => 2 })");
+        let json = AstJson::write(&prog);
+        assert_eq!(AstJson::read(&json).unwrap(), prog);
+        let prog = parse("var x = 0\nfor i in 0..=x step 3 {\nx += i\n}\nfor _ in 0..2 {\n}\nexit(x)");
+        let json = AstJson::write(&prog);
+        assert_eq!(AstJson::read(&json).unwrap(), prog);
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["stmts"][1]["body"]["stmts"][0]["value"]["rhs"]["type"], "int");
+        let prog = parse("let p = alloc(2 * 8)\nfree(p)\nexit(0)");
+        let json = AstJson::write(&prog);
+        assert_eq!(AstJson::read(&json).unwrap(), prog);
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["stmts"][0]["value"]["type"], "int");
+        let prog = parse("let n = read_int()\nexit(parse_int(arg(n)) + args())");
+        let json = AstJson::write(&prog);
+        assert_eq!(AstJson::read(&json).unwrap(), prog);
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["stmts"][1]["expr"]["lhs"]["argument"]["function"], "arg");
+        let prog = parse("extern fn printf(int, ...)\nextern fn rand()\nprintf(\"%d\\n\", rand() % 6)\nexit(0)");
+        let json = AstJson::write(&prog);
+        assert_eq!(AstJson::read(&json).unwrap(), prog);
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["stmts"][2]["arguments"][0]["value"], "%d\n");
+        assert_eq!(value["stmts"][2]["arguments"][1]["lhs"]["type"], "int");
+    }
+
+    #[test]
+    fn test_write(){
+        let value: Value = serde_json::from_str(&AstJson::write(&parse("let x = 1 + y\nexit(x)"))).unwrap();
+        let assignment = &value["stmts"][0];
+        assert_eq!(assignment["kind"], "Assignment");
+        assert_eq!(assignment["span"], json!({ "file": 0, "start": 0, "end": 13 }));
+        assert_eq!(assignment["declaration"], "let");
+        assert_eq!(assignment["declaration_span"], json!({ "file": 0, "start": 0, "end": 3 }));
+        assert_eq!(assignment["value"]["type"], "int");
+        assert_eq!(assignment["annotation"], Value::Null);
+        assert_eq!(assignment["value"]["op"], "+");
+        // Variables get the type of their declaration, unknown ones have no type
+        assert_eq!(assignment["value"]["rhs"]["type"], Value::Null);
+        assert_eq!(value["stmts"][1]["expr"]["type"], "int");
+    }
+
+    #[test]
+    fn test_read_without_spans(){
+        let json = r#"{"kind": "Program", "stmts": [
+            {"kind": "Exit", "expr": {"kind": "Operation", "op": "+",
+                "lhs": {"kind": "Number", "value": "40"}, "rhs": {"kind": "Number", "value": "2"}}}
+        ]}"#;
+        let prog = AstJson::read(json).unwrap();
+        assert_eq!(format!("{prog}"), "exit(40 + 2)");
+    }
+
+    #[test]
+    fn test_read_operation_type(){
+        // The type given to an operation is ignored, except for a cast
+        let json = r#"{"kind": "Program", "stmts": [{"kind": "Exit", "expr": {"kind": "Operation", "op": "+", "type": "bool",
+            "lhs": {"kind": "Number", "value": "1"}, "rhs": {"kind": "Number", "value": "2"}}}]}"#;
+        let Some(NodeStmt::Exit(exit)) = AstJson::read(json).unwrap().stmts.pop() else { panic!("Expected an exit") };
+        assert!(matches!(exit.expr, NodeArithmeticExpr::Operation(NodeArithmeticOperation { result_type: ResultType::Numeric, .. })));
+        let prog = parse("exit(true as int)");
+        assert_eq!(AstJson::read(&AstJson::write(&prog)).unwrap(), prog);
+    }
+
+    #[test]
+    fn test_read_errors(){
+        assert!(AstJson::read("{").unwrap_err().starts_with("Invalid JSON"));
+        assert_eq!(AstJson::read(r#"{"kind": "Exit"}"#).unwrap_err(), "Expected a `Program` node, found `Exit`.");
+        assert_eq!(AstJson::read(r#"{"kind": "Program", "stmts": [{"kind": "Loop"}]}"#).unwrap_err(), "Unknown statement kind `Loop`.");
+        let json = r#"{"kind": "Program", "stmts": [{"kind": "Exit", "expr": {"kind": "Number", "value": "-1"}}]}"#;
+        assert_eq!(AstJson::read(json).unwrap_err(), "Invalid number `-1`.");
+        let json = r#"{"kind": "Program", "stmts": [{"kind": "Exit", "expr": {"kind": "Number", "value": "9223372036854775808"}}]}"#;
+        assert_eq!(AstJson::read(json).unwrap_err(), "Invalid number `9223372036854775808`.");
+        let json = r#"{"kind": "Program", "stmts": [{"kind": "Assignment", "declaration": "mut",
+            "variable": {"kind": "Variable", "name": "x"}, "value": {"kind": "Number", "value": "1"}}]}"#;
+        assert_eq!(AstJson::read(json).unwrap_err(), "Unknown declaration `mut`.");
+        let json = r#"{"kind": "Program", "stmts": [{"kind": "Assignment", "declaration": "let", "annotation": "float",
+            "variable": {"kind": "Variable", "name": "x"}, "value": {"kind": "Number", "value": "1"}}]}"#;
+        assert_eq!(AstJson::read(json).unwrap_err(), "Unknown type `float`.");
+        let json = r#"{"kind": "Program", "stmts": [{"kind": "Extern", "name": {"kind": "Variable", "name": "f\n\tsyscall"}, "parameters": [], "variadic": false}]}"#;
+        assert_eq!(AstJson::read(json).unwrap_err(), "Invalid name `f\n\tsyscall`.");
+        let json = r#"{"kind": "Program", "stmts": [{"kind": "Exit", "expr": {"kind": "Variable", "name": "let"}}]}"#;
+        assert_eq!(AstJson::read(json).unwrap_err(), "Invalid name `let`.");
+        let json = r#"{"kind": "Program", "stmts": [{"kind": "Assignment", "declaration": "let", "variable": {"kind": "Variable", "name": "a"},
+            "value": {"kind": "Array", "length": 99999999999, "elements": [{"kind": "Number", "value": "1"}]}}]}"#;
+        assert_eq!(AstJson::read(json).unwrap_err(), "Expected the length of the array to be a positive integer up to 65536.");
+    }
+}
\ No newline at end of file
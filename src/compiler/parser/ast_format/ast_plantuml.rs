@@ -0,0 +1,87 @@
+use crate::compiler::parser::NodeProgram;
+use super::DiagramNode;
+
+/// Writes a program as a PlantUML mind map, in the style of `docs/arithmetic_tree.plantuml`.
+/// The operations closest to the bottom of the tree are executed first.
+pub struct AstPlantUml;
+
+impl AstPlantUml {
+    pub fn write(prog: &NodeProgram) -> String {
+        let mut output = String::from("@startmindmap\ntop to bottom direction\n");
+        Self::write_node(&DiagramNode::from_program(prog), 1, &mut output);
+        output.push_str("@endmindmap");
+        output
+    }
+
+    fn write_node(node: &DiagramNode, depth: usize, output: &mut String) {
+        output.push_str(&"*".repeat(depth));
+        if let Some(color) = node.m_color {
+            output.push_str(&format!("[#{color}]"));
+        }
+        output.push_str(&format!(" {}\n", Self::escape(&node.m_label)));
+        for child in &node.m_children {
+            Self::write_node(child, depth + 1, output);
+        }
+    }
+
+    /// Escapes the creole markup (`**bold**`, `//italic//`, ...) that PlantUML would otherwise interpret.
+    fn escape(label: &str) -> String {
+        let mut escaped = String::new();
+        let mut chars = label.chars().peekable();
+        while let Some(c) = chars.next() {
+            if matches!(c, '*' | '/' | '-' | '_' | '~' | '"') && chars.peek() == Some(&c) {
+                escaped.push('~');
+            }
+            escaped.push(c);
+        }
+        escaped
+    }
+}
+
+
+
+#[cfg(test)]
+mod test_ast_plantuml{
+    use std::sync::{Arc, Mutex};
+    use crate::compiler::logger::Logger;
+    use crate::compiler::parser::{Parser, ParserLogger};
+    use crate::compiler::tokenizer::Tokenizer;
+    use super::*;
+
+    fn write(code: &str) -> String {
+        let logger = Arc::new(Mutex::new(ParserLogger::new("".to_string(), code.to_string())));
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.tokenize(code);
+        AstPlantUml::write(&Parser::new(tokenizer.get_tokens(), logger).parse().expect("The test program should parse"))
+    }
+
+    #[test]
+    fn test_write(){
+        assert_eq!(write("let x = (3+5)*2 + 12//4\n{\nexit(x)\n}"), concat!(
+            "@startmindmap\n",
+            "top to bottom direction\n",
+            "*[#lightblue] program\n",
+            "**[#lightblue] let =\n",
+            "*** x\n",
+            "***[#lightgreen] +\n",
+            "****[#yellow] *\n",
+            "*****[#orange] +\n",
+            "****** 3\n",
+            "****** 5\n",
+            "***** 2\n",
+            "****[#yellow] /\n",
+            "***** 12\n",
+            "***** 4\n",
+            "**[#lightblue] scope\n",
+            "***[#lightblue] exit\n",
+            "**** x\n",
+            "@endmindmap"
+        ));
+    }
+
+    #[test]
+    fn test_escape(){
+        assert_eq!(AstPlantUml::escape("a__b"), "a~__b");
+        assert_eq!(AstPlantUml::escape("||"), "||");
+    }
+}
\ No newline at end of file
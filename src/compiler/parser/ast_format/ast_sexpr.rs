@@ -0,0 +1,354 @@
+use either::{Either, Left, Right};
+use crate::compiler::parser::{NodeArithmeticExpr, NodeArithmeticOperation, NodeBaseExpr, NodeField, NodeIndex, NodeLen, NodeProgram, NodeStmt, ResultType};
+use crate::compiler::span::Span;
+use crate::compiler::tokenizer::Token;
+use super::VariableTypes;
+
+/// Writes a program as an S-expression, one statement per line. Every node is followed by its span,
+/// written `@file:start..end`, and expressions by their inferred type (`:?` when unknown):
+/// ```text
+/// (program @0:0..19
+///   (declare let @0:0..9 (var x @0:4..5) (num 1 @0:8..9 :int))
+///   (exit @0:10..19 (+ @0:15..18 :int (var x @0:15..16 :int) (num 1 @0:17..18 :int))))
+/// ```
+/// Declarations are written `(declare <keyword> ...)` and assignments to an existing variable `(assign ...)`,
+/// with the annotated type, if any, after the variable. Array literals are written `(array ...)` with their elements,
+/// or `(repeat ...)` with the element and the length, and elements are written `(index ...)` with the array and the index.
+/// Struct declarations are written `(struct ...)` with a `(name :type)` list of fields, struct literals `(literal ...)`
+/// with a `(name value)` list of fields, and fields are written `(field ...)` with the struct and the names leading to the field.
+/// Enum declarations are written `(enum ...)` with a `(variant :type...)` list of variants, variant literals `(variant ...)`
+/// with the enum, the variant and its values, and matches `(match ...)` with the variable and a `(arm (variant binding...) value)`
+/// list of arms. Loops are written `(for ...)` with the loop variable, the range `(.. start end)` or `(..= start end)`,
+/// the `(step n)` if any, and the statements of the body. C functions are declared `(extern ...)` with the types of their
+/// parameters, followed by `...` when they take more, and called `(call name ...)` with the arguments.
+pub struct AstSexpr {
+    m_types: VariableTypes,
+    m_output: String
+}
+
+impl AstSexpr {
+    pub fn write(prog: &NodeProgram) -> String {
+        let mut writer = AstSexpr { m_types: VariableTypes::new(), m_output: String::new() };
+        writer.m_output.push_str(&format!("(program {}", Self::write_span(prog.span)));
+        writer.write_stmts(&prog.stmts, 1);
+        writer.m_output.push(')');
+        writer.m_output
+    }
+
+    fn write_stmts(&mut self, stmts: &[NodeStmt], depth: usize) {
+        for stmt in stmts {
+            self.m_output.push('\n');
+            self.m_output.push_str(&"  ".repeat(depth));
+            self.write_stmt(stmt, depth);
+        }
+    }
+
+    fn write_stmt(&mut self, stmt: &NodeStmt, depth: usize) {
+        let node = match stmt {
+            NodeStmt::Exit(exit) => format!("(exit {} {})", Self::write_span(exit.span), self.write_expr(&exit.expr)),
+            NodeStmt::ID(assignment) => {
+                let value = self.write_expr(&assignment.value);
+                self.m_types.declare_assignment(assignment);
+                let variable = match &assignment.variable {
+                    Token::ID { name, span } => format!("(var {name} {})", Self::write_span(*span)),
+                    token => format!("(invalid {})", Self::write_span(token.get_span())),
+                };
+                // The annotated type is written after the variable
+                let variable = match assignment.annotation {
+                    Some(annotation) => format!("{variable} :{annotation}"),
+                    None => variable,
+                };
+                let span = Self::write_span(assignment.get_span());
+                match assignment.declaration {
+                    Some(declaration) => format!("(declare {} {span} {variable} {value})", declaration.as_str()),
+                    None => format!("(assign {span} {variable} {value})"),
+                }
+            }
+            NodeStmt::Scope(scope) => {
+                self.m_output.push_str(&format!("(scope {}", Self::write_span(scope.span)));
+                self.m_types.enter_scope();
+                self.write_stmts(&scope.stmts, depth + 1);
+                self.m_types.exit_scope();
+                ")".to_string()
+            }
+            NodeStmt::Import(import) => format!("(import {} {:?})", Self::write_span(import.span), import.path),
+            NodeStmt::Index(assignment) => {
+                let element = self.write_base(&NodeBaseExpr::Index(assignment.element.clone()));
+                format!("(assign {} {element} {})", Self::write_span(stmt.get_span()), self.write_expr(&assignment.value))
+            }
+            NodeStmt::Struct(declaration) => {
+                self.m_types.declare_struct(declaration);
+                let fields: Vec<String> = declaration.fields.iter().map(|field| match (&field.name, &field.type_name) {
+                    (Token::ID { name, span }, Token::ID { name: type_name, .. }) => format!(" ({name} {} :{type_name})", Self::write_span(*span)),
+                    (token, _) => format!(" (invalid {})", Self::write_span(token.get_span())),
+                }).collect();
+                format!("(struct {} {}{})", Self::write_span(declaration.span), Self::write_name(&declaration.name), fields.concat())
+            }
+            NodeStmt::Field(assignment) => {
+                let field = self.write_base(&NodeBaseExpr::Field(assignment.field.clone()));
+                format!("(assign {} {field} {})", Self::write_span(stmt.get_span()), self.write_expr(&assignment.value))
+            }
+            NodeStmt::Enum(declaration) => {
+                self.m_types.declare_enum(declaration);
+                let variants: Vec<String> = declaration.variants.iter().map(|variant| {
+                    let types: Vec<String> = variant.types.iter().map(|type_name| format!(" :{}", Self::write_name(type_name))).collect();
+                    format!(" ({} {}{})", Self::write_name(&variant.name), Self::write_span(variant.name.get_span()), types.concat())
+                }).collect();
+                format!("(enum {} {}{})", Self::write_span(declaration.span), Self::write_name(&declaration.name), variants.concat())
+            }
+            NodeStmt::For(node) => {
+                let variable = match &node.variable {
+                    Token::ID { name, span } => format!("(var {name} {})", Self::write_span(*span)),
+                    token => format!("(_ {})", Self::write_span(token.get_span())),
+                };
+                let range = format!("({} {} {})", if node.inclusive { "..=" } else { ".." }, self.write_expr(&node.start), self.write_expr(&node.end));
+                let step = node.step.as_ref().map_or(String::new(), |_| format!(" (step {})", node.get_step()));
+                self.m_output.push_str(&format!("(for {} {variable} {range}{step}", Self::write_span(node.span)));
+                self.m_types.enter_scope();
+                self.m_types.declare(&node.variable, Some(ResultType::Numeric));
+                self.write_stmts(&node.body.stmts, depth + 1);
+                self.m_types.exit_scope();
+                ")".to_string()
+            }
+            NodeStmt::Free(node) => format!("(free {} {})", Self::write_span(node.span), self.write_expr(&node.pointer)),
+            NodeStmt::Extern(declaration) => {
+                let mut parameters: Vec<String> = declaration.parameters.iter().map(|parameter| format!(" :{}", Self::write_name(parameter))).collect();
+                if declaration.variadic {
+                    parameters.push(" ...".to_string());
+                }
+                format!("(extern {} {}{})", Self::write_span(declaration.span), Self::write_name(&declaration.name), parameters.concat())
+            }
+            NodeStmt::Call(call) => self.write_base(&NodeBaseExpr::Call(call.clone())),
+        };
+        self.m_output.push_str(&node);
+    }
+
+    fn write_expr(&self, expr: &NodeArithmeticExpr) -> String {
+        match expr {
+            NodeArithmeticExpr::Base(base) => self.write_base(base),
+            NodeArithmeticExpr::Operation(operation) => self.write_operation(operation),
+            NodeArithmeticExpr::Array(array) => {
+                let elements: Vec<String> = array.elements.iter().map(|element| self.write_expr(element)).collect();
+                let result_type = Self::write_type(self.m_types.get_expr_type(expr).as_ref());
+                let span = Self::write_span(array.span);
+                if elements.len() == array.length {
+                    format!("(array {span} {result_type} {})", elements.join(" "))
+                } else {
+                    format!("(repeat {span} {result_type} {} {})", elements.join(" "), array.length)
+                }
+            }
+            NodeArithmeticExpr::Struct(literal) => {
+                let fields: Vec<String> = literal.fields.iter().map(|(field, value)| format!(" ({} {})", Self::write_name(field), self.write_expr(value))).collect();
+                format!("(literal {} :{}{})", Self::write_span(literal.span), Self::write_name(&literal.name), fields.concat())
+            }
+            NodeArithmeticExpr::Variant(literal) => {
+                let values: Vec<String> = literal.values.iter().map(|value| format!(" {}", self.write_expr(value))).collect();
+                let span = Self::write_span(literal.span);
+                format!("(variant {span} :{} {}{})", Self::write_name(&literal.enum_name), Self::write_name(&literal.variant), values.concat())
+            }
+            NodeArithmeticExpr::Match(node) => {
+                let arms: Vec<String> = node.arms.iter().map(|arm| {
+                    let pattern: Vec<String> = std::iter::once(&arm.variant).chain(&arm.bindings).map(Self::write_name).collect();
+                    let writer = AstSexpr { m_types: self.m_types.with_arm(&node.variable, arm), m_output: String::new() };
+                    format!(" (arm ({}) {})", pattern.join(" "), writer.write_expr(&arm.value))
+                }).collect();
+                // The matched enum has no value type, like the variable of a field
+                let variable = match &node.variable {
+                    Token::ID { name, span } => format!("(var {name} {})", Self::write_span(*span)),
+                    token => format!("(invalid {})", Self::write_span(token.get_span())),
+                };
+                let result_type = Self::write_type(self.m_types.get_expr_type(expr).as_ref());
+                format!("(match {} {result_type} {variable}{})", Self::write_span(node.span), arms.concat())
+            }
+        }
+    }
+
+    fn write_operand(&self, operand: &Either<Box<NodeArithmeticOperation>, NodeBaseExpr>) -> String {
+        match operand {
+            Left(operation) => self.write_operation(operation),
+            Right(base) => self.write_base(base),
+        }
+    }
+
+    fn write_operation(&self, operation: &NodeArithmeticOperation) -> String {
+        // The operand of a unary operation is only written once
+        let rhs = if operation.op.is_unary() { String::new() } else { format!(" {}", self.write_operand(&operation.rhs)) };
+        format!(
+            "({} {} {} {}{})",
+            operation.op,
+            Self::write_span(operation.span),
+            Self::write_type(Some(&operation.result_type)),
+            self.write_operand(&operation.lhs),
+            rhs
+        )
+    }
+
+    fn write_base(&self, base: &NodeBaseExpr) -> String {
+        // The array of an element comes after the span and the type, like the operands of an operation
+        let (node, children) = match base {
+            NodeBaseExpr::Num(Token::Number { value, .. }) => (format!("num {value}"), String::new()),
+            NodeBaseExpr::ID(Token::ID { name, .. }) => (format!("var {name}"), String::new()),
+            NodeBaseExpr::Bool(Token::Boolean { value, .. }) => (format!("bool {value}"), String::new()),
+            NodeBaseExpr::Index(NodeIndex { variable: Token::ID { name, span }, index, .. }) => {
+                ("index".to_string(), format!(" (var {name} {}) {}", Self::write_span(*span), self.write_expr(index)))
+            }
+            NodeBaseExpr::Len(NodeLen { variable: Token::ID { name, span }, .. }) => {
+                ("len".to_string(), format!(" (var {name} {})", Self::write_span(*span)))
+            }
+            NodeBaseExpr::Field(NodeField { variable: Token::ID { name, span }, fields, .. }) => {
+                let fields: Vec<String> = fields.iter().map(Self::write_name).collect();
+                ("field".to_string(), format!(" (var {name} {}) {}", Self::write_span(*span), fields.join(" ")))
+            }
+            NodeBaseExpr::Alloc(node) => ("alloc".to_string(), format!(" {}", self.write_expr(&node.size))),
+            NodeBaseExpr::Builtin(node) => {
+                (node.function.name().to_string(), node.argument.as_ref().map_or(String::new(), |argument| format!(" {}", self.write_expr(argument))))
+            }
+            NodeBaseExpr::Call(call) => {
+                let arguments: Vec<String> = call.arguments.iter().map(|argument| format!(" {}", self.write_expr(argument))).collect();
+                (format!("call {}", Self::write_name(&call.function)), arguments.concat())
+            }
+            NodeBaseExpr::Str(_) => (format!("str {base}"), String::new()),
+            _ => ("invalid".to_string(), String::new()),
+        };
+        let result_type = self.m_types.get_base_type(base);
+        format!("({node} {} {}{children})", Self::write_span(base.get_span()), Self::write_type(result_type.as_ref()))
+    }
+
+    /// Name of a struct, of a field, of an enum or of a variant, which is `_` for the wildcard of a match.
+    fn write_name(token: &Token) -> String {
+        match token {
+            Token::ID { name, .. } => name.clone(),
+            Token::Underscore { .. } => "_".to_string(),
+            _ => "invalid".to_string(),
+        }
+    }
+
+    fn write_span(span: Span) -> String {
+        format!("@{}:{}..{}", span.m_file_id, span.m_start, span.m_end)
+    }
+
+    fn write_type(result_type: Option<&ResultType>) -> String {
+        format!(":{}", result_type.map_or("?", ResultType::as_str))
+    }
+}
+
+
+
+#[cfg(test)]
+mod test_ast_sexpr{
+    use std::sync::{Arc, Mutex};
+    use crate::compiler::logger::Logger;
+    use crate::compiler::parser::{Parser, ParserLogger};
+    use crate::compiler::tokenizer::Tokenizer;
+    use super::*;
+
+    fn write(code: &str) -> String {
+        let logger = Arc::new(Mutex::new(ParserLogger::new("".to_string(), code.to_string())));
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.tokenize(code);
+        AstSexpr::write(&Parser::new(tokenizer.get_tokens(), logger).parse().expect("The test program should parse"))
+    }
+
+    #[test]
+    fn test_write(){
+        assert_eq!(write("var x = 1\nx = 2\nexit(x + 1)"), concat!(
+            "(program @0:0..27\n",
+            "  (declare var @0:0..9 (var x @0:4..5) (num 1 @0:8..9 :int))\n",
+            "  (assign @0:10..15 (var x @0:10..11) (num 2 @0:14..15 :int))\n",
+            "  (exit @0:16..27 (+ @0:21..26 :int (var x @0:21..22 :int) (num 1 @0:25..26 :int))))"
+        ));
+    }
+
+    #[test]
+    fn test_unary_operation(){
+        assert_eq!(write("exit(~1)"), concat!(
+            "(program @0:0..8\n",
+            "  (exit @0:0..8 (~ @0:5..7 :int (num 1 @0:6..7 :int))))"
+        ));
+    }
+
+    #[test]
+    fn test_annotation_and_cast(){
+        assert_eq!(write("let x: int = true as int"), concat!(
+            "(program @0:0..24\n",
+            "  (declare let @0:0..24 (var x @0:4..5) :int (as @0:13..24 :int (bool true @0:13..17 :bool))))"
+        ));
+    }
+
+    #[test]
+    fn test_arrays(){
+        assert_eq!(write("var a: [bool; 2] = [true; 2]\na[0] = a[1]\nexit(len(a))"), concat!(
+            "(program @0:0..53\n",
+            "  (declare var @0:0..28 (var a @0:4..5) :[bool; 2] (repeat @0:19..28 :bool (bool true @0:20..24 :bool) 2))\n",
+            "  (assign @0:29..40 (index @0:29..33 :bool (var a @0:29..30) (num 0 @0:31..32 :int)) (index @0:36..40 :bool (var a @0:36..37) (num 1 @0:38..39 :int)))\n",
+            "  (exit @0:41..53 (len @0:46..52 :int (var a @0:50..51))))"
+        ));
+    }
+
+    #[test]
+    fn test_scopes(){
+        assert_eq!(write("{\nlet b = true\n}\nexit(b)"), concat!(
+            "(program @0:0..24\n",
+            "  (scope @0:0..16\n",
+            "    (declare let @0:2..14 (var b @0:6..7) (bool true @0:10..14 :bool)))\n",
+            "  (exit @0:17..24 (var b @0:22..23 :?)))"
+        ));
+    }
+
+    #[test]
+    fn test_structs(){
+        assert_eq!(write("struct P { x: int }\nvar p = P { x: 1 }\np.x = p.x"), concat!(
+            "(program @0:0..48\n",
+            "  (struct @0:0..19 P (x @0:11..12 :int))\n",
+            "  (declare var @0:20..38 (var p @0:24..25) (literal @0:28..38 :P (x (num 1 @0:35..36 :int))))\n",
+            "  (assign @0:39..48 (field @0:39..42 :int (var p @0:39..40) x) (field @0:45..48 :int (var p @0:45..46) x)))"
+        ));
+    }
+
+    #[test]
+    fn test_enums(){
+        assert_eq!(write("enum S { C(int), E }\nlet s = S::C(1)\nexit(match s { C(r) => r, _ => 0 })"), concat!(
+            "(program @0:0..72\n",
+            "  (enum @0:0..20 S (C @0:9..10 :int) (E @0:17..18))\n",
+            "  (declare let @0:21..36 (var s @0:25..26) (variant @0:29..36 :S C (num 1 @0:34..35 :int)))\n",
+            "  (exit @0:37..72 (match @0:42..71 :int (var s @0:48..49) (arm (C r) (var r @0:60..61 :int)) (arm (_) (num 0 @0:68..69 :int)))))"
+        ));
+    }
+
+    #[test]
+    fn test_for(){
+        assert_eq!(write("for i in 0..=4 step 2 {\nexit(i)\n}\nfor _ in 0..1 {\n}"), concat!(
+            "(program @0:0..51\n",
+            "  (for @0:0..33 (var i @0:4..5) (..= (num 0 @0:9..10 :int) (num 4 @0:13..14 :int)) (step 2)\n",
+            "    (exit @0:24..31 (var i @0:29..30 :int)))\n",
+            "  (for @0:34..51 (_ @0:38..39) (.. (num 0 @0:43..44 :int) (num 1 @0:46..47 :int))))"
+        ));
+    }
+
+    #[test]
+    fn test_heap(){
+        assert_eq!(write("let p = alloc(8)\nfree(p)"), concat!(
+            "(program @0:0..24\n",
+            "  (declare let @0:0..16 (var p @0:4..5) (alloc @0:8..16 :int (num 8 @0:14..15 :int)))\n",
+            "  (free @0:17..24 (var p @0:22..23 :int)))"
+        ));
+    }
+
+    #[test]
+    fn test_input(){
+        assert_eq!(write("let n = arg(0)\nexit(read_int())"), concat!(
+            "(program @0:0..31\n",
+            "  (declare let @0:0..14 (var n @0:4..5) (arg @0:8..14 :int (num 0 @0:12..13 :int)))\n",
+            "  (exit @0:15..31 (read_int @0:20..30 :int)))"
+        ));
+    }
+
+    #[test]
+    fn test_extern(){
+        assert_eq!(write("extern fn printf(int, ...)\nprintf(\"%d\\n\", 1)"), concat!(
+            "(program @0:0..44\n",
+            "  (extern @0:0..26 printf :int ...)\n",
+            "  (call printf @0:27..44 :int (str \"%d\\n\" @0:34..40 :int) (num 1 @0:42..43 :int)))"
+        ));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,272 @@
+mod ast_json;
+mod ast_sexpr;
+mod ast_plantuml;
+mod ast_dot;
+
+pub use ast_json::AstJson;
+pub use ast_sexpr::AstSexpr;
+pub use ast_plantuml::AstPlantUml;
+pub use ast_dot::AstDot;
+
+use std::collections::HashMap;
+use either::{Either, Left, Right};
+use crate::compiler::parser::{NodeArithmeticExpr, NodeArithmeticOperation, NodeBaseExpr, NodeIndex, NodeProgram, NodeStmt, NodeStruct, NodeStructLiteral, NodeVariableAssignment, NodeEnum, NodeVariantLiteral, NodeMatchArm, ResultType};
+use crate::compiler::tokenizer::{Operator, Token};
+
+/// Types of the variables visible while walking the program, used to annotate the variable reads.
+/// The type of an array is the type of its elements, and the fields of a struct are typed apart, like `l.start.x`.
+#[derive(Clone)]
+struct VariableTypes {
+    m_scopes: Vec<HashMap<String, ResultType>>,
+    /// Types of the fields of every struct, the fields of a nested struct being named like `start.x`.
+    m_structs: HashMap<String, Vec<(String, ResultType)>>,
+    /// Types of the values of the variants of every enum.
+    m_enums: HashMap<String, HashMap<String, Vec<ResultType>>>,
+    /// Enum of the enum variables, which have no type of their own.
+    m_enum_variables: HashMap<String, String>
+}
+
+impl VariableTypes {
+    fn new() -> Self {
+        VariableTypes { m_scopes: vec![HashMap::new()], m_structs: HashMap::new(), m_enums: HashMap::new(), m_enum_variables: HashMap::new() }
+    }
+
+    fn enter_scope(&mut self) {
+        self.m_scopes.push(HashMap::new());
+    }
+
+    fn exit_scope(&mut self) {
+        self.m_scopes.pop();
+    }
+
+    fn declare(&mut self, variable: &Token, result_type: Option<ResultType>) {
+        if let (Token::ID { name, .. }, Some(result_type)) = (variable, result_type) {
+            self.m_scopes.last_mut().unwrap().insert(name.clone(), result_type);
+        }
+    }
+
+    /// Declares the variable of an assignment that declares one, with its annotated or inferred type.
+    fn declare_assignment(&mut self, assignment: &NodeVariableAssignment) {
+        if assignment.declaration.is_none() {
+            return;
+        }
+        if let (Token::ID { name, .. }, NodeArithmeticExpr::Struct(NodeStructLiteral { name: Token::ID { name: struct_name, .. }, .. })) = (&assignment.variable, &assignment.value) {
+            let fields = self.m_structs.get(struct_name).cloned().unwrap_or_default();
+            self.m_scopes.last_mut().unwrap().extend(fields.into_iter().map(|(path, result_type)| (format!("{name}.{path}"), result_type)));
+            return;
+        }
+        if let (Token::ID { name, .. }, NodeArithmeticExpr::Variant(NodeVariantLiteral { enum_name: Token::ID { name: enum_name, .. }, .. })) = (&assignment.variable, &assignment.value) {
+            self.m_enum_variables.insert(name.clone(), enum_name.clone());
+            return;
+        }
+        let annotation = assignment.annotation.map(|annotation| annotation.result_type);
+        self.declare(&assignment.variable, annotation.or(self.get_expr_type(&assignment.value)));
+    }
+
+    fn declare_struct(&mut self, declaration: &NodeStruct) {
+        let Token::ID { name, .. } = &declaration.name else { return };
+        let mut fields = Vec::new();
+        for field in &declaration.fields {
+            let (Token::ID { name: field_name, .. }, Token::ID { name: type_name, .. }) = (&field.name, &field.type_name) else { continue };
+            match (ResultType::from_name(type_name), self.m_structs.get(type_name)) {
+                (Some(result_type), _) => fields.push((field_name.clone(), result_type)),
+                (None, Some(nested)) => fields.extend(nested.iter().map(|(path, result_type)| (format!("{field_name}.{path}"), *result_type))),
+                (None, None) => {}
+            }
+        }
+        self.m_structs.insert(name.clone(), fields);
+    }
+
+    fn declare_enum(&mut self, declaration: &NodeEnum) {
+        let Token::ID { name, .. } = &declaration.name else { return };
+        let variants = declaration.variants.iter().filter_map(|variant| {
+            let Token::ID { name: variant_name, .. } = &variant.name else { return None };
+            let types = variant.types.iter().filter_map(|type_name| match type_name {
+                Token::ID { name, .. } => ResultType::from_name(name),
+                _ => None,
+            }).collect();
+            Some((variant_name.clone(), types))
+        }).collect();
+        self.m_enums.insert(name.clone(), variants);
+    }
+
+    /// Types seen by the value of an arm matching `variable`, whose bindings have the types of the values of the variant.
+    fn with_arm(&self, variable: &Token, arm: &NodeMatchArm) -> VariableTypes {
+        let mut types = self.clone();
+        types.enter_scope();
+        let variant_types = match (variable, &arm.variant) {
+            (Token::ID { name, .. }, Token::ID { name: variant, .. }) => self.m_enum_variables.get(name)
+                .and_then(|enum_name| self.m_enums.get(enum_name))
+                .and_then(|variants| variants.get(variant))
+                .cloned()
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        for (binding, result_type) in arm.bindings.iter().zip(variant_types) {
+            types.declare(binding, Some(result_type));
+        }
+        types
+    }
+
+    fn get_base_type(&self, base: &NodeBaseExpr) -> Option<ResultType> {
+        match base {
+            NodeBaseExpr::Num(_) | NodeBaseExpr::Len(_) | NodeBaseExpr::Alloc(_) | NodeBaseExpr::Builtin(_)
+            | NodeBaseExpr::Call(_) | NodeBaseExpr::Str(_) => Some(ResultType::Numeric),
+            NodeBaseExpr::Bool(_) => Some(ResultType::Boolean),
+            NodeBaseExpr::ID(Token::ID { name, .. }) | NodeBaseExpr::Index(NodeIndex { variable: Token::ID { name, .. }, .. }) => {
+                self.m_scopes.iter().rev().find_map(|scope| scope.get(name)).cloned()
+            }
+            NodeBaseExpr::Field(field) => self.m_scopes.iter().rev().find_map(|scope| scope.get(&field.to_string())).cloned(),
+            NodeBaseExpr::ID(_) | NodeBaseExpr::Index(_) => None,
+        }
+    }
+
+    fn get_expr_type(&self, expr: &NodeArithmeticExpr) -> Option<ResultType> {
+        match expr {
+            NodeArithmeticExpr::Base(base) => self.get_base_type(base),
+            NodeArithmeticExpr::Operation(operation) => Some(operation.result_type),
+            NodeArithmeticExpr::Array(array) => self.get_expr_type(array.elements.first()?),
+            NodeArithmeticExpr::Struct(_) | NodeArithmeticExpr::Variant(_) => None,
+            NodeArithmeticExpr::Match(node) => {
+                let arm = node.arms.first()?;
+                self.with_arm(&node.variable, arm).get_expr_type(&arm.value)
+            }
+        }
+    }
+}
+
+/// Colors of the statements and, by depth in their expression tree, of the operations in the diagrams.
+const STATEMENT_COLOR: &str = "lightblue";
+const OPERATION_COLORS: [&str; 5] = ["lightgreen", "yellow", "orange", "pink", "red"];
+
+/// Node of the tree drawn by the diagram writers. Operands are the leaves of the tree.
+struct DiagramNode {
+    m_label: String,
+    m_color: Option<&'static str>,
+    m_children: Vec<DiagramNode>
+}
+
+impl DiagramNode {
+    fn from_program(prog: &NodeProgram) -> DiagramNode {
+        Self::statement("program".to_string(), prog.stmts.iter().map(Self::from_stmt).collect())
+    }
+
+    fn from_stmt(stmt: &NodeStmt) -> DiagramNode {
+        match stmt {
+            NodeStmt::Exit(exit) => Self::statement("exit".to_string(), vec![Self::from_expr(&exit.expr)]),
+            NodeStmt::ID(assignment) => {
+                let mut variable = Self::from_base(&NodeBaseExpr::ID(assignment.variable.clone()));
+                if let Some(annotation) = assignment.annotation {
+                    variable.m_label = format!("{}: {annotation}", variable.m_label);
+                }
+                let label = match assignment.declaration {
+                    Some(declaration) => format!("{} =", declaration.as_str()),
+                    None => "=".to_string(),
+                };
+                Self::statement(label, vec![variable, Self::from_expr(&assignment.value)])
+            }
+            NodeStmt::Scope(scope) => Self::statement("scope".to_string(), scope.stmts.iter().map(Self::from_stmt).collect()),
+            NodeStmt::Import(import) => Self::statement(format!("import \"{}\"", import.path), Vec::new()),
+            NodeStmt::Index(assignment) => {
+                let element = Self::from_base(&NodeBaseExpr::Index(assignment.element.clone()));
+                Self::statement("=".to_string(), vec![element, Self::from_expr(&assignment.value)])
+            }
+            NodeStmt::Struct(declaration) => {
+                let fields = declaration.fields.iter().map(|field| Self::leaf(format!("{}: {}", Self::name(&field.name), Self::name(&field.type_name)))).collect();
+                Self::statement(format!("struct {}", Self::name(&declaration.name)), fields)
+            }
+            NodeStmt::Field(assignment) => {
+                let field = Self::from_base(&NodeBaseExpr::Field(assignment.field.clone()));
+                Self::statement("=".to_string(), vec![field, Self::from_expr(&assignment.value)])
+            }
+            NodeStmt::Enum(declaration) => {
+                let variants = declaration.variants.iter().map(|variant| {
+                    let types = variant.types.iter().map(|type_name| Self::leaf(Self::name(type_name))).collect();
+                    DiagramNode { m_label: Self::name(&variant.name), m_color: None, m_children: types }
+                }).collect();
+                Self::statement(format!("enum {}", Self::name(&declaration.name)), variants)
+            }
+            NodeStmt::For(node) => {
+                let range = if node.inclusive { "..=" } else { ".." };
+                let step = node.step.as_ref().map_or(String::new(), |step| format!(" step {}", NodeBaseExpr::Num(step.clone())));
+                let body = Self::statement("scope".to_string(), node.body.stmts.iter().map(Self::from_stmt).collect());
+                Self::statement(format!("for {} in {range}{step}", Self::name(&node.variable)), vec![Self::from_expr(&node.start), Self::from_expr(&node.end), body])
+            }
+            NodeStmt::Free(node) => Self::statement("free".to_string(), vec![Self::from_expr(&node.pointer)]),
+            NodeStmt::Extern(declaration) => Self::statement(declaration.to_string(), Vec::new()),
+            NodeStmt::Call(call) => Self::statement(format!("{}()", Self::name(&call.function)), call.arguments.iter().map(Self::from_expr).collect()),
+        }
+    }
+
+    fn from_expr(expr: &NodeArithmeticExpr) -> DiagramNode {
+        match expr {
+            NodeArithmeticExpr::Base(base) => Self::from_base(base),
+            NodeArithmeticExpr::Operation(operation) => Self::from_operation(operation, 0),
+            NodeArithmeticExpr::Array(array) => {
+                // A repeated element is drawn once, with the length of the array
+                let m_label = if array.elements.len() == array.length { "array".to_string() } else { format!("array of {}", array.length) };
+                DiagramNode { m_label, m_color: None, m_children: array.elements.iter().map(Self::from_expr).collect() }
+            }
+            NodeArithmeticExpr::Struct(literal) => {
+                let fields = literal.fields.iter().map(|(field, value)| DiagramNode { m_label: Self::name(field), m_color: None, m_children: vec![Self::from_expr(value)] });
+                DiagramNode { m_label: Self::name(&literal.name), m_color: None, m_children: fields.collect() }
+            }
+            NodeArithmeticExpr::Variant(literal) => {
+                let m_label = format!("{}::{}", Self::name(&literal.enum_name), Self::name(&literal.variant));
+                DiagramNode { m_label, m_color: None, m_children: literal.values.iter().map(Self::from_expr).collect() }
+            }
+            NodeArithmeticExpr::Match(node) => {
+                // Every arm is drawn as its pattern, holding its value
+                let arms = node.arms.iter().map(|arm| {
+                    let bindings: Vec<String> = arm.bindings.iter().map(Self::name).collect();
+                    let m_label = if bindings.is_empty() { Self::name(&arm.variant) } else { format!("{}({})", Self::name(&arm.variant), bindings.join(", ")) };
+                    DiagramNode { m_label, m_color: None, m_children: vec![Self::from_expr(&arm.value)] }
+                });
+                DiagramNode { m_label: format!("match {}", Self::name(&node.variable)), m_color: None, m_children: arms.collect() }
+            }
+        }
+    }
+
+    fn from_operand(operand: &Either<Box<NodeArithmeticOperation>, NodeBaseExpr>, depth: usize) -> DiagramNode {
+        match operand {
+            Left(operation) => Self::from_operation(operation, depth),
+            Right(base) => Self::from_base(base),
+        }
+    }
+
+    fn from_operation(operation: &NodeArithmeticOperation, depth: usize) -> DiagramNode {
+        let m_label = match operation.op {
+            Operator::Cast { .. } => format!("as {}", operation.result_type),
+            op => op.to_string(),
+        };
+        DiagramNode {
+            m_label,
+            m_color: Some(OPERATION_COLORS[depth % OPERATION_COLORS.len()]),
+            m_children: if operation.op.is_unary() {
+                vec![Self::from_operand(&operation.lhs, depth + 1)]
+            } else {
+                vec![Self::from_operand(&operation.lhs, depth + 1), Self::from_operand(&operation.rhs, depth + 1)]
+            }
+        }
+    }
+
+    fn from_base(base: &NodeBaseExpr) -> DiagramNode {
+        Self::leaf(base.to_string())
+    }
+
+    /// Name of a struct, of a field, of a type or of a variant, which is `_` for the wildcard of a match.
+    fn name(token: &Token) -> String {
+        match token {
+            Token::Underscore { .. } => "_".to_string(),
+            token => NodeBaseExpr::ID(token.clone()).to_string(),
+        }
+    }
+
+    fn leaf(m_label: String) -> DiagramNode {
+        DiagramNode { m_label, m_color: None, m_children: Vec::new() }
+    }
+
+    fn statement(m_label: String, m_children: Vec<DiagramNode>) -> DiagramNode {
+        DiagramNode { m_label, m_color: Some(STATEMENT_COLOR), m_children }
+    }
+}
\ No newline at end of file
@@ -1,9 +1,10 @@
 use std::sync::{Arc, Mutex};
 use either::{Either, Left, Right};
-use crate::compiler::parser::{NodeArithmeticExpr, NodeArithmeticOperation, NodeBaseExpr, ParserErrorType, ParserLogger};
+use crate::compiler::parser::{NodeArithmeticExpr, NodeArithmeticOperation, NodeBaseExpr, NodeField, NodeIndex, NodeLen, NodeAlloc, NodeBuiltin, NodeCall, ParserErrorType, ParserLogger};
+use crate::compiler::span::Span;
 use crate::compiler::parser::expression_factory::reverse_polish_notation::ReversePolishNotation;
 use crate::compiler::parser::nodes::ResultType;
-use crate::compiler::tokenizer::{Operator, Token};
+use crate::compiler::tokenizer::{Builtin, Operator, Token};
 use crate::compiler::parser::token_stream::TokenStream;
 
 pub struct ExpressionFactory<'a> {
@@ -24,17 +25,30 @@ impl<'a> ExpressionFactory<'a> {
     pub fn create(&mut self) -> Option<Either<Box<NodeArithmeticOperation>, NodeBaseExpr>>{
         let polish = ReversePolishNotation::new(self.m_line_stream, self.m_logger.clone()).create();
         if let Some(p) = polish{
-            for token in p{
+            let mut tokens = p.into_iter().peekable();
+            while let Some(token) = tokens.next(){
                 match token {
                     Token::ID { .. } => {
                         self.m_expr_stack.push(NodeArithmeticExpr::Base(NodeBaseExpr::ID(token.clone())));
                     },
-                    Token::Number { .. } => {
-                        self.m_expr_stack.push(NodeArithmeticExpr::Base(NodeBaseExpr::Num(token.clone())));
+                    Token::Number { ref value, span } => {
+                        // A negated literal is read as a negative one, so that `-9223372036854775808` is in range
+                        let token = match tokens.next_if(|next| matches!(next, Token::Operator(Operator::Negate { .. }))) {
+                            Some(Token::Operator(Operator::Negate { span: minus })) => Token::Number { value: format!("-{value}"), span: minus.merge(span) },
+                            _ => token.clone(),
+                        };
+                        if matches!(&token, Token::Number { value, .. } if value.parse::<i64>().is_err()) {
+                            self.log_error(ParserErrorType::ErrIntegerOutOfRange, &token);
+                            return None;
+                        }
+                        self.m_expr_stack.push(NodeArithmeticExpr::Base(NodeBaseExpr::Num(token)));
                     },
                     Token::Boolean { .. } => {
                         self.m_expr_stack.push(NodeArithmeticExpr::Base(NodeBaseExpr::Bool(token.clone())));
                     },
+                    Token::StringLiteral { .. } => {
+                        self.m_expr_stack.push(NodeArithmeticExpr::Base(NodeBaseExpr::Str(token.clone())));
+                    },
                     Token::Operator(ref op_token) => {
                         if !self.create_operation(op_token)
                         {
@@ -47,73 +61,247 @@ impl<'a> ExpressionFactory<'a> {
                     }
                 }
             }
+            if self.m_expr_stack.len() > 1 {
+                // Every operand after the first one should have been consumed by an operator
+                let span = self.m_expr_stack.last().unwrap().get_span();
+                self.m_logger.lock().unwrap().log_error_at(ParserErrorType::ErrMissingOperator, span);
+                return None;
+            }
             match self.m_expr_stack.pop(){
                 Some(NodeArithmeticExpr::Base(base)) => {Some(Right(base))}
                 Some(NodeArithmeticExpr::Operation(op)) => {Some(Left(Box::new(op)))}
-                None => {None},
+                Some(NodeArithmeticExpr::Array(_) | NodeArithmeticExpr::Struct(_) | NodeArithmeticExpr::Variant(_) | NodeArithmeticExpr::Match(_)) | None => {None},
             }
         } else {
             None
         }
     }
     
+    /// Parses the value of the compound assignment of `target`, a variable or an element, returning `target operator (value)`.
+    pub fn create_compound(&mut self, target: NodeBaseExpr, operator: &Operator) -> Option<Either<Box<NodeArithmeticOperation>, NodeBaseExpr>>{
+        let value = match self.create()? {
+            Left(operation) => NodeArithmeticExpr::Operation(*operation),
+            Right(base) => NodeArithmeticExpr::Base(base)
+        };
+        self.m_expr_stack.push(NodeArithmeticExpr::Base(target));
+        self.m_expr_stack.push(value);
+        if !self.create_operation(operator) {
+            return None;
+        }
+        match self.m_expr_stack.pop() {
+            Some(NodeArithmeticExpr::Operation(operation)) => Some(Left(Box::new(operation))),
+            _ => None
+        }
+    }
+    
     fn create_operation(&mut self, operator: &Operator) -> bool{
+        match operator {
+            Operator::Cast { .. } => return self.create_cast(operator),
+            Operator::Index { .. } => return self.create_index(operator),
+            Operator::Len { .. } => return self.create_len(operator),
+            Operator::Alloc { .. } => return self.create_alloc(operator),
+            Operator::Builtin { function, .. } => return self.create_builtin(operator, *function),
+            Operator::Call { arguments, .. } => return self.create_call(operator, *arguments),
+            Operator::Field { .. } => return self.create_field(operator),
+            _ => {}
+        }
         let error_token = &Token::Operator(operator.clone());
         let rhs = self.m_expr_stack.pop();
-        let lhs = self.m_expr_stack.pop();
-        if lhs.is_none() || rhs.is_none() {
+        // Unary operations hold their only operand on both sides
+        let lhs = if operator.is_unary() { rhs.clone() } else { self.m_expr_stack.pop() };
+        let (Some(lhs), Some(rhs)) = (lhs, rhs) else {
             self.log_error(ParserErrorType::ErrMissingOperand, error_token);
             return false;
-        }
-        // For logical operators, make sure that both operands are booleans.
-        let operator_is_bool = matches!(operator, Operator::And { .. } | Operator::Or { .. } | Operator::Xor { .. });
-        if operator_is_bool && !ExpressionFactory::<'a>::type_check_logical_operands(&lhs.clone().unwrap(), &rhs.clone().unwrap())
+        };
+        // For logical operators, make sure that both operands are booleans, and for bitwise ones that none of them is.
+        let operator_is_bool = matches!(operator, Operator::And { .. } | Operator::Or { .. } | Operator::Xor { .. } | Operator::Not { .. });
+        let operator_is_bitwise = matches!(operator, Operator::BitAnd { .. } | Operator::BitOr { .. } | Operator::BitXor { .. }
+            | Operator::BitNot { .. } | Operator::ShiftLeft { .. } | Operator::ShiftRight { .. });
+        if (operator_is_bool && !ExpressionFactory::<'a>::type_check_logical_operands(&lhs, &rhs))
+            || (operator_is_bitwise && !ExpressionFactory::<'a>::type_check_numeric_operands(&lhs, &rhs))
         {
             self.log_error(ParserErrorType::ErrTypeMismatch, error_token);
             return false;
         }
 
-        let lhs_node = match lhs.unwrap() {
+        let span = operator.get_span().merge(lhs.get_span()).merge(rhs.get_span());
+        let lhs_node = match lhs {
             NodeArithmeticExpr::Base(base) => Right(base),
-            NodeArithmeticExpr::Operation(operation) => Left(Box::new(operation))
+            NodeArithmeticExpr::Operation(operation) => Left(Box::new(operation)),
+            NodeArithmeticExpr::Array(_) | NodeArithmeticExpr::Struct(_) | NodeArithmeticExpr::Variant(_) | NodeArithmeticExpr::Match(_) => unreachable!("Literals and matches are never operands")
         };
-        let rhs_node = match rhs.unwrap() {
+        let rhs_node = match rhs {
             NodeArithmeticExpr::Base(base) => Right(base),
-            NodeArithmeticExpr::Operation(operation) => Left(Box::new(operation))
+            NodeArithmeticExpr::Operation(operation) => Left(Box::new(operation)),
+            NodeArithmeticExpr::Array(_) | NodeArithmeticExpr::Struct(_) | NodeArithmeticExpr::Variant(_) | NodeArithmeticExpr::Match(_) => unreachable!("Literals and matches are never operands")
         };
         self.m_expr_stack.push(NodeArithmeticExpr::Operation(NodeArithmeticOperation {
             lhs: lhs_node,
             rhs: rhs_node,
             op: *operator,
-            result_type: self.get_result_type(&operator),
+            result_type: ResultType::from_operator(operator),
+            span,
         }));
         true
     }
     
-    fn type_check_logical_operands(lhs_expr: &NodeArithmeticExpr, rhs_expr: &NodeArithmeticExpr) -> bool{
-        let lhs_valid_base = matches!(lhs_expr, NodeArithmeticExpr::Base(NodeBaseExpr::Bool(_)));
-        let rhs_valid_base = matches!(rhs_expr, NodeArithmeticExpr::Base(NodeBaseExpr::Bool(_)));
-        let lhs_valid_op = matches!(lhs_expr, NodeArithmeticExpr::Operation(NodeArithmeticOperation{result_type: ResultType::Boolean, ..}));
-        let rhs_valid_op = matches!(rhs_expr, NodeArithmeticExpr::Operation(NodeArithmeticOperation{result_type: ResultType::Boolean, ..}));
-        (lhs_valid_base || lhs_valid_op) && (rhs_valid_base || rhs_valid_op)
-    }
-
-    fn get_result_type(& self, op: &Operator) -> ResultType{
-        let mut res = ResultType::Numeric;
-        match op{
-            Operator::And { .. } |
-            Operator::Or { .. } |
-            Operator::Xor { .. } |
-            Operator::Not { .. } => { res = ResultType::Boolean }
-            _ => {}
+    /// The type of a cast is the name written after `as`, which the reverse polish notation puts on top of the operand.
+    fn create_cast(&mut self, operator: &Operator) -> bool{
+        let target = self.m_expr_stack.pop();
+        let operand = self.m_expr_stack.pop();
+        let result_type = match &target {
+            Some(NodeArithmeticExpr::Base(NodeBaseExpr::ID(Token::ID { name, .. }))) => ResultType::from_name(name),
+            _ => None
+        };
+        let (Some(operand), Some(target), Some(result_type)) = (operand, target, result_type) else {
+            self.log_error(ParserErrorType::ErrTypeMissing, &Token::Operator(*operator));
+            return false;
+        };
+        let span = operand.get_span().merge(target.get_span());
+        let operand = match operand {
+            NodeArithmeticExpr::Base(base) => Right(base),
+            NodeArithmeticExpr::Operation(operation) => Left(Box::new(operation)),
+            NodeArithmeticExpr::Array(_) | NodeArithmeticExpr::Struct(_) | NodeArithmeticExpr::Variant(_) | NodeArithmeticExpr::Match(_) => unreachable!("Literals and matches are never operands")
+        };
+        self.m_expr_stack.push(NodeArithmeticExpr::Operation(NodeArithmeticOperation {
+            lhs: operand.clone(),
+            rhs: operand,
+            op: *operator,
+            result_type,
+            span,
+        }));
+        true
+    }
+
+    /// The reverse polish notation puts the index on top of the array, which can only be a variable.
+    fn create_index(&mut self, operator: &Operator) -> bool{
+        let index = self.m_expr_stack.pop();
+        let array = self.m_expr_stack.pop();
+        let (Some(NodeArithmeticExpr::Base(NodeBaseExpr::ID(variable))), Some(index)) = (array, index) else {
+            self.log_error(ParserErrorType::ErrMissingOperand, &Token::Operator(*operator));
+            return false;
+        };
+        if !ExpressionFactory::<'a>::type_check_numeric_operands(&index, &index) {
+            self.log_error_at(ParserErrorType::ErrTypeMismatch, index.get_span());
+            return false;
         }
-        res
+        self.m_expr_stack.push(NodeArithmeticExpr::Base(NodeBaseExpr::Index(NodeIndex {
+            span: variable.get_span().merge(operator.get_span()),
+            variable,
+            index: Box::new(index),
+        })));
+        true
     }
-    
+
+    /// The operator already spans the whole `len(a)`, and is put right after the array.
+    fn create_len(&mut self, operator: &Operator) -> bool{
+        let Some(NodeArithmeticExpr::Base(NodeBaseExpr::ID(variable))) = self.m_expr_stack.pop() else {
+            self.log_error(ParserErrorType::ErrArrayMissing, &Token::Operator(*operator));
+            return false;
+        };
+        self.m_expr_stack.push(NodeArithmeticExpr::Base(NodeBaseExpr::Len(NodeLen { variable, span: operator.get_span() })));
+        true
+    }
+
+    /// The operator already spans the whole `alloc(n)`, and is put right after the size.
+    fn create_alloc(&mut self, operator: &Operator) -> bool{
+        let Some(size) = self.m_expr_stack.pop() else {
+            self.log_error(ParserErrorType::ErrAllocSizeMissing, &Token::Operator(*operator));
+            return false;
+        };
+        if !ExpressionFactory::<'a>::type_check_numeric_operands(&size, &size) {
+            self.log_error_at(ParserErrorType::ErrTypeMismatch, size.get_span());
+            return false;
+        }
+        self.m_expr_stack.push(NodeArithmeticExpr::Base(NodeBaseExpr::Alloc(NodeAlloc { size: Box::new(size), span: operator.get_span() })));
+        true
+    }
+
+    /// The operator already spans the whole call and is put right after its argument, which must be inside the brackets.
+    fn create_builtin(&mut self, operator: &Operator, function: Builtin) -> bool{
+        let span = operator.get_span();
+        let argument = if function.arity() == 1 {
+            let argument = self.m_expr_stack.pop().filter(|argument| span.merge(argument.get_span()) == span);
+            let Some(argument) = argument else {
+                self.log_error(ParserErrorType::ErrBuiltinArgumentsMissing, &Token::Operator(*operator));
+                return false;
+            };
+            if !ExpressionFactory::<'a>::type_check_numeric_operands(&argument, &argument) {
+                self.log_error_at(ParserErrorType::ErrTypeMismatch, argument.get_span());
+                return false;
+            }
+            Some(Box::new(argument))
+        } else {
+            None
+        };
+        self.m_expr_stack.push(NodeArithmeticExpr::Base(NodeBaseExpr::Builtin(NodeBuiltin { function, argument, span })));
+        true
+    }
+
+    /// The operator already spans the whole call and is put right after its arguments, which follow the name of the function.
+    fn create_call(&mut self, operator: &Operator, count: usize) -> bool{
+        let span = operator.get_span();
+        let callee = self.m_expr_stack.len().checked_sub(count + 1);
+        let function = callee.and_then(|callee| match &self.m_expr_stack[callee] {
+            NodeArithmeticExpr::Base(NodeBaseExpr::ID(function)) if function.get_span().m_start == span.m_start => Some(function.clone()),
+            _ => None,
+        });
+        let (Some(callee), Some(function)) = (callee, function) else {
+            self.log_error(ParserErrorType::ErrBuiltinArgumentsMissing, &Token::Operator(*operator));
+            return false;
+        };
+        let arguments = self.m_expr_stack.split_off(callee + 1);
+        self.m_expr_stack.pop();
+        self.m_expr_stack.push(NodeArithmeticExpr::Base(NodeBaseExpr::Call(NodeCall { function, arguments, span })));
+        true
+    }
+
+    /// The reverse polish notation puts the name of the field on top of the struct it is read from, which is either
+    /// a variable or the field of a nested struct.
+    fn create_field(&mut self, operator: &Operator) -> bool{
+        let name = self.m_expr_stack.pop();
+        let base = self.m_expr_stack.pop();
+        let field = match (base, name) {
+            (Some(NodeArithmeticExpr::Base(NodeBaseExpr::ID(variable))), Some(NodeArithmeticExpr::Base(NodeBaseExpr::ID(name)))) => {
+                NodeField { span: variable.get_span().merge(operator.get_span()), variable, fields: vec![name] }
+            }
+            (Some(NodeArithmeticExpr::Base(NodeBaseExpr::Field(mut field))), Some(NodeArithmeticExpr::Base(NodeBaseExpr::ID(name)))) => {
+                field.span = field.span.merge(operator.get_span());
+                field.fields.push(name);
+                field
+            }
+            _ => {
+                self.log_error(ParserErrorType::ErrMissingOperand, &Token::Operator(*operator));
+                return false;
+            }
+        };
+        self.m_expr_stack.push(NodeArithmeticExpr::Base(NodeBaseExpr::Field(field)));
+        true
+    }
+
+    /// Variables are accepted, as their type is only known once the program is analysed.
+    fn type_check_logical_operands(lhs_expr: &NodeArithmeticExpr, rhs_expr: &NodeArithmeticExpr) -> bool{
+        let is_numeric = |expr: &NodeArithmeticExpr| matches!(expr,
+            NodeArithmeticExpr::Base(NodeBaseExpr::Num(_)) | NodeArithmeticExpr::Operation(NodeArithmeticOperation{result_type: ResultType::Numeric, ..}));
+        !is_numeric(lhs_expr) && !is_numeric(rhs_expr)
+    }
+
+    /// Variables are accepted, as their type is only known once the program is analysed.
+    fn type_check_numeric_operands(lhs_expr: &NodeArithmeticExpr, rhs_expr: &NodeArithmeticExpr) -> bool{
+        let is_bool = |expr: &NodeArithmeticExpr| matches!(expr,
+            NodeArithmeticExpr::Base(NodeBaseExpr::Bool(_)) | NodeArithmeticExpr::Operation(NodeArithmeticOperation{result_type: ResultType::Boolean, ..}));
+        !is_bool(lhs_expr) && !is_bool(rhs_expr)
+    }
+
     fn log_error(&self, error: ParserErrorType, token: &Token){
         let mut logger = self.m_logger.lock().unwrap();
         logger.log_error(error, token);
     }
+
+    fn log_error_at(&self, error: ParserErrorType, span: Span){
+        let mut logger = self.m_logger.lock().unwrap();
+        logger.log_error_at(error, span);
+    }
 }
 
 
@@ -256,7 +444,7 @@ mod test_expression_factory{
         let dummy_span = Span::new(0, 0, 0);
         let logger = setup_logger();
         let mut token_stream = TokenStream::new(vec![
-            Token::ID { name: "x".to_string(), span: dummy_span },
+            Token::Number { value: 1.to_string(), span: dummy_span },
             Token::Operator(Operator::And { span: dummy_span }),
             Token::Boolean { value: true, span: dummy_span }
         ],
@@ -267,4 +455,175 @@ mod test_expression_factory{
         let result = factory.create();
         assert!(result.is_none());
     }
+
+    #[test]
+    fn test_unary_operation(){
+        let dummy_span = Span::new(0, 0, 0);
+        let logger = setup_logger();
+        let mut token_stream = TokenStream::new(vec![
+            Token::Operator(Operator::BitNot { span: dummy_span }),
+            Token::ID { name: "x".to_string(), span: dummy_span },
+            Token::Operator(Operator::BitAnd { span: dummy_span }),
+            Token::Number { value: 1.to_string(), span: dummy_span },
+        ],
+            logger.clone()
+        );
+        let mut factory = ExpressionFactory::new(&mut token_stream, logger);
+
+        let Some(Left(operation)) = factory.create() else { panic!("Expected an operation") };
+        assert!(matches!(operation.op, Operator::BitAnd { .. }));
+        assert!(matches!(operation.lhs, Left(ref not) if matches!(not.op, Operator::BitNot { .. }) && not.lhs == not.rhs));
+    }
+
+    #[test]
+    fn test_negation(){
+        let logger = setup_logger();
+        let mut token_stream = TokenStream::new(vec![
+            Token::Operator(Operator::Minus { span: Span::new(0, 0, 1) }),
+            Token::ID { name: "x".to_string(), span: Span::new(0, 1, 2) },
+            Token::Operator(Operator::Minus { span: Span::new(0, 2, 3) }),
+            Token::Operator(Operator::Minus { span: Span::new(0, 3, 4) }),
+            Token::Number { value: 1.to_string(), span: Span::new(0, 4, 5) },
+        ],
+            logger.clone()
+        );
+        let mut factory = ExpressionFactory::new(&mut token_stream, logger);
+
+        let Some(Left(operation)) = factory.create() else { panic!("Expected an operation") };
+        assert_eq!(operation.to_string(), "(-x) - -1");
+        assert!(matches!(operation.lhs, Left(ref negation) if matches!(negation.op, Operator::Negate { .. }) && negation.span == Span::new(0, 0, 2)));
+        assert_eq!(operation.rhs, Right(NodeBaseExpr::Num(Token::Number { value: "-1".to_string(), span: Span::new(0, 3, 5) })));
+    }
+
+    #[test]
+    fn test_cast(){
+        let logger = setup_logger();
+        // !!b as int + 1
+        let mut token_stream = TokenStream::new(vec![
+            Token::Operator(Operator::Not { span: Span::new(0, 0, 2) }),
+            Token::ID { name: "b".to_string(), span: Span::new(0, 2, 3) },
+            Token::Operator(Operator::Cast { span: Span::new(0, 4, 6) }),
+            Token::ID { name: "int".to_string(), span: Span::new(0, 7, 10) },
+            Token::Operator(Operator::Plus { span: Span::new(0, 11, 12) }),
+            Token::Number { value: 1.to_string(), span: Span::new(0, 13, 14) },
+        ],
+            logger.clone()
+        );
+        let mut factory = ExpressionFactory::new(&mut token_stream, logger);
+
+        let Some(Left(operation)) = factory.create() else { panic!("Expected an operation") };
+        assert!(matches!(operation.op, Operator::Plus { .. }));
+        let Left(ref cast) = operation.lhs else { panic!("Expected a cast") };
+        assert!(matches!(cast.op, Operator::Cast { .. }) && cast.lhs == cast.rhs);
+        assert_eq!(cast.result_type, ResultType::Numeric);
+        assert_eq!(cast.span, Span::new(0, 0, 10));
+        assert!(matches!(cast.lhs, Left(ref not) if matches!(not.op, Operator::Not { .. })));
+    }
+
+    #[test]
+    fn test_unknown_cast_type(){
+        let dummy_span = Span::new(0, 0, 0);
+        let logger = setup_logger();
+        let mut token_stream = TokenStream::new(vec![
+            Token::ID { name: "x".to_string(), span: dummy_span },
+            Token::Operator(Operator::Cast { span: dummy_span }),
+            Token::ID { name: "y".to_string(), span: dummy_span },
+        ],
+            logger.clone()
+        );
+        let mut factory = ExpressionFactory::new(&mut token_stream, logger.clone());
+
+        assert!(factory.create().is_none());
+        let exp_error = (ParserErrorType::ErrTypeMissing.message().to_string(), dummy_span);
+        assert_eq!(logger.lock().unwrap().get_errors(), &[exp_error]);
+    }
+
+    #[test]
+    fn test_wrong_bitwise_operation(){
+        let dummy_span = Span::new(0, 0, 0);
+        let logger = setup_logger();
+        let mut token_stream = TokenStream::new(vec![
+            Token::Boolean { value: true, span: dummy_span },
+            Token::Operator(Operator::ShiftLeft { span: dummy_span }),
+            Token::Number { value: 1.to_string(), span: dummy_span },
+        ],
+            logger.clone()
+        );
+        let mut factory = ExpressionFactory::new(&mut token_stream, logger.clone());
+
+        assert!(factory.create().is_none());
+        let exp_error = (ParserErrorType::ErrTypeMismatch.message().to_string(), dummy_span);
+        assert_eq!(logger.lock().unwrap().get_errors(), &[exp_error]);
+    }
+
+    #[test]
+    fn test_missing_operator(){
+        let logger = setup_logger();
+        let mut token_stream = TokenStream::new(vec![
+            Token::Number { value: 1.to_string(), span: Span::new(0, 0, 0) },
+            Token::WhiteSpace { span: Span::new(0, 1, 1) },
+            Token::Number { value: 2.to_string(), span: Span::new(0, 2, 2) },
+        ],
+            logger.clone()
+        );
+        let mut factory = ExpressionFactory::new(&mut token_stream, logger.clone());
+
+        let result = factory.create();
+        assert!(result.is_none());
+        let exp_error = (ParserErrorType::ErrMissingOperator.message().to_string(), Span::new(0, 2, 2));
+        assert_eq!(logger.lock().unwrap().get_errors(), &[exp_error]);
+    }
+
+    #[test]
+    fn test_integer_out_of_range(){
+        let logger = setup_logger();
+        let span = Span::new(0, 0, 19);
+        let mut token_stream = TokenStream::new(vec![
+            Token::Number { value: "9223372036854775808".to_string(), span },
+        ],
+            logger.clone()
+        );
+        let mut factory = ExpressionFactory::new(&mut token_stream, logger.clone());
+
+        assert!(factory.create().is_none());
+        let exp_error = (ParserErrorType::ErrIntegerOutOfRange.message().to_string(), span);
+        assert_eq!(logger.lock().unwrap().get_errors(), &[exp_error]);
+
+        let mut token_stream = TokenStream::new(vec![
+            Token::Number { value: i64::MAX.to_string(), span },
+        ],
+            logger.clone()
+        );
+        assert!(ExpressionFactory::new(&mut token_stream, logger.clone()).create().is_some());
+
+        // The smallest integer can only be written negated
+        let mut token_stream = TokenStream::new(vec![
+            Token::Operator(Operator::Minus { span: Span::new(0, 0, 1) }),
+            Token::Number { value: "9223372036854775808".to_string(), span: Span::new(0, 1, 20) },
+        ],
+            logger.clone()
+        );
+        let min = Token::Number { value: i64::MIN.to_string(), span: Span::new(0, 0, 20) };
+        assert_eq!(ExpressionFactory::new(&mut token_stream, logger).create(), Some(Right(NodeBaseExpr::Num(min))));
+    }
+
+    #[test]
+    fn test_field(){
+        let logger = setup_logger();
+        let mut token_stream = TokenStream::new(vec![
+            Token::ID { name: "l".to_string(), span: Span::new(0, 0, 1) },
+            Token::Operator(Operator::Field { span: Span::new(0, 1, 2) }),
+            Token::ID { name: "a".to_string(), span: Span::new(0, 2, 3) },
+            Token::Operator(Operator::Field { span: Span::new(0, 3, 4) }),
+            Token::ID { name: "x".to_string(), span: Span::new(0, 4, 5) },
+        ],
+            logger.clone()
+        );
+        let mut factory = ExpressionFactory::new(&mut token_stream, logger);
+        let Some(Right(NodeBaseExpr::Field(field))) = factory.create() else {
+            panic!("Expected a field");
+        };
+        assert_eq!(field.to_string(), "l.a.x");
+        assert_eq!(field.span, Span::new(0, 0, 5));
+    }
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 use std::sync::{Arc, Mutex};
 use crate::compiler::parser::{ParserErrorType, ParserLogger};
 use crate::compiler::parser::token_stream::TokenStream;
+use crate::compiler::span::Span;
 use crate::compiler::tokenizer::{Token, Operator};
 
 pub struct ReversePolishNotation<'a>{
@@ -8,25 +9,65 @@ pub struct ReversePolishNotation<'a>{
     m_logger: Arc<Mutex<ParserLogger>>,
     m_stack: Vec<Operator>,
     m_polish: Vec<Token>,
+    /// Whether the next token starts an operand, in which case a minus negates it instead of subtracting.
+    m_expects_operand: bool,
 }
 
 impl<'a> ReversePolishNotation<'a>{
     pub fn new(line: &'a mut TokenStream, m_logger: Arc<Mutex<ParserLogger>>) -> ReversePolishNotation<'a>{
-        ReversePolishNotation{m_line_stream: line, m_logger, m_stack: vec![], m_polish: vec![]}
+        ReversePolishNotation{m_line_stream: line, m_logger, m_stack: vec![], m_polish: vec![], m_expects_operand: true}
     }
 
     pub fn create(&mut self) -> Option<Vec<Token>>{
         while let Some(token) = self.m_line_stream.peek(0) {
             match token{
-                Token::ID { .. } | Token::Number { .. } | Token::Boolean { .. } => {
+                // A name followed by an opening bracket is a call, whose arguments come next
+                Token::ID { span, .. } if matches!(self.m_line_stream.peek_range(2, true).as_deref(), Some([_, Token::Operator(Operator::OpenBracket { .. })])) => {
+                    self.handle_call(token.clone(), span);
+                },
+                Token::ID { .. } | Token::Number { .. } | Token::Boolean { .. } | Token::StringLiteral { .. } => {
                     self.m_polish.push(token.clone());
                 },
+                Token::Operator(Operator::Len { span }) => {
+                    if !self.handle_len(span){
+                        return None;
+                    }
+                },
+                Token::Operator(Operator::Field { span }) => {
+                    if !self.handle_field(span){
+                        return None;
+                    }
+                },
+                Token::Operator(Operator::Minus { span }) if self.m_expects_operand => {
+                    if !self.handle_operators(Operator::Negate { span }){
+                        return None;
+                    }
+                },
+                Token::Operator(op @ (Operator::Alloc { .. } | Operator::Builtin { .. })) => {
+                    if !self.handle_function(op){
+                        return None;
+                    }
+                },
                 Token::Operator(op) => {
                     if !self.handle_operators(op.clone()){
                         return None;
                     }
                 },
-                Token::NewLine {..} | Token::ClosedBracket {..} => {
+                // The index is held on the stack like an opening bracket, after the array it is applied to
+                Token::OpenSquareBracket { span } if matches!(self.m_line_stream.peek_back(1), Some(Token::ID { .. })) => {
+                    self.m_stack.push(Operator::Index { span });
+                },
+                // Otherwise the square bracket closes the array literal this expression is an element of
+                Token::ClosedSquareBracket { span } if self.m_stack.iter().any(|op| matches!(op, Operator::Index { .. })) => {
+                    if !self.handle_closed_square_bracket(span){
+                        return None;
+                    }
+                },
+                Token::Comma { .. } if matches!(self.get_innermost_bracket(), Some(Operator::Call { .. })) => {
+                    self.handle_argument_separator();
+                },
+                Token::NewLine {..} | Token::ClosedBracket {..} | Token::ClosedSquareBracket {..} | Token::ClosedCurlyBracket {..}
+                | Token::Comma {..} | Token::Semicolon {..} | Token::DotDot {..} | Token::DotDotEquals {..} | Token::Step {..} => {
                     break;
                 }
                 _ => {
@@ -34,6 +75,9 @@ impl<'a> ReversePolishNotation<'a>{
                     return None;
                 },
             }
+            // The handlers stop on the last token they read, which ends an operand or is followed by one
+            self.m_expects_operand = !matches!(self.m_line_stream.peek(0), Some(Token::ID { .. } | Token::Number { .. } | Token::Boolean { .. }
+                | Token::StringLiteral { .. } | Token::ClosedBracket { .. } | Token::ClosedSquareBracket { .. } | Token::Operator(Operator::ClosedBracket { .. })));
             self.m_line_stream.advance_skip_tokens(1, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
         }
         while let Some(i) = self.m_stack.pop(){
@@ -41,6 +85,14 @@ impl<'a> ReversePolishNotation<'a>{
                 self.log_error(ParserErrorType::ErrExpressionClosedBracketMissing, &Token::OpenBracket { span });
                 return None;
             }
+            if let Operator::Index { span } = i{
+                self.log_error(ParserErrorType::ErrSquareBracketMissing, &Token::OpenSquareBracket { span });
+                return None;
+            }
+            if let Operator::Alloc { .. } | Operator::Builtin { .. } | Operator::Call { .. } = i{
+                self.log_error(ParserErrorType::ErrExpressionClosedBracketMissing, &Token::Operator(i));
+                return None;
+            }
             self.m_polish.push(Token::Operator(i));
         }
         Some(self.m_polish.clone())
@@ -51,20 +103,32 @@ impl<'a> ReversePolishNotation<'a>{
             Operator::OpenBracket { .. } => {
                 self.m_stack.push(rhs_op);
             }
-            Operator::ClosedBracket { .. } => {
-                while !matches!(self.m_stack.last(), Some(Operator::OpenBracket {..})) {
-                    if self.m_stack.is_empty() {
+            Operator::ClosedBracket { span } => {
+                while !matches!(self.m_stack.last(), Some(Operator::OpenBracket {..} | Operator::Alloc {..} | Operator::Builtin {..} | Operator::Call {..})) {
+                    if matches!(self.m_stack.last(), None | Some(Operator::Index {..})) {
                         self.log_error(ParserErrorType::ErrExpressionOpenBracketMissing, &Token::Operator(rhs_op));
                         return false;
                     }
                     let op = self.m_stack.pop().unwrap();
                     self.m_polish.push(Token::Operator(op))
                 }
-                self.m_stack.pop();
+                // `alloc`, the builtins and the calls are output once their arguments are complete, spanning up to the closing bracket
+                match self.m_stack.pop() {
+                    Some(Operator::Alloc { span: alloc_span }) => {
+                        self.m_polish.push(Token::Operator(Operator::Alloc { span: alloc_span.merge(span) }));
+                    }
+                    Some(Operator::Builtin { span: builtin_span, function }) => {
+                        self.m_polish.push(Token::Operator(Operator::Builtin { span: builtin_span.merge(span), function }));
+                    }
+                    Some(Operator::Call { span: call_span, arguments }) => {
+                        self.m_polish.push(Token::Operator(Operator::Call { span: call_span.merge(span), arguments }));
+                    }
+                    _ => {}
+                }
             }
             _ => {
                 while let Some(lhs_op) = self.m_stack.pop() {
-                    let lhs_is_open_bracket = matches!(lhs_op, Operator::OpenBracket {..});
+                    let lhs_is_open_bracket = Self::is_open_bracket(lhs_op);
                     let lhs_leq_precedence = lhs_op.precedence() <= rhs_op.precedence();
                     let not_eq_precedence = lhs_op.precedence() != rhs_op.precedence();
                     let rhs_right_associative = rhs_op.associativity().eq("Right");
@@ -83,6 +147,109 @@ impl<'a> ReversePolishNotation<'a>{
         true
     }
 
+    /// Moves the operators applied to the index to the output, followed by the index operator, which spans from
+    /// the opening to the closing square bracket.
+    fn handle_closed_square_bracket(&mut self, span: Span) -> bool{
+        while let Some(op) = self.m_stack.pop() {
+            match op {
+                Operator::Index { span: open_span } => {
+                    self.m_polish.push(Token::Operator(Operator::Index { span: open_span.merge(span) }));
+                    return true;
+                }
+                Operator::OpenBracket { span } | Operator::Alloc { span } | Operator::Builtin { span, .. } | Operator::Call { span, .. } => {
+                    self.log_error(ParserErrorType::ErrExpressionClosedBracketMissing, &Token::OpenBracket { span });
+                    return false;
+                }
+                _ => self.m_polish.push(Token::Operator(op))
+            }
+        }
+        true
+    }
+
+    /// `len` is always followed by the name of an array between brackets, so it is output right after it, spanning
+    /// up to the closing bracket.
+    fn handle_len(&mut self, span: Span) -> bool{
+        match self.m_line_stream.peek_range(4, true).as_deref() {
+            Some([_, Token::Operator(Operator::OpenBracket { .. }), variable @ Token::ID { .. }, Token::Operator(Operator::ClosedBracket { span: end })]) => {
+                self.m_polish.push(variable.clone());
+                self.m_polish.push(Token::Operator(Operator::Len { span: span.merge(*end) }));
+                self.m_line_stream.advance_skip_tokens(3, false, |token| matches!(token, Some(Token::WhiteSpace {..})));
+                true
+            }
+            _ => {
+                self.log_error(ParserErrorType::ErrArrayMissing, &Token::Operator(Operator::Len { span }));
+                false
+            }
+        }
+    }
+
+    /// `alloc` and the builtins are always followed by their arguments between brackets, so they are held on the stack
+    /// in place of the opening bracket.
+    fn handle_function(&mut self, function: Operator) -> bool{
+        match self.m_line_stream.peek_range(2, true).as_deref() {
+            Some([_, Token::Operator(Operator::OpenBracket { .. })]) => {
+                self.m_stack.push(function);
+                self.m_line_stream.advance_skip_tokens(1, false, |token| matches!(token, Some(Token::WhiteSpace {..})));
+                true
+            }
+            _ => {
+                let error = if let Operator::Alloc { .. } = function {
+                    ParserErrorType::ErrAllocSizeMissing
+                } else {
+                    ParserErrorType::ErrBuiltinArgumentsMissing
+                };
+                self.log_error(error, &Token::Operator(function));
+                false
+            }
+        }
+    }
+
+    /// The name of the function is output first, and the call is held on the stack in place of the opening bracket,
+    /// counting the arguments read so far.
+    fn handle_call(&mut self, function: Token, span: Span){
+        let empty = matches!(self.m_line_stream.peek_range(3, true).as_deref(), Some([_, _, Token::Operator(Operator::ClosedBracket { .. })]));
+        self.m_polish.push(function);
+        self.m_stack.push(Operator::Call { span, arguments: usize::from(!empty) });
+        self.m_line_stream.advance_skip_tokens(1, false, |token| matches!(token, Some(Token::WhiteSpace {..})));
+    }
+
+    /// Moves the operators of the argument before the comma to the output, and counts the next argument of the call.
+    fn handle_argument_separator(&mut self){
+        while let Some(op) = self.m_stack.pop() {
+            if let Operator::Call { span, arguments } = op {
+                self.m_stack.push(Operator::Call { span, arguments: arguments + 1 });
+                return;
+            }
+            self.m_polish.push(Token::Operator(op));
+        }
+    }
+
+    /// Innermost operator held on the stack like an opening bracket, which a comma belongs to.
+    fn get_innermost_bracket(&self) -> Option<Operator> {
+        self.m_stack.iter().rev().copied().find(|op| Self::is_open_bracket(*op))
+    }
+
+    fn is_open_bracket(op: Operator) -> bool {
+        matches!(op, Operator::OpenBracket {..} | Operator::Index {..} | Operator::Alloc {..} | Operator::Builtin {..} | Operator::Call {..})
+    }
+
+    /// The name of a field is output right after the struct it is read from, followed by the field operator, which
+    /// spans from the dot to the name.
+    fn handle_field(&mut self, span: Span) -> bool{
+        match self.m_line_stream.peek_range(2, true).as_deref() {
+            Some([_, field @ Token::ID { span: end, .. }]) => {
+                self.m_polish.push(field.clone());
+                self.m_polish.push(Token::Operator(Operator::Field { span: span.merge(*end) }));
+                self.m_line_stream.advance_skip_tokens(1, false, |token| matches!(token, Some(Token::WhiteSpace {..})));
+                true
+            }
+            _ => {
+                self.log_error(ParserErrorType::ErrFieldMissing, &Token::Operator(Operator::Field { span }));
+                false
+            }
+        }
+    }
+
     fn log_error(&self, error: ParserErrorType, token: &Token){
         let mut logger = self.m_logger.lock().unwrap();
         logger.log_error(error, token);
@@ -233,4 +400,124 @@ mod test_reverse_polish_notation{
         let polish = ReversePolishNotation::new(&mut token_stream, logger).create();
         assert!(polish.is_none());
     }
+    #[test]
+    fn test_index_and_len(){
+        // a[i + 1] * len(b)
+        let expr = vec![
+            Token::ID { name: "a".to_string(), span: Span::new(0, 0, 1) },
+            Token::OpenSquareBracket { span: Span::new(0, 1, 2) },
+            Token::ID { name: "i".to_string(), span: Span::new(0, 2, 3) },
+            Token::Operator(Operator::Plus { span: Span::new(0, 4, 5) }),
+            Token::Number { value: "1".to_string(), span: Span::new(0, 6, 7) },
+            Token::ClosedSquareBracket { span: Span::new(0, 7, 8) },
+            Token::Operator(Operator::Multiplication { span: Span::new(0, 9, 10) }),
+            Token::Operator(Operator::Len { span: Span::new(0, 11, 14) }),
+            Token::Operator(Operator::OpenBracket { span: Span::new(0, 14, 15) }),
+            Token::ID { name: "b".to_string(), span: Span::new(0, 15, 16) },
+            Token::Operator(Operator::ClosedBracket { span: Span::new(0, 16, 17) }),
+        ];
+        let exp_notation = vec![
+            Token::ID { name: "a".to_string(), span: Span::new(0, 0, 1) },
+            Token::ID { name: "i".to_string(), span: Span::new(0, 2, 3) },
+            Token::Number { value: "1".to_string(), span: Span::new(0, 6, 7) },
+            Token::Operator(Operator::Plus { span: Span::new(0, 4, 5) }),
+            Token::Operator(Operator::Index { span: Span::new(0, 1, 8) }),
+            Token::ID { name: "b".to_string(), span: Span::new(0, 15, 16) },
+            Token::Operator(Operator::Len { span: Span::new(0, 11, 17) }),
+            Token::Operator(Operator::Multiplication { span: Span::new(0, 9, 10) }),
+        ];
+        let logger = Arc::new(Mutex::new(ParserLogger::new("".to_string(), "".to_string())));
+        let mut token_stream = TokenStream::new(expr, logger.clone());
+        let polish = ReversePolishNotation::new(&mut token_stream, logger).create();
+        assert_eq!(polish, Some(exp_notation));
+    }
+
+    #[test]
+    fn test_unclosed_index(){
+        let dummy_span = Span::new(0, 0, 0);
+        let expr = vec![
+            Token::ID { name: "a".to_string(), span: dummy_span },
+            Token::OpenSquareBracket { span: Span::new(0, 1, 2) },
+            Token::Number { value: "1".to_string(), span: dummy_span },
+        ];
+        let logger = Arc::new(Mutex::new(ParserLogger::new("".to_string(), "".to_string())));
+        let mut token_stream = TokenStream::new(expr, logger.clone());
+        let polish = ReversePolishNotation::new(&mut token_stream, logger.clone()).create();
+        assert!(polish.is_none());
+        let exp_error = (ParserErrorType::ErrSquareBracketMissing.message().to_string(), Span::new(0, 1, 2));
+        assert_eq!(logger.lock().unwrap().get_errors(), &[exp_error]);
+    }
+
+    #[test]
+    fn test_field(){
+        // l.a.x * 2
+        let expr = vec![
+            Token::ID { name: "l".to_string(), span: Span::new(0, 0, 1) },
+            Token::Operator(Operator::Field { span: Span::new(0, 1, 2) }),
+            Token::ID { name: "a".to_string(), span: Span::new(0, 2, 3) },
+            Token::Operator(Operator::Field { span: Span::new(0, 3, 4) }),
+            Token::ID { name: "x".to_string(), span: Span::new(0, 4, 5) },
+            Token::Operator(Operator::Multiplication { span: Span::new(0, 6, 7) }),
+            Token::Number { value: "2".to_string(), span: Span::new(0, 8, 9) },
+        ];
+        let exp_notation = vec![
+            Token::ID { name: "l".to_string(), span: Span::new(0, 0, 1) },
+            Token::ID { name: "a".to_string(), span: Span::new(0, 2, 3) },
+            Token::Operator(Operator::Field { span: Span::new(0, 1, 3) }),
+            Token::ID { name: "x".to_string(), span: Span::new(0, 4, 5) },
+            Token::Operator(Operator::Field { span: Span::new(0, 3, 5) }),
+            Token::Number { value: "2".to_string(), span: Span::new(0, 8, 9) },
+            Token::Operator(Operator::Multiplication { span: Span::new(0, 6, 7) }),
+        ];
+        let logger = Arc::new(Mutex::new(ParserLogger::new("".to_string(), "".to_string())));
+        let mut token_stream = TokenStream::new(expr, logger.clone());
+        let polish = ReversePolishNotation::new(&mut token_stream, logger).create();
+        assert_eq!(polish, Some(exp_notation));
+
+        let expr = vec![
+            Token::ID { name: "p".to_string(), span: Span::new(0, 0, 1) },
+            Token::Operator(Operator::Field { span: Span::new(0, 1, 2) }),
+            Token::Number { value: "1".to_string(), span: Span::new(0, 2, 3) },
+        ];
+        let logger = Arc::new(Mutex::new(ParserLogger::new("".to_string(), "".to_string())));
+        let mut token_stream = TokenStream::new(expr, logger.clone());
+        assert!(ReversePolishNotation::new(&mut token_stream, logger.clone()).create().is_none());
+        let exp_error = (ParserErrorType::ErrFieldMissing.message().to_string(), Span::new(0, 1, 2));
+        assert_eq!(logger.lock().unwrap().get_errors(), &[exp_error]);
+    }
+
+    #[test]
+    fn test_call(){
+        // f(x + 1, g()) * 2
+        let expr = vec![
+            Token::ID { name: "f".to_string(), span: Span::new(0, 0, 1) },
+            Token::Operator(Operator::OpenBracket { span: Span::new(0, 1, 2) }),
+            Token::ID { name: "x".to_string(), span: Span::new(0, 2, 3) },
+            Token::Operator(Operator::Plus { span: Span::new(0, 4, 5) }),
+            Token::Number { value: "1".to_string(), span: Span::new(0, 6, 7) },
+            Token::Comma { span: Span::new(0, 7, 8) },
+            Token::WhiteSpace { span: Span::new(0, 8, 9) },
+            Token::ID { name: "g".to_string(), span: Span::new(0, 9, 10) },
+            Token::Operator(Operator::OpenBracket { span: Span::new(0, 10, 11) }),
+            Token::Operator(Operator::ClosedBracket { span: Span::new(0, 11, 12) }),
+            Token::Operator(Operator::ClosedBracket { span: Span::new(0, 12, 13) }),
+            Token::Operator(Operator::Multiplication { span: Span::new(0, 14, 15) }),
+            Token::Number { value: "2".to_string(), span: Span::new(0, 16, 17) },
+        ];
+        let exp_notation = vec![
+            Token::ID { name: "f".to_string(), span: Span::new(0, 0, 1) },
+            Token::ID { name: "x".to_string(), span: Span::new(0, 2, 3) },
+            Token::Number { value: "1".to_string(), span: Span::new(0, 6, 7) },
+            Token::Operator(Operator::Plus { span: Span::new(0, 4, 5) }),
+            Token::ID { name: "g".to_string(), span: Span::new(0, 9, 10) },
+            Token::Operator(Operator::Call { span: Span::new(0, 9, 12), arguments: 0 }),
+            Token::Operator(Operator::Call { span: Span::new(0, 0, 13), arguments: 2 }),
+            Token::Number { value: "2".to_string(), span: Span::new(0, 16, 17) },
+            Token::Operator(Operator::Multiplication { span: Span::new(0, 14, 15) }),
+        ];
+        let logger = Arc::new(Mutex::new(ParserLogger::new("".to_string(), "".to_string())));
+        let mut token_stream = TokenStream::new(expr, logger.clone());
+        let polish = ReversePolishNotation::new(&mut token_stream, logger).create();
+        assert_eq!(polish, Some(exp_notation));
+    }
 }
\ No newline at end of file
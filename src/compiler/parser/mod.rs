@@ -4,23 +4,46 @@ mod parser_logger;
 mod token_stream;
 mod expression_factory;
 mod statement_factory;
+mod ast_format;
 
 pub use nodes::{
     NodeProgram,
     NodeStmt,
     NodeVariableAssignment,
+    NodeIndexAssignment,
+    NodeStruct,
+    NodeFieldDeclaration,
+    NodeFieldAssignment,
+    NodeEnum,
+    NodeVariant,
+    Declaration,
+    TypeAnnotation,
     NodeExit,
+    NodeImport,
     NodeArithmeticExpr,
     NodeBaseExpr,
+    NodeIndex,
+    NodeLen,
+    NodeAlloc,
+    NodeBuiltin,
+    NodeFree,
+    NodeExtern,
+    NodeCall,
+    NodeArrayLiteral,
+    NodeStructLiteral,
+    NodeField,
+    NodeVariantLiteral,
+    NodeMatch,
+    NodeMatchArm,
+    NodeFor,
     NodeArithmeticOperation,
-    NodeScope
-};
-
-#[cfg(test)]
-pub use nodes::{
+    NodeScope,
     ResultType
 };
 
+/// Operand of a `NodeArithmeticOperation`, either a nested operation or a base expression.
+pub use either::Either;
+
 pub use parser::{
     Parser,
 };
@@ -32,4 +55,11 @@ pub use parser_logger::{
 
 pub use expression_factory::{
     ExpressionFactory
+};
+
+pub use ast_format::{
+    AstJson,
+    AstSexpr,
+    AstPlantUml,
+    AstDot
 };
\ No newline at end of file
@@ -1,63 +1,246 @@
 use std::fmt;
 use std::fmt::{Formatter};
 use either::{Either, Left};
-use crate::compiler::Token;
-use crate::compiler::tokenizer::Operator;
+use crate::compiler::span::Span;
+use crate::compiler::tokenizer::Token;
+use crate::compiler::tokenizer::{Builtin, Operator};
 
 #[derive(Debug, PartialEq)]
 pub struct NodeProgram{
-    pub(crate) stmts: Vec<NodeStmt>
+    pub stmts: Vec<NodeStmt>,
+    pub span: Span
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum NodeStmt {
     Exit(NodeExit),
     ID(NodeVariableAssignment),
-    Scope(NodeScope)
+    Scope(NodeScope),
+    Import(NodeImport),
+    Index(NodeIndexAssignment),
+    Struct(NodeStruct),
+    Field(NodeFieldAssignment),
+    Enum(NodeEnum),
+    For(Box<NodeFor>),
+    Free(NodeFree),
+    Extern(NodeExtern),
+    Call(NodeCall)
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct NodeExit {
-    pub(crate) expr: NodeArithmeticExpr
+    pub expr: NodeArithmeticExpr,
+    pub span: Span
+}
+
+/// Release of the memory returned by `alloc`, like `free(p)`. Freeing 0 does nothing.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeFree {
+    pub pointer: NodeArithmeticExpr,
+    pub span: Span
+}
+
+/// Declaration of a C function, like `extern fn printf(int, ...)`. Its parameters are `int` or `bool` and it returns
+/// an `int`. After `...`, it takes any number of further arguments.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeExtern {
+    pub name: Token,
+    pub parameters: Vec<Token>,
+    pub variadic: bool,
+    pub span: Span
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct NodeVariableAssignment {
+    pub declaration: Option<Declaration>,
     pub variable: Token,
+    pub annotation: Option<TypeAnnotation>,
     pub value: NodeArithmeticExpr
 }
 
+/// Keyword declaring a new variable. An assignment without one overwrites a `var` declared before it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Declaration {
+    Let { span: Span },
+    Var { span: Span },
+    Const { span: Span }
+}
+
+/// Type written after the name of a variable, like `int` in `let x: int = 5`.
+/// The type of an array, like `[int; 8]`, is the type of its elements with their number.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TypeAnnotation {
+    pub result_type: ResultType,
+    pub length: Option<usize>,
+    pub span: Span
+}
+
+/// Assignment to an element of an array, like `a[i] = 5`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeIndexAssignment {
+    pub element: NodeIndex,
+    pub value: NodeArithmeticExpr
+}
+
+/// Declaration of a struct type, like `struct Point { x: int, y: int }`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeStruct {
+    pub name: Token,
+    pub fields: Vec<NodeFieldDeclaration>,
+    pub span: Span
+}
+
+/// Field of a struct declaration, whose type is `int`, `bool` or the name of a struct declared before.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeFieldDeclaration {
+    pub name: Token,
+    pub type_name: Token
+}
+
+/// Declaration of an enum type, like `enum Shape { Circle(int), Square(int), Empty }`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeEnum {
+    pub name: Token,
+    pub variants: Vec<NodeVariant>,
+    pub span: Span
+}
+
+/// Variant of an enum declaration, with the types of the values it holds, `int` or `bool`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeVariant {
+    pub name: Token,
+    pub types: Vec<Token>
+}
+
+/// Assignment to a field of a struct, like `p.x = 5`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeFieldAssignment {
+    pub field: NodeField,
+    pub value: NodeArithmeticExpr
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeImport {
+    pub path: String,
+    pub span: Span
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct NodeScope {
-    pub stmts: Vec<NodeStmt>
+    pub stmts: Vec<NodeStmt>,
+    pub span: Span
 }
+
+/// Loop over a range of integers, like `for i in 0..n { ... }`, or `for i in 0..=n step 2 { ... }` when the range
+/// includes its end. The bounds are evaluated once, and the loop variable only exists in the body.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeFor {
+    /// Name of the loop variable, or `_` when the body doesn't read it.
+    pub variable: Token,
+    pub start: NodeArithmeticExpr,
+    pub end: NodeArithmeticExpr,
+    pub inclusive: bool,
+    /// Positive integer literal added to the loop variable after every iteration, 1 when there is none.
+    pub step: Option<Token>,
+    pub body: NodeScope,
+    pub span: Span
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum NodeArithmeticExpr {
     Base(NodeBaseExpr),
-    Operation(NodeArithmeticOperation)
+    Operation(NodeArithmeticOperation),
+    /// Only found as the value of an assignment, as arrays can't be operands.
+    Array(NodeArrayLiteral),
+    /// Only found as the value of an assignment or of a field of another struct literal.
+    Struct(NodeStructLiteral),
+    /// Only found as the value of an assignment, as enums can't be operands.
+    Variant(NodeVariantLiteral),
+    /// Only found as the value of an assignment or as an exit code.
+    Match(NodeMatch)
 }
 
+/// Elements of a new array, like `[1, 2, 3]`, or `[0; 8]` where a single element is repeated `length` times.
 #[derive(Clone, Debug, PartialEq)]
-pub struct NodeArithmeticOperation {
-    pub(crate) lhs: Either<Box<NodeArithmeticOperation>, NodeBaseExpr>,
-    pub(crate) rhs: Either<Box<NodeArithmeticOperation>, NodeBaseExpr>,
-    pub(crate) op: Operator,
-    pub(crate) result_type: ResultType
+pub struct NodeArrayLiteral {
+    pub elements: Vec<NodeArithmeticExpr>,
+    pub length: usize,
+    pub span: Span
+}
+
+/// New value of a struct, like `Point { x: 1, y: 2 }`, whose fields can be given in any order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeStructLiteral {
+    pub name: Token,
+    pub fields: Vec<(Token, NodeArithmeticExpr)>,
+    pub span: Span
+}
+
+/// New value of an enum, like `Shape::Circle(2)` or `Shape::Empty`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeVariantLiteral {
+    pub enum_name: Token,
+    pub variant: Token,
+    pub values: Vec<NodeArithmeticExpr>,
+    pub span: Span
+}
+
+/// Value of the first arm matching the variant of an enum variable, like `match s { Circle(r) => r * r, _ => 0 }`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeMatch {
+    pub variable: Token,
+    pub arms: Vec<NodeMatchArm>,
+    pub span: Span
 }
 
+/// Arm of a match, whose variant is `_` for the arm matching every variant. The bindings name the values of the variant.
 #[derive(Clone, Debug, PartialEq)]
+pub struct NodeMatchArm {
+    pub variant: Token,
+    pub bindings: Vec<Token>,
+    pub value: NodeArithmeticExpr
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeArithmeticOperation {
+    pub lhs: Either<Box<NodeArithmeticOperation>, NodeBaseExpr>,
+    pub rhs: Either<Box<NodeArithmeticOperation>, NodeBaseExpr>,
+    pub op: Operator,
+    pub result_type: ResultType,
+    pub span: Span
+}
+
+/// Type of a value, shared by the parser, the checks of the linter and the generator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ResultType{
     Numeric,
     Boolean
 }
 
 impl ResultType {
+    /// Name of the type in the annotations and the casts.
     pub fn as_str(&self) -> &str{
         match self {
-            ResultType::Numeric => {"num"}
+            ResultType::Numeric => {"int"}
             ResultType::Boolean => {"bool"}
         }
     }
+
+    pub fn from_name(name: &str) -> Option<ResultType> {
+        match name {
+            "int" => Some(ResultType::Numeric),
+            "bool" => Some(ResultType::Boolean),
+            _ => None
+        }
+    }
+
+    /// Type of the result of an operation using `op`. The type of a cast is its target, which the operator doesn't hold.
+    pub fn from_operator(op: &Operator) -> ResultType {
+        match op {
+            Operator::And { .. } | Operator::Or { .. } | Operator::Xor { .. } | Operator::Not { .. } => ResultType::Boolean,
+            _ => ResultType::Numeric
+        }
+    }
 }
 
 
@@ -66,6 +249,60 @@ pub enum NodeBaseExpr {
     Num(Token),
     ID(Token),
     Bool(Token),
+    Index(NodeIndex),
+    Len(NodeLen),
+    Field(NodeField),
+    Alloc(NodeAlloc),
+    Builtin(NodeBuiltin),
+    Call(NodeCall),
+    /// String literal, whose value is the address of its bytes, followed by a zero byte.
+    Str(Token),
+}
+
+/// Element of an array, like `a[i]`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeIndex {
+    pub variable: Token,
+    pub index: Box<NodeArithmeticExpr>,
+    pub span: Span
+}
+
+/// Number of elements of an array, like `len(a)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeLen {
+    pub variable: Token,
+    pub span: Span
+}
+
+/// Address of `size` bytes allocated on the heap, like `alloc(n)`. The memory is released by `free`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeAlloc {
+    pub size: Box<NodeArithmeticExpr>,
+    pub span: Span
+}
+
+/// Call of a function of the runtime, like `arg(1)` or `read_int()`. Its only argument, when it takes one, is an integer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeBuiltin {
+    pub function: Builtin,
+    pub argument: Option<Box<NodeArithmeticExpr>>,
+    pub span: Span
+}
+
+/// Call of a function declared with `extern`, like `printf("%d\n", x)`, which returns its result as an integer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeCall {
+    pub function: Token,
+    pub arguments: Vec<NodeArithmeticExpr>,
+    pub span: Span
+}
+
+/// Field of a struct variable, like `p.x`, or `l.start.x` for a field of a nested struct.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeField {
+    pub variable: Token,
+    pub fields: Vec<Token>,
+    pub span: Span
 }
 
 impl NodeProgram{
@@ -74,10 +311,148 @@ impl NodeProgram{
     }
 }
 
+impl NodeStmt {
+    pub fn get_span(&self) -> Span {
+        match self {
+            NodeStmt::Exit(exit) => exit.get_span(),
+            NodeStmt::ID(var_assign) => var_assign.get_span(),
+            NodeStmt::Scope(scope) => scope.get_span(),
+            NodeStmt::Import(import) => import.span,
+            NodeStmt::Index(assignment) => assignment.get_span(),
+            NodeStmt::Struct(declaration) => declaration.span,
+            NodeStmt::Field(assignment) => assignment.field.span.merge(assignment.value.get_span()),
+            NodeStmt::Enum(declaration) => declaration.span,
+            NodeStmt::For(node) => node.span,
+            NodeStmt::Free(free) => free.span,
+            NodeStmt::Extern(declaration) => declaration.span,
+            NodeStmt::Call(call) => call.span,
+        }
+    }
+}
+
+impl NodeExit {
+    /// Span from the `exit` keyword to the closing parenthesis.
+    pub fn get_span(&self) -> Span {
+        self.span
+    }
+}
+
+impl NodeVariableAssignment {
+    /// Span from the declaration keyword, or the variable when there is none, to the end of the value.
+    pub fn get_span(&self) -> Span {
+        let start = self.declaration.map_or(self.variable.get_span(), |declaration| declaration.get_span());
+        start.merge(self.value.get_span())
+    }
+}
+
+impl NodeIndexAssignment {
+    pub fn get_span(&self) -> Span {
+        self.element.span.merge(self.value.get_span())
+    }
+}
+
+impl Declaration {
+    pub fn get_span(&self) -> Span {
+        match self {
+            Declaration::Let { span } | Declaration::Var { span } | Declaration::Const { span } => *span,
+        }
+    }
+
+    pub fn is_mutable(&self) -> bool {
+        matches!(self, Declaration::Var { .. })
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Declaration::Let { .. } => "let",
+            Declaration::Var { .. } => "var",
+            Declaration::Const { .. } => "const",
+        }
+    }
+
+    pub fn from_name(name: &str, span: Span) -> Option<Declaration> {
+        match name {
+            "let" => Some(Declaration::Let { span }),
+            "var" => Some(Declaration::Var { span }),
+            "const" => Some(Declaration::Const { span }),
+            _ => None
+        }
+    }
+}
+
+impl NodeScope {
+    /// Span from the opening to the closing curly bracket.
+    pub fn get_span(&self) -> Span {
+        self.span
+    }
+}
+
+impl NodeFor {
+    /// Value added to the loop variable after every iteration.
+    pub fn get_step(&self) -> i64 {
+        match &self.step {
+            Some(Token::Number { value, .. }) => value.parse().unwrap_or(1),
+            _ => 1
+        }
+    }
+}
+
+impl NodeArithmeticExpr {
+    pub fn get_span(&self) -> Span {
+        match self {
+            NodeArithmeticExpr::Base(base) => base.get_span(),
+            NodeArithmeticExpr::Operation(operation) => operation.get_span(),
+            NodeArithmeticExpr::Array(array) => array.span,
+            NodeArithmeticExpr::Struct(literal) => literal.span,
+            NodeArithmeticExpr::Variant(literal) => literal.span,
+            NodeArithmeticExpr::Match(node) => node.span,
+        }
+    }
+}
+
+impl NodeArithmeticOperation {
+    pub fn get_span(&self) -> Span {
+        self.span
+    }
+}
+
+impl NodeBaseExpr {
+    pub fn get_span(&self) -> Span {
+        match self {
+            NodeBaseExpr::Num(token) | NodeBaseExpr::ID(token) | NodeBaseExpr::Bool(token) | NodeBaseExpr::Str(token) => token.get_span(),
+            NodeBaseExpr::Index(NodeIndex { span, .. }) | NodeBaseExpr::Len(NodeLen { span, .. }) | NodeBaseExpr::Field(NodeField { span, .. })
+            | NodeBaseExpr::Alloc(NodeAlloc { span, .. }) | NodeBaseExpr::Builtin(NodeBuiltin { span, .. }) | NodeBaseExpr::Call(NodeCall { span, .. }) => *span,
+        }
+    }
+}
+
+impl NodeArrayLiteral {
+    /// Longest array, whose elements are all on the stack.
+    pub const MAX_LENGTH: usize = 65536;
+}
+
+impl NodeField {
+    /// Names of the fields after the variable, like `start.x` in `l.start.x`.
+    pub fn get_path(&self) -> String {
+        let names: Vec<&str> = self.fields.iter().map(|field| match field {
+            Token::ID { name, .. } => name.as_str(),
+            _ => "",
+        }).collect();
+        names.join(".")
+    }
+}
+
 impl fmt::Display for NodeVariableAssignment {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if let Token::ID { name, .. } = &self.variable {
-            write!(f, "{} = {}", name, self.value)
+            if let Some(declaration) = self.declaration {
+                write!(f, "{} ", declaration.as_str())?;
+            }
+            write!(f, "{}", name)?;
+            if let Some(annotation) = self.annotation {
+                write!(f, ": {}", annotation)?;
+            }
+            write!(f, " = {}", self.value)
         } else {
             write!(f, "Invalid variable token")
         }
@@ -89,6 +464,10 @@ impl fmt::Display for NodeArithmeticExpr {
         match self {
             NodeArithmeticExpr::Base(base) => {write!(f, "{base}")}
             NodeArithmeticExpr::Operation(op) => {write!(f, "{op}")}
+            NodeArithmeticExpr::Array(array) => {write!(f, "{array}")}
+            NodeArithmeticExpr::Struct(literal) => {write!(f, "{literal}")}
+            NodeArithmeticExpr::Variant(literal) => {write!(f, "{literal}")}
+            NodeArithmeticExpr::Match(node) => {write!(f, "{node}")}
         }
     }
 }
@@ -106,6 +485,15 @@ impl fmt::Display for NodeArithmeticOperation {
         } else{
             format!("{}", self.rhs)
         };
+        if let Operator::Cast { .. } = self.op {
+            return write!(f, "{} as {}", lhs_str, self.result_type);
+        }
+        if let Operator::Negate { .. } = self.op {
+            return write!(f, "-{}", lhs_str);
+        }
+        if self.op.is_unary() {
+            return write!(f, "{}{}", self.op, lhs_str);
+        }
         write!(
             f,
             "{} {} {}",
@@ -116,12 +504,161 @@ impl fmt::Display for NodeArithmeticOperation {
     }
 }
 
+impl fmt::Display for ResultType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl fmt::Display for TypeAnnotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.length {
+            Some(length) => write!(f, "[{}; {}]", self.result_type, length),
+            None => write!(f, "{}", self.result_type),
+        }
+    }
+}
+
+impl fmt::Display for NodeArrayLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let elements: Vec<String> = self.elements.iter().map(|element| element.to_string()).collect();
+        if elements.len() == self.length {
+            write!(f, "[{}]", elements.join(", "))
+        } else {
+            write!(f, "[{}; {}]", elements.join(", "), self.length)
+        }
+    }
+}
+
+impl fmt::Display for NodeStructLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields: Vec<String> = self.fields.iter().map(|(field, value)| match field {
+            Token::ID { name, .. } => format!("{name}: {value}"),
+            _ => format!("Invalid field token: {value}"),
+        }).collect();
+        match &self.name {
+            Token::ID { name, .. } => write!(f, "{} {{ {} }}", name, fields.join(", ")),
+            _ => write!(f, "Invalid struct token"),
+        }
+    }
+}
+
+impl fmt::Display for NodeStruct {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields: Vec<String> = self.fields.iter().map(|field| match (&field.name, &field.type_name) {
+            (Token::ID { name, .. }, Token::ID { name: type_name, .. }) => format!("{name}: {type_name}"),
+            _ => "Invalid field token".to_string(),
+        }).collect();
+        match &self.name {
+            Token::ID { name, .. } => write!(f, "struct {} {{ {} }}", name, fields.join(", ")),
+            _ => write!(f, "Invalid struct token"),
+        }
+    }
+}
+
+impl fmt::Display for NodeEnum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let variants: Vec<String> = self.variants.iter().map(|variant| {
+            let types: Vec<String> = variant.types.iter().map(|type_name| NodeBaseExpr::ID(type_name.clone()).to_string()).collect();
+            format_variant(&variant.name, &types)
+        }).collect();
+        match &self.name {
+            Token::ID { name, .. } => write!(f, "enum {} {{ {} }}", name, variants.join(", ")),
+            _ => write!(f, "Invalid enum token"),
+        }
+    }
+}
+
+impl fmt::Display for NodeVariantLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values: Vec<String> = self.values.iter().map(NodeArithmeticExpr::to_string).collect();
+        match &self.enum_name {
+            Token::ID { name, .. } => write!(f, "{}::{}", name, format_variant(&self.variant, &values)),
+            _ => write!(f, "Invalid enum token"),
+        }
+    }
+}
+
+impl fmt::Display for NodeMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let arms: Vec<String> = self.arms.iter().map(|arm| {
+            let bindings: Vec<String> = arm.bindings.iter().map(|binding| format_variant(binding, &[])).collect();
+            format!("{} => {}", format_variant(&arm.variant, &bindings), arm.value)
+        }).collect();
+        match &self.variable {
+            Token::ID { name, .. } => write!(f, "match {} {{ {} }}", name, arms.join(", ")),
+            _ => write!(f, "Invalid variable token"),
+        }
+    }
+}
+
+/// A variant followed by its values, or by their types or bindings, between parentheses when it has some.
+/// The wildcard arm, the unused bindings and an unused loop variable are written `_`.
+fn format_variant(variant: &Token, values: &[String]) -> String {
+    let name = match variant {
+        Token::ID { name, .. } => name.as_str(),
+        Token::Underscore { .. } => "_",
+        _ => "Invalid variant token",
+    };
+    if values.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}({})", name, values.join(", "))
+    }
+}
+
+/// A string literal as written in the source, with its special characters escaped again.
+fn format_string(value: &str) -> String {
+    let mut literal = String::from("\"");
+    for ch in value.chars() {
+        match ch {
+            '\n' => literal.push_str("\\n"),
+            '\t' => literal.push_str("\\t"),
+            '\r' => literal.push_str("\\r"),
+            '\0' => literal.push_str("\\0"),
+            '"' | '\\' => {
+                literal.push('\\');
+                literal.push(ch);
+            }
+            ch => literal.push(ch),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+impl fmt::Display for NodeField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.variable {
+            Token::ID { name, .. } => write!(f, "{}.{}", name, self.get_path()),
+            _ => write!(f, "Invalid variable token"),
+        }
+    }
+}
+
+impl fmt::Display for NodeIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.variable {
+            Token::ID { name, .. } => write!(f, "{}[{}]", name, self.index),
+            _ => write!(f, "Invalid variable token"),
+        }
+    }
+}
+
 impl fmt::Display for NodeBaseExpr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             NodeBaseExpr::Num(Token::Number { value, .. }) => write!(f, "{}", value),
             NodeBaseExpr::ID(Token::ID { name, .. }) => write!(f, "{}", name),
             NodeBaseExpr::Bool(Token::Boolean { value, .. }) => write!(f, "{}", value),
+            NodeBaseExpr::Index(element) => write!(f, "{}", element),
+            NodeBaseExpr::Len(NodeLen { variable: Token::ID { name, .. }, .. }) => write!(f, "len({})", name),
+            NodeBaseExpr::Field(field) => write!(f, "{}", field),
+            NodeBaseExpr::Alloc(alloc) => write!(f, "alloc({})", alloc.size),
+            NodeBaseExpr::Builtin(NodeBuiltin { function, argument: Some(argument), .. }) => write!(f, "{}({})", function.name(), argument),
+            NodeBaseExpr::Builtin(NodeBuiltin { function, argument: None, .. }) => write!(f, "{}()", function.name()),
+            NodeBaseExpr::Call(call) => write!(f, "{}", call),
+            NodeBaseExpr::Str(Token::StringLiteral { value, .. }) => write!(f, "{}", format_string(value)),
             _ => write!(f, "Invalid base expression"),
         }
     }
@@ -133,19 +670,67 @@ impl fmt::Display for NodeExit {
     }
 }
 
+impl fmt::Display for NodeFree {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "free({})", self.pointer)
+    }
+}
+
+impl fmt::Display for NodeExtern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut parameters: Vec<String> = self.parameters.iter().map(|parameter| format_variant(parameter, &[])).collect();
+        if self.variadic {
+            parameters.push("...".to_string());
+        }
+        write!(f, "extern fn {}", format_variant(&self.name, &[]))?;
+        write!(f, "({})", parameters.join(", "))
+    }
+}
+
+impl fmt::Display for NodeCall {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let arguments: Vec<String> = self.arguments.iter().map(ToString::to_string).collect();
+        write!(f, "{}({})", format_variant(&self.function, &[]), arguments.join(", "))
+    }
+}
+
 impl fmt::Display for NodeStmt {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             NodeStmt::Exit(exit) => write!(f, "{}", exit),
             NodeStmt::ID(var_assign) => write!(f, "{}", var_assign),
-            NodeStmt::Scope(scope) => {
-                write!(f, "{{")?;
-                for stmt in &scope.stmts {
-                    write!(f, "{}", stmt)?;
-                }
-                write!(f, "}}")
-            },
+            NodeStmt::Scope(scope) => write!(f, "{}", scope),
+            NodeStmt::Import(import) => write!(f, "import \"{}\"", import.path),
+            NodeStmt::Index(assignment) => write!(f, "{} = {}", assignment.element, assignment.value),
+            NodeStmt::Struct(declaration) => write!(f, "{}", declaration),
+            NodeStmt::Field(assignment) => write!(f, "{} = {}", assignment.field, assignment.value),
+            NodeStmt::Enum(declaration) => write!(f, "{}", declaration),
+            NodeStmt::For(node) => write!(f, "{}", node),
+            NodeStmt::Free(free) => write!(f, "{}", free),
+            NodeStmt::Extern(declaration) => write!(f, "{}", declaration),
+            NodeStmt::Call(call) => write!(f, "{}", call),
+        }
+    }
+}
+
+impl fmt::Display for NodeScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{")?;
+        for stmt in &self.stmts {
+            write!(f, "{}", stmt)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+impl fmt::Display for NodeFor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let range = if self.inclusive { "..=" } else { ".." };
+        write!(f, "for {} in {}{}{}", format_variant(&self.variable, &[]), self.start, range, self.end)?;
+        if self.step.is_some() {
+            write!(f, " step {}", self.get_step())?;
         }
+        write!(f, " {}", self.body)
     }
 }
 
@@ -155,7 +740,7 @@ impl fmt::Display for NodeProgram {
         for (i, stmt) in self.stmts.iter().enumerate() {
             write!(f, "{}", stmt)?;
             if i < stmt_count - 1 {
-                write!(f, "\n")?;
+                writeln!(f)?;
             }
         }
         Ok(())
@@ -171,8 +756,10 @@ mod test_nodes {
     
     #[test]
     fn test_formatting_result_type(){
-        assert_eq!(ResultType::Numeric.as_str(), "num");
-        assert_eq!(ResultType::Boolean.as_str(), "bool")
+        assert_eq!(ResultType::Numeric.as_str(), "int");
+        assert_eq!(ResultType::Boolean.as_str(), "bool");
+        assert_eq!(ResultType::from_name("bool"), Some(ResultType::Boolean));
+        assert_eq!(ResultType::from_name("num"), None);
     }
 
     #[test]
@@ -214,19 +801,36 @@ mod test_nodes {
             rhs: Either::Right(base_expr_2.clone()),
             op: Operator::Plus { span: dummy_span }, // Assuming Operator::Add is an addition operator
             result_type: ResultType::Numeric,
+            span: Span::default(),
         };
 
         let formatted = format!("{}", operation);
         assert_eq!(formatted, "5 + 3");
     }
 
+    #[test]
+    fn test_formatting_unary_operation() {
+        let dummy_span = Span::new(0, 0, 0);
+        let operand = Either::Right(NodeBaseExpr::ID(Token::ID { name: "x".to_string(), span: dummy_span }));
+        let operation = NodeArithmeticOperation {
+            lhs: operand.clone(),
+            rhs: operand,
+            op: Operator::BitNot { span: dummy_span },
+            result_type: ResultType::Numeric,
+            span: Span::default(),
+        };
+        assert_eq!(format!("{}", operation), "~x");
+        let cast = NodeArithmeticOperation { op: Operator::Cast { span: dummy_span }, result_type: ResultType::Boolean, ..operation };
+        assert_eq!(format!("{}", cast), "x as bool");
+    }
+
     #[test]
     fn test_formatting_node_exit() {
         let dummy_span = Span::new(0, 0, 0);
         let num_token = Token::Number { value: "10".to_string(), span: dummy_span };
         let base_expr = NodeBaseExpr::Num(num_token);
 
-        let exit_node = NodeExit { expr: NodeArithmeticExpr::Base(base_expr) };
+        let exit_node = NodeExit { expr: NodeArithmeticExpr::Base(base_expr), span: Span::default() };
 
         // Testing NodeExit
         let formatted = format!("{}", NodeStmt::Exit(exit_node));
@@ -239,17 +843,102 @@ mod test_nodes {
         let var_token = Token::ID { name: "x".to_string(), span: dummy_span };
         let num_token = Token::Number { value: "1".to_string(), span: dummy_span };
         let base_expr = NodeBaseExpr::Num(num_token);
-        let var_assign = NodeVariableAssignment { variable: var_token, value: NodeArithmeticExpr::Base(base_expr.clone()) };
+        let var_assign = NodeVariableAssignment { declaration: None, variable: var_token, annotation: None, value: NodeArithmeticExpr::Base(base_expr.clone()) };
 
         // Testing valid variable assignment
         assert_eq!(format!("{}", var_assign), "x = 1");
+        let declaration = NodeVariableAssignment { declaration: Some(Declaration::Let { span: dummy_span }), ..var_assign.clone() };
+        assert_eq!(format!("{}", declaration), "let x = 1");
+        let annotation = Some(TypeAnnotation { result_type: ResultType::Numeric, length: None, span: dummy_span });
+        let annotated = NodeVariableAssignment { annotation, ..declaration };
+        assert_eq!(format!("{}", annotated), "let x: int = 1");
+        assert_eq!(Declaration::from_name("var", dummy_span).map(|declaration| declaration.is_mutable()), Some(true));
 
         // Testing invalid variable token
         let wrong_var_token = Token::NewLine { span: dummy_span };
-        let invalid_var_assign = NodeVariableAssignment { variable: wrong_var_token, value: NodeArithmeticExpr::Base(base_expr) };
+        let invalid_var_assign = NodeVariableAssignment { declaration: None, variable: wrong_var_token, annotation: None, value: NodeArithmeticExpr::Base(base_expr) };
         assert_eq!(format!("{}", invalid_var_assign), "Invalid variable token");
     }
 
+    #[test]
+    fn test_formatting_arrays() {
+        let dummy_span = Span::new(0, 0, 0);
+        let variable = Token::ID { name: "a".to_string(), span: dummy_span };
+        let one = NodeArithmeticExpr::Base(NodeBaseExpr::Num(Token::Number { value: "1".to_string(), span: dummy_span }));
+        let element = NodeIndex { variable: variable.clone(), index: Box::new(one.clone()), span: dummy_span };
+        let len = NodeArithmeticExpr::Base(NodeBaseExpr::Len(NodeLen { variable: variable.clone(), span: dummy_span }));
+        let assignment = NodeStmt::Index(NodeIndexAssignment { element, value: len });
+        assert_eq!(format!("{}", assignment), "a[1] = len(a)");
+
+        let annotation = Some(TypeAnnotation { result_type: ResultType::Numeric, length: Some(2), span: dummy_span });
+        let array = NodeArrayLiteral { elements: vec![one.clone(), one.clone()], length: 2, span: dummy_span };
+        let declaration = NodeVariableAssignment { declaration: Some(Declaration::Var { span: dummy_span }), variable, annotation, value: NodeArithmeticExpr::Array(array) };
+        assert_eq!(format!("{}", declaration), "var a: [int; 2] = [1, 1]");
+        let repeated = NodeArrayLiteral { elements: vec![one], length: 2, span: dummy_span };
+        assert_eq!(format!("{}", NodeArithmeticExpr::Array(repeated)), "[1; 2]");
+    }
+
+    #[test]
+    fn test_formatting_structs() {
+        let dummy_span = Span::new(0, 0, 0);
+        let id = |name: &str| Token::ID { name: name.to_string(), span: dummy_span };
+        let fields = vec![NodeFieldDeclaration { name: id("x"), type_name: id("int") }, NodeFieldDeclaration { name: id("y"), type_name: id("bool") }];
+        let declaration = NodeStmt::Struct(NodeStruct { name: id("Point"), fields, span: dummy_span });
+        assert_eq!(format!("{}", declaration), "struct Point { x: int, y: bool }");
+
+        let one = NodeArithmeticExpr::Base(NodeBaseExpr::Num(Token::Number { value: "1".to_string(), span: dummy_span }));
+        let point = NodeArithmeticExpr::Struct(NodeStructLiteral { name: id("Point"), fields: vec![(id("x"), one.clone())], span: dummy_span });
+        let line = NodeStructLiteral { name: id("Line"), fields: vec![(id("start"), point)], span: dummy_span };
+        assert_eq!(format!("{}", NodeArithmeticExpr::Struct(line)), "Line { start: Point { x: 1 } }");
+
+        let field = NodeField { variable: id("l"), fields: vec![id("start"), id("x")], span: dummy_span };
+        assert_eq!(field.get_path(), "start.x");
+        assert_eq!(format!("{}", NodeStmt::Field(NodeFieldAssignment { field, value: one })), "l.start.x = 1");
+    }
+
+    #[test]
+    fn test_formatting_enums() {
+        let dummy_span = Span::new(0, 0, 0);
+        let id = |name: &str| Token::ID { name: name.to_string(), span: dummy_span };
+        let variants = vec![NodeVariant { name: id("Circle"), types: vec![id("int")] }, NodeVariant { name: id("Empty"), types: Vec::new() }];
+        let declaration = NodeStmt::Enum(NodeEnum { name: id("Shape"), variants, span: dummy_span });
+        assert_eq!(format!("{}", declaration), "enum Shape { Circle(int), Empty }");
+
+        let one = NodeArithmeticExpr::Base(NodeBaseExpr::Num(Token::Number { value: "1".to_string(), span: dummy_span }));
+        let circle = NodeVariantLiteral { enum_name: id("Shape"), variant: id("Circle"), values: vec![one.clone()], span: dummy_span };
+        assert_eq!(format!("{}", NodeArithmeticExpr::Variant(circle)), "Shape::Circle(1)");
+
+        let arms = vec![
+            NodeMatchArm { variant: id("Circle"), bindings: vec![id("r")], value: NodeArithmeticExpr::Base(NodeBaseExpr::ID(id("r"))) },
+            NodeMatchArm { variant: Token::Underscore { span: dummy_span }, bindings: Vec::new(), value: one },
+        ];
+        let node = NodeMatch { variable: id("s"), arms, span: dummy_span };
+        assert_eq!(format!("{}", NodeArithmeticExpr::Match(node)), "match s { Circle(r) => r, _ => 1 }");
+    }
+
+    #[test]
+    fn test_formatting_for() {
+        let dummy_span = Span::new(0, 0, 0);
+        let id = |name: &str| Token::ID { name: name.to_string(), span: dummy_span };
+        let number = |value: &str| Token::Number { value: value.to_string(), span: dummy_span };
+        let assignment = NodeVariableAssignment { declaration: None, variable: id("x"), annotation: None, value: NodeArithmeticExpr::Base(NodeBaseExpr::ID(id("i"))) };
+        let mut node = NodeFor {
+            variable: id("i"),
+            start: NodeArithmeticExpr::Base(NodeBaseExpr::Num(number("0"))),
+            end: NodeArithmeticExpr::Base(NodeBaseExpr::ID(id("n"))),
+            inclusive: false,
+            step: None,
+            body: NodeScope { stmts: vec![NodeStmt::ID(assignment)], span: dummy_span },
+            span: dummy_span
+        };
+        assert_eq!(node.get_step(), 1);
+        assert_eq!(format!("{}", NodeStmt::For(Box::new(node.clone()))), "for i in 0..n {x = i}");
+        node.inclusive = true;
+        node.step = Some(number("2"));
+        assert_eq!(node.get_step(), 2);
+        assert_eq!(format!("{}", NodeStmt::For(Box::new(node))), "for i in 0..=n step 2 {x = i}");
+    }
+
     #[test]
     fn test_formatting_node_stmt_scope() {
         let dummy_span = Span::new(0, 0, 0);
@@ -257,14 +946,33 @@ mod test_nodes {
         let base_expr = NodeBaseExpr::Num(num_token);
 
         let var_token = Token::ID { name: "x".to_string(), span: dummy_span };
-        let var_assign = NodeVariableAssignment { variable: var_token, value: NodeArithmeticExpr::Base(base_expr) };
-        let scope_stmt = NodeStmt::Scope(NodeScope { stmts: vec![NodeStmt::ID(var_assign)] });
+        let var_assign = NodeVariableAssignment { declaration: None, variable: var_token, annotation: None, value: NodeArithmeticExpr::Base(base_expr) };
+        let scope_stmt = NodeStmt::Scope(NodeScope { stmts: vec![NodeStmt::ID(var_assign)], span: Span::default() });
 
         // Testing Scope Statement
         let formatted = format!("{}", scope_stmt);
         assert_eq!(formatted, "{x = 1}");
     }
     
+    #[test]
+    fn test_formatting_node_import() {
+        let import = NodeStmt::Import(NodeImport { path: "lib/math.brs".to_string(), span: Span::default() });
+        assert_eq!(format!("{}", import), "import \"lib/math.brs\"");
+    }
+
+    #[test]
+    fn test_formatting_extern_and_call() {
+        let dummy_span = Span::new(0, 0, 0);
+        let id = |name: &str| Token::ID { name: name.to_string(), span: dummy_span };
+        let declaration = NodeStmt::Extern(NodeExtern { name: id("printf"), parameters: vec![id("int")], variadic: true, span: dummy_span });
+        assert_eq!(format!("{}", declaration), "extern fn printf(int, ...)");
+        let format = NodeArithmeticExpr::Base(NodeBaseExpr::Str(Token::StringLiteral { value: "%d \"%s\"\n".to_string(), span: dummy_span }));
+        let call = NodeStmt::Call(NodeCall { function: id("printf"), arguments: vec![format, NodeArithmeticExpr::Base(NodeBaseExpr::ID(id("x")))], span: dummy_span });
+        assert_eq!(format!("{}", call), "printf(\"%d \\\"%s\\\"\\n\", x)");
+        let call = NodeBaseExpr::Call(NodeCall { function: id("rand"), arguments: Vec::new(), span: dummy_span });
+        assert_eq!(format!("{}", call), "rand()");
+    }
+
     #[test]
     fn test_bad_node_base_expr(){
         let dummy_span = Span::new(0, 0, 0);
@@ -286,6 +994,7 @@ mod test_nodes {
             rhs: Either::Right(base_expr_2.clone()),
             op: Operator::Plus {span: dummy_span},
             result_type: ResultType::Numeric,
+            span: Span::default(),
         };
 
         let operation_2 = NodeArithmeticOperation {
@@ -293,6 +1002,7 @@ mod test_nodes {
             rhs: Either::Left(Box::new(operation_1.clone())),
             op: Operator::Multiplication {span: dummy_span},
             result_type: ResultType::Numeric,
+            span: Span::default(),
         };
 
         // Testing nested arithmetic operation
@@ -307,10 +1017,10 @@ mod test_nodes {
         let base_expr = NodeBaseExpr::Num(num_token);
 
         let var_token = Token::ID { name: "x".to_string(), span: dummy_span };
-        let var_assign = NodeVariableAssignment { variable: var_token, value: NodeArithmeticExpr::Base(base_expr) };
+        let var_assign = NodeVariableAssignment { declaration: None, variable: var_token, annotation: None, value: NodeArithmeticExpr::Base(base_expr) };
         let stmt = NodeStmt::ID(var_assign);
 
-        let program = NodeProgram { stmts: vec![stmt.clone(), stmt] };
+        let program = NodeProgram { stmts: vec![stmt.clone(), stmt], span: Span::default() };
 
         // Testing NodeProgram formatting
         let formatted = format!("{}", program);
@@ -1,5 +1,6 @@
 use super::nodes::{NodeProgram};
 use super::parser_logger::{ParserLogger};
+use crate::compiler::span::Span;
 use crate::compiler::tokenizer::{Token};
 use super::token_stream::TokenStream;
 use std::sync::{Arc, Mutex};
@@ -26,22 +27,18 @@ impl Parser {
             stmt_factory.create(&mut stmts);
             self.m_token_stream.advance_stmt(true);
         }
-        if self.flush_errors() {
+        if self.m_logger.lock().is_ok_and(|logger| logger.failed_parsing()) {
+            // Errors are reported by the caller, once every file has been parsed
             None
         } else {
-            let prog = NodeProgram { stmts };
+            let span = match (stmts.first(), stmts.last()) {
+                (Some(first), Some(last)) => first.get_span().merge(last.get_span()),
+                _ => Span::default()
+            };
+            let prog = NodeProgram { stmts, span };
             Some(prog)
         }
     }
-    
-    fn flush_errors(&mut self) -> bool{
-        if self.m_logger.lock().is_ok_and(|logger| logger.failed_parsing()) {
-            self.m_logger.lock().unwrap().report_errors();
-            return true
-        }
-        false
-    }
-
 }
 
 
@@ -49,7 +46,7 @@ impl Parser {
 #[cfg(test)]
 mod test_parser{
     use crate::compiler::logger::Logger;
-    use crate::compiler::parser::{NodeArithmeticExpr, NodeBaseExpr, NodeExit, NodeStmt, NodeVariableAssignment};
+    use crate::compiler::parser::{NodeArithmeticExpr, NodeBaseExpr, NodeExit, NodeProgram, NodeStmt, NodeVariableAssignment, ParserErrorType};
     use crate::compiler::span::Span;
     use super::*;
     
@@ -80,16 +77,193 @@ mod test_parser{
         let exp_stmts = vec![
             NodeStmt::ID(
                 NodeVariableAssignment {
+                    declaration: None,
                     variable: Token::ID { name: "x".to_string(), span: dummy_span },
+                    annotation: None,
                     value: NodeArithmeticExpr::Base(NodeBaseExpr::Num(Token::Number { value: 1.to_string(), span: dummy_span })) }
             ),
             NodeStmt::Exit(
-                NodeExit{ expr: NodeArithmeticExpr::Base(NodeBaseExpr::Num(Token::Number { value: 1.to_string(), span: dummy_span })) }
+                NodeExit{ expr: NodeArithmeticExpr::Base(NodeBaseExpr::Num(Token::Number { value: 1.to_string(), span: dummy_span })), span: Span::default() }
             )
         ];
         assert_eq!(node_prog_stmt, exp_stmts);
     }
     
+    #[test]
+    fn test_node_spans(){
+        let code = "let x = 1 + 2\n{\nexit(x * 3)\n}";
+        let mut tokenizer = crate::compiler::tokenizer::Tokenizer::new();
+        tokenizer.tokenize(code);
+        let prog = create_parser(tokenizer.get_tokens()).parse().unwrap();
+        assert_eq!(prog.span, Span::new(0, 0, 29));
+        let stmts = prog.get_stmts();
+        assert_eq!(stmts[0].get_span(), Span::new(0, 0, 13));
+        let NodeStmt::ID(NodeVariableAssignment { value, .. }) = &stmts[0] else { panic!("Expected an assignment") };
+        assert_eq!(value.get_span(), Span::new(0, 8, 13));
+        // The scope spans multiple lines, from '{' to '}'
+        let NodeStmt::Scope(scope) = &stmts[1] else { panic!("Expected a scope") };
+        assert_eq!(scope.get_span(), Span::new(0, 14, 29));
+        assert_eq!(scope.stmts[0].get_span(), Span::new(0, 16, 27));
+        let NodeStmt::Exit(exit) = &scope.stmts[0] else { panic!("Expected an exit") };
+        assert_eq!(&code[exit.expr.get_span().range()], "x * 3");
+    }
+
+    fn parse_code(code: &str) -> (Option<NodeProgram>, Vec<(String, Span)>) {
+        let logger = Arc::new(Mutex::new(ParserLogger::new("".to_string(), code.to_string())));
+        let mut tokenizer = crate::compiler::tokenizer::Tokenizer::new();
+        tokenizer.tokenize(code);
+        let prog = Parser::new(tokenizer.get_tokens(), logger.clone()).parse();
+        let errors = logger.lock().unwrap().get_errors().to_vec();
+        (prog, errors)
+    }
+
+    #[test]
+    fn test_arrays(){
+        let code = "var a: [int; 3] = [1, 2 + 3, 4]\nlet b = [true; 8]\na[a[0]] = 2\na[1] *= 3\nexit(a[len(a) - 1])";
+        let (prog, errors) = parse_code(code);
+        assert_eq!(errors, vec![]);
+        let prog = prog.unwrap();
+        assert_eq!(format!("{prog}"), "var a: [int; 3] = [1, 2 + 3, 4]\nlet b = [true; 8]\na[a[0]] = 2\na[1] = a[1] * 3\nexit(a[len(a) - 1])");
+        let stmts = prog.get_stmts();
+        assert_eq!(stmts[0].get_span(), Span::new(0, 0, 31));
+        assert_eq!(stmts[2].get_span(), Span::new(0, 50, 61));
+        let NodeStmt::Exit(exit) = &stmts[4] else { panic!("Expected an exit") };
+        assert_eq!(&code[exit.expr.get_span().range()], "a[len(a) - 1]");
+    }
+
+    #[test]
+    fn test_array_errors(){
+        let error = |code: &str| parse_code(code).1.into_iter().map(|(message, _)| message).collect::<Vec<_>>();
+        assert_eq!(error("let a: [int] = [1]"), vec![ParserErrorType::ErrArrayLengthMissing.message()]);
+        assert_eq!(error("let a = [1; 0]"), vec![ParserErrorType::ErrArrayLengthMissing.message()]);
+        assert_eq!(error("let a = [1; 65537]"), vec![ParserErrorType::ErrArrayLengthMissing.message()]);
+        assert_eq!(error("let a: [int; 99999999999] = [1; 2]"), vec![ParserErrorType::ErrArrayLengthMissing.message()]);
+        assert_eq!(error("let a = [1, 2"), vec![ParserErrorType::ErrSquareBracketMissing.message()]);
+        assert_eq!(error("exit(a[1)"), vec![ParserErrorType::ErrSquareBracketMissing.message()]);
+        assert_eq!(error("exit(len(1))"), vec![ParserErrorType::ErrArrayMissing.message()]);
+    }
+
+    #[test]
+    fn test_structs(){
+        let code = "struct Point { x: int, y: bool }\nstruct Line {\n    start: Point,\n    end: Point,\n}\nvar l = Line { start: Point { x: 1, y: true }, end: Point {\n    x: 2,\n    y: false,\n} }\nl.start.x -= l.end.x\nexit(l.start.x)";
+        let (prog, errors) = parse_code(code);
+        assert_eq!(errors, vec![]);
+        let prog = prog.unwrap();
+        assert_eq!(format!("{prog}"), concat!(
+            "struct Point { x: int, y: bool }\nstruct Line { start: Point, end: Point }\n",
+            "var l = Line { start: Point { x: 1, y: true }, end: Point { x: 2, y: false } }\n",
+            "l.start.x = l.start.x - l.end.x\nexit(l.start.x)"
+        ));
+        let stmts = prog.get_stmts();
+        assert_eq!(&code[stmts[1].get_span().range()], "struct Line {\n    start: Point,\n    end: Point,\n}");
+        let NodeStmt::Exit(exit) = &stmts[4] else { panic!("Expected an exit") };
+        assert_eq!(&code[exit.expr.get_span().range()], "l.start.x");
+    }
+
+    #[test]
+    fn test_struct_errors(){
+        let error = |code: &str| parse_code(code).1.into_iter().map(|(message, _)| message).collect::<Vec<_>>();
+        assert_eq!(error("struct P"), vec![ParserErrorType::ErrStructBodyMissing.message()]);
+        assert_eq!(error("struct P { x }"), vec![ParserErrorType::ErrFieldDeclarationMissing.message()]);
+        assert_eq!(error("struct P { x: int y: int }"), vec![ParserErrorType::ErrStructBodyMissing.message()]);
+        assert_eq!(error("exit(p.)"), vec![ParserErrorType::ErrFieldMissing.message()]);
+    }
+
+    #[test]
+    fn test_enums(){
+        let code = "enum Shape { Circle(int), Rect(int, bool),\n    Empty }\nvar s = Shape::Rect(1 + 2, true)\ns = Shape::Empty\nlet x = match s {\n    Rect(w, _) => w * 2,\n    _ => 0,\n}\nexit(match s { Circle(r) => r, _ => x })";
+        let (prog, errors) = parse_code(code);
+        assert_eq!(errors, vec![]);
+        let prog = prog.unwrap();
+        assert_eq!(format!("{prog}"), concat!(
+            "enum Shape { Circle(int), Rect(int, bool), Empty }\n",
+            "var s = Shape::Rect(1 + 2, true)\ns = Shape::Empty\n",
+            "let x = match s { Rect(w, _) => w * 2, _ => 0 }\nexit(match s { Circle(r) => r, _ => x })"
+        ));
+        let stmts = prog.get_stmts();
+        assert_eq!(&code[stmts[1].get_span().range()], "var s = Shape::Rect(1 + 2, true)");
+        assert_eq!(&code[stmts[3].get_span().range()], "let x = match s {\n    Rect(w, _) => w * 2,\n    _ => 0,\n}");
+    }
+
+    #[test]
+    fn test_enum_errors(){
+        let error = |code: &str| parse_code(code).1.into_iter().map(|(message, _)| message).collect::<Vec<_>>();
+        assert_eq!(error("enum E"), vec![ParserErrorType::ErrEnumBodyMissing.message()]);
+        assert_eq!(error("enum E { A(int }"), vec![ParserErrorType::ErrVariantMissing.message()]);
+        assert_eq!(error("let s = E::A(1"), vec![ParserErrorType::ErrVariantMissing.message()]);
+        assert_eq!(error("let x = match"), vec![ParserErrorType::ErrMatchVariableMissing.message()]);
+        assert_eq!(error("let x = match s { A 1 }"), vec![ParserErrorType::ErrMatchArmMissing.message()]);
+    }
+
+    #[test]
+    fn test_for(){
+        let code = "var x = 0\nfor i in 0..3 {\n    x = x + i\n}\nfor _ in 1 + 1..=x * 2 step 2 {\n}\nexit(x)";
+        let (prog, errors) = parse_code(code);
+        assert_eq!(errors, vec![]);
+        let prog = prog.unwrap();
+        assert_eq!(format!("{prog}"), "var x = 0\nfor i in 0..3 {x = x + i}\nfor _ in 1 + 1..=x * 2 step 2 {}\nexit(x)");
+        let stmts = prog.get_stmts();
+        assert_eq!(&code[stmts[1].get_span().range()], "for i in 0..3 {\n    x = x + i\n}");
+        let NodeStmt::For(node) = &stmts[2] else { panic!("Expected a loop") };
+        assert_eq!((node.inclusive, node.get_step()), (true, 2));
+    }
+
+    #[test]
+    fn test_for_errors(){
+        let error = |code: &str| parse_code(code).1.into_iter().map(|(message, _)| message).collect::<Vec<_>>();
+        assert_eq!(error("for i 0..3 {\n}"), vec![ParserErrorType::ErrForRangeMissing.message()]);
+        assert_eq!(error("for i in 0.. {\n}"), vec![ParserErrorType::ErrForRangeMissing.message()]);
+        assert_eq!(error("for i in 0..3\nexit(1)"), vec![ParserErrorType::ErrForBodyMissing.message()]);
+        assert_eq!(error("for i in 0..3 step 0 {\n}"), vec![ParserErrorType::ErrStepMissing.message()]);
+    }
+
+    #[test]
+    fn test_heap(){
+        let code = "let p = alloc((1 + 2) * 8) + 8\nfree(p - 8)\nexit(alloc(len(a)))";
+        let (prog, errors) = parse_code(code);
+        assert_eq!(errors, vec![]);
+        let prog = prog.unwrap();
+        assert_eq!(format!("{prog}"), "let p = alloc((1 + 2) * 8) + 8\nfree(p - 8)\nexit(alloc(len(a)))");
+        let stmts = prog.get_stmts();
+        assert_eq!(&code[stmts[1].get_span().range()], "free(p - 8)");
+        let NodeStmt::ID(assignment) = &stmts[0] else { panic!("Expected an assignment") };
+        let NodeArithmeticExpr::Operation(operation) = &assignment.value else { panic!("Expected an operation") };
+        assert_eq!(&code[operation.lhs.as_ref().either(|lhs| lhs.span, |lhs| lhs.get_span()).range()], "alloc((1 + 2) * 8)");
+    }
+
+    #[test]
+    fn test_heap_errors(){
+        let error = |code: &str| parse_code(code).1.into_iter().map(|(message, _)| message).collect::<Vec<_>>();
+        assert_eq!(error("let p = alloc 8"), vec![ParserErrorType::ErrAllocSizeMissing.message()]);
+        assert_eq!(error("let p = alloc()"), vec![ParserErrorType::ErrAllocSizeMissing.message()]);
+        assert_eq!(error("let p = alloc(8"), vec![ParserErrorType::ErrExpressionClosedBracketMissing.message()]);
+        assert_eq!(error("free p"), vec![ParserErrorType::ErrFreePointerMissing.message()]);
+        assert_eq!(error("free()"), vec![ParserErrorType::ErrFreePointerMissing.message()]);
+    }
+
+    #[test]
+    fn test_input(){
+        let code = "let n = parse_int(arg(args() - 1)) + read_int()\nexit(parse_int(read_line()) * n)";
+        let (prog, errors) = parse_code(code);
+        assert_eq!(errors, vec![]);
+        let prog = prog.unwrap();
+        assert_eq!(format!("{prog}"), code);
+        let NodeStmt::ID(assignment) = &prog.get_stmts()[0] else { panic!("Expected an assignment") };
+        let NodeArithmeticExpr::Operation(operation) = &assignment.value else { panic!("Expected an operation") };
+        assert_eq!(&code[operation.lhs.as_ref().either(|lhs| lhs.span, |lhs| lhs.get_span()).range()], "parse_int(arg(args() - 1))");
+    }
+
+    #[test]
+    fn test_input_errors(){
+        let error = |code: &str| parse_code(code).1.into_iter().map(|(message, _)| message).collect::<Vec<_>>();
+        assert_eq!(error("let n = read_int"), vec![ParserErrorType::ErrBuiltinArgumentsMissing.message()]);
+        assert_eq!(error("let s = arg()"), vec![ParserErrorType::ErrBuiltinArgumentsMissing.message()]);
+        // The argument must be between the brackets of the call
+        assert_eq!(error("let s = 1 arg()"), vec![ParserErrorType::ErrBuiltinArgumentsMissing.message()]);
+        assert_eq!(error("let n = parse_int(true)"), vec![ParserErrorType::ErrTypeMismatch.message()]);
+        assert_eq!(error("let n = args(1)"), vec![ParserErrorType::ErrMissingOperator.message()]);
+    }
+
     #[test]
     fn test_wrong_parsing(){
         let dummy_span = Span::new(0, 0, 0);
@@ -99,4 +273,36 @@ mod test_parser{
         let mut parser = create_parser(tokens);
         assert!(parser.parse().is_none());
     }
+
+    #[test]
+    fn test_error_recovery(){
+        let tokens = vec![
+            // exit 1) 2
+            Token::Exit { span: Span::new(0, 0, 4) },
+            Token::WhiteSpace { span: Span::new(0, 4, 5) },
+            Token::Number { value: 1.to_string(), span: Span::new(0, 5, 6) },
+            Token::ClosedBracket { span: Span::new(0, 6, 7) },
+            Token::WhiteSpace { span: Span::new(0, 7, 8) },
+            Token::Number { value: 2.to_string(), span: Span::new(0, 8, 9) },
+            Token::NewLine { span: Span::new(0, 9, 10) },
+            // x=
+            Token::ID { name: "x".to_string(), span: Span::new(0, 10, 11) },
+            Token::Equals { span: Span::new(0, 11, 12) },
+            Token::NewLine { span: Span::new(0, 12, 13) },
+            // y=1
+            Token::ID { name: "y".to_string(), span: Span::new(0, 13, 14) },
+            Token::Equals { span: Span::new(0, 14, 15) },
+            Token::Number { value: 1.to_string(), span: Span::new(0, 15, 16) },
+        ];
+        let logger = Arc::new(Mutex::new(ParserLogger::new("".to_string(), "".to_string())));
+        let mut parser = Parser::new(tokens, logger.clone());
+        assert!(parser.parse().is_none());
+
+        // Each broken statement reports exactly one error, and parsing carries on after them
+        let exp_errors = vec![
+            (ParserErrorType::ErrExitOpenBracketMissing.message().to_string(), Span::new(0, 0, 4)),
+            (ParserErrorType::ErrExpressionMissing.message().to_string(), Span::new(0, 11, 12)),
+        ];
+        assert_eq!(logger.lock().unwrap().get_errors(), exp_errors.as_slice());
+    }
 }
\ No newline at end of file
@@ -1,12 +1,16 @@
-use ariadne::{Color, Label, Report, ReportKind, Source};
+use std::sync::Arc;
+use crate::compiler::diagnostic::{Diagnostic, Severity};
 use crate::compiler::tokenizer::Token;
 use crate::compiler::logger::Logger;
-use crate::compiler::span::Span;
+use crate::compiler::span::{LineIndex, Span};
 
 pub struct ParserLogger{
     file_name: String,
-    source: Source,
-    errors: Vec<(String, Span)>
+    source: Arc<str>,
+    line_index: LineIndex,
+    errors: Vec<(&'static str, String, Span)>,
+    warnings: Vec<(&'static str, String, Span)>,
+    recovering: bool
 }
 
 impl ParserLogger {
@@ -14,21 +18,84 @@ impl ParserLogger {
         !self.errors.is_empty()
     }
 
+    /// Zero-based line and byte column of the start of `span`.
+    pub fn get_line_col(&self, span: Span) -> (usize, usize) {
+        self.line_index.line_col(span.m_start)
+    }
+
+    #[cfg(test)]
+    pub fn get_errors(&self) -> Vec<(String, Span)> {
+        self.errors.iter().map(|(_, message, span)| (message.clone(), *span)).collect()
+    }
+
+    #[cfg(test)]
+    pub fn get_warnings(&self) -> Vec<(String, Span)> {
+        self.warnings.iter().map(|(_, message, span)| (message.clone(), *span)).collect()
+    }
+
+    /// Logs a syntax error. Once an error has been logged, the parser is recovering and every follow-on
+    /// error is dropped until the token stream synchronises at the start of the next statement.
     pub fn log_error(&mut self, error: ParserErrorType, token: &Token) {
-        let span : Span = token.get_span();
-        let res = (error.message().to_string(), span);
-        self.errors.push(res);
+        self.log_error_at(error, token.get_span());
+    }
+
+    pub fn log_error_at(&mut self, error: ParserErrorType, span: Span) {
+        if self.recovering {
+            return;
+        }
+        self.recovering = true;
+        let res = (error.code(), error.message().to_string(), span);
+        if !self.errors.contains(&res) {
+            self.errors.push(res);
+        }
+    }
+
+    pub fn synchronize(&mut self) {
+        self.recovering = false;
+    }
+
+    /// Logs an error that isn't a syntax error, identified by `code`.
+    pub fn log_error_message(&mut self, code: &'static str, message: String, span: Span) {
+        self.errors.push((code, message, span));
+    }
+
+    pub fn log_warning(&mut self, code: &'static str, message: String, span: Span) {
+        self.warnings.push((code, message, span));
     }
     
     pub fn report_errors(&self){
-        // Check if the code is being run with a test profile
-        let is_test_profile = std::thread::current().name().map_or(false, |name| name.contains("test"));
-        if !is_test_profile {
-            for (error, span) in self.errors.clone() {
+        if !Self::is_test_profile() {
+            for (_, error, span) in self.errors.clone() {
                 self.report_error(error.as_str(), span)
             }
         }
     }
+
+    pub fn report_warnings(&self){
+        if !Self::is_test_profile() {
+            for (_, warning, span) in self.warnings.clone() {
+                self.report_warning(warning.as_str(), span)
+            }
+        }
+    }
+
+    /// Every logged error followed by every logged warning.
+    pub fn get_diagnostics(&self) -> Vec<Diagnostic> {
+        let errors = self.errors.iter().map(|(code, message, span)| (Severity::Error, code, message, span));
+        let warnings = self.warnings.iter().map(|(code, message, span)| (Severity::Warning, code, message, span));
+        errors.chain(warnings)
+            .map(|(severity, code, message, span)| self.create_diagnostic(severity, message, *span).with_code(code))
+            .collect()
+    }
+
+    fn create_diagnostic(&self, severity: Severity, message: &str, span: Span) -> Diagnostic {
+        Diagnostic::new(severity, message.to_string(), self.file_name.clone(), span, self.source.clone())
+    }
+
+    fn is_test_profile() -> bool {
+        // Check if the code is being run with a test profile
+        std::thread::current().name().is_some_and(|name| name.contains("test"))
+    }
 }
 
 #[derive(Eq, PartialEq, Hash, Clone)]
@@ -40,11 +107,78 @@ pub enum ParserErrorType{
     ErrExpressionOpenBracketMissing,
     ErrExpressionClosedBracketMissing,
     ErrScopeClosesCurlyBracketMissing,
+    ErrScopeOpenCurlyBracketMissing,
     ErrMissingOperand,
+    ErrMissingOperator,
     ErrTypeMismatch,
+    ErrExitCodeMissing,
+    ErrExpressionMissing,
+    ErrImportPathMissing,
+    ErrIntegerOutOfRange,
+    ErrInvalidNumber,
+    ErrDeclarationMissing,
+    ErrTypeMissing,
+    ErrArrayLengthMissing,
+    ErrSquareBracketMissing,
+    ErrArrayMissing,
+    ErrFieldMissing,
+    ErrFieldDeclarationMissing,
+    ErrStructBodyMissing,
+    ErrEnumBodyMissing,
+    ErrVariantMissing,
+    ErrMatchArmMissing,
+    ErrMatchVariableMissing,
+    ErrForRangeMissing,
+    ErrForBodyMissing,
+    ErrStepMissing,
+    ErrAllocSizeMissing,
+    ErrFreePointerMissing,
+    ErrBuiltinArgumentsMissing,
+    ErrExternDeclarationInvalid,
 }
 
 impl ParserErrorType {
+    /// Stable identifier of the error, shown with the message.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParserErrorType::ErrInvalidStatement => "E0001",
+            ParserErrorType::ErrExitOpenBracketMissing => "E0002",
+            ParserErrorType::ErrExitClosedBracketMissing => "E0003",
+            ParserErrorType::ErrUnexpectedToken => "E0004",
+            ParserErrorType::ErrExpressionOpenBracketMissing => "E0005",
+            ParserErrorType::ErrExpressionClosedBracketMissing => "E0006",
+            ParserErrorType::ErrScopeClosesCurlyBracketMissing => "E0007",
+            ParserErrorType::ErrScopeOpenCurlyBracketMissing => "E0008",
+            ParserErrorType::ErrMissingOperand => "E0009",
+            ParserErrorType::ErrMissingOperator => "E0010",
+            ParserErrorType::ErrTypeMismatch => "E0011",
+            ParserErrorType::ErrExitCodeMissing => "E0012",
+            ParserErrorType::ErrExpressionMissing => "E0013",
+            ParserErrorType::ErrImportPathMissing => "E0014",
+            ParserErrorType::ErrIntegerOutOfRange => "E0015",
+            ParserErrorType::ErrInvalidNumber => "E0016",
+            ParserErrorType::ErrDeclarationMissing => "E0017",
+            ParserErrorType::ErrTypeMissing => "E0018",
+            ParserErrorType::ErrArrayLengthMissing => "E0019",
+            ParserErrorType::ErrSquareBracketMissing => "E0021",
+            ParserErrorType::ErrArrayMissing => "E0022",
+            ParserErrorType::ErrFieldMissing => "E0023",
+            ParserErrorType::ErrFieldDeclarationMissing => "E0024",
+            ParserErrorType::ErrStructBodyMissing => "E0025",
+            ParserErrorType::ErrEnumBodyMissing => "E0026",
+            ParserErrorType::ErrVariantMissing => "E0027",
+            ParserErrorType::ErrMatchArmMissing => "E0028",
+            ParserErrorType::ErrMatchVariableMissing => "E0029",
+            ParserErrorType::ErrForRangeMissing => "E0033",
+            ParserErrorType::ErrForBodyMissing => "E0034",
+            ParserErrorType::ErrStepMissing => "E0035",
+            ParserErrorType::ErrAllocSizeMissing => "E0036",
+            ParserErrorType::ErrFreePointerMissing => "E0037",
+            ParserErrorType::ErrBuiltinArgumentsMissing => "E0038",
+            ParserErrorType::ErrExternDeclarationInvalid => "E0039",
+        }
+    }
+
     pub fn message(&self) -> &'static str {
         match self {
             ParserErrorType::ErrInvalidStatement => "Invalid statement",
@@ -54,29 +188,48 @@ impl ParserErrorType {
             ParserErrorType::ErrExpressionOpenBracketMissing => "Mismatched Parenthesis: ( is missing",
             ParserErrorType::ErrExpressionClosedBracketMissing => "Mismatched Parenthesis: ) is missing",
             ParserErrorType::ErrScopeClosesCurlyBracketMissing => "Scope is initialized but never closes",
+            ParserErrorType::ErrScopeOpenCurlyBracketMissing => "Scope is closed but was never opened",
             ParserErrorType::ErrMissingOperand => "Missing operand for operator.",
+            ParserErrorType::ErrMissingOperator => "Missing operator between operands.",
             ParserErrorType::ErrTypeMismatch => "Type mismatch in expression.",
+            ParserErrorType::ErrExitCodeMissing => "Exit code is missing between the parenthesis.",
+            ParserErrorType::ErrExpressionMissing => "Expected an expression after '='.",
+            ParserErrorType::ErrImportPathMissing => "Expected a file path string after 'import'.",
+            ParserErrorType::ErrIntegerOutOfRange => "Integer literal does not fit in a 64-bit signed integer.",
+            ParserErrorType::ErrInvalidNumber => "Invalid digit in numeric literal.",
+            ParserErrorType::ErrDeclarationMissing => "Expected a variable name and '=' after the declaration keyword.",
+            ParserErrorType::ErrTypeMissing => "Expected a type, `int` or `bool`.",
+            ParserErrorType::ErrArrayLengthMissing => "Expected the length of the array, a positive integer literal up to 65536.",
+            ParserErrorType::ErrSquareBracketMissing => "Mismatched square bracket: ] is missing",
+            ParserErrorType::ErrArrayMissing => "Expected the name of an array, as in `len(a)`.",
+            ParserErrorType::ErrFieldMissing => "Expected the name of a field after '.'.",
+            ParserErrorType::ErrFieldDeclarationMissing => "Expected a field followed by ':' and its type or value, as in `x: int` or `x: 1`.",
+            ParserErrorType::ErrStructBodyMissing => "Expected the fields of the struct between curly brackets.",
+            ParserErrorType::ErrEnumBodyMissing => "Expected the variants of the enum between curly brackets.",
+            ParserErrorType::ErrVariantMissing => "Expected a variant, followed by its values between parentheses if it has some, as in `Circle(int)` or `Shape::Circle(2)`.",
+            ParserErrorType::ErrMatchArmMissing => "Expected an arm, as in `Circle(r) => r * 2` or `_ => 0`, or the end of the match.",
+            ParserErrorType::ErrMatchVariableMissing => "Expected the enum variable to match, followed by its arms between curly brackets.",
+            ParserErrorType::ErrForRangeMissing => "Expected a loop variable followed by `in` and a range, as in `for i in 0..n` or `for i in 0..=n`.",
+            ParserErrorType::ErrForBodyMissing => "Expected the body of the loop between curly brackets, after the range.",
+            ParserErrorType::ErrStepMissing => "Expected the step of the range, a positive integer literal.",
+            ParserErrorType::ErrAllocSizeMissing => "Expected the number of bytes to allocate between brackets, as in `alloc(n)`.",
+            ParserErrorType::ErrFreePointerMissing => "Expected the address to free between brackets, as in `free(p)`.",
+            ParserErrorType::ErrBuiltinArgumentsMissing => "Expected the arguments of the function between brackets, as in `arg(1)` or `read_int()`.",
+            ParserErrorType::ErrExternDeclarationInvalid => "Expected the declaration of a C function, as in `extern fn printf(int, ...)`.",
         }
     }
 }
 
 impl Logger for ParserLogger{
     fn new(file_name: String, code: String) -> ParserLogger{
-        ParserLogger{ file_name, source: Source::from(code), errors: vec![] }
+        ParserLogger{ file_name, line_index: LineIndex::new(&code), source: Arc::from(code), errors: vec![], warnings: vec![], recovering: false }
     }
 
     fn report_error(&self, message: &str, span: Span) {
-        let (line_i, row_start, row_end) = (span.m_line, span.m_start, span.m_end);
-        let offset = self.source.line(line_i).expect("Custom Span logic returned wrong line ID").offset();
-        Report::build(ReportKind::Error, (self.file_name.as_str(), offset + row_start..offset + row_end))
-            .with_message(message)
-            .with_label(
-                Label::new((self.file_name.as_str(), offset + row_start..offset + row_end))
-                    .with_message(message)
-                    .with_color(Color::Red),
-            )
-            .finish()
-            .eprint((self.file_name.as_str(), self.source.clone()))
-            .unwrap();
+        self.create_diagnostic(Severity::Error, message, span).report();
+    }
+
+    fn report_warning(&self, message: &str, span: Span) {
+        self.create_diagnostic(Severity::Warning, message, span).report();
     }
 }
\ No newline at end of file
@@ -1,8 +1,9 @@
 use std::sync::{Arc, Mutex};
 use either::{Either, Left, Right};
-use crate::compiler::parser::{NodeArithmeticExpr, NodeStmt, NodeExit, ParserErrorType, ParserLogger, ExpressionFactory, NodeArithmeticOperation, NodeBaseExpr, NodeScope, NodeVariableAssignment};
+use crate::compiler::parser::{NodeArithmeticExpr, NodeStmt, NodeExit, ParserErrorType, ParserLogger, ExpressionFactory, NodeArithmeticOperation, NodeBaseExpr, NodeScope, NodeVariableAssignment, NodeImport, Declaration, ResultType, TypeAnnotation, NodeArrayLiteral, NodeIndex, NodeIndexAssignment, NodeStruct, NodeFieldDeclaration, NodeStructLiteral, NodeField, NodeFieldAssignment, NodeEnum, NodeVariant, NodeVariantLiteral, NodeMatch, NodeMatchArm, NodeFor, NodeFree, NodeExtern, NodeCall};
 use crate::compiler::parser::token_stream::TokenStream;
-use crate::compiler::tokenizer::Token;
+use crate::compiler::span::Span;
+use crate::compiler::tokenizer::{Operator, Token};
 
 pub struct StatementFactory<'a>{
     m_token_stream: &'a mut TokenStream,
@@ -15,10 +16,14 @@ impl<'a> StatementFactory<'a>{
     }
     
     pub fn create(&mut self, stmts: &mut Vec<NodeStmt>){
-        if !self.m_token_stream.is_err_in_stmt(){
-            let _ = self.parse_stmt().map_or((), |stmt| stmts.push(stmt));
-        } else { 
-            self.log_error(ParserErrorType::ErrInvalidStatement, &self.m_token_stream.peek(0).unwrap())
+        if let Some(err) = self.m_token_stream.get_err_in_stmt(){
+            let error = match err {
+                Token::InvalidNumber { .. } => ParserErrorType::ErrInvalidNumber,
+                _ => ParserErrorType::ErrInvalidStatement
+            };
+            self.log_error(error, &err)
+        } else if let Some(stmt) = self.parse_stmt() {
+            stmts.push(stmt);
         }
     }
 
@@ -26,80 +31,692 @@ impl<'a> StatementFactory<'a>{
         if let Some(exit_node) = self.parse_exit(){
             Some(NodeStmt::Exit(exit_node))
         }
+        else if let Some(free_node) = self.parse_free(){
+            Some(NodeStmt::Free(free_node))
+        }
+        else if let Some(call_node) = self.parse_call(){
+            Some(NodeStmt::Call(call_node))
+        }
         else if let Some(variable_assignment) = self.parse_variable_assignment(){
             Some(NodeStmt::ID(variable_assignment))
         }
+        else if let Some(index_assignment) = self.parse_index_assignment(){
+            Some(NodeStmt::Index(index_assignment))
+        }
+        else if let Some(field_assignment) = self.parse_field_assignment(){
+            Some(NodeStmt::Field(field_assignment))
+        }
+        else if let Some(struct_node) = self.parse_struct(){
+            Some(NodeStmt::Struct(struct_node))
+        }
+        else if let Some(enum_node) = self.parse_enum(){
+            Some(NodeStmt::Enum(enum_node))
+        }
+        else if let Some(for_node) = self.parse_for(){
+            Some(NodeStmt::For(Box::new(for_node)))
+        }
         else if let Some(scope_node) = self.parse_scope(){
             Some(NodeStmt::Scope(scope_node))
         }
-        else { None }
+        else if let Some(import_node) = self.parse_import(){
+            Some(NodeStmt::Import(import_node))
+        }
+        else if let Some(extern_node) = self.parse_extern(){
+            Some(NodeStmt::Extern(extern_node))
+        }
+        else {
+            if let Some(token @ Token::ClosedCurlyBracket { .. }) = self.m_token_stream.peek(0) {
+                self.log_error(ParserErrorType::ErrScopeOpenCurlyBracketMissing, &token);
+            }
+            None
+        }
     }
 
     fn parse_exit(&mut self) -> Option<NodeExit>{
         // Check if the first token is 'exit'
-        if !matches!(self.m_token_stream.peek(0), Some(Token::Exit { .. })) {
+        let Some(Token::Exit { span: exit_span }) = self.m_token_stream.peek(0) else {
             return None;
-        }
+        };
         // Check if the second token is an opening parenthesis
-        if !matches!(self.m_token_stream.peek(1), Some(Token::OpenBracket { .. })) {
-            let token = self.m_token_stream.peek(0).unwrap();
-            self.log_error(ParserErrorType::ErrExitOpenBracketMissing, &token);
-            return None;
-        }
+        let open_bracket = match self.m_token_stream.peek(1) {
+            Some(token @ Token::OpenBracket { .. }) => token,
+            _ => {
+                self.log_error_at(ParserErrorType::ErrExitOpenBracketMissing, exit_span);
+                return None;
+            }
+        };
         // Advance past 'exit' and '(' tokens
         self.m_token_stream.advance(2);
 
-        // Parse the arithmetic expression
-        let expr = self.parse_arithmetic_expr();
+        // Parse the arithmetic expression, or the match giving the exit code
+        let expr = if matches!(self.m_token_stream.peek(0), Some(Token::Match { .. })) {
+            self.parse_match().map(NodeArithmeticExpr::Match)
+        } else {
+            self.parse_arithmetic_expr().map(|expr| match expr {
+                Left(operation) => NodeArithmeticExpr::Operation(*operation),
+                Right(base) => NodeArithmeticExpr::Base(base)
+            })
+        };
+        let Some(expr) = expr else {
+            let open_span = open_bracket.get_span();
+            let error_span = match self.m_token_stream.peek(0) {
+                Some(Token::ClosedBracket { span }) => open_span.merge(span),
+                _ => open_span
+            };
+            self.log_error_at(ParserErrorType::ErrExitCodeMissing, error_span);
+            return None;
+        };
 
         // Check for closing parenthesis
-        if !matches!(self.m_token_stream.peek(0), Some(Token::ClosedBracket {..})) {
-            self.log_error(ParserErrorType::ErrExitClosedBracketMissing, &self.m_token_stream.peek_back(1).unwrap());
+        let Some(Token::ClosedBracket { span: close_span }) = self.m_token_stream.peek(0) else {
+            let error_span = self.m_token_stream.peek_back(1).map_or(exit_span, |token| token.get_span());
+            self.log_error_at(ParserErrorType::ErrExitClosedBracketMissing, error_span);
             return None;
-        }
+        };
+        let span = exit_span.merge(close_span);
 
         // Advance past the closing parenthesis
         self.m_token_stream.advance(1);
 
         // Return the parsed NodeExit
-        match expr{
-            Some(Left(operation)) => {Some(NodeExit { expr: NodeArithmeticExpr::Operation(*operation) })}
-            Some(Right(base)) => {Some(NodeExit { expr: NodeArithmeticExpr::Base(base) })}
-            None => {
-                //TODO This should report an error. There should be an exit code passed in between parenthesis
+        Some(NodeExit { expr, span })
+    }
+
+    /// Parses `free(p)`. Any missing part of it is reported as the missing address.
+    fn parse_free(&mut self) -> Option<NodeFree>{
+        let Some(Token::Free { span: free_span }) = self.m_token_stream.peek(0) else {
+            return None;
+        };
+        if !matches!(self.m_token_stream.peek(1), Some(Token::OpenBracket { .. })) {
+            self.log_error_at(ParserErrorType::ErrFreePointerMissing, free_span);
+            return None;
+        }
+        self.m_token_stream.advance(2);
+
+        let Some(pointer) = self.parse_arithmetic_expr().map(|expr| match expr {
+            Left(operation) => NodeArithmeticExpr::Operation(*operation),
+            Right(base) => NodeArithmeticExpr::Base(base)
+        }) else {
+            let error_span = match self.m_token_stream.peek(0) {
+                Some(Token::ClosedBracket { span }) => free_span.merge(span),
+                _ => free_span
+            };
+            self.log_error_at(ParserErrorType::ErrFreePointerMissing, error_span);
+            return None;
+        };
+
+        let Some(Token::ClosedBracket { span: close_span }) = self.m_token_stream.peek(0) else {
+            let error_span = self.m_token_stream.peek_back(1).map_or(free_span, |token| token.get_span());
+            self.log_error_at(ParserErrorType::ErrFreePointerMissing, free_span.merge(error_span));
+            return None;
+        };
+        self.m_token_stream.advance(1);
+        Some(NodeFree { pointer, span: free_span.merge(close_span) })
+    }
+
+    /// Parses a call whose result is discarded, like `printf("%d\n", x)`. The statement is only the call.
+    fn parse_call(&mut self) -> Option<NodeCall>{
+        if !matches!(self.m_token_stream.peek_range(2, true).as_deref(), Some([Token::ID { .. }, Token::Operator(Operator::OpenBracket { .. })])) {
+            return None;
+        }
+        match self.parse_arithmetic_expr()? {
+            Right(NodeBaseExpr::Call(call)) => Some(call),
+            Right(base) => {
+                self.log_error_at(ParserErrorType::ErrInvalidStatement, base.get_span());
+                None
+            }
+            Left(operation) => {
+                self.log_error_at(ParserErrorType::ErrInvalidStatement, operation.get_span());
                 None
             }
         }
     }
 
+    /// Parses `x = value`, or a compound assignment like `x += value` that is desugared into `x = x + (value)`.
+    /// Declarations start with `let`, `var` or `const`, and can't be compound assignments.
+    /// The variable can be annotated with its type, as in `let x: int = value`, and the value can be an array, a struct
+    /// or a variant literal, or a match.
     fn parse_variable_assignment(&mut self) -> Option<NodeVariableAssignment>{
-        if let Some(tokens) = self.m_token_stream.peek_range(2, true){
-            return match &tokens[..2] {
-                [
-                ref id @ Token::ID { .. },           // First token: Identifier
-                Token::Equals { .. },            // Second token: Equals
-                ] => {
-                    self.m_token_stream.advance_skip_tokens(2, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
-                    match self.parse_arithmetic_expr() {
-                        Some(expr) => {
-                            Some(NodeVariableAssignment {
-                                variable: id.clone(),
-                                value: {match expr{
-                                    Left(operation) => {NodeArithmeticExpr::Operation(*operation)}
-                                    Right(base) => {NodeArithmeticExpr::Base(base)}
-                                }
-                                },  // The parsed value as a ArithmeticExpr
-                            })
+        let keyword = self.m_token_stream.peek(0)?;
+        let declaration = match &keyword {
+            Token::Let { span } => Some(Declaration::Let { span: *span }),
+            Token::Var { span } => Some(Declaration::Var { span: *span }),
+            Token::Const { span } => Some(Declaration::Const { span: *span }),
+            _ => None
+        };
+        let skipped = usize::from(declaration.is_some());
+        let tokens = self.m_token_stream.peek_range(skipped + 2, true)?;
+        let (id, annotation, operator, equals, token_count) = match &tokens[skipped..] {
+            [id @ Token::ID { .. }, equals @ Token::Equals { .. }] => (id.clone(), None, None, equals.clone(), 2),
+            [id @ Token::ID { .. }, colon @ Token::Colon { .. }] => {
+                let (annotation, type_length) = self.parse_annotation(skipped + 2, colon)?;
+                match self.m_token_stream.peek_range(skipped + 3 + type_length, true).as_deref().and_then(<[Token]>::last) {
+                    Some(equals @ Token::Equals { .. }) => (id.clone(), Some(annotation), None, equals.clone(), 3 + type_length),
+                    _ if declaration.is_some() => {
+                        self.log_error(ParserErrorType::ErrDeclarationMissing, &keyword);
+                        return None;
+                    }
+                    _ => return None
+                }
+            }
+            [id @ Token::ID { .. }, Token::Operator(operator)] if declaration.is_none() && Self::is_compound_operator(operator) => {
+                match self.parse_compound_equals(1, operator) {
+                    Some(equals) => (id.clone(), None, Some(*operator), equals, 3),
+                    None => return None
+                }
+            }
+            _ if declaration.is_some() => {
+                self.log_error(ParserErrorType::ErrDeclarationMissing, &keyword);
+                return None;
+            }
+            _ => return None
+        };
+        self.m_token_stream.advance_skip_tokens(skipped + token_count, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+        if operator.is_none() && matches!(self.m_token_stream.peek(0), Some(Token::OpenSquareBracket { .. })) {
+            let array = self.parse_array_literal()?;
+            return Some(NodeVariableAssignment { declaration, variable: id, annotation, value: NodeArithmeticExpr::Array(array) });
+        }
+        if operator.is_none() && self.starts_struct_literal() {
+            let literal = self.parse_struct_literal()?;
+            return Some(NodeVariableAssignment { declaration, variable: id, annotation, value: NodeArithmeticExpr::Struct(literal) });
+        }
+        if operator.is_none() && self.starts_variant_literal() {
+            let literal = self.parse_variant_literal()?;
+            return Some(NodeVariableAssignment { declaration, variable: id, annotation, value: NodeArithmeticExpr::Variant(literal) });
+        }
+        if operator.is_none() && matches!(self.m_token_stream.peek(0), Some(Token::Match { .. })) {
+            let node = self.parse_match()?;
+            return Some(NodeVariableAssignment { declaration, variable: id, annotation, value: NodeArithmeticExpr::Match(node) });
+        }
+        let mut expression_factory = ExpressionFactory::new(self.m_token_stream, self.m_logger.clone());
+        let expr = match operator {
+            Some(operator) => expression_factory.create_compound(NodeBaseExpr::ID(id.clone()), &operator),
+            None => expression_factory.create(),
+        };
+        let Some(expr) = expr else {
+            self.log_error(ParserErrorType::ErrExpressionMissing, &equals);
+            return None;
+        };
+        Some(NodeVariableAssignment {
+            declaration,
+            variable: id,
+            annotation,
+            value: match expr {
+                Left(operation) => NodeArithmeticExpr::Operation(*operation),
+                Right(base) => NodeArithmeticExpr::Base(base)
+            }
+        })
+    }
+
+    /// Parses the type of an annotation, starting at the `start`-th token after the whitespaces. Returns it with
+    /// its number of tokens, as the type of an array, like `[int; 8]`, is made of several of them.
+    fn parse_annotation(&self, start: usize, colon: &Token) -> Option<(TypeAnnotation, usize)> {
+        let token = |i: usize| self.m_token_stream.peek_range(start + i + 1, true).and_then(|tokens| tokens.last().cloned());
+        let first = token(0);
+        let element_token = match &first {
+            Some(Token::OpenSquareBracket { .. }) => token(1),
+            _ => first.clone()
+        };
+        let result_type = match &element_token {
+            Some(Token::ID { name, .. }) => ResultType::from_name(name),
+            _ => None
+        };
+        let (Some(result_type), Some(type_token)) = (result_type, element_token.clone()) else {
+            self.log_error(ParserErrorType::ErrTypeMissing, element_token.as_ref().or(first.as_ref()).unwrap_or(colon));
+            return None;
+        };
+        let Some(Token::OpenSquareBracket { span: open_span }) = first else {
+            return Some((TypeAnnotation { result_type, length: None, span: type_token.get_span() }, 1));
+        };
+        let length = match (token(2), token(3)) {
+            (Some(Token::Semicolon { .. }), Some(Token::Number { value, .. })) => {
+                value.parse::<usize>().ok().filter(|length| (1..=NodeArrayLiteral::MAX_LENGTH).contains(length))
+            }
+            _ => None
+        };
+        let Some(length) = length else {
+            let error_token = match token(2) {
+                Some(Token::Semicolon { .. }) => token(3),
+                token => token
+            };
+            self.log_error(ParserErrorType::ErrArrayLengthMissing, error_token.as_ref().unwrap_or(&type_token));
+            return None;
+        };
+        let Some(Token::ClosedSquareBracket { span: close_span }) = token(4) else {
+            self.log_error_at(ParserErrorType::ErrSquareBracketMissing, open_span);
+            return None;
+        };
+        Some((TypeAnnotation { result_type, length: Some(length), span: open_span.merge(close_span) }, 5))
+    }
+
+    /// Parses the elements of `[a, b, c]`, or `[value; length]` where a single value is repeated.
+    fn parse_array_literal(&mut self) -> Option<NodeArrayLiteral>{
+        let Some(Token::OpenSquareBracket { span: open_span }) = self.m_token_stream.peek(0) else {
+            return None;
+        };
+        let mut elements = Vec::new();
+        let mut length = None;
+        let close_span = loop {
+            // Skips the opening square bracket or the comma before the element
+            self.m_token_stream.advance_skip_tokens(1, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+            let Some(element) = self.parse_arithmetic_expr() else {
+                match self.m_token_stream.peek(0) {
+                    Some(token) => self.log_error(ParserErrorType::ErrUnexpectedToken, &token),
+                    None => self.log_error_at(ParserErrorType::ErrSquareBracketMissing, open_span),
+                }
+                return None;
+            };
+            elements.push(match element {
+                Left(operation) => NodeArithmeticExpr::Operation(*operation),
+                Right(base) => NodeArithmeticExpr::Base(base)
+            });
+            match self.m_token_stream.peek(0) {
+                Some(Token::Comma { .. }) => {}
+                Some(semicolon @ Token::Semicolon { .. }) if elements.len() == 1 => {
+                    self.m_token_stream.advance_skip_tokens(1, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+                    let repeat = match self.m_token_stream.peek(0) {
+                        Some(Token::Number { value, .. }) => value.parse::<usize>().ok().filter(|length| (1..=NodeArrayLiteral::MAX_LENGTH).contains(length)),
+                        _ => None
+                    };
+                    let Some(repeat) = repeat else {
+                        self.log_error(ParserErrorType::ErrArrayLengthMissing, &self.m_token_stream.peek(0).unwrap_or(semicolon));
+                        return None;
+                    };
+                    length = Some(repeat);
+                    self.m_token_stream.advance_skip_tokens(1, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+                    let Some(Token::ClosedSquareBracket { span }) = self.m_token_stream.peek(0) else {
+                        self.log_error_at(ParserErrorType::ErrSquareBracketMissing, open_span);
+                        return None;
+                    };
+                    break span;
+                }
+                Some(Token::ClosedSquareBracket { span }) => break span,
+                _ => {
+                    self.log_error_at(ParserErrorType::ErrSquareBracketMissing, open_span);
+                    return None;
+                }
+            }
+        };
+        self.m_token_stream.advance(1);
+        Some(NodeArrayLiteral { length: length.unwrap_or(elements.len()), elements, span: open_span.merge(close_span) })
+    }
+
+    /// Parses `a[i] = value`, or a compound assignment like `a[i] += value`.
+    fn parse_index_assignment(&mut self) -> Option<NodeIndexAssignment>{
+        let (Some(variable @ Token::ID { .. }), Some(Token::OpenSquareBracket { span: open_span })) = (self.m_token_stream.peek(0), self.m_token_stream.peek(1)) else {
+            return None;
+        };
+        self.m_token_stream.advance_skip_tokens(2, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+        let Some(index) = self.parse_arithmetic_expr() else {
+            let error_token = self.m_token_stream.peek(0).unwrap_or(Token::OpenSquareBracket { span: open_span });
+            self.log_error(ParserErrorType::ErrUnexpectedToken, &error_token);
+            return None;
+        };
+        let Some(Token::ClosedSquareBracket { span: close_span }) = self.m_token_stream.peek(0) else {
+            self.log_error_at(ParserErrorType::ErrSquareBracketMissing, open_span);
+            return None;
+        };
+        self.m_token_stream.advance_skip_tokens(1, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+        let element = NodeIndex {
+            span: variable.get_span().merge(close_span),
+            variable,
+            index: Box::new(match index {
+                Left(operation) => NodeArithmeticExpr::Operation(*operation),
+                Right(base) => NodeArithmeticExpr::Base(base)
+            }),
+        };
+        let (operator, equals) = match self.m_token_stream.peek(0) {
+            Some(equals @ Token::Equals { .. }) => (None, Some(equals)),
+            Some(Token::Operator(operator)) if Self::is_compound_operator(&operator) => (Some(operator), self.parse_compound_equals(0, &operator)),
+            _ => (None, None)
+        };
+        let Some(equals) = equals else {
+            // The element is read without being part of a statement
+            let error_span = self.m_token_stream.peek(0).map_or(element.span, |token| token.get_span());
+            self.log_error_at(ParserErrorType::ErrUnexpectedToken, error_span);
+            return None;
+        };
+        let token_count = if operator.is_some() { 2 } else { 1 };
+        self.m_token_stream.advance_skip_tokens(token_count, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+        let mut expression_factory = ExpressionFactory::new(self.m_token_stream, self.m_logger.clone());
+        let expr = match operator {
+            Some(operator) => expression_factory.create_compound(NodeBaseExpr::Index(element.clone()), &operator),
+            None => expression_factory.create(),
+        };
+        let Some(expr) = expr else {
+            self.log_error(ParserErrorType::ErrExpressionMissing, &equals);
+            return None;
+        };
+        Some(NodeIndexAssignment {
+            element,
+            value: match expr {
+                Left(operation) => NodeArithmeticExpr::Operation(*operation),
+                Right(base) => NodeArithmeticExpr::Base(base)
+            }
+        })
+    }
+
+    /// Parses `p.x = value`, or a compound assignment like `p.x += value`. A field holding a struct can be given a struct literal.
+    fn parse_field_assignment(&mut self) -> Option<NodeFieldAssignment>{
+        let (Some(variable @ Token::ID { .. }), Some(Token::Operator(Operator::Field { .. }))) = (self.m_token_stream.peek(0), self.m_token_stream.peek(1)) else {
+            return None;
+        };
+        self.m_token_stream.advance(1);
+        let mut field = NodeField { span: variable.get_span(), variable, fields: Vec::new() };
+        while let Some(Token::Operator(Operator::Field { span: dot_span })) = self.m_token_stream.peek(0) {
+            self.m_token_stream.advance_skip_tokens(1, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+            let Some(name @ Token::ID { .. }) = self.m_token_stream.peek(0) else {
+                self.log_error_at(ParserErrorType::ErrFieldMissing, dot_span);
+                return None;
+            };
+            field.span = field.span.merge(name.get_span());
+            field.fields.push(name);
+            self.m_token_stream.advance_skip_tokens(1, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+        }
+        let (operator, equals) = match self.m_token_stream.peek(0) {
+            Some(equals @ Token::Equals { .. }) => (None, Some(equals)),
+            Some(Token::Operator(operator)) if Self::is_compound_operator(&operator) => (Some(operator), self.parse_compound_equals(0, &operator)),
+            _ => (None, None)
+        };
+        let Some(equals) = equals else {
+            // The field is read without being part of a statement
+            let error_span = self.m_token_stream.peek(0).map_or(field.span, |token| token.get_span());
+            self.log_error_at(ParserErrorType::ErrUnexpectedToken, error_span);
+            return None;
+        };
+        let token_count = if operator.is_some() { 2 } else { 1 };
+        self.m_token_stream.advance_skip_tokens(token_count, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+        if operator.is_none() && self.starts_struct_literal() {
+            let literal = self.parse_struct_literal()?;
+            return Some(NodeFieldAssignment { field, value: NodeArithmeticExpr::Struct(literal) });
+        }
+        let mut expression_factory = ExpressionFactory::new(self.m_token_stream, self.m_logger.clone());
+        let expr = match operator {
+            Some(operator) => expression_factory.create_compound(NodeBaseExpr::Field(field.clone()), &operator),
+            None => expression_factory.create(),
+        };
+        let Some(expr) = expr else {
+            self.log_error(ParserErrorType::ErrExpressionMissing, &equals);
+            return None;
+        };
+        Some(NodeFieldAssignment {
+            field,
+            value: match expr {
+                Left(operation) => NodeArithmeticExpr::Operation(*operation),
+                Right(base) => NodeArithmeticExpr::Base(base)
+            }
+        })
+    }
+
+    /// Parses `struct Name { field: type, ... }`. A struct has at least one field, and a comma can follow the last one.
+    fn parse_struct(&mut self) -> Option<NodeStruct>{
+        let Some(keyword @ Token::Struct { .. }) = self.m_token_stream.peek(0) else {
+            return None;
+        };
+        let (name, open_span) = match self.m_token_stream.peek_range(3, true).as_deref() {
+            Some([_, name @ Token::ID { .. }, Token::OpenCurlyBracket { span }]) => (name.clone(), *span),
+            _ => {
+                self.log_error(ParserErrorType::ErrStructBodyMissing, &keyword);
+                return None;
+            }
+        };
+        self.m_token_stream.advance_skip_tokens(3, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+        let mut fields = Vec::new();
+        let close_span = loop {
+            match self.m_token_stream.peek_range(3, true).as_deref() {
+                Some([name @ Token::ID { .. }, Token::Colon { .. }, type_name @ Token::ID { .. }]) => {
+                    fields.push(NodeFieldDeclaration { name: name.clone(), type_name: type_name.clone() });
+                }
+                _ => {
+                    self.log_item_error(ParserErrorType::ErrFieldDeclarationMissing, ParserErrorType::ErrStructBodyMissing, open_span);
+                    return None;
+                }
+            }
+            self.m_token_stream.advance_skip_tokens(3, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+            if let Some(close_span) = self.parse_separator(open_span, ParserErrorType::ErrStructBodyMissing)? {
+                break close_span;
+            }
+        };
+        self.m_token_stream.advance(1);
+        Some(NodeStruct { name, fields, span: keyword.get_span().merge(close_span) })
+    }
+
+    /// Whether the next tokens open a struct literal, as in `Point {`.
+    fn starts_struct_literal(&self) -> bool {
+        matches!(self.m_token_stream.peek_range(2, true).as_deref(), Some([Token::ID { .. }, Token::OpenCurlyBracket { .. }]))
+    }
+
+    /// Parses `Name { field: value, ... }`, where the value of a field can itself be a struct literal.
+    fn parse_struct_literal(&mut self) -> Option<NodeStructLiteral>{
+        let (name, open_span) = match self.m_token_stream.peek_range(2, true).as_deref() {
+            Some([name @ Token::ID { .. }, Token::OpenCurlyBracket { span }]) => (name.clone(), *span),
+            _ => return None
+        };
+        self.m_token_stream.advance_skip_tokens(2, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+        let mut fields = Vec::new();
+        let close_span = loop {
+            let field = match self.m_token_stream.peek_range(2, true).as_deref() {
+                Some([field @ Token::ID { .. }, Token::Colon { .. }]) => field.clone(),
+                _ => {
+                    self.log_item_error(ParserErrorType::ErrFieldDeclarationMissing, ParserErrorType::ErrStructBodyMissing, open_span);
+                    return None;
+                }
+            };
+            self.m_token_stream.advance_skip_tokens(2, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+            let value = if self.starts_struct_literal() {
+                NodeArithmeticExpr::Struct(self.parse_struct_literal()?)
+            } else {
+                match self.parse_arithmetic_expr() {
+                    Some(Left(operation)) => NodeArithmeticExpr::Operation(*operation),
+                    Some(Right(base)) => NodeArithmeticExpr::Base(base),
+                    None => {
+                        match self.m_token_stream.peek(0) {
+                            Some(token) => self.log_error(ParserErrorType::ErrUnexpectedToken, &token),
+                            None => self.log_error_at(ParserErrorType::ErrStructBodyMissing, open_span),
                         }
-                        None => None, //TODO Handle the error if the last token is not a valid PrimaryExpr
+                        return None;
                     }
                 }
+            };
+            fields.push((field, value));
+            if let Some(close_span) = self.parse_separator(open_span, ParserErrorType::ErrStructBodyMissing)? {
+                break close_span;
+            }
+        };
+        self.m_token_stream.advance_skip_tokens(1, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+        Some(NodeStructLiteral { span: name.get_span().merge(close_span), name, fields })
+    }
+
+    /// Parses `enum Name { Variant(type, ...), ... }`. An enum has at least one variant, and a variant without values has no parentheses.
+    fn parse_enum(&mut self) -> Option<NodeEnum>{
+        let Some(keyword @ Token::Enum { .. }) = self.m_token_stream.peek(0) else {
+            return None;
+        };
+        let (name, open_span) = match self.m_token_stream.peek_range(3, true).as_deref() {
+            Some([_, name @ Token::ID { .. }, Token::OpenCurlyBracket { span }]) => (name.clone(), *span),
+            _ => {
+                self.log_error(ParserErrorType::ErrEnumBodyMissing, &keyword);
+                return None;
+            }
+        };
+        self.m_token_stream.advance_skip_tokens(3, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+        let mut variants = Vec::new();
+        let close_span = loop {
+            let Some(variant @ Token::ID { .. }) = self.m_token_stream.peek(0) else {
+                self.log_item_error(ParserErrorType::ErrVariantMissing, ParserErrorType::ErrEnumBodyMissing, open_span);
+                return None;
+            };
+            self.m_token_stream.advance_skip_tokens(1, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+            let types = match self.m_token_stream.peek(0) {
+                Some(Token::Operator(Operator::OpenBracket { span })) => self.parse_parenthesized(span, Self::parse_name)?.0,
+                _ => Vec::new()
+            };
+            variants.push(NodeVariant { name: variant, types });
+            if let Some(close_span) = self.parse_separator(open_span, ParserErrorType::ErrEnumBodyMissing)? {
+                break close_span;
+            }
+        };
+        self.m_token_stream.advance(1);
+        Some(NodeEnum { name, variants, span: keyword.get_span().merge(close_span) })
+    }
+
+    /// Whether the next tokens start a variant literal, as in `Shape::`.
+    fn starts_variant_literal(&self) -> bool {
+        matches!(self.m_token_stream.peek_range(2, true).as_deref(), Some([Token::ID { .. }, Token::DoubleColon { .. }]))
+    }
+
+    /// Parses `Enum::Variant(value, ...)`, or `Enum::Variant` when the variant holds no value.
+    fn parse_variant_literal(&mut self) -> Option<NodeVariantLiteral>{
+        let (enum_name, variant) = match self.m_token_stream.peek_range(3, true).as_deref() {
+            Some([enum_name @ Token::ID { .. }, _, variant @ Token::ID { .. }]) => (enum_name.clone(), variant.clone()),
+            Some([_, double_colon, ..]) => {
+                self.log_error(ParserErrorType::ErrVariantMissing, double_colon);
+                return None;
+            }
+            _ => return None
+        };
+        self.m_token_stream.advance_skip_tokens(3, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+        let (values, end_span) = match self.m_token_stream.peek(0) {
+            Some(Token::OpenBracket { span }) => self.parse_parenthesized(span, |factory| {
+                factory.parse_arithmetic_expr().map(|expr| match expr {
+                    Left(operation) => NodeArithmeticExpr::Operation(*operation),
+                    Right(base) => NodeArithmeticExpr::Base(base)
+                })
+            })?,
+            _ => (Vec::new(), variant.get_span())
+        };
+        Some(NodeVariantLiteral { span: enum_name.get_span().merge(end_span), enum_name, variant, values })
+    }
+
+    /// Parses `match variable { Variant(binding, ...) => value, _ => value }`. A match has at least one arm.
+    fn parse_match(&mut self) -> Option<NodeMatch>{
+        let Some(keyword @ Token::Match { .. }) = self.m_token_stream.peek(0) else {
+            return None;
+        };
+        let (variable, open_span) = match self.m_token_stream.peek_range(3, true).as_deref() {
+            Some([_, variable @ Token::ID { .. }, Token::OpenCurlyBracket { span }]) => (variable.clone(), *span),
+            _ => {
+                self.log_error(ParserErrorType::ErrMatchVariableMissing, &keyword);
+                return None;
+            }
+        };
+        self.m_token_stream.advance_skip_tokens(3, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+        let mut arms = Vec::new();
+        let close_span = loop {
+            let Some(variant @ (Token::ID { .. } | Token::Underscore { .. })) = self.m_token_stream.peek(0) else {
+                self.log_item_error(ParserErrorType::ErrMatchArmMissing, ParserErrorType::ErrMatchArmMissing, open_span);
+                return None;
+            };
+            self.m_token_stream.advance_skip_tokens(1, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+            let bindings = match self.m_token_stream.peek(0) {
+                Some(Token::Operator(Operator::OpenBracket { span })) if matches!(variant, Token::ID { .. }) => self.parse_parenthesized(span, Self::parse_binding)?.0,
+                _ => Vec::new()
+            };
+            if !matches!(self.m_token_stream.peek(0), Some(Token::Arrow { .. })) {
+                self.log_item_error(ParserErrorType::ErrMatchArmMissing, ParserErrorType::ErrMatchArmMissing, open_span);
+                return None;
+            }
+            self.m_token_stream.advance_skip_tokens(1, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+            let value = match self.parse_arithmetic_expr() {
+                Some(Left(operation)) => NodeArithmeticExpr::Operation(*operation),
+                Some(Right(base)) => NodeArithmeticExpr::Base(base),
+                None => {
+                    self.log_item_error(ParserErrorType::ErrMatchArmMissing, ParserErrorType::ErrMatchArmMissing, open_span);
+                    return None;
+                }
+            };
+            arms.push(NodeMatchArm { variant, bindings, value });
+            if let Some(close_span) = self.parse_separator(open_span, ParserErrorType::ErrMatchArmMissing)? {
+                break close_span;
+            }
+        };
+        self.m_token_stream.advance_skip_tokens(1, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+        Some(NodeMatch { variable, arms, span: keyword.get_span().merge(close_span) })
+    }
+
+    /// Parses the items between the parentheses opened at `open_span`, separated by commas, and returns them with the span
+    /// of the closing parenthesis.
+    fn parse_parenthesized<T>(&mut self, open_span: Span, mut parse_item: impl FnMut(&mut Self) -> Option<T>) -> Option<(Vec<T>, Span)> {
+        let mut items = Vec::new();
+        let close_span = loop {
+            // Skips the opening parenthesis or the comma before the item
+            self.m_token_stream.advance_skip_tokens(1, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+            let Some(item) = parse_item(self) else {
+                self.log_item_error(ParserErrorType::ErrVariantMissing, ParserErrorType::ErrVariantMissing, open_span);
+                return None;
+            };
+            items.push(item);
+            match self.m_token_stream.peek(0) {
+                Some(Token::Comma { .. }) => {}
+                Some(Token::ClosedBracket { span } | Token::Operator(Operator::ClosedBracket { span })) => break span,
                 _ => {
-                    None
+                    self.log_item_error(ParserErrorType::ErrVariantMissing, ParserErrorType::ErrVariantMissing, open_span);
+                    return None;
                 }
             }
+        };
+        self.m_token_stream.advance_skip_tokens(1, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+        Some((items, close_span))
+    }
+
+    /// Parses a binding of a match arm, which is `_` when its value isn't used.
+    fn parse_binding(&mut self) -> Option<Token> {
+        let binding @ (Token::ID { .. } | Token::Underscore { .. }) = self.m_token_stream.peek(0)? else {
+            return None;
+        };
+        self.m_token_stream.advance_skip_tokens(1, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+        Some(binding)
+    }
+
+    /// Parses the type of a value of a variant.
+    fn parse_name(&mut self) -> Option<Token> {
+        let name @ Token::ID { .. } = self.m_token_stream.peek(0)? else {
+            return None;
+        };
+        self.m_token_stream.advance_skip_tokens(1, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+        Some(name)
+    }
+
+    /// Skips the comma after a field, a variant or an arm, returning the span of the closing curly bracket when it ends them.
+    /// Returns `None`, after logging `body_error`, when neither of them follows.
+    fn parse_separator(&mut self, open_span: Span, body_error: ParserErrorType) -> Option<Option<Span>> {
+        match self.m_token_stream.peek(0) {
+            Some(Token::Comma { .. }) => {
+                self.m_token_stream.advance_skip_tokens(1, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+                match self.m_token_stream.peek(0) {
+                    Some(Token::ClosedCurlyBracket { span }) => Some(Some(span)),
+                    _ => Some(None)
+                }
+            }
+            Some(Token::ClosedCurlyBracket { span }) => Some(Some(span)),
+            _ => {
+                self.log_error_at(body_error, open_span);
+                None
+            }
+        }
+    }
+
+    /// Reports a malformed field, variant or arm, or `body_error` at the opening bracket when the line ends instead.
+    fn log_item_error(&self, error: ParserErrorType, body_error: ParserErrorType, open_span: Span) {
+        match self.m_token_stream.peek(0) {
+            Some(token) => self.log_error(error, &token),
+            None => self.log_error_at(body_error, open_span),
         }
-        None
+    }
+
+    /// The `=` of a compound assignment, whose operator is the `position`-th token, ignoring whitespaces.
+    /// The operator and the `=` are written without space between them.
+    fn parse_compound_equals(&self, position: usize, operator: &Operator) -> Option<Token> {
+        match self.m_token_stream.peek_range(position + 2, true).as_deref().and_then(<[Token]>::last) {
+            Some(equals @ Token::Equals { span }) if operator.get_span().m_end == span.m_start => Some(equals.clone()),
+            _ => None
+        }
+    }
+
+    fn is_compound_operator(operator: &Operator) -> bool {
+        matches!(operator, Operator::Plus { .. } | Operator::Minus { .. } | Operator::Multiplication { .. } | Operator::Division { .. }
+            | Operator::Modulus { .. } | Operator::Exponent { .. } | Operator::BitAnd { .. } | Operator::BitOr { .. }
+            | Operator::BitXor { .. } | Operator::ShiftLeft { .. } | Operator::ShiftRight { .. })
     }
 
     fn parse_scope(&mut self) -> Option<NodeScope>{
@@ -111,7 +728,7 @@ impl<'a> StatementFactory<'a>{
         self.m_token_stream.advance_stmt(true);
         let mut stmts = Vec::new();
         //TODO Rewrite this section (from while to the if after)
-        while !matches!(self.m_token_stream.peek(0), Some(Token::ClosedCurlyBracket { .. })) && !matches!(self.m_token_stream.peek(0), None) {
+        while !matches!(self.m_token_stream.peek(0), Some(Token::ClosedCurlyBracket { .. })) && self.m_token_stream.peek(0).is_some() {
             if let Some(stmt) = self.parse_stmt() {
                 stmts.push(stmt);
             }
@@ -119,22 +736,148 @@ impl<'a> StatementFactory<'a>{
                 self.m_token_stream.advance_stmt(true);
             }
         }
-        if let Some(Token::ClosedCurlyBracket { .. }) = self.m_token_stream.peek(0) {
+        if let Some(Token::ClosedCurlyBracket { span }) = self.m_token_stream.peek(0) {
             self.m_token_stream.advance(1);
-            return Some(NodeScope { stmts })
+            return Some(NodeScope { stmts, span: jump_back.merge(span) })
         }
-        self.log_error(ParserErrorType::ErrScopeClosesCurlyBracketMissing, &Token::OpenCurlyBracket { span: jump_back });
+        // The error covers the whole unclosed scope, from the '{' to the end of the source.
+        let scope_span = self.m_token_stream.get_last_span().map_or(jump_back, |last| jump_back.merge(last));
+        self.log_error_at(ParserErrorType::ErrScopeClosesCurlyBracketMissing, scope_span);
         None
     }
 
+    /// Parses `for i in start..end { ... }`, where the range is written `start..=end` when it includes its end and can be
+    /// followed by `step` and a positive integer literal. The body starts on the line of the range, like a scope.
+    fn parse_for(&mut self) -> Option<NodeFor>{
+        let Some(keyword @ Token::For { .. }) = self.m_token_stream.peek(0) else {
+            return None;
+        };
+        let variable = match self.m_token_stream.peek_range(3, true).as_deref() {
+            Some([_, variable @ (Token::ID { .. } | Token::Underscore { .. }), Token::In { .. }]) => variable.clone(),
+            _ => {
+                self.log_error(ParserErrorType::ErrForRangeMissing, &keyword);
+                return None;
+            }
+        };
+        self.m_token_stream.advance_skip_tokens(3, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+        let start = self.parse_range_bound(&keyword)?;
+        let inclusive = match self.m_token_stream.peek(0) {
+            Some(Token::DotDot { .. }) => false,
+            Some(Token::DotDotEquals { .. }) => true,
+            token => {
+                self.log_error(ParserErrorType::ErrForRangeMissing, token.as_ref().unwrap_or(&keyword));
+                return None;
+            }
+        };
+        self.m_token_stream.advance_skip_tokens(1, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+        let end = self.parse_range_bound(&keyword)?;
+        let step = match self.m_token_stream.peek(0) {
+            Some(step_token @ Token::Step { .. }) => {
+                self.m_token_stream.advance_skip_tokens(1, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+                match self.m_token_stream.peek(0) {
+                    Some(Token::Number { value, span }) if value.parse::<i64>().is_ok_and(|step| step > 0) => {
+                        self.m_token_stream.advance_skip_tokens(1, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+                        Some(Token::Number { value, span })
+                    }
+                    token => {
+                        self.log_error(ParserErrorType::ErrStepMissing, token.as_ref().unwrap_or(&step_token));
+                        return None;
+                    }
+                }
+            }
+            _ => None
+        };
+        if let Some(token) = self.m_token_stream.peek(0) {
+            self.log_error(ParserErrorType::ErrUnexpectedToken, &token);
+            return None;
+        }
+        if !matches!(self.m_token_stream.peek_next_stmt(), Some(Token::OpenCurlyBracket { .. })) {
+            let header_span = keyword.get_span().merge(end.get_span());
+            self.log_error_at(ParserErrorType::ErrForBodyMissing, step.as_ref().map_or(header_span, |step| header_span.merge(step.get_span())));
+            return None;
+        }
+        self.m_token_stream.advance_stmt(false);
+        let body = self.parse_scope()?;
+        Some(NodeFor { span: keyword.get_span().merge(body.span), variable, start, end, inclusive, step, body })
+    }
+
+    /// Parses a bound of the range of a loop, reporting a missing one at the `for` keyword when the line ends.
+    fn parse_range_bound(&mut self, keyword: &Token) -> Option<NodeArithmeticExpr> {
+        match self.parse_arithmetic_expr() {
+            Some(Left(operation)) => Some(NodeArithmeticExpr::Operation(*operation)),
+            Some(Right(base)) => Some(NodeArithmeticExpr::Base(base)),
+            None => {
+                self.log_error(ParserErrorType::ErrForRangeMissing, &self.m_token_stream.peek(0).unwrap_or(keyword.clone()));
+                None
+            }
+        }
+    }
+
+    fn parse_import(&mut self) -> Option<NodeImport>{
+        let Some(import_token @ Token::Import { .. }) = self.m_token_stream.peek(0) else {
+            return None;
+        };
+        self.m_token_stream.advance_skip_tokens(1, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+        let Some(Token::StringLiteral { value, span }) = self.m_token_stream.peek(0) else {
+            self.log_error(ParserErrorType::ErrImportPathMissing, &import_token);
+            return None;
+        };
+        self.m_token_stream.advance(1);
+        Some(NodeImport { path: value, span: import_token.get_span().merge(span) })
+    }
+
+    /// Parses `extern fn name(type, ...)`, declaring a C function. Every parameter is a type, and `...` comes last.
+    fn parse_extern(&mut self) -> Option<NodeExtern>{
+        let Some(keyword @ Token::Extern { .. }) = self.m_token_stream.peek(0) else {
+            return None;
+        };
+        let name = match self.m_token_stream.peek_range(4, true).as_deref() {
+            Some([_, Token::Fn { .. }, name @ Token::ID { .. }, Token::Operator(Operator::OpenBracket { .. })]) => name.clone(),
+            _ => {
+                self.log_error(ParserErrorType::ErrExternDeclarationInvalid, &keyword);
+                return None;
+            }
+        };
+        self.m_token_stream.advance_skip_tokens(4, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+        let mut parameters = Vec::new();
+        let mut variadic = false;
+        let close_span = loop {
+            match self.m_token_stream.peek(0) {
+                Some(Token::Operator(Operator::ClosedBracket { span })) if parameters.is_empty() && !variadic => break span,
+                Some(parameter @ Token::ID { .. }) if !variadic => parameters.push(parameter),
+                Some(Token::Ellipsis { .. }) if !variadic => variadic = true,
+                token => {
+                    self.log_error(ParserErrorType::ErrExternDeclarationInvalid, &token.unwrap_or(keyword));
+                    return None;
+                }
+            }
+            self.m_token_stream.advance_skip_tokens(1, true, |token| matches!(token, Some(Token::WhiteSpace {..})));
+            match self.m_token_stream.peek(0) {
+                Some(Token::Comma { .. }) => self.m_token_stream.advance_skip_tokens(1, true, |token| matches!(token, Some(Token::WhiteSpace {..}))),
+                Some(Token::Operator(Operator::ClosedBracket { span })) => break span,
+                token => {
+                    self.log_error(ParserErrorType::ErrExternDeclarationInvalid, &token.unwrap_or(keyword));
+                    return None;
+                }
+            }
+        };
+        self.m_token_stream.advance(1);
+        Some(NodeExtern { name, parameters, variadic, span: keyword.get_span().merge(close_span) })
+    }
+
     fn parse_arithmetic_expr(&mut self) -> Option<Either<Box<NodeArithmeticOperation>, NodeBaseExpr>> {
-        ExpressionFactory::new(&mut self.m_token_stream, self.m_logger.clone()).create()
+        ExpressionFactory::new(self.m_token_stream, self.m_logger.clone()).create()
     }
 
     fn log_error(&self, error: ParserErrorType, token: &Token){
         let mut logger = self.m_logger.lock().unwrap();
         logger.log_error(error, token);
     }
+
+    fn log_error_at(&self, error: ParserErrorType, span: Span){
+        let mut logger = self.m_logger.lock().unwrap();
+        logger.log_error_at(error, span);
+    }
 }
 
 
@@ -143,7 +886,6 @@ impl<'a> StatementFactory<'a>{
 mod test_statement_factory{
     use crate::compiler::logger::Logger;
     use crate::compiler::parser::nodes::ResultType;
-    use crate::compiler::span::Span;
     use crate::compiler::tokenizer::Operator;
     use super::*;
 
@@ -191,19 +933,20 @@ mod test_statement_factory{
     
     #[test]
     fn test_missing_exit_code(){
-        let dummy_span = Span::new(0, 0, 0);
         let logger = setup_logger();
         let mut token_stream = TokenStream::new(vec![
-            Token::Exit {span: dummy_span},
-            Token::OpenBracket {span: dummy_span},
-            Token::ClosedBracket {span: dummy_span}],
+            Token::Exit {span: Span::new(0, 0, 3)},
+            Token::OpenBracket {span: Span::new(0, 4, 4)},
+            Token::ClosedBracket {span: Span::new(0, 5, 5)}],
                                                 logger.clone()
         );
-        let mut factory = StatementFactory::new(&mut token_stream, logger);
+        let mut factory = StatementFactory::new(&mut token_stream, logger.clone());
         let res : &mut Vec<NodeStmt> = &mut Vec::new();
 
         factory.create(res);
         assert!(res.is_empty());
+        let exp_error = (ParserErrorType::ErrExitCodeMissing.message().to_string(), Span::new(0, 4, 5));
+        assert_eq!(logger.lock().unwrap().get_errors(), &[exp_error]);
     }
     
     #[test]
@@ -256,7 +999,9 @@ mod test_statement_factory{
         factory.create(res);
         let exp_stmt: &mut  Vec<NodeStmt> = &mut vec![
             NodeStmt::ID(NodeVariableAssignment{
+                declaration: None,
                 variable: Token::ID { name: "x".to_string(), span: dummy_span},
+                annotation: None,
                 value: NodeArithmeticExpr::Base(NodeBaseExpr::Num(
                             Token::Number { value: 1.to_string(), span: dummy_span }
                         )
@@ -284,18 +1029,184 @@ mod test_statement_factory{
         factory.create(res);
         let exp_stmt: &mut  Vec<NodeStmt> = &mut vec![
             NodeStmt::ID(NodeVariableAssignment{
+                declaration: None,
                 variable: Token::ID { name: "x".to_string(), span: dummy_span},
+                annotation: None,
                 value: NodeArithmeticExpr::Operation(NodeArithmeticOperation{
                     lhs: Right(NodeBaseExpr::Num(Token::Number { value: 1.to_string(), span: dummy_span })),
                     rhs: Right(NodeBaseExpr::Num(Token::Number { value: 1.to_string(), span: dummy_span })),
                     op: Operator::Plus {span: dummy_span},
                     result_type: ResultType::Numeric,
+                    span: Span::default(),
                 })
             }
             )];
         assert_eq!(res, exp_stmt);
     }
     
+    #[test]
+    fn test_compound_assignment(){
+        let logger = setup_logger();
+        // x **= 2
+        let mut token_stream = TokenStream::new(vec![
+            Token::ID { name: "x".to_string(), span: Span::new(0, 0, 1)},
+            Token::WhiteSpace {span: Span::new(0, 1, 2)},
+            Token::Operator(Operator::Exponent {span: Span::new(0, 2, 4)}),
+            Token::Equals {span: Span::new(0, 4, 5)},
+            Token::WhiteSpace {span: Span::new(0, 5, 6)},
+            Token::Number { value: 2.to_string(), span: Span::new(0, 6, 7) }],
+            logger.clone()
+        );
+        let mut factory = StatementFactory::new(&mut token_stream, logger);
+        let res : &mut Vec<NodeStmt> = &mut Vec::new();
+
+        factory.create(res);
+        let exp_stmt: &mut  Vec<NodeStmt> = &mut vec![
+            NodeStmt::ID(NodeVariableAssignment{
+                declaration: None,
+                variable: Token::ID { name: "x".to_string(), span: Span::new(0, 0, 1)},
+                annotation: None,
+                value: NodeArithmeticExpr::Operation(NodeArithmeticOperation{
+                    lhs: Right(NodeBaseExpr::ID(Token::ID { name: "x".to_string(), span: Span::new(0, 0, 1) })),
+                    rhs: Right(NodeBaseExpr::Num(Token::Number { value: 2.to_string(), span: Span::new(0, 6, 7) })),
+                    op: Operator::Exponent {span: Span::new(0, 2, 4)},
+                    result_type: ResultType::Numeric,
+                    span: Span::new(0, 0, 7),
+                })
+            }
+            )];
+        assert_eq!(res, exp_stmt);
+    }
+
+    #[test]
+    fn test_spaced_compound_assignment(){
+        let logger = setup_logger();
+        // x + = 2
+        let mut token_stream = TokenStream::new(vec![
+            Token::ID { name: "x".to_string(), span: Span::new(0, 0, 1)},
+            Token::WhiteSpace {span: Span::new(0, 1, 2)},
+            Token::Operator(Operator::Plus {span: Span::new(0, 2, 3)}),
+            Token::WhiteSpace {span: Span::new(0, 3, 4)},
+            Token::Equals {span: Span::new(0, 4, 5)},
+            Token::WhiteSpace {span: Span::new(0, 5, 6)},
+            Token::Number { value: 2.to_string(), span: Span::new(0, 6, 7) }],
+            logger.clone()
+        );
+        let mut factory = StatementFactory::new(&mut token_stream, logger);
+        let res : &mut Vec<NodeStmt> = &mut Vec::new();
+
+        factory.create(res);
+        assert!(res.is_empty());
+    }
+    
+    #[test]
+    fn test_declaration(){
+        let logger = setup_logger();
+        // const x = 2
+        let mut token_stream = TokenStream::new(vec![
+            Token::Const {span: Span::new(0, 0, 5)},
+            Token::WhiteSpace {span: Span::new(0, 5, 6)},
+            Token::ID { name: "x".to_string(), span: Span::new(0, 6, 7)},
+            Token::WhiteSpace {span: Span::new(0, 7, 8)},
+            Token::Equals {span: Span::new(0, 8, 9)},
+            Token::WhiteSpace {span: Span::new(0, 9, 10)},
+            Token::Number { value: 2.to_string(), span: Span::new(0, 10, 11) }],
+            logger.clone()
+        );
+        let mut factory = StatementFactory::new(&mut token_stream, logger);
+        let res : &mut Vec<NodeStmt> = &mut Vec::new();
+
+        factory.create(res);
+        let exp_stmt = NodeVariableAssignment{
+            declaration: Some(Declaration::Const {span: Span::new(0, 0, 5)}),
+            variable: Token::ID { name: "x".to_string(), span: Span::new(0, 6, 7)},
+            annotation: None,
+            value: NodeArithmeticExpr::Base(NodeBaseExpr::Num(Token::Number { value: 2.to_string(), span: Span::new(0, 10, 11) }))
+        };
+        assert_eq!(exp_stmt.get_span(), Span::new(0, 0, 11));
+        assert_eq!(res, &vec![NodeStmt::ID(exp_stmt)]);
+    }
+
+    #[test]
+    fn test_compound_declaration(){
+        let logger = setup_logger();
+        // let x += 2
+        let mut token_stream = TokenStream::new(vec![
+            Token::Let {span: Span::new(0, 0, 3)},
+            Token::WhiteSpace {span: Span::new(0, 3, 4)},
+            Token::ID { name: "x".to_string(), span: Span::new(0, 4, 5)},
+            Token::WhiteSpace {span: Span::new(0, 5, 6)},
+            Token::Operator(Operator::Plus {span: Span::new(0, 6, 7)}),
+            Token::Equals {span: Span::new(0, 7, 8)},
+            Token::WhiteSpace {span: Span::new(0, 8, 9)},
+            Token::Number { value: 2.to_string(), span: Span::new(0, 9, 10) }],
+            logger.clone()
+        );
+        let mut factory = StatementFactory::new(&mut token_stream, logger.clone());
+        let res : &mut Vec<NodeStmt> = &mut Vec::new();
+
+        factory.create(res);
+        assert!(res.is_empty());
+        let exp_error = (ParserErrorType::ErrDeclarationMissing.message().to_string(), Span::new(0, 0, 3));
+        assert_eq!(logger.lock().unwrap().get_errors(), &[exp_error]);
+    }
+
+    #[test]
+    fn test_annotation(){
+        let logger = setup_logger();
+        // let b: bool = true
+        let mut token_stream = TokenStream::new(vec![
+            Token::Let {span: Span::new(0, 0, 3)},
+            Token::WhiteSpace {span: Span::new(0, 3, 4)},
+            Token::ID { name: "b".to_string(), span: Span::new(0, 4, 5)},
+            Token::Colon {span: Span::new(0, 5, 6)},
+            Token::WhiteSpace {span: Span::new(0, 6, 7)},
+            Token::ID { name: "bool".to_string(), span: Span::new(0, 7, 11)},
+            Token::WhiteSpace {span: Span::new(0, 11, 12)},
+            Token::Equals {span: Span::new(0, 12, 13)},
+            Token::WhiteSpace {span: Span::new(0, 13, 14)},
+            Token::Boolean { value: true, span: Span::new(0, 14, 18) }],
+            logger.clone()
+        );
+        let mut factory = StatementFactory::new(&mut token_stream, logger);
+        let res : &mut Vec<NodeStmt> = &mut Vec::new();
+
+        factory.create(res);
+        let exp_stmt = NodeVariableAssignment{
+            declaration: Some(Declaration::Let {span: Span::new(0, 0, 3)}),
+            variable: Token::ID { name: "b".to_string(), span: Span::new(0, 4, 5)},
+            annotation: Some(TypeAnnotation { result_type: ResultType::Boolean, length: None, span: Span::new(0, 7, 11) }),
+            value: NodeArithmeticExpr::Base(NodeBaseExpr::Bool(Token::Boolean { value: true, span: Span::new(0, 14, 18) }))
+        };
+        assert_eq!(res, &vec![NodeStmt::ID(exp_stmt)]);
+    }
+
+    #[test]
+    fn test_unknown_annotation(){
+        let logger = setup_logger();
+        // let x: float = 1
+        let mut token_stream = TokenStream::new(vec![
+            Token::Let {span: Span::new(0, 0, 3)},
+            Token::WhiteSpace {span: Span::new(0, 3, 4)},
+            Token::ID { name: "x".to_string(), span: Span::new(0, 4, 5)},
+            Token::Colon {span: Span::new(0, 5, 6)},
+            Token::WhiteSpace {span: Span::new(0, 6, 7)},
+            Token::ID { name: "float".to_string(), span: Span::new(0, 7, 12)},
+            Token::WhiteSpace {span: Span::new(0, 12, 13)},
+            Token::Equals {span: Span::new(0, 13, 14)},
+            Token::WhiteSpace {span: Span::new(0, 14, 15)},
+            Token::Number { value: 1.to_string(), span: Span::new(0, 15, 16) }],
+            logger.clone()
+        );
+        let mut factory = StatementFactory::new(&mut token_stream, logger.clone());
+        let res : &mut Vec<NodeStmt> = &mut Vec::new();
+
+        factory.create(res);
+        assert!(res.is_empty());
+        let exp_error = (ParserErrorType::ErrTypeMissing.message().to_string(), Span::new(0, 7, 12));
+        assert_eq!(logger.lock().unwrap().get_errors(), &[exp_error]);
+    }
+
     #[test]
     fn test_bad_variable(){
         let dummy_span = Span::new(0, 0, 0);
@@ -305,11 +1216,13 @@ mod test_statement_factory{
             Token::Equals {span: dummy_span}],
             logger.clone()
         );
-        let mut factory = StatementFactory::new(&mut token_stream, logger);
+        let mut factory = StatementFactory::new(&mut token_stream, logger.clone());
         let res : &mut Vec<NodeStmt> = &mut Vec::new();
 
         factory.create(res);
         assert!(res.is_empty());
+        let exp_error = (ParserErrorType::ErrExpressionMissing.message().to_string(), dummy_span);
+        assert_eq!(logger.lock().unwrap().get_errors(), &[exp_error]);
     }
     
     #[test]
@@ -331,32 +1244,74 @@ mod test_statement_factory{
         let exp_stmt: &mut  Vec<NodeStmt> = &mut vec![
             NodeStmt::Scope(NodeScope{ stmts: vec![
                 NodeStmt::ID(NodeVariableAssignment{
+                    declaration: None,
                     variable: Token::ID { name: "x".to_string(), span: dummy_span},
+                    annotation: None,
                     value: NodeArithmeticExpr::Base(NodeBaseExpr::Num(
                                 Token::Number { value: 1.to_string(), span: dummy_span }
                             )
                         )
                     })
-                ] }),
+                ], span: Span::default() }),
             ];
         assert_eq!(res, exp_stmt);
     }
     
     #[test]
     fn test_bad_scope(){
+        let logger = setup_logger();
+        // {\nx = 1
+        let mut token_stream = TokenStream::new(vec![
+            Token::OpenCurlyBracket {span: Span::new(0, 0, 1)},
+            Token::NewLine {span: Span::new(0, 1, 2)},
+            Token::ID { name: "x".to_string(), span: Span::new(0, 2, 3)},
+            Token::Equals {span: Span::new(0, 4, 5)},
+            Token::Number { value: 1.to_string(), span: Span::new(0, 6, 7) }],
+            logger.clone()
+        );
+        let mut factory = StatementFactory::new(&mut token_stream, logger.clone());
+        let res : &mut Vec<NodeStmt> = &mut Vec::new();
+
+        factory.create(res);
+        assert!(res.is_empty());
+        // The error covers the whole unclosed scope, across lines
+        let exp_error = (ParserErrorType::ErrScopeClosesCurlyBracketMissing.message().to_string(), Span::new(0, 0, 7));
+        assert_eq!(logger.lock().unwrap().get_errors(), [exp_error]);
+    }
+    
+    #[test]
+    fn test_unopened_scope(){
         let dummy_span = Span::new(0, 0, 0);
         let logger = setup_logger();
         let mut token_stream = TokenStream::new(vec![
-            Token::OpenCurlyBracket {span: dummy_span},
-            Token::ID { name: "x".to_string(), span: dummy_span},
-            Token::Equals {span: dummy_span},
-            Token::Number { value: 1.to_string(), span: dummy_span }],
+            Token::ClosedCurlyBracket {span: dummy_span}],
             logger.clone()
         );
-        let mut factory = StatementFactory::new(&mut token_stream, logger);
+        let mut factory = StatementFactory::new(&mut token_stream, logger.clone());
+        let res : &mut Vec<NodeStmt> = &mut Vec::new();
+
+        factory.create(res);
+        assert!(res.is_empty());
+        let exp_error = (ParserErrorType::ErrScopeOpenCurlyBracketMissing.message().to_string(), dummy_span);
+        assert_eq!(logger.lock().unwrap().get_errors(), &[exp_error]);
+    }
+    
+    #[test]
+    fn test_invalid_statement(){
+        let err_span = Span::new(0, 2, 3);
+        let logger = setup_logger();
+        let mut token_stream = TokenStream::new(vec![
+            Token::ID { name: "x".to_string(), span: Span::new(0, 0, 0)},
+            Token::Equals {span: Span::new(0, 1, 1)},
+            Token::Err {span: err_span}],
+            logger.clone()
+        );
+        let mut factory = StatementFactory::new(&mut token_stream, logger.clone());
         let res : &mut Vec<NodeStmt> = &mut Vec::new();
 
         factory.create(res);
         assert!(res.is_empty());
+        let exp_error = (ParserErrorType::ErrInvalidStatement.message().to_string(), err_span);
+        assert_eq!(logger.lock().unwrap().get_errors(), &[exp_error]);
     }
 }
\ No newline at end of file
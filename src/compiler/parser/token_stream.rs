@@ -1,6 +1,7 @@
 use std::sync::{Arc, Mutex};
 use crate::compiler::parser::{ParserErrorType};
 use crate::compiler::parser::parser_logger::ParserLogger;
+use crate::compiler::span::Span;
 use crate::compiler::tokenizer::Token;
 
 #[derive(Clone)]
@@ -26,8 +27,21 @@ impl TokenStream{
             line
         }
 
+        // Depth of the curly brackets of the struct, enum or match being read
+        let mut struct_depth = 0;
         for token in tokens{
             match token{
+                // The curly brackets of a struct stay on the line of its name, with the fields written between them,
+                // and so do the ones of an enum with its variants and of a match with its arms
+                Token::OpenCurlyBracket { .. } if struct_depth > 0 || Self::opens_struct(&line) => {
+                    struct_depth += 1;
+                    line.push(token);
+                }
+                Token::ClosedCurlyBracket { .. } if struct_depth > 0 => {
+                    struct_depth -= 1;
+                    line.push(token);
+                }
+                Token::NewLine { span } if struct_depth > 0 => line.push(Token::WhiteSpace { span }),
                 Token::NewLine { .. } => {
                     if !trim_whitespace(line.clone()).is_empty() {m_tokens.push(trim_whitespace(line))};
                     line = Vec::new();
@@ -57,10 +71,25 @@ impl TokenStream{
         TokenStream{ m_tokens, m_logger, m_index: 0, m_stmt_index: 0}
     }
 
+    /// Whether a curly bracket after `line` opens the fields of a struct, as in `struct Point {` or `p = Point {`,
+    /// the variants of an enum, as in `enum Shape {`, or the arms of a match, as in `match s {`.
+    fn opens_struct(line: &[Token]) -> bool {
+        let mut tokens = line.iter().rev().filter(|token| !matches!(token, Token::WhiteSpace {..}));
+        matches!(
+            (tokens.next(), tokens.next()),
+            (Some(Token::ID { .. }), Some(Token::Struct { .. } | Token::Equals { .. } | Token::Enum { .. } | Token::Match { .. }))
+        )
+    }
+
     pub fn peek(&self, step: usize) -> Option<Token>{
         self.m_tokens.get(self.m_stmt_index)?.get(self.m_index+step).cloned()
     }
     
+    /// First token of the statement after the current one.
+    pub fn peek_next_stmt(&self) -> Option<Token>{
+        self.m_tokens.get(self.m_stmt_index + 1)?.first().cloned()
+    }
+
     pub fn peek_back(&self, step: usize) -> Option<Token>{
         if step > self.m_index { return None };
         self.m_tokens.get(self.m_stmt_index)?.get(self.m_index-step).cloned()
@@ -97,24 +126,42 @@ impl TokenStream{
 
     pub fn is_end(&self) -> bool{
         self.m_stmt_index+1 >= self.m_tokens.len() &&
-            self.m_tokens.last().map_or(true,
-                                        |line| self.m_index >= line.len())
+            self.m_tokens.last().is_none_or(|line| self.m_index >= line.len())
     }
     
-    pub fn is_err_in_stmt(&self) -> bool{
+    pub fn get_err_in_stmt(&self) -> Option<Token>{
         let mut offset = 0;
-        while self.peek(offset).is_some(){
-            if matches!(self.peek(offset), Some(Token::Err { .. })){
-                return true;
+        while let Some(token) = self.peek(offset){
+            if matches!(token, Token::Err { .. } | Token::InvalidNumber { .. }){
+                return Some(token);
             }
             offset += 1;
         }
-        false
+        None
+    }
+
+    /// Span of the last non whitespace token of the whole stream.
+    pub fn get_last_span(&self) -> Option<Span>{
+        self.m_tokens.iter().flatten()
+            .rfind(|token| !matches!(token, Token::WhiteSpace {..}))
+            .map(Token::get_span)
+    }
+
+    /// Span covering every token left in the current statement, ignoring whitespaces.
+    fn get_remaining_span(&self) -> Option<Span>{
+        let remaining: Vec<Span> = self.m_tokens.get(self.m_stmt_index)?
+            .get(self.m_index..)?
+            .iter()
+            .filter(|token| !matches!(token, Token::WhiteSpace {..}))
+            .map(Token::get_span)
+            .collect();
+        let (first, last) = (remaining.first()?, remaining.last()?);
+        Some(first.merge(*last))
     }
 
     // Advance Methods
     pub fn advance(&mut self, step: usize){
-        let line_len = self.m_tokens[self.m_stmt_index].len();
+        let line_len = self.m_tokens.get(self.m_stmt_index).map_or(0, Vec::len);
         self.m_index = usize::min(self.m_index + step, line_len);
     }
 
@@ -144,19 +191,26 @@ impl TokenStream{
         }
     }
 
+    /// Skips the rest of the current statement and synchronises the parser on the next one.
+    /// When `report` is set, the skipped tokens are reported as a single error.
     pub fn advance_stmt(&mut self, report: bool){
-        while self.peek(0).is_some(){
-            if report{
+        if report{
+            if let Some(span) = self.get_remaining_span() {
                 if let Ok(mut logger) = self.m_logger.lock() {
-                    logger.log_error(ParserErrorType::ErrUnexpectedToken, &self.peek(0).unwrap());
+                    logger.log_error_at(ParserErrorType::ErrUnexpectedToken, span);
                 }
             }
+        }
+        while self.peek(0).is_some(){
             self.advance(1);
         }
         while self.peek(0).is_none() && !self.is_end(){
             self.m_index = 0;
             self.m_stmt_index += 1;
         }
+        if let Ok(mut logger) = self.m_logger.lock() {
+            logger.synchronize();
+        }
     }
 }
 
@@ -221,6 +275,62 @@ mod test_token_stream{
         assert_eq!(token_stream.m_stmt_index, 0);
     }
     
+    #[test]
+    fn test_init_struct(){
+        let dummy_span = Span::new(0, 0, 0);
+        let id = |name: &str| Token::ID { name: name.to_string(), span: dummy_span };
+        let tokens = vec![
+            Token::Struct { span: dummy_span },
+            id("P"),
+            Token::OpenCurlyBracket { span: dummy_span },
+            Token::NewLine { span: dummy_span },
+            id("x"),
+            Token::ClosedCurlyBracket { span: dummy_span },
+            Token::NewLine { span: dummy_span },
+            id("p"),
+            Token::Equals { span: dummy_span },
+            id("P"),
+            Token::OpenCurlyBracket { span: dummy_span },
+            Token::OpenCurlyBracket { span: dummy_span },
+            Token::ClosedCurlyBracket { span: dummy_span },
+            Token::ClosedCurlyBracket { span: dummy_span },
+            Token::NewLine { span: dummy_span },
+            id("p"),
+            Token::OpenCurlyBracket { span: dummy_span },
+        ];
+        let token_stream = create_stream(tokens.clone());
+        let mut expected_stream_tokens: Vec<Vec<Token>> = vec![tokens[..6].to_vec(), tokens[7..14].to_vec(), vec![id("p")], vec![Token::OpenCurlyBracket { span: dummy_span }]];
+        expected_stream_tokens[0][3] = Token::WhiteSpace { span: dummy_span };
+        assert_eq!(token_stream.m_tokens, expected_stream_tokens);
+    }
+
+    #[test]
+    fn test_init_match(){
+        let dummy_span = Span::new(0, 0, 0);
+        let id = |name: &str| Token::ID { name: name.to_string(), span: dummy_span };
+        let tokens = vec![
+            Token::Enum { span: dummy_span },
+            id("S"),
+            Token::OpenCurlyBracket { span: dummy_span },
+            id("A"),
+            Token::ClosedCurlyBracket { span: dummy_span },
+            Token::NewLine { span: dummy_span },
+            Token::Exit { span: dummy_span },
+            Token::OpenBracket { span: dummy_span },
+            Token::Match { span: dummy_span },
+            id("s"),
+            Token::OpenCurlyBracket { span: dummy_span },
+            Token::NewLine { span: dummy_span },
+            Token::Underscore { span: dummy_span },
+            Token::ClosedCurlyBracket { span: dummy_span },
+            Token::ClosedBracket { span: dummy_span },
+        ];
+        let token_stream = create_stream(tokens.clone());
+        let mut expected_stream_tokens: Vec<Vec<Token>> = vec![tokens[..5].to_vec(), tokens[6..].to_vec()];
+        expected_stream_tokens[1][5] = Token::WhiteSpace { span: dummy_span };
+        assert_eq!(token_stream.m_tokens, expected_stream_tokens);
+    }
+
     #[test]
     fn test_peek(){
         let dummy_span = Span::new(0, 0, 0);
@@ -297,9 +407,9 @@ mod test_token_stream{
             Token::Err {span:dummy_span}
         ];
         let mut token_stream = create_stream(tokens);
-        assert!(!token_stream.is_err_in_stmt());
+        assert!(token_stream.get_err_in_stmt().is_none());
         token_stream.advance_stmt(false);
-        assert!(token_stream.is_err_in_stmt())
+        assert!(token_stream.get_err_in_stmt().is_some())
     }
     
     #[test]
@@ -386,4 +496,40 @@ mod test_token_stream{
         assert!(token_stream.peek(0).is_none());
         assert!(token_stream.is_end());
     }
+
+    #[test]
+    fn test_advance_stmt_report(){
+        let tokens = vec![
+            Token::ID { name: "x".to_string(), span: Span::new(0, 0, 0) },
+            Token::WhiteSpace {span: Span::new(0, 1, 1)},
+            Token::Number { value: 1.to_string(), span: Span::new(0, 2, 2) },
+            Token::WhiteSpace {span: Span::new(0, 3, 3)},
+            Token::Number { value: 2.to_string(), span: Span::new(0, 4, 4) },
+            Token::NewLine {span: Span::new(0, 5, 5)},
+            Token::Number { value: 3.to_string(), span: Span::new(1, 0, 0) },
+        ];
+        let mut token_stream = create_stream(tokens);
+        token_stream.advance(1);
+
+        // The leftover tokens are reported once, with a span covering all of them
+        token_stream.advance_stmt(true);
+        token_stream.advance_stmt(true);
+        let logger = token_stream.m_logger.lock().unwrap();
+        assert_eq!(logger.get_errors(), &[
+            (ParserErrorType::ErrUnexpectedToken.message().to_string(), Span::new(0, 2, 4)),
+            (ParserErrorType::ErrUnexpectedToken.message().to_string(), Span::new(1, 0, 0)),
+        ]);
+    }
+
+    #[test]
+    fn test_get_err_in_stmt(){
+        let dummy_span = Span::new(0, 0, 0);
+        let err_span = Span::new(0, 1, 1);
+        let tokens = vec![
+            Token::OpenBracket {span:dummy_span},
+            Token::Err {span:err_span}
+        ];
+        let token_stream = create_stream(tokens);
+        assert_eq!(token_stream.get_err_in_stmt(), Some(Token::Err {span: err_span}));
+    }
 }
\ No newline at end of file
@@ -1,12 +1,91 @@
-#[derive(Clone, Debug, PartialEq, Copy)]
+use std::ops::Range;
+
+/// Source range in bytes, `m_start` inclusive and `m_end` exclusive, inside the file identified by `m_file_id`.
+#[derive(Clone, Debug, PartialEq, Copy, Default)]
 pub struct Span {
-    pub m_line: usize,
+    pub m_file_id: usize,
     pub m_start: usize,
     pub m_end: usize
 }
 
 impl Span{
-    pub fn new(m_line: usize, m_start: usize, m_end: usize) -> Self{
-        Span{m_line, m_start, m_end}
+    pub fn new(m_file_id: usize, m_start: usize, m_end: usize) -> Self{
+        Span{m_file_id, m_start, m_end}
+    }
+
+    /// Smallest span covering both `self` and `other`, which must be in the same file.
+    pub fn merge(&self, other: Span) -> Span {
+        debug_assert_eq!(self.m_file_id, other.m_file_id, "Spans of different files can't be merged");
+        Span::new(self.m_file_id, self.m_start.min(other.m_start), self.m_end.max(other.m_end))
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.m_start..self.m_end
+    }
+}
+
+/// Byte offset of the start of every line of a source, used to convert offsets into line and column numbers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LineIndex {
+    m_line_starts: Vec<usize>
+}
+
+impl LineIndex {
+    pub fn new(source: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(offset, _)| offset + 1))
+            .collect();
+        LineIndex { m_line_starts: line_starts }
+    }
+
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        self.m_line_starts.get(line).copied()
+    }
+
+    /// Zero-based line and byte column of `offset`.
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let line = self.m_line_starts.partition_point(|&start| start <= offset) - 1;
+        (line, offset - self.m_line_starts[line])
+    }
+}
+
+
+
+#[cfg(test)]
+mod test_span{
+    use super::*;
+
+    #[test]
+    fn test_merge(){
+        let lhs = Span::new(0, 2, 4);
+        let rhs = Span::new(0, 10, 12);
+        assert_eq!(lhs.merge(rhs), Span::new(0, 2, 12));
+        assert_eq!(rhs.merge(lhs), Span::new(0, 2, 12));
+        assert_eq!(lhs.merge(Span::new(0, 3, 3)), lhs);
+        assert_eq!(lhs.range(), 2..4);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Spans of different files can't be merged")]
+    fn test_merge_files(){
+        Span::new(0, 2, 4).merge(Span::new(1, 10, 12));
+    }
+
+    #[test]
+    fn test_line_index(){
+        let index = LineIndex::new("x = 1\n\nexit(x)\n");
+        assert_eq!(index.line_start(0), Some(0));
+        assert_eq!(index.line_start(1), Some(6));
+        assert_eq!(index.line_start(2), Some(7));
+        assert_eq!(index.line_start(3), Some(15));
+        assert!(index.line_start(4).is_none());
+
+        assert_eq!(index.line_col(0), (0, 0));
+        assert_eq!(index.line_col(4), (0, 4));
+        assert_eq!(index.line_col(5), (0, 5));
+        assert_eq!(index.line_col(6), (1, 0));
+        assert_eq!(index.line_col(12), (2, 5));
+        assert_eq!(index.line_col(15), (3, 0));
     }
 }
\ No newline at end of file
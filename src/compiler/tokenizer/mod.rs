@@ -3,6 +3,6 @@ mod token;
 mod operator;
 mod parenthesis_handler;
 
-pub use operator::Operator;
+pub use operator::{Builtin, Operator};
 pub use token::Token;
 pub use tokenizer::Tokenizer;
@@ -1,6 +1,42 @@
 use crate::compiler::span::Span;
 use std::fmt;
 
+/// Functions of the runtime, which read the input of the program.
+#[derive(Clone, Debug, PartialEq, Copy)]
+pub enum Builtin {
+    Args,
+    Arg,
+    ReadInt,
+    ReadLine,
+    ParseInt
+}
+
+impl Builtin {
+    pub const ALL: [Builtin; 5] = [Builtin::Args, Builtin::Arg, Builtin::ReadInt, Builtin::ReadLine, Builtin::ParseInt];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Builtin::Args => "args",
+            Builtin::Arg => "arg",
+            Builtin::ReadInt => "read_int",
+            Builtin::ReadLine => "read_line",
+            Builtin::ParseInt => "parse_int"
+        }
+    }
+
+    /// Number of arguments between the brackets, which are all integers.
+    pub fn arity(self) -> usize {
+        match self {
+            Builtin::Arg | Builtin::ParseInt => 1,
+            Builtin::Args | Builtin::ReadInt | Builtin::ReadLine => 0
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Builtin> {
+        Builtin::ALL.into_iter().find(|builtin| builtin.name() == name)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Copy)]
 pub enum Operator {
     Plus {span: Span},
@@ -13,24 +49,55 @@ pub enum Operator {
     Or {span: Span},
     Xor {span: Span},
     Not {span: Span},
+    BitAnd {span: Span},
+    BitOr {span: Span},
+    BitXor {span: Span},
+    BitNot {span: Span},
+    /// Unary minus, which the reverse polish notation tells apart from the subtraction by its position.
+    Negate {span: Span},
+    ShiftLeft {span: Span},
+    ShiftRight {span: Span},
+    Cast {span: Span},
+    Index {span: Span},
+    Len {span: Span},
+    Alloc {span: Span},
+    Builtin {span: Span, function: Builtin},
+    /// Call of a C function, with its number of arguments, which the reverse polish notation puts after its name.
+    Call {span: Span, arguments: usize},
+    Field {span: Span},
     OpenBracket {span: Span},
     ClosedBracket {span: Span}
 }
 
 impl Operator {
 
+    /// Binding strength of the operator, from the logical operators to the unary ones, following C for the bitwise operators.
+    /// Like in Rust, a cast binds tighter than the binary operators, but not than the unary ones, and indexing and
+    /// field accesses bind tighter than everything. `alloc`, the builtins and the calls are held on the stack like an opening bracket.
     pub fn precedence(self) -> usize {
         match self {
-            Operator::Plus { .. } | Operator::Minus { .. } | Operator::And { .. } | Operator::Or { .. } | Operator::Xor { .. } => {0}
-            Operator::Multiplication { .. } | Operator::Division { .. } | Operator::Modulus { .. } => {1}
-            Operator::OpenBracket { .. } | Operator::ClosedBracket { .. } | Operator::Exponent { .. } => {2}
-            Operator::Not { .. } => 3
+            Operator::And { .. } | Operator::Or { .. } | Operator::Xor { .. } => {0}
+            Operator::BitOr { .. } => {1}
+            Operator::BitXor { .. } => {2}
+            Operator::BitAnd { .. } => {3}
+            Operator::ShiftLeft { .. } | Operator::ShiftRight { .. } => {4}
+            Operator::Plus { .. } | Operator::Minus { .. } => {5}
+            Operator::Multiplication { .. } | Operator::Division { .. } | Operator::Modulus { .. } => {6}
+            Operator::OpenBracket { .. } | Operator::ClosedBracket { .. } | Operator::Exponent { .. } => {7}
+            Operator::Cast { .. } => 8,
+            Operator::Not { .. } | Operator::BitNot { .. } | Operator::Negate { .. } => 9,
+            Operator::Index { .. } | Operator::Len { .. } | Operator::Alloc { .. } | Operator::Builtin { .. } | Operator::Call { .. } | Operator::Field { .. } => 10
         }
     }
 
+    /// Unary operators are written before their only operand, except for the casts, which are followed by their type.
+    pub fn is_unary(self) -> bool {
+        matches!(self, Operator::Not { .. } | Operator::BitNot { .. } | Operator::Negate { .. } | Operator::Cast { .. })
+    }
+
     pub fn associativity(self) -> String {
         match self{
-            Operator::Exponent { .. } | Operator::Not { .. } => {"Right".to_string()}
+            Operator::Exponent { .. } | Operator::Not { .. } | Operator::BitNot { .. } | Operator::Negate { .. } => {"Right".to_string()}
             _ => {"Left".to_string()}
         }
     }
@@ -46,10 +113,56 @@ impl Operator {
             | Operator::Or { span }
             | Operator::Xor { span }
             | Operator::Not { span }
+            | Operator::BitAnd { span }
+            | Operator::BitOr { span }
+            | Operator::BitXor { span }
+            | Operator::BitNot { span }
+            | Operator::Negate { span }
+            | Operator::ShiftLeft { span }
+            | Operator::ShiftRight { span }
+            | Operator::Cast { span }
+            | Operator::Index { span }
+            | Operator::Len { span }
+            | Operator::Alloc { span }
+            | Operator::Builtin { span, .. }
+            | Operator::Call { span, .. }
+            | Operator::Field { span }
             | Operator::OpenBracket { span }
             | Operator::ClosedBracket { span } => *span,
         }
     }
+
+    /// Inverse of the `Display` implementation.
+    pub fn from_symbol(symbol: &str, span: Span) -> Option<Operator> {
+        match symbol {
+            "+" => Some(Operator::Plus { span }),
+            "-" => Some(Operator::Minus { span }),
+            "*" => Some(Operator::Multiplication { span }),
+            "/" => Some(Operator::Division { span }),
+            "**" => Some(Operator::Exponent { span }),
+            "%" => Some(Operator::Modulus { span }),
+            "&&" => Some(Operator::And { span }),
+            "||" => Some(Operator::Or { span }),
+            "^|" => Some(Operator::Xor { span }),
+            "!!" => Some(Operator::Not { span }),
+            "&" => Some(Operator::BitAnd { span }),
+            "|" => Some(Operator::BitOr { span }),
+            "^" => Some(Operator::BitXor { span }),
+            "~" => Some(Operator::BitNot { span }),
+            "neg" => Some(Operator::Negate { span }),
+            "<<" => Some(Operator::ShiftLeft { span }),
+            ">>" => Some(Operator::ShiftRight { span }),
+            "as" => Some(Operator::Cast { span }),
+            "[]" => Some(Operator::Index { span }),
+            "len" => Some(Operator::Len { span }),
+            "alloc" => Some(Operator::Alloc { span }),
+            "()" => Some(Operator::Call { span, arguments: 0 }),
+            "." => Some(Operator::Field { span }),
+            "(" => Some(Operator::OpenBracket { span }),
+            ")" => Some(Operator::ClosedBracket { span }),
+            _ => Builtin::from_name(symbol).map(|function| Operator::Builtin { span, function })
+        }
+    }
 }
 
 // Implement Display for Operator
@@ -60,12 +173,27 @@ impl fmt::Display for Operator {
             Operator::Minus { span: _ } => "-",
             Operator::Multiplication { span: _ } => "*",
             Operator::Division { span: _ } => "/",
-            Operator::Exponent { span: _ } => "^",
+            Operator::Exponent { span: _ } => "**",
             Operator::Modulus { span: _ } => "%",
             Operator::And { .. } => "&&",
             Operator::Or { .. } => "||",
             Operator::Xor { .. } => "^|",
             Operator::Not { .. } => "!!",
+            Operator::BitAnd { .. } => "&",
+            Operator::BitOr { .. } => "|",
+            Operator::BitXor { .. } => "^",
+            Operator::BitNot { .. } => "~",
+            // Written `-` in the source, the symbol only tells it apart from the subtraction in the AST dumps
+            Operator::Negate { .. } => "neg",
+            Operator::ShiftLeft { .. } => "<<",
+            Operator::ShiftRight { .. } => ">>",
+            Operator::Cast { .. } => "as",
+            Operator::Index { .. } => "[]",
+            Operator::Len { .. } => "len",
+            Operator::Alloc { .. } => "alloc",
+            Operator::Builtin { function, .. } => function.name(),
+            Operator::Call { .. } => "()",
+            Operator::Field { .. } => ".",
             Operator::OpenBracket { span: _ } => "(",
             Operator::ClosedBracket { span: _ } => ")"
 
@@ -93,17 +221,31 @@ mod test_operator{
             Operator::Or {span},
             Operator::Xor {span},
             Operator::Not {span},
+            Operator::BitAnd {span},
+            Operator::BitOr {span},
+            Operator::BitXor {span},
+            Operator::BitNot {span},
+            Operator::Negate {span},
+            Operator::ShiftLeft {span},
+            Operator::ShiftRight {span},
+            Operator::Cast {span},
+            Operator::Index {span},
+            Operator::Len {span},
+            Operator::Alloc {span},
+            Operator::Builtin {span, function: Builtin::ReadLine},
+            Operator::Call {span, arguments: 0},
+            Operator::Field {span},
             Operator::OpenBracket {span},
             Operator::ClosedBracket {span}
         ]
     }
     
     fn expected_precedence() -> Vec<usize> {
-        vec![0, 0, 1, 1, 2, 1, 0, 0, 0, 3, 2, 2]
+        vec![5, 5, 6, 6, 7, 6, 0, 0, 0, 9, 3, 1, 2, 9, 9, 4, 4, 8, 10, 10, 10, 10, 10, 10, 7, 7]
     }
     
     fn expected_format() -> Vec<&'static str>{
-        vec!["+", "-", "*", "/", "^", "%", "&&", "||", "^|", "!!", "(", ")"]
+        vec!["+", "-", "*", "/", "**", "%", "&&", "||", "^|", "!!", "&", "|", "^", "~", "neg", "<<", ">>", "as", "[]", "len", "alloc", "read_line", "()", ".", "(", ")"]
     }
     
     #[test]
@@ -118,13 +260,21 @@ mod test_operator{
     fn test_associativity(){
         let dummy_span = Span::new(0, 0, 0);
         for op in all_operators(dummy_span){
-            let exp_associativity = if matches!(op, Operator::Exponent {..} | Operator::Not {..}){
+            let exp_associativity = if matches!(op, Operator::Exponent {..} | Operator::Not {..} | Operator::BitNot {..} | Operator::Negate {..}){
                 "Right"
             } else { "Left"};
             assert_eq!(op.associativity(), exp_associativity);
         }
     }
     
+    #[test]
+    fn test_is_unary(){
+        let dummy_span = Span::new(0, 0, 0);
+        for op in all_operators(dummy_span){
+            assert_eq!(op.is_unary(), matches!(op, Operator::Not {..} | Operator::BitNot {..} | Operator::Negate {..} | Operator::Cast {..}));
+        }
+    }
+    
     #[test]
     fn test_get_span(){
         let dummy_span = Span::new(0, 0, 0);
@@ -140,4 +290,23 @@ mod test_operator{
             assert_eq!(format!("{}", op), exp_format);
         }
     }
+
+    #[test]
+    fn test_from_symbol(){
+        let dummy_span = Span::new(0, 0, 0);
+        for op in all_operators(dummy_span){
+            assert_eq!(Operator::from_symbol(&op.to_string(), dummy_span), Some(op));
+        }
+        assert!(Operator::from_symbol("^^", dummy_span).is_none());
+    }
+
+    #[test]
+    fn test_builtin_names(){
+        for builtin in Builtin::ALL{
+            assert_eq!(Builtin::from_name(builtin.name()), Some(builtin));
+        }
+        assert_eq!(Builtin::Arg.arity(), 1);
+        assert_eq!(Builtin::Args.arity(), 0);
+        assert!(Builtin::from_name("print").is_none());
+    }
 }
\ No newline at end of file
@@ -8,14 +8,40 @@ pub enum Token {
     Number { value: String, span: Span },
     Boolean { value: bool, span: Span },
     Exit {span: Span},
+    Free {span: Span},
+    Import {span: Span},
+    Let {span: Span},
+    Var {span: Span},
+    Const {span: Span},
+    Struct {span: Span},
+    Enum {span: Span},
+    Match {span: Span},
+    For {span: Span},
+    In {span: Span},
+    Step {span: Span},
+    Extern {span: Span},
+    Fn {span: Span},
+    StringLiteral { value: String, span: Span },
     OpenBracket {span: Span},
     ClosedBracket {span: Span},
     OpenCurlyBracket {span: Span},
     ClosedCurlyBracket {span: Span},
+    OpenSquareBracket {span: Span},
+    ClosedSquareBracket {span: Span},
+    Comma {span: Span},
+    Semicolon {span: Span},
     Equals {span: Span},
+    Colon {span: Span},
+    DoubleColon {span: Span},
+    Arrow {span: Span},
+    Underscore {span: Span},
+    DotDot {span: Span},
+    DotDotEquals {span: Span},
+    Ellipsis {span: Span},
     Operator(Operator),
     WhiteSpace {span: Span},
     NewLine {span: Span},
+    InvalidNumber {span: Span},
     Err {span: Span}
 }
 
@@ -26,13 +52,39 @@ impl Token {
             | Token::Number { span, .. }
             | Token::Boolean { span, .. }
             | Token::Exit { span }
+            | Token::Free { span }
+            | Token::Import { span }
+            | Token::Let { span }
+            | Token::Var { span }
+            | Token::Const { span }
+            | Token::Struct { span }
+            | Token::Enum { span }
+            | Token::Match { span }
+            | Token::For { span }
+            | Token::In { span }
+            | Token::Step { span }
+            | Token::Extern { span }
+            | Token::Fn { span }
+            | Token::StringLiteral { span, .. }
             | Token::OpenBracket { span }
             | Token::ClosedBracket { span }
             | Token::OpenCurlyBracket { span }
             | Token::ClosedCurlyBracket { span }
+            | Token::OpenSquareBracket { span }
+            | Token::ClosedSquareBracket { span }
+            | Token::Comma { span }
+            | Token::Semicolon { span }
             | Token::Equals { span }
+            | Token::Colon { span }
+            | Token::DoubleColon { span }
+            | Token::Arrow { span }
+            | Token::Underscore { span }
+            | Token::DotDot { span }
+            | Token::DotDotEquals { span }
+            | Token::Ellipsis { span }
             | Token::WhiteSpace { span }
             | Token::NewLine { span }
+            | Token::InvalidNumber { span }
             | Token::Err { span } => span.clone(),
             Token::Operator(op) => op.get_span(),
         }
@@ -47,11 +99,36 @@ impl fmt::Display for Token {
             Token::Number { value, span } => write!(f, "Number({}, {:?})", value, span),
             Token::Boolean {value, span} => write!(f, "Boolean({}, {:?})", value, span),
             Token::Exit { .. } => write!(f, "exit()"),
+            Token::Free { .. } => write!(f, "free()"),
+            Token::Import { .. } => write!(f, "import"),
+            Token::Let { .. } => write!(f, "let"),
+            Token::Var { .. } => write!(f, "var"),
+            Token::Const { .. } => write!(f, "const"),
+            Token::Struct { .. } => write!(f, "struct"),
+            Token::Enum { .. } => write!(f, "enum"),
+            Token::Match { .. } => write!(f, "match"),
+            Token::For { .. } => write!(f, "for"),
+            Token::In { .. } => write!(f, "in"),
+            Token::Step { .. } => write!(f, "step"),
+            Token::Extern { .. } => write!(f, "extern"),
+            Token::Fn { .. } => write!(f, "fn"),
+            Token::StringLiteral { value, .. } => write!(f, "\"{}\"", value),
             Token::OpenBracket { .. } => write!(f, "("),
             Token::ClosedBracket { .. } => write!(f, ")"),
             Token::OpenCurlyBracket { .. } => write!(f, "{{"),
             Token::ClosedCurlyBracket { .. } => write!(f, "}}"),
+            Token::OpenSquareBracket { .. } => write!(f, "["),
+            Token::ClosedSquareBracket { .. } => write!(f, "]"),
+            Token::Comma { .. } => write!(f, ","),
+            Token::Semicolon { .. } => write!(f, ";"),
             Token::Equals {..} => write!(f, "="),
+            Token::Colon {..} => write!(f, ":"),
+            Token::DoubleColon {..} => write!(f, "::"),
+            Token::Arrow {..} => write!(f, "=>"),
+            Token::Underscore {..} => write!(f, "_"),
+            Token::DotDot {..} => write!(f, ".."),
+            Token::DotDotEquals {..} => write!(f, "..="),
+            Token::Ellipsis {..} => write!(f, "..."),
             Token::Operator(op) => write!(f, "{}", op),
             Token::WhiteSpace {..} => write!(f, " "),
             Token::NewLine {..} => write!(f, "\n"),
@@ -73,32 +150,84 @@ mod test_token{
             Token::Number { value: "42".to_string(), span },
             Token::Boolean { value: true, span },
             Token::Exit { span },
+            Token::Free { span },
+            Token::Import { span },
+            Token::Let { span },
+            Token::Var { span },
+            Token::Const { span },
+            Token::Struct { span },
+            Token::Enum { span },
+            Token::Match { span },
+            Token::For { span },
+            Token::In { span },
+            Token::Step { span },
+            Token::Extern { span },
+            Token::Fn { span },
+            Token::StringLiteral { value: "lib.brs".to_string(), span },
             Token::OpenBracket { span },
             Token::ClosedBracket { span },
             Token::OpenCurlyBracket { span },
             Token::ClosedCurlyBracket { span },
+            Token::OpenSquareBracket { span },
+            Token::ClosedSquareBracket { span },
+            Token::Comma { span },
+            Token::Semicolon { span },
             Token::Equals { span },
+            Token::Colon { span },
+            Token::DoubleColon { span },
+            Token::Arrow { span },
+            Token::Underscore { span },
+            Token::DotDot { span },
+            Token::DotDotEquals { span },
+            Token::Ellipsis { span },
             Token::Operator(Operator::Plus { span }),
             Token::WhiteSpace { span },
             Token::NewLine { span },
+            Token::InvalidNumber { span },
             Token::Err { span },
         ]
     }
     
     fn expected_format() -> Vec<&'static str>{
         vec![
-            "ID(x, Span { m_line: 0, m_start: 0, m_end: 0 })",
-            "Number(42, Span { m_line: 0, m_start: 0, m_end: 0 })",
-            "Boolean(true, Span { m_line: 0, m_start: 0, m_end: 0 })",
+            "ID(x, Span { m_file_id: 0, m_start: 0, m_end: 0 })",
+            "Number(42, Span { m_file_id: 0, m_start: 0, m_end: 0 })",
+            "Boolean(true, Span { m_file_id: 0, m_start: 0, m_end: 0 })",
             "exit()",
+            "free()",
+            "import",
+            "let",
+            "var",
+            "const",
+            "struct",
+            "enum",
+            "match",
+            "for",
+            "in",
+            "step",
+            "extern",
+            "fn",
+            "\"lib.brs\"",
             "(",
             ")",
             "{",
             "}",
+            "[",
+            "]",
+            ",",
+            ";",
             "=",
+            ":",
+            "::",
+            "=>",
+            "_",
+            "..",
+            "..=",
+            "...",
             "+",
             " ",
             "\n",
+            "err",
             "err"
         ]
     }
@@ -1,4 +1,4 @@
-use super::operator::Operator;
+use super::operator::{Builtin, Operator};
 use super::parenthesis_handler::ParenthesisHandler;
 use super::token::Token;
 use crate::compiler::span::Span;
@@ -7,80 +7,103 @@ use std::str::Chars;
 
 pub struct Tokenizer {
     m_tokens : Vec<Token>,
-    m_line: usize,
-    m_row: usize,
+    m_file_id: usize,
+    m_offset: usize,
     m_parenthesis_handler: ParenthesisHandler,
 }
 
 impl Tokenizer {
 
     pub fn new() -> Self {
-        Tokenizer { m_tokens: Vec::new(), m_line: 0, m_row: 0, m_parenthesis_handler: ParenthesisHandler::new()}
+        Tokenizer { m_tokens: Vec::new(), m_file_id: 0, m_offset: 0, m_parenthesis_handler: ParenthesisHandler::new()}
+    }
+
+    pub fn set_file_id(&mut self, file_id: usize) {
+        self.m_file_id = file_id;
     }
 
     pub fn get_tokens(&self) -> Vec<Token> { self.m_tokens.clone() }
+
+    /// Whether `name` is read as a single identifier, so not a keyword, a literal or several tokens.
+    pub fn is_identifier(name: &str) -> bool {
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.tokenize(name);
+        matches!(tokenizer.m_tokens.as_slice(), [Token::ID { name: id, .. }] if id == name)
+    }
     
     fn emit_token(&mut self, token : Token) {
-        self.m_row += 1;
-        if matches!(token, Token::NewLine {..}){
-            self.handle_newline();
-        }
+        self.m_offset = token.get_span().m_end;
         self.m_tokens.push(token);
     }
 
     fn clear(&mut self){
         self.m_tokens.clear();
-        self.m_line = 0;
-        self.m_row = 0
+        self.m_offset = 0;
     }
     
     pub fn tokenize(&mut self, input: &str){
         self.clear();
-        let normalized_input = input.replace("\r", "");
         let mut buf = String::new();
-        let mut chars = normalized_input.chars().peekable();
+        let mut chars = input.chars().peekable();
         while let Some(ch) = chars.next(){
-            buf.push(ch);
-            if let Some(token) = self.check_buf(&buf, &mut chars) {
+            if ch == '\r' && buf.is_empty() {
+                // Carriage returns are skipped, but still take space in the source
+                self.m_offset += 1;
+                continue;
+            }
+            if ch == '"' && buf.is_empty() {
+                let token = self.tokenize_string(&mut chars);
                 self.emit_token(token);
-                buf.clear();
                 continue;
             }
-            if buf.chars().count() > 1 && ch == '\n'{
-                let error_span = self.get_span(buf.chars().count()-1);
-                self.m_row += 1;
-                let new_line_span = self.get_span(1);
-                self.emit_token(Token::Err {span: error_span });
-                self.emit_token(Token::NewLine {span: new_line_span});
+            buf.push(ch);
+            if let Some(token) = self.check_buf(&buf, &mut chars) {
+                self.emit_token(token);
                 buf.clear();
                 continue;
             }
-            if let Some(token) = self.match_ch(ch, chars.peek()) {
+            let prefix_len = buf.len() - ch.len_utf8();
+            if let Some(token) = self.match_ch(ch, chars.peek(), prefix_len) {
+                if prefix_len > 0 {
+                    // The characters before this one could not be tokenized
+                    let error_span = self.get_span(prefix_len);
+                    self.emit_token(Token::Err {span: error_span });
+                }
                 self.emit_token(token);
                 buf.clear();
                 continue;
             }
         }
         if !buf.is_empty() {
-            let error_span = self.get_span(buf.chars().count());
+            let error_span = self.get_span(buf.len());
             self.emit_token(Token::Err {span: error_span });
         }
     }
     
-    fn match_ch(&mut self, ch: char, peek: Option<&char>) -> Option<Token> {
-        if ch == '\r' {
-            return None;
-        }
-
-        let span = Span::new(self.m_line, self.m_row, self.m_row);
+    fn match_ch(&mut self, ch: char, peek: Option<&char>, prefix_len: usize) -> Option<Token> {
+        let start = self.m_offset + prefix_len;
+        let span = Span::new(self.m_file_id, start, start + ch.len_utf8());
         match ch {
             '(' | ')' => Some(self.m_parenthesis_handler.emit_bracket_token(span, ch == '(')),
             '{' => Some(Token::OpenCurlyBracket { span }),
             '}' => Some(Token::ClosedCurlyBracket { span }),
-            '=' => Some(Token::Equals { span }),
+            // `=>` and `::` are read by `check_buf`
+            '=' if peek != Some(&'>') => Some(Token::Equals { span }),
+            ':' if peek != Some(&':') => Some(Token::Colon { span }),
+            '[' => Some(Token::OpenSquareBracket { span }),
+            ']' => Some(Token::ClosedSquareBracket { span }),
+            ',' => Some(Token::Comma { span }),
+            ';' => Some(Token::Semicolon { span }),
+            // `..`, `..=` and `...` are read by `check_buf`
+            '.' if !matches!(peek, Some('.' | '=')) => Some(Token::Operator(Operator::Field { span })),
             '+' => Some(Token::Operator(Operator::Plus { span })),
             '-' => Some(Token::Operator(Operator::Minus { span })),
             '%' => Some(Token::Operator(Operator::Modulus { span })),
+            '~' => Some(Token::Operator(Operator::BitNot { span })),
+            // `&&`, `||` and `^|` are logical operators, they are read by `check_buf`
+            '&' if peek != Some(&'&') => Some(Token::Operator(Operator::BitAnd { span })),
+            '|' if peek != Some(&'|') => Some(Token::Operator(Operator::BitOr { span })),
+            '^' if peek != Some(&'|') => Some(Token::Operator(Operator::BitXor { span })),
             '*' => {
                 if peek == Some(&'*'){
                     None
@@ -106,12 +129,48 @@ impl Tokenizer {
                 self.m_parenthesis_handler.activate_function_detector();
                 Some(Token::Exit {span : self.get_span(buf.len())})
             },
+            "free" if !input.peek().is_some_and(|ch| Self::is_word_char(*ch)) => {
+                self.m_parenthesis_handler.activate_function_detector();
+                Some(Token::Free {span : self.get_span(buf.len())})
+            },
+            "import" if !input.peek().is_some_and(|ch| Self::is_word_char(*ch)) => {
+                Some(Token::Import {span : self.get_span(buf.len())})
+            },
+            "let" if !input.peek().is_some_and(|ch| Self::is_word_char(*ch)) => Some(Token::Let {span : self.get_span(buf.len())}),
+            "var" if !input.peek().is_some_and(|ch| Self::is_word_char(*ch)) => Some(Token::Var {span : self.get_span(buf.len())}),
+            "const" if !input.peek().is_some_and(|ch| Self::is_word_char(*ch)) => Some(Token::Const {span : self.get_span(buf.len())}),
+            "struct" if !input.peek().is_some_and(|ch| Self::is_word_char(*ch)) => Some(Token::Struct {span : self.get_span(buf.len())}),
+            "enum" if !input.peek().is_some_and(|ch| Self::is_word_char(*ch)) => Some(Token::Enum {span : self.get_span(buf.len())}),
+            "match" if !input.peek().is_some_and(|ch| Self::is_word_char(*ch)) => Some(Token::Match {span : self.get_span(buf.len())}),
+            "for" if !input.peek().is_some_and(|ch| Self::is_word_char(*ch)) => Some(Token::For {span : self.get_span(buf.len())}),
+            "in" if !input.peek().is_some_and(|ch| Self::is_word_char(*ch)) => Some(Token::In {span : self.get_span(buf.len())}),
+            "step" if !input.peek().is_some_and(|ch| Self::is_word_char(*ch)) => Some(Token::Step {span : self.get_span(buf.len())}),
+            "extern" if !input.peek().is_some_and(|ch| Self::is_word_char(*ch)) => Some(Token::Extern {span : self.get_span(buf.len())}),
+            "fn" if !input.peek().is_some_and(|ch| Self::is_word_char(*ch)) => Some(Token::Fn {span : self.get_span(buf.len())}),
+            "_" if !input.peek().is_some_and(|ch| Self::is_word_char(*ch)) => Some(Token::Underscore {span : self.get_span(buf.len())}),
+            "as" if !input.peek().is_some_and(|ch| Self::is_word_char(*ch)) => Some(Token::Operator(Operator::Cast {span : self.get_span(buf.len())})),
+            "len" if !input.peek().is_some_and(|ch| Self::is_word_char(*ch)) => Some(Token::Operator(Operator::Len {span : self.get_span(buf.len())})),
+            "alloc" if !input.peek().is_some_and(|ch| Self::is_word_char(*ch)) => Some(Token::Operator(Operator::Alloc {span : self.get_span(buf.len())})),
+            name if !input.peek().is_some_and(|ch| Self::is_word_char(*ch)) && Builtin::from_name(name).is_some() => {
+                Builtin::from_name(name).map(|function| Token::Operator(Operator::Builtin { span: self.get_span(buf.len()), function }))
+            },
             "**" => Some(Token::Operator(Operator::Exponent {span : self.get_span(buf.len())})),
             "//" => Some(Token::Operator(Operator::Division {span : self.get_span(buf.len())})),
             "&&" => Some(Token::Operator(Operator::And { span: self.get_span(buf.len()) })),
             "||" => Some(Token::Operator(Operator::Or { span: self.get_span(buf.len()) })),
             "!!" => Some(Token::Operator(Operator::Not { span: self.get_span(buf.len()) })),
             "^|" => Some(Token::Operator(Operator::Xor { span: self.get_span(buf.len()) })),
+            "<<" => Some(Token::Operator(Operator::ShiftLeft { span: self.get_span(buf.len()) })),
+            ">>" => Some(Token::Operator(Operator::ShiftRight { span: self.get_span(buf.len()) })),
+            "=>" => Some(Token::Arrow { span: self.get_span(buf.len()) }),
+            ".." if !matches!(input.peek(), Some('=' | '.')) => Some(Token::DotDot { span: self.get_span(buf.len()) }),
+            "..=" => Some(Token::DotDotEquals { span: self.get_span(buf.len()) }),
+            "..." => Some(Token::Ellipsis { span: self.get_span(buf.len()) }),
+            "::" => {
+                // The values of a variant are read like the exit code, between the outer parentheses
+                self.m_parenthesis_handler.activate_function_detector();
+                Some(Token::DoubleColon { span: self.get_span(buf.len()) })
+            },
             "true" => Some(Token::Boolean { value: true, span: self.get_span(buf.len()) }),
             "false" => Some(Token::Boolean { value: false, span: self.get_span(buf.len()) }),
             "" => {
@@ -128,34 +187,98 @@ impl Tokenizer {
         }
     }
 
+    /// Reads a string literal up to its closing quote. String literals can't span multiple lines.
+    /// The escapes `\n`, `\t`, `\r`, `\0`, `\\` and `\"` are replaced, and any other backslash is kept as written.
+    fn tokenize_string(&mut self, input: &mut Peekable<Chars>) -> Token {
+        let mut value = String::new();
+        let mut length = 0;
+        while let Some(ch) = input.next_if(|ch| *ch != '"' && *ch != '\n') {
+            length += ch.len_utf8();
+            if ch != '\\' {
+                value.push(ch);
+                continue;
+            }
+            let Some(escaped) = input.next_if(|ch| *ch != '\n') else {
+                value.push(ch);
+                continue;
+            };
+            length += escaped.len_utf8();
+            match escaped {
+                'n' => value.push('\n'),
+                't' => value.push('\t'),
+                'r' => value.push('\r'),
+                '0' => value.push('\0'),
+                '\\' | '"' => value.push(escaped),
+                _ => {
+                    value.push(ch);
+                    value.push(escaped);
+                }
+            }
+        }
+        let closed = input.next_if_eq(&'"').is_some();
+        let span = self.get_span(length + 1 + usize::from(closed));
+        if closed {
+            Token::StringLiteral { value, span }
+        } else {
+            Token::Err { span }
+        }
+    }
+
     fn tokenize_primary_expr(&mut self, buf : &str, input: &mut Peekable<Chars>) -> Option<Token> {
         self.get_num(buf, input)
             .or_else(|| self.get_id(buf, input))
-            .or_else(|| self.get_error(buf, input))
     }
     
+    /// Reads a whole numeric literal, such as `42`, `1_000`, `0x1F`, `0b1010` or `0o17`. Its value is written
+    /// in decimal, and literals with invalid digits, including non-ASCII ones, become `InvalidNumber` tokens.
     fn get_num(&mut self, buf: &str, input: &mut Peekable<Chars>) -> Option<Token>{
         let next_char = input.peek().unwrap_or(&' ');
-        if buf.chars().all(char::is_numeric) && !next_char.is_alphanumeric() {
-            let value = buf.to_string();
-            let value_len = buf.len();
-            return Some(Token::Number {
-                value,
-                span: self.get_span(value_len),
-            });
+        let first = buf.chars().next()?;
+        if !first.is_numeric() || !buf.chars().all(Self::is_word_char) || Self::is_word_char(*next_char) {
+            return None;
         }
-        None
+        let span = self.get_span(buf.len());
+        Some(match Self::parse_number(buf) {
+            Some(value) => Token::Number { value, span },
+            None => Token::InvalidNumber { span },
+        })
     }
-    
+
+    /// Decimal value of a numeric literal. A literal too big for 64 bits is kept as written, without its
+    /// underscores, and is reported by the parser.
+    fn parse_number(literal: &str) -> Option<String> {
+        let (radix, digits) = match literal.get(..2) {
+            Some("0x") => (16, &literal[2..]),
+            Some("0o") => (8, &literal[2..]),
+            Some("0b") => (2, &literal[2..]),
+            _ => (10, literal),
+        };
+        // Underscores are only allowed between digits
+        if digits.is_empty() || digits.starts_with('_') || digits.ends_with('_') || digits.contains("__") {
+            return None;
+        }
+        let mut value: Option<i64> = Some(0);
+        for ch in digits.chars().filter(|ch| *ch != '_') {
+            let digit = ch.to_digit(radix)?;
+            value = value.and_then(|value| value.checked_mul(i64::from(radix))?.checked_add(i64::from(digit)));
+        }
+        Some(value.map_or_else(|| literal.replace('_', ""), |value| value.to_string()))
+    }
+
+    fn is_word_char(ch: char) -> bool {
+        ch.is_alphanumeric() || ch == '_'
+    }
+
     fn get_id(&mut self, buf: &str, input: &mut Peekable<Chars>) -> Option<Token>{
         let next_char = input.peek().unwrap_or(&' ');
         let mut chars = buf.chars();
         // Ensure there's at least one character and it is alphabetic.
         if let Some(first) = chars.next() {
-            // Check that the entire buffer is alphanumeric and the next char is not part of an identifier.
+            // Check that the entire buffer is alphanumeric and the next char is not part of a word, so that
+            // keywords with underscores are read whole.
             return if first.is_alphabetic()
                 && buf.chars().all(char::is_alphanumeric)
-                && !next_char.is_alphanumeric()
+                && !Self::is_word_char(*next_char)
             {
                 let value = buf.to_string();
                 let value_len = buf.len();
@@ -168,34 +291,14 @@ impl Tokenizer {
         None
     }
     
-    fn get_error(&mut self, buf: &str, input: &mut Peekable<Chars>) -> Option<Token>{
-        let next_char = input.peek().unwrap_or(&' ');
-        let mut chars = buf.chars();
-        if let Some(first) = chars.next() {
-            // If the buffer starts with a numeric but then contains only alphanumerics,
-            // it may be an error if it doesn't form a proper number.
-            return if first.is_numeric()
-                && buf.chars().all(char::is_alphanumeric)
-                && !next_char.is_alphanumeric()
-            {
-                let value_len = buf.len();
-                Some(Token::Err {
-                    span: self.get_span(value_len),
-                })
-            } else { None }
-        }
-        None
-    }
-    
-    fn handle_newline(&mut self){
-        self.m_line += 1;
-        self.m_row = 0;
+    fn get_span(&self, length: usize) -> Span {
+        Span::new(self.m_file_id, self.m_offset, self.m_offset + length)
     }
-    
-    fn get_span(&mut self, length: usize) -> Span {
-        let res = Span::new(self.m_line, self.m_row, self.m_row + length-1);
-        self.m_row += length - 1;
-        res
+}
+
+impl Default for Tokenizer {
+    fn default() -> Self {
+        Tokenizer::new()
     }
 }
 
@@ -211,15 +314,16 @@ mod test_tokenizer{
         #[test]
         fn test_get_span() {
             let mut temp_tokenizer = Tokenizer::new();
-            let exp_span = Span::new(0, 0, 0);
+            let exp_span = Span::new(0, 0, 1);
             let res_span = temp_tokenizer.get_span(1);
             assert_eq!(exp_span, res_span);
-            assert_eq!(temp_tokenizer.m_row, 0);
+            assert_eq!(temp_tokenizer.m_offset, 0);
 
-            let exp_span = Span::new(0, 0, 1);
+            temp_tokenizer.m_offset = 3;
+            temp_tokenizer.m_file_id = 2;
+            let exp_span = Span::new(2, 3, 5);
             let res_span = temp_tokenizer.get_span(2);
             assert_eq!(exp_span, res_span);
-            assert_eq!(temp_tokenizer.m_row, 1)
         }
         
         #[test]
@@ -227,19 +331,18 @@ mod test_tokenizer{
             let mut temp_tokenizer = Tokenizer::new();
             let temp_span = Span::new(0, 0, 0);
             temp_tokenizer.m_tokens = vec![Token::NewLine {span: temp_span}];
-            temp_tokenizer.m_line = 42;
-            temp_tokenizer.m_row = 42;
+            temp_tokenizer.m_offset = 42;
             
             temp_tokenizer.clear();
             assert!(temp_tokenizer.m_tokens.is_empty());
-            assert_eq!(temp_tokenizer.m_line, 0);
-            assert_eq!(temp_tokenizer.m_row, 0);
+            assert_eq!(temp_tokenizer.m_offset, 0);
         }
         
         #[test]
         fn test_match_ch(){
             let mut temp_tokenizer = Tokenizer::new();
-            assert_eq!(temp_tokenizer.match_ch(' ', None), Some(Token::WhiteSpace {span: Span::new(0, 0, 0)}));
+            assert_eq!(temp_tokenizer.match_ch(' ', None, 0), Some(Token::WhiteSpace {span: Span::new(0, 0, 1)}));
+            assert_eq!(temp_tokenizer.match_ch('=', None, 2), Some(Token::Equals {span: Span::new(0, 2, 3)}));
         }
         
         #[test]
@@ -247,11 +350,33 @@ mod test_tokenizer{
             let mut temp_tokenizer = Tokenizer::new();
             assert!(temp_tokenizer.get_id("", &mut "".chars().peekable()).is_none());
         }
+
+        #[test]
+        fn test_is_identifier(){
+            assert!(Tokenizer::is_identifier("x") && Tokenizer::is_identifier("point2") && Tokenizer::is_identifier("letter"));
+            for name in ["", "2x", "let", "exit", "len", "read_int", "true", "a b", "a_b", "x\n\tsyscall", "x;"] {
+                assert!(!Tokenizer::is_identifier(name), "{name}");
+            }
+        }
         
         #[test]
-        fn test_get_error(){
+        fn test_get_num(){
             let mut temp_tokenizer = Tokenizer::new();
-            assert!(temp_tokenizer.get_error("", &mut "".chars().peekable()).is_none());
+            assert!(temp_tokenizer.get_num("", &mut "".chars().peekable()).is_none());
+            assert!(temp_tokenizer.get_num("1", &mut "x".chars().peekable()).is_none());
+        }
+        
+        #[test]
+        fn test_parse_number(){
+            assert_eq!(Tokenizer::parse_number("1_000_000"), Some("1000000".to_string()));
+            assert_eq!(Tokenizer::parse_number("0xFF"), Some("255".to_string()));
+            assert_eq!(Tokenizer::parse_number("0x7fff_ffff_ffff_ffff"), Some(i64::MAX.to_string()));
+            assert_eq!(Tokenizer::parse_number("0b1010"), Some("10".to_string()));
+            assert_eq!(Tokenizer::parse_number("0o17"), Some("15".to_string()));
+            assert_eq!(Tokenizer::parse_number("99_999_999_999_999_999_999"), Some("99999999999999999999".to_string()));
+            for literal in ["0x", "0b102", "0o8", "1_", "_1", "1__0", "0x_1", "12a", "1٣"] {
+                assert_eq!(Tokenizer::parse_number(literal), None, "{literal}");
+            }
         }
     }
     
@@ -273,10 +398,10 @@ mod test_tokenizer{
             tokenizer.tokenize("exit(0)");
 
             let expected_token = vec!(
-                Token::Exit { span: Span::new(0, 0, 3) },
-                Token::OpenBracket { span: Span::new(0, 4, 4) },
-                Token::Number { value: "0".to_string(), span: Span::new(0, 5, 5) },
-                Token::ClosedBracket { span: Span::new(0, 6, 6) }
+                Token::Exit { span: Span::new(0, 0, 4) },
+                Token::OpenBracket { span: Span::new(0, 4, 5) },
+                Token::Number { value: "0".to_string(), span: Span::new(0, 5, 6) },
+                Token::ClosedBracket { span: Span::new(0, 6, 7) }
             );
             assert_eq!(tokenizer.get_tokens(), expected_token);
         }
@@ -286,12 +411,12 @@ mod test_tokenizer{
             tokenizer.tokenize("x       =     0  ");
 
             let expected_token = vec!(
-                Token::ID { name: "x".to_string(), span: Span::new(0, 0, 0) },
-                Token::WhiteSpace { span: Span::new(0, 1, 7) },
-                Token::Equals {span: Span::new(0, 8, 8)},
-                Token::WhiteSpace { span: Span::new(0, 9, 13) },
-                Token::Number { value: "0".to_string(), span: Span::new(0, 14, 14) },
-                Token::WhiteSpace { span: Span::new(0, 15, 16) }
+                Token::ID { name: "x".to_string(), span: Span::new(0, 0, 1) },
+                Token::WhiteSpace { span: Span::new(0, 1, 8) },
+                Token::Equals {span: Span::new(0, 8, 9)},
+                Token::WhiteSpace { span: Span::new(0, 9, 14) },
+                Token::Number { value: "0".to_string(), span: Span::new(0, 14, 15) },
+                Token::WhiteSpace { span: Span::new(0, 15, 17) }
             );
             assert_eq!(tokenizer.get_tokens(), expected_token);
         }
@@ -301,9 +426,9 @@ mod test_tokenizer{
             let mut tokenizer = Tokenizer::new();
             tokenizer.tokenize("x1=0");
             let expected_token = vec!(
-                Token::ID { name: "x1".to_string(), span: Span::new(0, 0, 1) },
-                Token::Equals {span : Span::new(0, 2, 2)},
-                Token::Number { value: "0".to_string(), span: Span::new(0, 3, 3) },
+                Token::ID { name: "x1".to_string(), span: Span::new(0, 0, 2) },
+                Token::Equals {span : Span::new(0, 2, 3)},
+                Token::Number { value: "0".to_string(), span: Span::new(0, 3, 4) },
             );
             assert_eq!(tokenizer.get_tokens(), expected_token);
         }
@@ -314,19 +439,19 @@ mod test_tokenizer{
             tokenizer.tokenize("x = 0\nexit(x)\n{}");
 
             let expected_token = vec!(
-                Token::ID{ name: "x".to_string(), span: Span::new(0, 0, 0) },
-                Token::WhiteSpace { span: Span::new(0, 1, 1)},
-                Token::Equals {span : Span::new(0, 2, 2)},
-                Token::WhiteSpace { span: Span::new(0, 3, 3)},
-                Token::Number { value: "0".to_string(), span: Span::new(0, 4, 4) },
-                Token::NewLine { span: Span::new(0, 5, 5)},
-                Token::Exit { span: Span::new(1, 0, 3) },
-                Token::OpenBracket { span: Span::new(1, 4, 4)},
-                Token::ID { name: "x".to_string(), span: Span::new(1, 5, 5) },
-                Token::ClosedBracket { span: Span::new(1, 6, 6) },
-                Token::NewLine { span: Span::new(1, 7, 7)},
-                Token::OpenCurlyBracket { span: Span::new(2, 0, 0)},
-                Token::ClosedCurlyBracket { span: Span::new(2, 1, 1)},
+                Token::ID{ name: "x".to_string(), span: Span::new(0, 0, 1) },
+                Token::WhiteSpace { span: Span::new(0, 1, 2)},
+                Token::Equals {span : Span::new(0, 2, 3)},
+                Token::WhiteSpace { span: Span::new(0, 3, 4)},
+                Token::Number { value: "0".to_string(), span: Span::new(0, 4, 5) },
+                Token::NewLine { span: Span::new(0, 5, 6)},
+                Token::Exit { span: Span::new(0, 6, 10) },
+                Token::OpenBracket { span: Span::new(0, 10, 11)},
+                Token::ID { name: "x".to_string(), span: Span::new(0, 11, 12) },
+                Token::ClosedBracket { span: Span::new(0, 12, 13) },
+                Token::NewLine { span: Span::new(0, 13, 14)},
+                Token::OpenCurlyBracket { span: Span::new(0, 14, 15)},
+                Token::ClosedCurlyBracket { span: Span::new(0, 15, 16)},
             );
             assert_eq!(tokenizer.get_tokens(), expected_token);
         }
@@ -336,11 +461,25 @@ mod test_tokenizer{
             let mut tokenizer = Tokenizer::new();
             tokenizer.tokenize("1x = 0");
             let expected_token = vec!(
-                Token::Err {span: Span::new(0, 0, 1) },
-                Token::WhiteSpace { span: Span::new(0, 2, 2)},
-                Token::Equals {span : Span::new(0, 3, 3)},
-                Token::WhiteSpace { span: Span::new(0, 4, 4)},
-                Token::Number { value: "0".to_string(), span: Span::new(0, 5, 5) }
+                Token::InvalidNumber {span: Span::new(0, 0, 2) },
+                Token::WhiteSpace { span: Span::new(0, 2, 3)},
+                Token::Equals {span : Span::new(0, 3, 4)},
+                Token::WhiteSpace { span: Span::new(0, 4, 5)},
+                Token::Number { value: "0".to_string(), span: Span::new(0, 5, 6) }
+            );
+            assert_eq!(tokenizer.get_tokens(), expected_token);
+        }
+        
+        #[test]
+        fn test_number_literals() {
+            let mut tokenizer = Tokenizer::new();
+            tokenizer.tokenize("0x1F+1_0-0b2");
+            let expected_token = vec!(
+                Token::Number { value: "31".to_string(), span: Span::new(0, 0, 4) },
+                Token::Operator(Operator::Plus { span: Span::new(0, 4, 5) }),
+                Token::Number { value: "10".to_string(), span: Span::new(0, 5, 8) },
+                Token::Operator(Operator::Minus { span: Span::new(0, 8, 9) }),
+                Token::InvalidNumber { span: Span::new(0, 9, 12) }
             );
             assert_eq!(tokenizer.get_tokens(), expected_token);
         }
@@ -348,17 +487,17 @@ mod test_tokenizer{
         #[test]
         fn test_undefined_char(){
             let mut tokenizer = Tokenizer::new();
-            tokenizer.tokenize("~∞");
+            tokenizer.tokenize("@∞");
             let expected_token = vec!(
-                Token::Err {span: Span::new(0, 0, 1) }
+                Token::Err {span: Span::new(0, 0, 4) }
             );
             assert_eq!(tokenizer.get_tokens(), expected_token);
             
             let mut tokenizer = Tokenizer::new();
-            tokenizer.tokenize("~∞\n");
+            tokenizer.tokenize("@∞\n");
             let expected_token = vec!(
-                Token::Err {span: Span::new(0, 0, 1) },
-                Token::NewLine {span: Span::new(0, 2, 2)}
+                Token::Err {span: Span::new(0, 0, 4) },
+                Token::NewLine { span: Span::new(0, 4, 5)}
             );
             assert_eq!(tokenizer.get_tokens(), expected_token);
         }
@@ -369,29 +508,29 @@ mod test_tokenizer{
             tokenizer.tokenize("(+-*//**%)");
 
             let expected_token = vec!(
-                Token::Operator(Operator::OpenBracket {span: Span::new(0, 0, 0)}),
-                Token::Operator(Operator::Plus {span: Span::new(0, 1, 1)}),
-                Token::Operator(Operator::Minus {span: Span::new(0, 2, 2)}),
-                Token::Operator(Operator::Multiplication {span: Span::new(0, 3, 3)}),
-                Token::Operator(Operator::Division {span: Span::new(0, 4, 5)}),
-                Token::Operator(Operator::Exponent {span: Span::new(0, 6, 7)}),
-                Token::Operator(Operator::Modulus {span: Span::new(0, 8, 8)}),
-                Token::Operator(Operator::ClosedBracket {span: Span::new(0, 9, 9)})
+                Token::Operator(Operator::OpenBracket {span: Span::new(0, 0, 1)}),
+                Token::Operator(Operator::Plus {span: Span::new(0, 1, 2)}),
+                Token::Operator(Operator::Minus {span: Span::new(0, 2, 3)}),
+                Token::Operator(Operator::Multiplication {span: Span::new(0, 3, 4)}),
+                Token::Operator(Operator::Division {span: Span::new(0, 4, 6)}),
+                Token::Operator(Operator::Exponent {span: Span::new(0, 6, 8)}),
+                Token::Operator(Operator::Modulus {span: Span::new(0, 8, 9)}),
+                Token::Operator(Operator::ClosedBracket {span: Span::new(0, 9, 10)})
             );
             assert_eq!(tokenizer.get_tokens(), expected_token);
 
             tokenizer.tokenize("*** * ** ** *");
             let expected_token = vec!(
-                Token::Operator(Operator::Exponent {span: Span::new(0, 0, 1)}),
-                Token::Operator(Operator::Multiplication {span: Span::new(0, 2, 2)}),
-                Token::WhiteSpace { span: Span::new(0, 3, 3) },
-                Token::Operator(Operator::Multiplication {span: Span::new(0, 4, 4)}),
-                Token::WhiteSpace { span: Span::new(0, 5, 5) },
-                Token::Operator(Operator::Exponent {span: Span::new(0, 6, 7)}),
-                Token::WhiteSpace { span: Span::new(0, 8, 8) },
-                Token::Operator(Operator::Exponent {span: Span::new(0, 9, 10)}),
-                Token::WhiteSpace { span: Span::new(0, 11, 11) },
-                Token::Operator(Operator::Multiplication {span: Span::new(0, 12, 12)}),
+                Token::Operator(Operator::Exponent {span: Span::new(0, 0, 2)}),
+                Token::Operator(Operator::Multiplication {span: Span::new(0, 2, 3)}),
+                Token::WhiteSpace { span: Span::new(0, 3, 4) },
+                Token::Operator(Operator::Multiplication {span: Span::new(0, 4, 5)}),
+                Token::WhiteSpace { span: Span::new(0, 5, 6) },
+                Token::Operator(Operator::Exponent {span: Span::new(0, 6, 8)}),
+                Token::WhiteSpace { span: Span::new(0, 8, 9) },
+                Token::Operator(Operator::Exponent {span: Span::new(0, 9, 11)}),
+                Token::WhiteSpace { span: Span::new(0, 11, 12) },
+                Token::Operator(Operator::Multiplication {span: Span::new(0, 12, 13)}),
             );
             assert_eq!(tokenizer.get_tokens(), expected_token);
         }
@@ -402,10 +541,28 @@ mod test_tokenizer{
             tokenizer.tokenize("&&||!!^|");
 
             let expected_tokens = vec![
-                Token::Operator(Operator::And { span: Span::new(0, 0, 1) }),
-                Token::Operator(Operator::Or  { span: Span::new(0, 2, 3) }),
-                Token::Operator(Operator::Not { span: Span::new(0, 4, 5) }),
-                Token::Operator(Operator::Xor { span: Span::new(0, 6, 7) }),
+                Token::Operator(Operator::And { span: Span::new(0, 0, 2) }),
+                Token::Operator(Operator::Or  { span: Span::new(0, 2, 4) }),
+                Token::Operator(Operator::Not { span: Span::new(0, 4, 6) }),
+                Token::Operator(Operator::Xor { span: Span::new(0, 6, 8) }),
+            ];
+            assert_eq!(tokenizer.get_tokens(), expected_tokens);
+        }
+
+        #[test]
+        fn test_bitwise_operators() {
+            let mut tokenizer = Tokenizer::new();
+            tokenizer.tokenize("&|^~<<>>&&^|");
+
+            let expected_tokens = vec![
+                Token::Operator(Operator::BitAnd { span: Span::new(0, 0, 1) }),
+                Token::Operator(Operator::BitOr { span: Span::new(0, 1, 2) }),
+                Token::Operator(Operator::BitXor { span: Span::new(0, 2, 3) }),
+                Token::Operator(Operator::BitNot { span: Span::new(0, 3, 4) }),
+                Token::Operator(Operator::ShiftLeft { span: Span::new(0, 4, 6) }),
+                Token::Operator(Operator::ShiftRight { span: Span::new(0, 6, 8) }),
+                Token::Operator(Operator::And { span: Span::new(0, 8, 10) }),
+                Token::Operator(Operator::Xor { span: Span::new(0, 10, 12) }),
             ];
             assert_eq!(tokenizer.get_tokens(), expected_tokens);
         }
@@ -416,21 +573,21 @@ mod test_tokenizer{
             tokenizer.tokenize("x && y || !! z ^| w");
 
             let expected_tokens = vec![
-                Token::ID { name: "x".to_string(), span: Span::new(0, 0, 0) },
-                Token::WhiteSpace { span: Span::new(0, 1, 1) },
-                Token::Operator(Operator::And { span: Span::new(0, 2, 3) }),
-                Token::WhiteSpace { span: Span::new(0, 4, 4) },
-                Token::ID { name: "y".to_string(), span: Span::new(0, 5, 5) },
-                Token::WhiteSpace { span: Span::new(0, 6, 6) },
-                Token::Operator(Operator::Or { span: Span::new(0, 7, 8) }),
-                Token::WhiteSpace { span: Span::new(0, 9, 9) },
-                Token::Operator(Operator::Not { span: Span::new(0, 10, 11) }),
-                Token::WhiteSpace { span: Span::new(0, 12, 12) },
-                Token::ID { name: "z".to_string(), span: Span::new(0, 13, 13) },
-                Token::WhiteSpace { span: Span::new(0, 14, 14) },
-                Token::Operator(Operator::Xor { span: Span::new(0, 15, 16) }),
-                Token::WhiteSpace { span: Span::new(0, 17, 17) },
-                Token::ID { name: "w".to_string(), span: Span::new(0, 18, 18) },
+                Token::ID { name: "x".to_string(), span: Span::new(0, 0, 1) },
+                Token::WhiteSpace { span: Span::new(0, 1, 2) },
+                Token::Operator(Operator::And { span: Span::new(0, 2, 4) }),
+                Token::WhiteSpace { span: Span::new(0, 4, 5) },
+                Token::ID { name: "y".to_string(), span: Span::new(0, 5, 6) },
+                Token::WhiteSpace { span: Span::new(0, 6, 7) },
+                Token::Operator(Operator::Or { span: Span::new(0, 7, 9) }),
+                Token::WhiteSpace { span: Span::new(0, 9, 10) },
+                Token::Operator(Operator::Not { span: Span::new(0, 10, 12) }),
+                Token::WhiteSpace { span: Span::new(0, 12, 13) },
+                Token::ID { name: "z".to_string(), span: Span::new(0, 13, 14) },
+                Token::WhiteSpace { span: Span::new(0, 14, 15) },
+                Token::Operator(Operator::Xor { span: Span::new(0, 15, 17) }),
+                Token::WhiteSpace { span: Span::new(0, 17, 18) },
+                Token::ID { name: "w".to_string(), span: Span::new(0, 18, 19) },
             ];
             assert_eq!(tokenizer.get_tokens(), expected_tokens);
         }
@@ -441,9 +598,9 @@ mod test_tokenizer{
             tokenizer.tokenize("true false");
 
             let expected_tokens = vec![
-                Token::Boolean { value: true, span: Span::new(0, 0, 3) },
-                Token::WhiteSpace { span: Span::new(0, 4, 4) },
-                Token::Boolean { value: false, span: Span::new(0, 5, 9) },
+                Token::Boolean { value: true, span: Span::new(0, 0, 4) },
+                Token::WhiteSpace { span: Span::new(0, 4, 5) },
+                Token::Boolean { value: false, span: Span::new(0, 5, 10) },
             ];
             assert_eq!(tokenizer.get_tokens(), expected_tokens);
         }
@@ -454,24 +611,295 @@ mod test_tokenizer{
             tokenizer.tokenize("x && y\n|| !! z\n^| w");
 
             let expected_tokens = vec![
-                Token::ID { name: "x".to_string(), span: Span::new(0, 0, 0) },
-                Token::WhiteSpace { span: Span::new(0, 1, 1) },
-                Token::Operator(Operator::And { span: Span::new(0, 2, 3) }),
-                Token::WhiteSpace { span: Span::new(0, 4, 4) },
-                Token::ID { name: "y".to_string(), span: Span::new(0, 5, 5) },
-                Token::NewLine { span: Span::new(0, 6, 6) },
-                Token::Operator(Operator::Or { span: Span::new(1, 0, 1) }),
-                Token::WhiteSpace { span: Span::new(1, 2, 2) },
-                Token::Operator(Operator::Not { span: Span::new(1, 3, 4) }),
-                Token::WhiteSpace { span: Span::new(1, 5, 5) },
-                Token::ID { name: "z".to_string(), span: Span::new(1, 6, 6) },
-                Token::NewLine { span: Span::new(1, 7, 7) },
-                Token::Operator(Operator::Xor { span: Span::new(2, 0, 1) }),
-                Token::WhiteSpace { span: Span::new(2, 2, 2) },
-                Token::ID { name: "w".to_string(), span: Span::new(2, 3, 3) },
+                Token::ID { name: "x".to_string(), span: Span::new(0, 0, 1) },
+                Token::WhiteSpace { span: Span::new(0, 1, 2) },
+                Token::Operator(Operator::And { span: Span::new(0, 2, 4) }),
+                Token::WhiteSpace { span: Span::new(0, 4, 5) },
+                Token::ID { name: "y".to_string(), span: Span::new(0, 5, 6) },
+                Token::NewLine { span: Span::new(0, 6, 7) },
+                Token::Operator(Operator::Or { span: Span::new(0, 7, 9) }),
+                Token::WhiteSpace { span: Span::new(0, 9, 10) },
+                Token::Operator(Operator::Not { span: Span::new(0, 10, 12) }),
+                Token::WhiteSpace { span: Span::new(0, 12, 13) },
+                Token::ID { name: "z".to_string(), span: Span::new(0, 13, 14) },
+                Token::NewLine { span: Span::new(0, 14, 15) },
+                Token::Operator(Operator::Xor { span: Span::new(0, 15, 17) }),
+                Token::WhiteSpace { span: Span::new(0, 17, 18) },
+                Token::ID { name: "w".to_string(), span: Span::new(0, 18, 19) },
             ];
 
             assert_eq!(tokenizer.get_tokens(), expected_tokens);
         }
+
+        #[test]
+        fn test_carriage_return(){
+            let mut tokenizer = Tokenizer::new();
+            tokenizer.tokenize("x\r\nexit(x)");
+            let expected_tokens = vec![
+                Token::ID { name: "x".to_string(), span: Span::new(0, 0, 1) },
+                Token::NewLine { span: Span::new(0, 2, 3) },
+                Token::Exit { span: Span::new(0, 3, 7) },
+                Token::OpenBracket { span: Span::new(0, 7, 8) },
+                Token::ID { name: "x".to_string(), span: Span::new(0, 8, 9) },
+                Token::ClosedBracket { span: Span::new(0, 9, 10) },
+            ];
+            assert_eq!(tokenizer.get_tokens(), expected_tokens);
+        }
+
+        #[test]
+        fn test_undefined_char_before_token(){
+            let mut tokenizer = Tokenizer::new();
+            tokenizer.tokenize("x∞=1");
+            let expected_tokens = vec![
+                Token::ID { name: "x".to_string(), span: Span::new(0, 0, 1) },
+                Token::Err { span: Span::new(0, 1, 4) },
+                Token::Equals { span: Span::new(0, 4, 5) },
+                Token::Number { value: "1".to_string(), span: Span::new(0, 5, 6) },
+            ];
+            assert_eq!(tokenizer.get_tokens(), expected_tokens);
+        }
+
+        #[test]
+        fn test_import(){
+            let mut tokenizer = Tokenizer::new();
+            tokenizer.set_file_id(1);
+            tokenizer.tokenize("import \"lib/math.brs\"\nimport_x = \"x");
+            let expected_tokens = vec![
+                Token::Import { span: Span::new(1, 0, 6) },
+                Token::WhiteSpace { span: Span::new(1, 6, 7) },
+                Token::StringLiteral { value: "lib/math.brs".to_string(), span: Span::new(1, 7, 21) },
+                Token::NewLine { span: Span::new(1, 21, 22) },
+                Token::Err { span: Span::new(1, 22, 30) },
+                Token::WhiteSpace { span: Span::new(1, 30, 31) },
+                Token::Equals { span: Span::new(1, 31, 32) },
+                Token::WhiteSpace { span: Span::new(1, 32, 33) },
+                Token::Err { span: Span::new(1, 33, 35) },
+            ];
+            assert_eq!(tokenizer.get_tokens(), expected_tokens);
+        }
+
+        #[test]
+        fn test_declarations(){
+            let mut tokenizer = Tokenizer::new();
+            tokenizer.tokenize("let x\nvar letter\nconst var2");
+            let expected_tokens = vec![
+                Token::Let { span: Span::new(0, 0, 3) },
+                Token::WhiteSpace { span: Span::new(0, 3, 4) },
+                Token::ID { name: "x".to_string(), span: Span::new(0, 4, 5) },
+                Token::NewLine { span: Span::new(0, 5, 6) },
+                Token::Var { span: Span::new(0, 6, 9) },
+                Token::WhiteSpace { span: Span::new(0, 9, 10) },
+                Token::ID { name: "letter".to_string(), span: Span::new(0, 10, 16) },
+                Token::NewLine { span: Span::new(0, 16, 17) },
+                Token::Const { span: Span::new(0, 17, 22) },
+                Token::WhiteSpace { span: Span::new(0, 22, 23) },
+                Token::ID { name: "var2".to_string(), span: Span::new(0, 23, 27) },
+            ];
+            assert_eq!(tokenizer.get_tokens(), expected_tokens);
+        }
+
+        #[test]
+        fn test_annotation_and_cast(){
+            let mut tokenizer = Tokenizer::new();
+            tokenizer.tokenize("x: int
+b as bool
+ask");
+            let expected_tokens = vec![
+                Token::ID { name: "x".to_string(), span: Span::new(0, 0, 1) },
+                Token::Colon { span: Span::new(0, 1, 2) },
+                Token::WhiteSpace { span: Span::new(0, 2, 3) },
+                Token::ID { name: "int".to_string(), span: Span::new(0, 3, 6) },
+                Token::NewLine { span: Span::new(0, 6, 7) },
+                Token::ID { name: "b".to_string(), span: Span::new(0, 7, 8) },
+                Token::WhiteSpace { span: Span::new(0, 8, 9) },
+                Token::Operator(Operator::Cast { span: Span::new(0, 9, 11) }),
+                Token::WhiteSpace { span: Span::new(0, 11, 12) },
+                Token::ID { name: "bool".to_string(), span: Span::new(0, 12, 16) },
+                Token::NewLine { span: Span::new(0, 16, 17) },
+                Token::ID { name: "ask".to_string(), span: Span::new(0, 17, 20) },
+            ];
+            assert_eq!(tokenizer.get_tokens(), expected_tokens);
+        }
+
+        #[test]
+        fn test_arrays(){
+            let mut tokenizer = Tokenizer::new();
+            tokenizer.tokenize("a[i]
+[1, 2; 3]
+len(a)
+length");
+            let expected_tokens = vec![
+                Token::ID { name: "a".to_string(), span: Span::new(0, 0, 1) },
+                Token::OpenSquareBracket { span: Span::new(0, 1, 2) },
+                Token::ID { name: "i".to_string(), span: Span::new(0, 2, 3) },
+                Token::ClosedSquareBracket { span: Span::new(0, 3, 4) },
+                Token::NewLine { span: Span::new(0, 4, 5) },
+                Token::OpenSquareBracket { span: Span::new(0, 5, 6) },
+                Token::Number { value: "1".to_string(), span: Span::new(0, 6, 7) },
+                Token::Comma { span: Span::new(0, 7, 8) },
+                Token::WhiteSpace { span: Span::new(0, 8, 9) },
+                Token::Number { value: "2".to_string(), span: Span::new(0, 9, 10) },
+                Token::Semicolon { span: Span::new(0, 10, 11) },
+                Token::WhiteSpace { span: Span::new(0, 11, 12) },
+                Token::Number { value: "3".to_string(), span: Span::new(0, 12, 13) },
+                Token::ClosedSquareBracket { span: Span::new(0, 13, 14) },
+                Token::NewLine { span: Span::new(0, 14, 15) },
+                Token::Operator(Operator::Len { span: Span::new(0, 15, 18) }),
+                Token::Operator(Operator::OpenBracket { span: Span::new(0, 18, 19) }),
+                Token::ID { name: "a".to_string(), span: Span::new(0, 19, 20) },
+                Token::Operator(Operator::ClosedBracket { span: Span::new(0, 20, 21) }),
+                Token::NewLine { span: Span::new(0, 21, 22) },
+                Token::ID { name: "length".to_string(), span: Span::new(0, 22, 28) },
+            ];
+            assert_eq!(tokenizer.get_tokens(), expected_tokens);
+        }
+
+        #[test]
+        fn test_structs(){
+            let mut tokenizer = Tokenizer::new();
+            tokenizer.tokenize("struct P {x: int}
+p.x
+structure");
+            let expected_tokens = vec![
+                Token::Struct { span: Span::new(0, 0, 6) },
+                Token::WhiteSpace { span: Span::new(0, 6, 7) },
+                Token::ID { name: "P".to_string(), span: Span::new(0, 7, 8) },
+                Token::WhiteSpace { span: Span::new(0, 8, 9) },
+                Token::OpenCurlyBracket { span: Span::new(0, 9, 10) },
+                Token::ID { name: "x".to_string(), span: Span::new(0, 10, 11) },
+                Token::Colon { span: Span::new(0, 11, 12) },
+                Token::WhiteSpace { span: Span::new(0, 12, 13) },
+                Token::ID { name: "int".to_string(), span: Span::new(0, 13, 16) },
+                Token::ClosedCurlyBracket { span: Span::new(0, 16, 17) },
+                Token::NewLine { span: Span::new(0, 17, 18) },
+                Token::ID { name: "p".to_string(), span: Span::new(0, 18, 19) },
+                Token::Operator(Operator::Field { span: Span::new(0, 19, 20) }),
+                Token::ID { name: "x".to_string(), span: Span::new(0, 20, 21) },
+                Token::NewLine { span: Span::new(0, 21, 22) },
+                Token::ID { name: "structure".to_string(), span: Span::new(0, 22, 31) },
+            ];
+            assert_eq!(tokenizer.get_tokens(), expected_tokens);
+        }
+
+        #[test]
+        fn test_enums(){
+            let mut tokenizer = Tokenizer::new();
+            tokenizer.tokenize("S::A(1)\nmatch s {_ => x}");
+            let expected_tokens = vec![
+                Token::ID { name: "S".to_string(), span: Span::new(0, 0, 1) },
+                Token::DoubleColon { span: Span::new(0, 1, 3) },
+                Token::ID { name: "A".to_string(), span: Span::new(0, 3, 4) },
+                Token::OpenBracket { span: Span::new(0, 4, 5) },
+                Token::Number { value: "1".to_string(), span: Span::new(0, 5, 6) },
+                Token::ClosedBracket { span: Span::new(0, 6, 7) },
+                Token::NewLine { span: Span::new(0, 7, 8) },
+                Token::Match { span: Span::new(0, 8, 13) },
+                Token::WhiteSpace { span: Span::new(0, 13, 14) },
+                Token::ID { name: "s".to_string(), span: Span::new(0, 14, 15) },
+                Token::WhiteSpace { span: Span::new(0, 15, 16) },
+                Token::OpenCurlyBracket { span: Span::new(0, 16, 17) },
+                Token::Underscore { span: Span::new(0, 17, 18) },
+                Token::WhiteSpace { span: Span::new(0, 18, 19) },
+                Token::Arrow { span: Span::new(0, 19, 21) },
+                Token::WhiteSpace { span: Span::new(0, 21, 22) },
+                Token::ID { name: "x".to_string(), span: Span::new(0, 22, 23) },
+                Token::ClosedCurlyBracket { span: Span::new(0, 23, 24) },
+            ];
+            assert_eq!(tokenizer.get_tokens(), expected_tokens);
+        }
+
+        #[test]
+        fn test_ranges(){
+            let mut tokenizer = Tokenizer::new();
+            tokenizer.tokenize("for i in 0..n\n1..=p.x step 2\ninput");
+            let expected_tokens = vec![
+                Token::For { span: Span::new(0, 0, 3) },
+                Token::WhiteSpace { span: Span::new(0, 3, 4) },
+                Token::ID { name: "i".to_string(), span: Span::new(0, 4, 5) },
+                Token::WhiteSpace { span: Span::new(0, 5, 6) },
+                Token::In { span: Span::new(0, 6, 8) },
+                Token::WhiteSpace { span: Span::new(0, 8, 9) },
+                Token::Number { value: "0".to_string(), span: Span::new(0, 9, 10) },
+                Token::DotDot { span: Span::new(0, 10, 12) },
+                Token::ID { name: "n".to_string(), span: Span::new(0, 12, 13) },
+                Token::NewLine { span: Span::new(0, 13, 14) },
+                Token::Number { value: "1".to_string(), span: Span::new(0, 14, 15) },
+                Token::DotDotEquals { span: Span::new(0, 15, 18) },
+                Token::ID { name: "p".to_string(), span: Span::new(0, 18, 19) },
+                Token::Operator(Operator::Field { span: Span::new(0, 19, 20) }),
+                Token::ID { name: "x".to_string(), span: Span::new(0, 20, 21) },
+                Token::WhiteSpace { span: Span::new(0, 21, 22) },
+                Token::Step { span: Span::new(0, 22, 26) },
+                Token::WhiteSpace { span: Span::new(0, 26, 27) },
+                Token::Number { value: "2".to_string(), span: Span::new(0, 27, 28) },
+                Token::NewLine { span: Span::new(0, 28, 29) },
+                Token::ID { name: "input".to_string(), span: Span::new(0, 29, 34) },
+            ];
+            assert_eq!(tokenizer.get_tokens(), expected_tokens);
+        }
+
+        #[test]
+        fn test_heap(){
+            let mut tokenizer = Tokenizer::new();
+            tokenizer.tokenize("free(alloc(8))
+freed");
+            let expected_tokens = vec![
+                Token::Free { span: Span::new(0, 0, 4) },
+                Token::OpenBracket { span: Span::new(0, 4, 5) },
+                Token::Operator(Operator::Alloc { span: Span::new(0, 5, 10) }),
+                Token::Operator(Operator::OpenBracket { span: Span::new(0, 10, 11) }),
+                Token::Number { value: "8".to_string(), span: Span::new(0, 11, 12) },
+                Token::Operator(Operator::ClosedBracket { span: Span::new(0, 12, 13) }),
+                Token::ClosedBracket { span: Span::new(0, 13, 14) },
+                Token::NewLine { span: Span::new(0, 14, 15) },
+                Token::ID { name: "freed".to_string(), span: Span::new(0, 15, 20) },
+            ];
+            assert_eq!(tokenizer.get_tokens(), expected_tokens);
+        }
+
+        #[test]
+        fn test_input(){
+            let mut tokenizer = Tokenizer::new();
+            tokenizer.tokenize("arg(args) read_line()
+argument");
+            let expected_tokens = vec![
+                Token::Operator(Operator::Builtin { span: Span::new(0, 0, 3), function: Builtin::Arg }),
+                Token::Operator(Operator::OpenBracket { span: Span::new(0, 3, 4) }),
+                Token::Operator(Operator::Builtin { span: Span::new(0, 4, 8), function: Builtin::Args }),
+                Token::Operator(Operator::ClosedBracket { span: Span::new(0, 8, 9) }),
+                Token::WhiteSpace { span: Span::new(0, 9, 10) },
+                Token::Operator(Operator::Builtin { span: Span::new(0, 10, 19), function: Builtin::ReadLine }),
+                Token::Operator(Operator::OpenBracket { span: Span::new(0, 19, 20) }),
+                Token::Operator(Operator::ClosedBracket { span: Span::new(0, 20, 21) }),
+                Token::NewLine { span: Span::new(0, 21, 22) },
+                Token::ID { name: "argument".to_string(), span: Span::new(0, 22, 30) },
+            ];
+            assert_eq!(tokenizer.get_tokens(), expected_tokens);
+        }
+
+        #[test]
+        fn test_extern(){
+            let mut tokenizer = Tokenizer::new();
+            tokenizer.tokenize("extern fn puts(int, ...)\nputs(\"a\\tb\\\"\\q\")");
+            let expected_tokens = vec![
+                Token::Extern { span: Span::new(0, 0, 6) },
+                Token::WhiteSpace { span: Span::new(0, 6, 7) },
+                Token::Fn { span: Span::new(0, 7, 9) },
+                Token::WhiteSpace { span: Span::new(0, 9, 10) },
+                Token::ID { name: "puts".to_string(), span: Span::new(0, 10, 14) },
+                Token::Operator(Operator::OpenBracket { span: Span::new(0, 14, 15) }),
+                Token::ID { name: "int".to_string(), span: Span::new(0, 15, 18) },
+                Token::Comma { span: Span::new(0, 18, 19) },
+                Token::WhiteSpace { span: Span::new(0, 19, 20) },
+                Token::Ellipsis { span: Span::new(0, 20, 23) },
+                Token::Operator(Operator::ClosedBracket { span: Span::new(0, 23, 24) }),
+                Token::NewLine { span: Span::new(0, 24, 25) },
+                Token::ID { name: "puts".to_string(), span: Span::new(0, 25, 29) },
+                Token::Operator(Operator::OpenBracket { span: Span::new(0, 29, 30) }),
+                Token::StringLiteral { value: "a\tb\"\\q".to_string(), span: Span::new(0, 30, 40) },
+                Token::Operator(Operator::ClosedBracket { span: Span::new(0, 40, 41) }),
+            ];
+            assert_eq!(tokenizer.get_tokens(), expected_tokens);
+        }
     }
 }
\ No newline at end of file
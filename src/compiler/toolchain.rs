@@ -0,0 +1,153 @@
+use std::path::Path;
+use std::process::Command;
+use crate::utility::{Arch, LinkMode, Target, OS};
+
+/// External assembler and linker used to turn the generated assembly into an executable.
+pub struct Toolchain {
+    m_target: Target,
+    m_link_mode: LinkMode
+}
+
+impl Toolchain {
+    pub fn new(m_target: Target) -> Self {
+        Toolchain { m_target, m_link_mode: LinkMode::Freestanding }
+    }
+
+    pub fn set_link_mode(&mut self, link_mode: LinkMode) {
+        self.m_link_mode = link_mode;
+    }
+
+    pub fn assemble(&self, asm_file: &Path, o_file: &Path) -> Result<(), String> {
+        let mut cmd = match (self.m_target.m_os, self.m_target.m_arch) {
+            (OS::MacOS, Arch::X86_64) => {
+                let mut cmd = Command::new("nasm");
+                cmd.arg("-f").arg("macho64").arg("-o").arg(o_file).arg(asm_file);
+                cmd
+            }
+            (OS::MacOS, Arch::AArch64) => {
+                let mut cmd = Command::new("as");
+                cmd.arg("-arch").arg("arm64").arg("-o").arg(o_file).arg(asm_file);
+                cmd
+            }
+            (OS::Linux, Arch::X86_64) => {
+                let mut cmd = Command::new("nasm");
+                cmd.arg("-f").arg("elf64").arg(asm_file).arg("-o").arg(o_file);
+                cmd
+            }
+            (OS::Linux, Arch::AArch64) => {
+                let mut cmd = Command::new("aarch64-linux-gnu-as");
+                cmd.arg("-o").arg(o_file).arg(asm_file);
+                cmd
+            }
+            (OS::Windows, Arch::X86_64) => {
+                let mut cmd = Command::new("yasm");
+                cmd.arg("-f").arg("win64").arg(asm_file).arg("-o").arg(o_file);
+                cmd
+            }
+            (OS::Windows, Arch::AArch64) => {
+                let mut cmd = Command::new("aarch64-w64-mingw32-as");
+                cmd.arg("-o").arg(o_file).arg(asm_file);
+                cmd
+            }
+        };
+        Self::run_command(&mut cmd).map(|_| ())
+    }
+
+    /// Links the object file into an executable, returning the output of the linker.
+    pub fn link(&self, o_file: &Path, final_file: &Path) -> Result<String, String> {
+        if self.m_link_mode == LinkMode::Libc {
+            return self.link_libc(o_file, final_file);
+        }
+        let mut cmd = match self.m_target.m_os {
+            OS::MacOS => {
+                let mut cmd = Command::new("ld");
+                cmd.arg("-arch")
+                    .arg(if self.m_target.m_arch == Arch::X86_64 { "x86_64" } else { "arm64" })
+                    .arg("-macos_version_min")
+                    .arg("11.0.0")
+                    .arg("-o")
+                    .arg(final_file)
+                    .arg(o_file)
+                    .arg("-e")
+                    .arg("_start");
+                match self.m_target.m_arch {
+                    Arch::X86_64 => { cmd.arg("-static"); }
+                    Arch::AArch64 => { cmd.arg("-lSystem").arg("-syslibroot").arg(Self::get_macos_sdk_path()?); }
+                }
+                cmd
+            }
+            OS::Linux => {
+                let mut cmd = match self.m_target.m_arch {
+                    Arch::X86_64 => Command::new("ld"),
+                    Arch::AArch64 => Command::new("aarch64-linux-gnu-ld"),
+                };
+                cmd.arg("-o").arg(final_file).arg(o_file).arg("-e").arg("_start").arg("-static");
+                cmd
+            }
+            OS::Windows => {
+                let mut cmd = match self.m_target.m_arch {
+                    Arch::X86_64 => Command::new("x86_64-w64-mingw32-gcc"),
+                    Arch::AArch64 => {
+                        let mut cmd = Command::new("aarch64-w64-mingw32-gcc");
+                        cmd.arg("-target").arg("aarch64-pc-windows-gnu");
+                        cmd
+                    }
+                };
+                cmd.arg("-nostdlib")
+                    .arg("-o")
+                    .arg(final_file)
+                    .arg(o_file)
+                    .arg("--entry")
+                    .arg("_start")
+                    .arg("-lkernel32");
+                cmd
+            }
+        };
+        Self::run_command(&mut cmd)
+    }
+
+    /// Links through the C compiler, which adds the C library and the startup code calling `main`.
+    fn link_libc(&self, o_file: &Path, final_file: &Path) -> Result<String, String> {
+        let mut cmd = match (self.m_target.m_os, self.m_target.m_arch) {
+            (OS::MacOS, arch) => {
+                let mut cmd = Command::new("cc");
+                cmd.arg("-arch").arg(if arch == Arch::X86_64 { "x86_64" } else { "arm64" });
+                cmd
+            }
+            (OS::Linux, Arch::X86_64) => Command::new("cc"),
+            (OS::Linux, Arch::AArch64) => Command::new("aarch64-linux-gnu-gcc"),
+            (OS::Windows, Arch::X86_64) => Command::new("x86_64-w64-mingw32-gcc"),
+            (OS::Windows, Arch::AArch64) => {
+                let mut cmd = Command::new("aarch64-w64-mingw32-gcc");
+                cmd.arg("-target").arg("aarch64-pc-windows-gnu");
+                cmd
+            }
+        };
+        cmd.arg("-o").arg(final_file).arg(o_file);
+        if self.m_target.m_os == OS::Windows {
+            cmd.arg("-lkernel32");
+        }
+        Self::run_command(&mut cmd)
+    }
+
+    fn run_command(cmd: &mut Command) -> Result<String, String> {
+        let program = cmd.get_program().to_string_lossy().to_string();
+        let output = cmd.output().map_err(|err| format!("Failed to execute `{program}`: {err}"))?;
+        if !output.status.success() {
+            return Err(format!("Command `{program}` failed: {}", String::from_utf8_lossy(&output.stderr)));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    fn get_macos_sdk_path() -> Result<String, String> {
+        let mut cmd = Command::new("xcrun");
+        cmd.arg("--sdk")
+            .arg("macosx")
+            .arg("--show-sdk-path");
+        let sdk_path = Self::run_command(&mut cmd)?.trim().to_string();
+        if sdk_path.is_empty() {
+            return Err("xcrun returned an empty SDK path.".to_string());
+        }
+        Ok(sdk_path)
+    }
+}
\ No newline at end of file
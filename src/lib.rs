@@ -0,0 +1,5 @@
+pub mod compiler;
+pub mod utility;
+
+pub use compiler::{Artifact, Compiler, Diagnostic, OptLevel, OutputKind, Severity};
+pub use utility::{LinkMode, Target};
\ No newline at end of file
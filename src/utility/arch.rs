@@ -4,36 +4,59 @@ pub const TARGET_ARCH: Arch = Arch::X86_64;
 #[cfg(target_arch = "aarch64")]
 pub const TARGET_ARCH: Arch = Arch::AArch64;
 
-#[allow(dead_code)] // Only one arch will be used, so it's an expected behaviour
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Arch {
     X86_64,
     AArch64,
 }
 
 impl Arch{
-    pub fn get_base_reg(&self) -> &str{
+    pub fn from_name(name: &str) -> Option<Arch> {
+        match name {
+            "x86_64" => Some(Arch::X86_64),
+            "aarch64" => Some(Arch::AArch64),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Arch::X86_64 => "x86_64",
+            Arch::AArch64 => "aarch64",
+        }
+    }
+
+    pub fn get_base_reg(&self) -> &'static str{
         match self {
             Arch::X86_64 => "rax",
             Arch::AArch64 => "x0"
         }
     }
     
-    pub fn get_arithmetic_regs(&self) -> (&str, &str, &str){
+    /// The operands are popped into the first two registers, which AArch64 instructions read before writing `x0`.
+    pub fn get_arithmetic_regs(&self) -> (&'static str, &'static str, &'static str){
         match self {
             Arch::X86_64 => ("rax", "rbx", "rax"),
-            Arch::AArch64 => ("x0", "x1", "x0"),
+            Arch::AArch64 => ("x1", "x2", "x0"),
         }
     }
 
-    pub fn get_exponentiation_regs(&self) -> (&str, &str, &str){
+    pub fn get_exponentiation_regs(&self) -> (&'static str, &'static str, &'static str){
         match self{
             Arch::X86_64 => ("rcx", "rdx", "rax"),
             Arch::AArch64 => ("x1", "x2", "x0"),
         }
     }
 
-    pub fn get_modulo_reg(&self) -> &str {
+    /// The shift count is in `rcx`, as x86_64 shifts by a register only take their count from `cl`.
+    pub fn get_shift_regs(&self) -> (&'static str, &'static str, &'static str){
+        match self {
+            Arch::X86_64 => ("rax", "rcx", "rax"),
+            Arch::AArch64 => ("x1", "x2", "x0"),
+        }
+    }
+
+    pub fn get_modulo_reg(&self) -> &'static str {
         match self {
             Arch::X86_64 => "rdx",
             Arch::AArch64 => "x0",
@@ -0,0 +1,27 @@
+/// How the program is linked, which decides its entry point.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LinkMode {
+    /// The program starts at `_start` and only uses system calls, without any library.
+    #[default]
+    Freestanding,
+    /// The program is a C `main` returning its exit code, linked with the C library through the C compiler,
+    /// so that it can call the C functions declared with `extern fn`.
+    Libc
+}
+
+impl LinkMode {
+    pub fn from_name(name: &str) -> Option<LinkMode> {
+        match name {
+            "freestanding" => Some(LinkMode::Freestanding),
+            "libc" => Some(LinkMode::Libc),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LinkMode::Freestanding => "freestanding",
+            LinkMode::Libc => "libc",
+        }
+    }
+}
\ No newline at end of file
@@ -1,5 +1,9 @@
 mod arch;
+mod link_mode;
 mod os;
+mod target;
 
 pub use arch::{Arch, TARGET_ARCH};
-pub use os::{OS, TARGET_OS};
\ No newline at end of file
+pub use link_mode::LinkMode;
+pub use os::{OS, TARGET_OS};
+pub use target::Target;
\ No newline at end of file
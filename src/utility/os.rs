@@ -7,10 +7,28 @@ pub const TARGET_OS: OS = OS::MacOS;
 #[cfg(target_os = "windows")]
 pub const TARGET_OS: OS = OS::Windows;
 
-#[allow(dead_code)] // Only one arch will be used, so it's an expected behaviour
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum OS {
     Linux,
     MacOS,
     Windows
+}
+
+impl OS {
+    pub fn from_name(name: &str) -> Option<OS> {
+        match name {
+            "linux" => Some(OS::Linux),
+            "macos" => Some(OS::MacOS),
+            "windows" => Some(OS::Windows),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OS::Linux => "linux",
+            OS::MacOS => "macos",
+            OS::Windows => "windows",
+        }
+    }
 }
\ No newline at end of file
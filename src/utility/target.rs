@@ -0,0 +1,61 @@
+use std::fmt;
+use super::{Arch, OS, TARGET_ARCH, TARGET_OS};
+
+/// Platform the generated code runs on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Target {
+    pub m_arch: Arch,
+    pub m_os: OS
+}
+
+impl Target {
+    pub fn new(m_arch: Arch, m_os: OS) -> Self {
+        Target { m_arch, m_os }
+    }
+
+    /// The platform the compiler is running on.
+    pub fn host() -> Self {
+        Target::new(TARGET_ARCH, TARGET_OS)
+    }
+
+    /// Parses a target written as `<arch>-<os>`, for example `x86_64-linux` or `aarch64-macos`.
+    pub fn from_name(name: &str) -> Option<Target> {
+        let (arch, os) = name.split_once('-')?;
+        Some(Target::new(Arch::from_name(arch)?, OS::from_name(os)?))
+    }
+}
+
+impl Default for Target {
+    fn default() -> Self {
+        Target::host()
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.m_arch.name(), self.m_os.name())
+    }
+}
+
+
+
+#[cfg(test)]
+mod test_target{
+    use super::*;
+
+    #[test]
+    fn test_from_name(){
+        assert_eq!(Target::from_name("x86_64-linux"), Some(Target::new(Arch::X86_64, OS::Linux)));
+        assert_eq!(Target::from_name("aarch64-macos"), Some(Target::new(Arch::AArch64, OS::MacOS)));
+        assert_eq!(Target::from_name("x86_64-windows"), Some(Target::new(Arch::X86_64, OS::Windows)));
+        assert!(Target::from_name("riscv64-linux").is_none());
+        assert!(Target::from_name("x86_64").is_none());
+    }
+
+    #[test]
+    fn test_display(){
+        for name in ["x86_64-linux", "aarch64-macos", "aarch64-windows"] {
+            assert_eq!(Target::from_name(name).unwrap().to_string(), name);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,474 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use brs::compiler::evaluator::Evaluator;
+use brs::compiler::parser::AstJson;
+use brs::{Compiler, OutputKind};
+
+/// Differential testing: random well-typed programs are evaluated by the reference `Evaluator` and
+/// compiled and run natively, and the exit statuses must match. The number of programs and the seed can
+/// be changed with `BRS_DIFF_CASES` and `BRS_DIFF_SEED`.
+const DEFAULT_CASES: usize = 40;
+const DEFAULT_SEED: u64 = 0x5EED_B125;
+
+/// Xorshift generator, so that a failing seed always gives the same programs.
+struct Rng {
+    m_state: u64
+}
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Rng { m_state: seed.max(1) }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.m_state ^= self.m_state << 13;
+        self.m_state ^= self.m_state >> 7;
+        self.m_state ^= self.m_state << 17;
+        self.m_state
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+
+    fn chance(&mut self, percent: u64) -> bool {
+        self.below(100) < percent
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Num(i64),
+    Bool(bool),
+    Var(String),
+    Unary(&'static str, Box<Expr>),
+    Operation(Box<Expr>, &'static str, Box<Expr>),
+    Cast(Box<Expr>, &'static str),
+    /// Elements of an array literal, only used as the value of a declaration.
+    Array(Vec<Expr>),
+    Index(String, usize),
+    Len(String)
+}
+
+#[derive(Clone, Debug)]
+enum Stmt {
+    /// Keyword, name, type annotation and value.
+    Declare(&'static str, String, Option<&'static str>, Expr),
+    Assign(String, Expr),
+    Compound(String, &'static str, Expr),
+    Element(String, usize, Expr),
+    Scope(Vec<Stmt>),
+    /// Loop variable, start, end, whether the end is included and body.
+    For(String, i64, i64, bool, Vec<Stmt>),
+    Exit(Expr)
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Num(value) => write!(f, "{value}"),
+            Expr::Bool(value) => write!(f, "{value}"),
+            Expr::Var(name) => write!(f, "{name}"),
+            Expr::Unary(op, operand) => write!(f, "({op}{operand})"),
+            Expr::Operation(lhs, op, rhs) => write!(f, "({lhs} {op} {rhs})"),
+            Expr::Cast(operand, result_type) => write!(f, "({operand} as {result_type})"),
+            Expr::Array(elements) => write!(f, "[{}]", elements.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")),
+            Expr::Index(name, index) => write!(f, "{name}[{index}]"),
+            Expr::Len(name) => write!(f, "len({name})"),
+        }
+    }
+}
+
+fn render(stmts: &[Stmt]) -> String {
+    let mut lines = Vec::new();
+    render_stmts(stmts, &mut lines);
+    lines.join("\n")
+}
+
+fn render_stmts(stmts: &[Stmt], lines: &mut Vec<String>) {
+    for stmt in stmts {
+        match stmt {
+            Stmt::Declare(keyword, name, None, value) => lines.push(format!("{keyword} {name} = {value}")),
+            Stmt::Declare(keyword, name, Some(annotation), value) => lines.push(format!("{keyword} {name}: {annotation} = {value}")),
+            Stmt::Assign(name, value) => lines.push(format!("{name} = {value}")),
+            Stmt::Compound(name, op, value) => lines.push(format!("{name} {op}= {value}")),
+            Stmt::Element(name, index, value) => lines.push(format!("{name}[{index}] = {value}")),
+            Stmt::Scope(stmts) => {
+                lines.push("{".to_string());
+                render_stmts(stmts, lines);
+                lines.push("}".to_string());
+            }
+            Stmt::For(name, start, end, inclusive, stmts) => {
+                lines.push(format!("for {name} in {start}{}{end} {{", if *inclusive { "..=" } else { ".." }));
+                render_stmts(stmts, lines);
+                lines.push("}".to_string());
+            }
+            Stmt::Exit(value) => lines.push(format!("exit({value})")),
+        }
+    }
+}
+
+/// A variable visible while generating.
+struct Variable {
+    m_name: String,
+    m_is_bool: bool,
+    m_keyword: &'static str,
+    /// Length of the integer arrays.
+    m_length: Option<usize>
+}
+
+/// Values where the native arithmetic is most likely to differ from the evaluator.
+const BOUNDARY_VALUES: [i64; 8] = [i64::MIN, i64::MIN + 1, i64::MAX, -1, -64, 63, 64, 0];
+
+/// Builds programs that pass the type checker and the linter: numeric and logical operations only mix through casts,
+/// variables are only read in the scopes where they are visible, only `var`s are assigned, the values
+/// of the constants only read other constants and arrays are only indexed within their bounds.
+struct Generator {
+    m_rng: Rng,
+    m_scopes: Vec<Vec<Variable>>,
+    m_variable_count: usize,
+    m_constant_only: bool
+}
+
+impl Generator {
+    fn program(&mut self) -> Vec<Stmt> {
+        self.m_scopes = vec![Vec::new()];
+        self.m_variable_count = 0;
+        let mut stmts = self.stmts(0);
+        let is_bool = self.m_rng.chance(20);
+        stmts.push(Stmt::Exit(self.expr(is_bool, 3)));
+        stmts
+    }
+
+    fn stmts(&mut self, depth: usize) -> Vec<Stmt> {
+        let count = self.m_rng.below(5) as usize;
+        (0..count).map(|_| self.stmt(depth)).collect()
+    }
+
+    fn stmt(&mut self, depth: usize) -> Stmt {
+        match self.m_rng.below(10) {
+            0 | 1 if depth < 2 => {
+                self.m_scopes.push(Vec::new());
+                let stmts = self.stmts(depth + 1);
+                self.m_scopes.pop();
+                Stmt::Scope(stmts)
+            }
+            2 if depth > 0 => Stmt::Exit(self.expr(false, 3)),
+            3 if depth < 2 => {
+                // A few iterations are enough, the bodies can be nested
+                let name = self.name();
+                let (start, end) = (self.m_rng.below(3) as i64, self.m_rng.below(5) as i64);
+                self.m_scopes.push(vec![Variable { m_name: name.clone(), m_is_bool: false, m_keyword: "let", m_length: None }]);
+                let stmts = self.stmts(depth + 1);
+                self.m_scopes.pop();
+                Stmt::For(name, start, end, self.m_rng.chance(50), stmts)
+            }
+            4 if !self.mutable(false).is_empty() => {
+                let variables = self.mutable(false);
+                let name = variables[self.m_rng.below(variables.len() as u64) as usize].clone();
+                let op = ["+", "-", "*", "//", "%", "&", "|", "^", "<<", ">>"][self.m_rng.below(10) as usize];
+                let value = if matches!(op, "//" | "%") { self.divisor() } else { self.expr(false, 2) };
+                Stmt::Compound(name, op, value)
+            }
+            6 if !self.arrays(&["var"]).is_empty() => {
+                let arrays = self.arrays(&["var"]);
+                let (name, length) = arrays[self.m_rng.below(arrays.len() as u64) as usize].clone();
+                Stmt::Element(name, self.m_rng.below(length as u64) as usize, self.expr(false, 3))
+            }
+            5 => {
+                let is_bool = self.m_rng.chance(25);
+                let variables = self.mutable(is_bool);
+                if variables.is_empty() {
+                    return self.declaration(depth);
+                }
+                let name = variables[self.m_rng.below(variables.len() as u64) as usize].clone();
+                Stmt::Assign(name, self.expr(is_bool, 3))
+            }
+            _ => self.declaration(depth),
+        }
+    }
+
+    fn declaration(&mut self, depth: usize) -> Stmt {
+        let is_bool = self.m_rng.chance(25);
+        let keyword = match self.m_rng.below(10) {
+            0 | 1 if depth == 0 => "const",
+            0..=4 => "var",
+            _ => "let",
+        };
+        if keyword != "const" && !is_bool && self.m_rng.chance(20) {
+            let length = 1 + self.m_rng.below(4) as usize;
+            let elements = (0..length).map(|_| self.expr(false, 2)).collect();
+            let name = self.name();
+            self.m_scopes.last_mut().unwrap().push(Variable { m_name: name.clone(), m_is_bool: false, m_keyword: keyword, m_length: Some(length) });
+            return Stmt::Declare(keyword, name, None, Expr::Array(elements));
+        }
+        self.m_constant_only = keyword == "const";
+        let value = self.expr(is_bool, 3);
+        self.m_constant_only = false;
+        let name = self.name();
+        self.m_scopes.last_mut().unwrap().push(Variable { m_name: name.clone(), m_is_bool: is_bool, m_keyword: keyword, m_length: None });
+        let annotation = self.m_rng.chance(30).then_some(if is_bool { "bool" } else { "int" });
+        Stmt::Declare(keyword, name, annotation, value)
+    }
+
+    fn name(&mut self) -> String {
+        self.m_variable_count += 1;
+        format!("v{}", self.m_variable_count - 1)
+    }
+
+    fn variables(&self, is_bool: bool, keywords: &[&str]) -> Vec<String> {
+        self.m_scopes.iter().flatten()
+            .filter(|variable| variable.m_is_bool == is_bool && variable.m_length.is_none() && keywords.contains(&variable.m_keyword))
+            .map(|variable| variable.m_name.clone())
+            .collect()
+    }
+
+    /// Arrays that can be read, none while generating the value of a constant, with their length.
+    fn arrays(&self, keywords: &[&str]) -> Vec<(String, usize)> {
+        if self.m_constant_only {
+            return Vec::new();
+        }
+        self.m_scopes.iter().flatten()
+            .filter(|variable| keywords.contains(&variable.m_keyword))
+            .filter_map(|variable| Some((variable.m_name.clone(), variable.m_length?)))
+            .collect()
+    }
+
+    /// Variables that can be read, only the constants while generating the value of a constant.
+    fn visible(&self, is_bool: bool) -> Vec<String> {
+        if self.m_constant_only { self.variables(is_bool, &["const"]) } else { self.variables(is_bool, &["let", "var", "const"]) }
+    }
+
+    fn mutable(&self, is_bool: bool) -> Vec<String> {
+        self.variables(is_bool, &["var"])
+    }
+
+    fn expr(&mut self, is_bool: bool, depth: usize) -> Expr {
+        if depth == 0 || self.m_rng.chance(30) {
+            return self.leaf(is_bool);
+        }
+        if is_bool {
+            return self.logical(depth);
+        }
+        match self.m_rng.below(10) {
+            0 => Expr::Operation(Box::new(self.expr(false, depth - 1)), "-", Box::new(self.expr(false, depth - 1))),
+            1 => Expr::Operation(Box::new(self.expr(false, depth - 1)), "*", Box::new(self.expr(false, depth - 1))),
+            // A negative exponent is a runtime error, the exponent is never negative
+            2 => Expr::Operation(Box::new(self.expr(false, depth - 1)), "**", Box::new(Expr::Num(self.m_rng.below(4) as i64))),
+            3 => {
+                let op = if self.m_rng.chance(50) { "//" } else { "%" };
+                Expr::Operation(Box::new(self.expr(false, depth - 1)), op, Box::new(self.divisor()))
+            }
+            4 => {
+                let op = ["&", "|", "^", "<<", ">>"][self.m_rng.below(5) as usize];
+                Expr::Operation(Box::new(self.expr(false, depth - 1)), op, Box::new(self.expr(false, depth - 1)))
+            }
+            5 => {
+                let op = if self.m_rng.chance(50) { "~" } else { "-" };
+                Expr::Unary(op, Box::new(self.expr(false, depth - 1)))
+            }
+            6 => Expr::Cast(Box::new(self.logical(depth - 1)), "int"),
+            _ => Expr::Operation(Box::new(self.expr(false, depth - 1)), "+", Box::new(self.expr(false, depth - 1))),
+        }
+    }
+
+    /// The operands of a logical operation are booleans: literals, variables, logical operations or casts.
+    fn logical(&mut self, depth: usize) -> Expr {
+        if depth == 0 || self.m_rng.chance(30) {
+            return self.leaf(true);
+        }
+        if self.m_rng.chance(20) {
+            return Expr::Unary("!!", Box::new(self.logical(depth - 1)));
+        }
+        if self.m_rng.chance(15) {
+            return Expr::Cast(Box::new(self.expr(false, depth - 1)), "bool");
+        }
+        let op = ["&&", "||", "^|"][self.m_rng.below(3) as usize];
+        Expr::Operation(Box::new(self.logical(depth - 1)), op, Box::new(self.logical(depth - 1)))
+    }
+
+    /// Division by zero is not what is tested here, the divisor is never 0, but it is often -1.
+    fn divisor(&mut self) -> Expr {
+        match self.m_rng.below(4) {
+            0 => Expr::Num(-1),
+            1 => Expr::Num(-1 - self.m_rng.below(9) as i64),
+            _ => Expr::Num(1 + self.m_rng.below(9) as i64),
+        }
+    }
+
+    fn leaf(&mut self, is_bool: bool) -> Expr {
+        let variables = self.visible(is_bool);
+        if !variables.is_empty() && self.m_rng.chance(40) {
+            return Expr::Var(variables[self.m_rng.below(variables.len() as u64) as usize].clone());
+        }
+        if is_bool {
+            return Expr::Bool(self.m_rng.chance(50));
+        }
+        let arrays = self.arrays(&["let", "var"]);
+        if !arrays.is_empty() && self.m_rng.chance(30) {
+            let (name, length) = arrays[self.m_rng.below(arrays.len() as u64) as usize].clone();
+            return if self.m_rng.chance(20) { Expr::Len(name) } else { Expr::Index(name, self.m_rng.below(length as u64) as usize) };
+        }
+        match self.m_rng.below(10) {
+            0 => Expr::Num(BOUNDARY_VALUES[self.m_rng.below(BOUNDARY_VALUES.len() as u64) as usize]),
+            1 | 2 => Expr::Num(-(self.m_rng.below(20) as i64)),
+            _ => Expr::Num(self.m_rng.below(20) as i64),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    /// The program is not valid, or the evaluator rejects it
+    Invalid,
+    Match,
+    Mismatch { m_expected: i64, m_status: i32 },
+    CompileError(String)
+}
+
+impl Outcome {
+    fn is_failure(&self) -> bool {
+        matches!(self, Outcome::Mismatch { .. } | Outcome::CompileError(_))
+    }
+}
+
+fn check(source: &str, out_dir: &Path) -> Outcome {
+    let mut compiler = Compiler::new();
+    compiler.set_output_kind(OutputKind::AstJson);
+    let Ok(json) = compiler.compile("diff.brs", source) else { return Outcome::Invalid };
+    let Ok(expected) = AstJson::read(&json.m_output).map_err(|_| ()).and_then(|prog| Evaluator::evaluate(&prog).map_err(|_| ())) else {
+        return Outcome::Invalid
+    };
+
+    compiler.set_output_kind(OutputKind::Executable);
+    compiler.set_out_dir(out_dir);
+    let executable = match compiler.compile("diff.brs", source) {
+        Ok(artifact) => artifact.m_path.expect("Executables are written to a file"),
+        Err(diagnostics) => return Outcome::CompileError(diagnostics.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n")),
+    };
+    let status = Command::new(&executable).status().expect("The compiled program should run").code().unwrap_or(-1);
+    if i64::from(status) == expected & 0xFF { Outcome::Match } else { Outcome::Mismatch { m_expected: expected, m_status: status } }
+}
+
+/// Every program one step smaller than the given one: a statement removed, an operation replaced
+/// by one of its operands or a literal made smaller.
+fn shrink(stmts: &[Stmt]) -> Vec<Vec<Stmt>> {
+    let mut candidates = Vec::new();
+    for (i, stmt) in stmts.iter().enumerate() {
+        let mut without = stmts.to_vec();
+        without.remove(i);
+        candidates.push(without);
+        let replacements: Vec<Stmt> = match stmt {
+            Stmt::Declare(keyword, name, annotation, value) => {
+                shrink_expr(value).into_iter().map(|value| Stmt::Declare(keyword, name.clone(), *annotation, value)).collect()
+            }
+            Stmt::Assign(name, value) => shrink_expr(value).into_iter().map(|value| Stmt::Assign(name.clone(), value)).collect(),
+            Stmt::Compound(name, op, value) => shrink_expr(value).into_iter().map(|value| Stmt::Compound(name.clone(), op, value)).collect(),
+            Stmt::Element(name, index, value) => shrink_expr(value).into_iter().map(|value| Stmt::Element(name.clone(), *index, value)).collect(),
+            Stmt::Scope(inner) => shrink(inner).into_iter().map(Stmt::Scope).collect(),
+            Stmt::For(name, start, end, inclusive, inner) => {
+                shrink(inner).into_iter().map(|inner| Stmt::For(name.clone(), *start, *end, *inclusive, inner)).collect()
+            }
+            Stmt::Exit(value) => shrink_expr(value).into_iter().map(Stmt::Exit).collect(),
+        };
+        for replacement in replacements {
+            let mut candidate = stmts.to_vec();
+            candidate[i] = replacement;
+            candidates.push(candidate);
+        }
+    }
+    candidates
+}
+
+fn shrink_expr(expr: &Expr) -> Vec<Expr> {
+    match expr {
+        Expr::Num(value) => {
+            let mut smaller = vec![0, 1, -1, value / 2];
+            smaller.dedup();
+            smaller.into_iter().filter(|smaller| smaller.unsigned_abs() < value.unsigned_abs()).map(Expr::Num).collect()
+        }
+        Expr::Operation(lhs, op, rhs) => {
+            let mut candidates = vec![(**lhs).clone(), (**rhs).clone()];
+            candidates.extend(shrink_expr(lhs).into_iter().map(|lhs| Expr::Operation(Box::new(lhs), op, rhs.clone())));
+            candidates.extend(shrink_expr(rhs).into_iter().map(|rhs| Expr::Operation(lhs.clone(), op, Box::new(rhs))));
+            candidates
+        }
+        Expr::Unary(op, operand) => {
+            let mut candidates = vec![(**operand).clone()];
+            candidates.extend(shrink_expr(operand).into_iter().map(|operand| Expr::Unary(op, Box::new(operand))));
+            candidates
+        }
+        // The operand of a cast has another type, so it can't replace it
+        Expr::Cast(operand, result_type) => shrink_expr(operand).into_iter().map(|operand| Expr::Cast(Box::new(operand), result_type)).collect(),
+        // The length of an array is part of its type, the elements are shrunk in place
+        Expr::Array(elements) => (0..elements.len())
+            .flat_map(|i| shrink_expr(&elements[i]).into_iter().map(move |element| (i, element)))
+            .map(|(i, element)| {
+                let mut elements = elements.clone();
+                elements[i] = element;
+                Expr::Array(elements)
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Greedily applies the first shrink that keeps the same kind of failure, until none does.
+fn minimise(mut stmts: Vec<Stmt>, failure: &Outcome, out_dir: &Path) -> (Vec<Stmt>, Outcome) {
+    let mut outcome = check(&render(&stmts), out_dir);
+    'shrinking: loop {
+        for candidate in shrink(&stmts) {
+            let candidate_outcome = check(&render(&candidate), out_dir);
+            if std::mem::discriminant(&candidate_outcome) == std::mem::discriminant(failure) {
+                stmts = candidate;
+                outcome = candidate_outcome;
+                continue 'shrinking;
+            }
+        }
+        return (stmts, outcome);
+    }
+}
+
+/// The executables can only be built and run when the assembler and the linker of the host are installed.
+fn toolchain_available(out_dir: &Path) -> bool {
+    let mut compiler = Compiler::new();
+    compiler.set_output_kind(OutputKind::Executable);
+    compiler.set_out_dir(out_dir);
+    compiler.compile("toolchain.brs", "exit(0)").ok()
+        .and_then(|artifact| artifact.m_path)
+        .is_some_and(|executable| Command::new(executable).status().is_ok_and(|status| status.success()))
+}
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name).ok().and_then(|value| value.parse().ok()).unwrap_or(default)
+}
+
+#[test]
+fn test_differential(){
+    let cases = env_or("BRS_DIFF_CASES", DEFAULT_CASES);
+    let seed = env_or("BRS_DIFF_SEED", DEFAULT_SEED);
+    let out_dir = std::env::temp_dir().join(format!("brs_differential_{}", std::process::id()));
+    fs::create_dir_all(&out_dir).unwrap();
+    if !toolchain_available(&out_dir) {
+        fs::remove_dir_all(&out_dir).ok();
+        eprintln!("Skipping the differential test, the programs can't be built or run on this host.");
+        return;
+    }
+    let mut generator = Generator { m_rng: Rng::new(seed), m_scopes: Vec::new(), m_variable_count: 0, m_constant_only: false };
+
+    let mut failure = None;
+    for case in 0..cases {
+        let program = generator.program();
+        let outcome = check(&render(&program), &out_dir);
+        if outcome.is_failure() {
+            failure = Some((case, minimise(program, &outcome, &out_dir)));
+            break;
+        }
+    }
+    fs::remove_dir_all(&out_dir).ok();
+    if let Some((case, (program, outcome))) = failure {
+        panic!("Program {case} of seed {seed} differs from the evaluator ({outcome:?}), minimised to:\n{}", render(&program));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,46 @@
+use brs::{Compiler, OutputKind};
+use proptest::prelude::*;
+
+/// Inputs that used to make the compiler panic.
+const REGRESSIONS: [&str; 3] = [
+    // A variable read without being assigned had no stack slot in the generator
+    "exit(y)",
+    "{\nlet x = 1\n}\nexit(x)",
+    // `𐏈` is alphabetic, so it is an identifier and not a number
+    "𝒞=𐏈",
+];
+
+/// Pieces of the grammar, so that the generated inputs get past the tokenizer more often than random text does.
+const FRAGMENTS: [&str; 66] = [
+    "x", "y", "1", "42", "0x1F", "0b12", "true", "false", "exit", "import", "let ", "var ", "const ", ": int", ": bool", " as int", " as bool", "\"lib.brs\"",
+    "(", ")", "{", "}", "[", "]", ", ", "; 3", "len(", "=", "+", "-", "**", "//", "%", "&&", "!!", "&", "|", "^", "~", "<<", ">>", "+=", "//=", "<<=", "#![deny(shadowing)]", "struct ", ".", "P { x: 1 }", "enum ", "match ", "::", "=>", "_", "for ", " in ", "..", "..=", " step 2", "alloc(", "free(", "args()", "arg(", "read_line()", "parse_int(", " ", "\n",
+];
+
+/// Compiles the input to assembly: it must either succeed or fail with at least one error, and never panic.
+fn check(input: &str) -> Result<(), TestCaseError> {
+    let mut compiler = Compiler::new();
+    compiler.set_output_kind(OutputKind::Assembly);
+    if let Err(diagnostics) = compiler.compile("fuzz.brs", input) {
+        prop_assert!(diagnostics.iter().any(|diagnostic| diagnostic.is_error()), "Compilation of {input:?} failed without an error");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_regressions(){
+    for input in REGRESSIONS {
+        check(input).unwrap();
+    }
+}
+
+proptest! {
+    #[test]
+    fn test_any_text(input in "\\PC*") {
+        check(&input)?;
+    }
+
+    #[test]
+    fn test_grammar_fragments(fragments in prop::collection::vec(prop::sample::select(&FRAGMENTS[..]), 0..40)) {
+        check(&fragments.concat())?;
+    }
+}
\ No newline at end of file
@@ -0,0 +1,183 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use brs::{Compiler, Diagnostic, OutputKind};
+
+/// Expected behavior of a golden file, read from the `//@` annotations at its top:
+/// ```text
+/// //@ flags: -O1 -D shadowing
+/// //@ args: 7 input.txt
+/// //@ stdin: first line
+/// //@ exit: 42
+/// //@ stdout: first line
+/// //@ stderr: Runtime error: integer overflow
+/// //@ error: E0003 at 2:1..2:7
+/// //@ warning: unused_variables
+/// ```
+/// Errors are always checked, warnings only when at least one is annotated. Without any error annotation
+/// the program must compile, and is then run with the arguments and the lines of input to check its exit status
+/// and outputs. Without a toolchain to build the programs, they are only compiled to assembly and not run.
+#[derive(Default)]
+struct Expectation {
+    m_flags: Vec<String>,
+    m_args: Vec<String>,
+    m_stdin: String,
+    m_exit: i32,
+    m_stdout: Vec<String>,
+    m_stderr: Vec<String>,
+    m_errors: Vec<String>,
+    m_warnings: Option<Vec<String>>
+}
+
+/// A golden file: its annotations and its source, where the annotation lines are replaced by spaces
+/// so that the spans of the diagnostics are the same as in the file.
+struct Case {
+    m_path: PathBuf,
+    m_source: String,
+    m_expectation: Expectation
+}
+
+impl Case {
+    fn load(path: &Path) -> Result<Case, String> {
+        let text = fs::read_to_string(path).map_err(|err| format!("Cannot read the file: {err}"))?;
+        let mut expectation = Expectation::default();
+        let mut source = String::new();
+        for line in text.split_inclusive('\n') {
+            let Some(annotation) = line.trim_end().strip_prefix("//@") else {
+                source.push_str(line);
+                continue;
+            };
+            source.push_str(&line.replace(|c: char| c != '\n', " "));
+            let (key, value) = annotation.split_once(':').ok_or(format!("Malformed annotation `{}`", line.trim_end()))?;
+            let value = value.trim();
+            match key.trim() {
+                "flags" => expectation.m_flags.extend(value.split_whitespace().map(str::to_string)),
+                "args" => expectation.m_args.extend(value.split_whitespace().map(str::to_string)),
+                "stdin" => expectation.m_stdin.push_str(&format!("{value}\n")),
+                "exit" => expectation.m_exit = value.parse().map_err(|_| format!("Invalid exit status `{value}`"))?,
+                "stdout" => expectation.m_stdout.push(value.to_string()),
+                "stderr" => expectation.m_stderr.push(value.to_string()),
+                "error" => expectation.m_errors.push(value.to_string()),
+                "warning" => expectation.m_warnings.get_or_insert_with(Vec::new).push(value.to_string()),
+                key => return Err(format!("Unknown annotation `{key}`")),
+            }
+        }
+        Ok(Case { m_path: path.to_path_buf(), m_source: source, m_expectation: expectation })
+    }
+
+    /// Compiles and, when `executes` is set, runs the case, returning a description of every mismatch.
+    fn run(&self, out_dir: &Path, executes: bool) -> Result<(), String> {
+        let expectation = &self.m_expectation;
+        let mut compiler = Compiler::new();
+        compiler.set_output_kind(if executes { OutputKind::Executable } else { OutputKind::Assembly });
+        compiler.set_out_dir(out_dir);
+        compiler.apply_args(&expectation.m_flags)?;
+
+        let (diagnostics, executable) = match compiler.compile(self.m_path.to_str().unwrap(), &self.m_source) {
+            Ok(artifact) => (artifact.m_warnings, artifact.m_path),
+            Err(diagnostics) => (diagnostics, None),
+        };
+        let (errors, warnings): (Vec<Diagnostic>, Vec<Diagnostic>) = diagnostics.into_iter().partition(Diagnostic::is_error);
+        let mut report = String::new();
+        report.push_str(&diff("errors", &expectation.m_errors, &format_diagnostics(&errors, &expectation.m_errors)));
+        if let Some(expected_warnings) = &expectation.m_warnings {
+            report.push_str(&diff("warnings", expected_warnings, &format_diagnostics(&warnings, expected_warnings)));
+        }
+
+        if let Some(executable) = executable.filter(|_| expectation.m_errors.is_empty()) {
+            let mut child = Command::new(&executable).args(&expectation.m_args)
+                .stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped())
+                .spawn().map_err(|err| format!("Cannot run the program: {err}"))?;
+            // The program may stop before reading all of its input
+            let _ = child.stdin.take().unwrap().write_all(expectation.m_stdin.as_bytes());
+            let output = child.wait_with_output().map_err(|err| format!("Cannot run the program: {err}"))?;
+            let status = output.status.code().unwrap_or(-1);
+            if status != expectation.m_exit {
+                report.push_str(&format!("exit status: expected {}, got {status}\n", expectation.m_exit));
+            }
+            let stdout: Vec<String> = String::from_utf8_lossy(&output.stdout).lines().map(str::to_string).collect();
+            report.push_str(&diff("stdout", &expectation.m_stdout, &stdout));
+            // Source locations are written relative to the golden directory
+            let golden_dir = format!("{}/", self.m_path.parent().unwrap().display());
+            let stderr: Vec<String> = String::from_utf8_lossy(&output.stderr).lines().map(|line| line.replace(&golden_dir, "")).collect();
+            report.push_str(&diff("stderr", &expectation.m_stderr, &stderr));
+        }
+        if report.is_empty() { Ok(()) } else { Err(report) }
+    }
+}
+
+/// Writes the diagnostics like their annotations: `E0003 at 2:1..2:7`. When the matching annotation has
+/// no span, the span is left out as well. Diagnostics without a code, like the failures of the toolchain, are
+/// written with their message.
+fn format_diagnostics(diagnostics: &[Diagnostic], expected: &[String]) -> Vec<String> {
+    diagnostics.iter().enumerate().map(|(i, diagnostic)| {
+        let code = diagnostic.m_code.map_or_else(|| format!("? {}", diagnostic.m_message), str::to_string);
+        let with_span = expected.get(i).is_none_or(|annotation| annotation.contains(" at "));
+        match (diagnostic.get_location(), diagnostic.get_end_location()) {
+            (Some((line, col)), Some((end_line, end_col))) if with_span => format!("{code} at {line}:{col}..{end_line}:{end_col}"),
+            _ => code.to_string(),
+        }
+    }).collect()
+}
+
+/// The executables can only be built and run when the assembler and the linker of the host are installed.
+fn toolchain_available(out_dir: &Path) -> bool {
+    let mut compiler = Compiler::new();
+    compiler.set_output_kind(OutputKind::Executable);
+    compiler.set_out_dir(out_dir);
+    compiler.compile("toolchain.brs", "exit(0)").ok()
+        .and_then(|artifact| artifact.m_path)
+        .is_some_and(|executable| Command::new(executable).status().is_ok_and(|status| status.success()))
+}
+
+/// Line by line comparison, with the expected lines prefixed by `-` and the actual ones by `+`.
+fn diff(name: &str, expected: &[String], actual: &[String]) -> String {
+    if expected == actual {
+        return String::new();
+    }
+    let mut report = format!("{name}:\n");
+    for i in 0..expected.len().max(actual.len()) {
+        match (expected.get(i), actual.get(i)) {
+            (Some(expected), Some(actual)) if expected == actual => report.push_str(&format!("    {expected}\n")),
+            (expected, actual) => {
+                if let Some(expected) = expected {
+                    report.push_str(&format!("  - {expected}\n"));
+                }
+                if let Some(actual) = actual {
+                    report.push_str(&format!("  + {actual}\n"));
+                }
+            }
+        }
+    }
+    report
+}
+
+#[test]
+fn test_golden_files(){
+    let golden_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let out_dir = std::env::temp_dir().join(format!("brs_golden_{}", std::process::id()));
+    // Files in subdirectories are only imported by the cases
+    let mut paths: Vec<PathBuf> = fs::read_dir(&golden_dir).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "brs"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "No golden file found in {}", golden_dir.display());
+    fs::create_dir_all(&out_dir).unwrap();
+    let executes = toolchain_available(&out_dir);
+    if !executes {
+        eprintln!("The golden programs are not run, they can't be built or run on this host.");
+    }
+
+    let mut failures = Vec::new();
+    for path in &paths {
+        let case_dir = out_dir.join(path.file_stem().unwrap());
+        fs::create_dir_all(&case_dir).unwrap();
+        if let Err(report) = Case::load(path).and_then(|case| case.run(&case_dir, executes)) {
+            failures.push(format!("--- {}\n{report}", path.file_name().unwrap().to_str().unwrap()));
+        }
+    }
+    fs::remove_dir_all(&out_dir).ok();
+    assert!(failures.is_empty(), "{} of {} golden files failed:\n{}", failures.len(), paths.len(), failures.join("\n"));
+}
\ No newline at end of file
@@ -0,0 +1,132 @@
+use brs::compiler::{Lint, LintConfig, LintLevel};
+use brs::compiler::generator::Generator;
+use brs::compiler::parser::{Either, NodeArithmeticExpr, NodeArithmeticOperation, NodeBaseExpr, NodeExit, NodeProgram, NodeStmt, ResultType};
+use brs::compiler::span::Span;
+use brs::compiler::tokenizer::{Operator, Token};
+use brs::{Compiler, LinkMode, OptLevel, OutputKind, Severity, Target};
+
+fn assembly_compiler() -> Compiler {
+    let mut compiler = Compiler::new();
+    compiler.set_output_kind(OutputKind::Assembly);
+    compiler
+}
+
+#[test]
+fn test_compile_to_assembly(){
+    let mut compiler = assembly_compiler();
+    let artifact = compiler.compile("main.brs", "let x = 1\nexit(x + 2)").expect("The program should compile");
+    assert_eq!(artifact.m_kind, OutputKind::Assembly);
+    assert!(artifact.m_path.is_none());
+    assert!(artifact.m_warnings.is_empty());
+    assert!(artifact.m_output.contains("_start"));
+}
+
+#[test]
+fn test_compile_errors(){
+    let mut compiler = assembly_compiler();
+    let diagnostics = compiler.compile("main.brs", "let x = 1\nexit(x +)").unwrap_err();
+    assert!(!diagnostics.is_empty());
+    assert_eq!(diagnostics[0].m_severity, Severity::Error);
+    assert_eq!(diagnostics[0].get_location().map(|(line, _)| line), Some(2));
+
+    // Denied lints make the compilation fail
+    let mut lint_config = LintConfig::new();
+    lint_config.set_level(Lint::UnusedVariables, LintLevel::Deny);
+    compiler.set_lint_config(lint_config);
+    let diagnostics = compiler.compile("main.brs", "let x = 1\nexit(0)").unwrap_err();
+    assert!(diagnostics.iter().all(|diagnostic| diagnostic.is_error()));
+}
+
+#[test]
+fn test_compile_warnings(){
+    let mut compiler = assembly_compiler();
+    let artifact = compiler.compile("main.brs", "let x = 1\nexit(0)").expect("Warnings should not fail the compilation");
+    assert_eq!(artifact.m_warnings.len(), 1);
+    assert_eq!(artifact.m_warnings[0].m_severity, Severity::Warning);
+}
+
+#[test]
+fn test_compile_options(){
+    let mut compiler = assembly_compiler();
+    compiler.set_opt_level(OptLevel::O1);
+    let folded = compiler.compile("main.brs", "exit((3 + 5) * 2)").unwrap().m_output;
+    compiler.set_opt_level(OptLevel::O0);
+    let unfolded = compiler.compile("main.brs", "exit((3 + 5) * 2)").unwrap().m_output;
+    assert!(folded.len() < unfolded.len());
+
+    compiler.apply_args(&["--overflow-checks".to_string()]).unwrap();
+    assert!(compiler.compile("main.brs", "exit(1 + 2)").unwrap().m_output.contains("overflow_trap"));
+    assert!(compiler.apply_args(&["--unknown".to_string()]).is_err());
+
+    for target in ["x86_64-linux", "aarch64-linux", "x86_64-macos", "aarch64-windows"] {
+        compiler.set_target(Target::from_name(target).unwrap());
+        assert!(compiler.compile("main.brs", "exit(0)").is_ok(), "Compilation for {target} failed");
+    }
+}
+
+#[test]
+fn test_build_program(){
+    // Programs can be built and inspected without parsing any source
+    let span = Span::new(0, 0, 0);
+    let number = |value: &str| Either::Right(NodeBaseExpr::Num(Token::Number { value: value.to_string(), span }));
+    let sum = NodeArithmeticOperation { lhs: number("1"), rhs: number("2"), op: Operator::Plus { span }, result_type: ResultType::Numeric, span };
+    let exit = NodeExit { expr: NodeArithmeticExpr::Operation(sum), span };
+    let prog = NodeProgram { stmts: vec![NodeStmt::Exit(exit)], span };
+    assert_eq!(prog.to_string(), "exit(1 + 2)");
+    let NodeStmt::Exit(exit) = &prog.stmts[0] else { panic!("The program should exit") };
+    assert!(matches!(&exit.expr, NodeArithmeticExpr::Operation(operation) if operation.result_type == ResultType::Numeric));
+
+    let mut generator = Generator::new(prog);
+    generator.generate();
+    assert!(generator.get_out_assembly().contains("_start"));
+}
+
+#[test]
+fn test_link_mode(){
+    let source = "extern fn printf(int, ...)\nprintf(\"%d\\n\", 42)\nexit(0)";
+    let mut compiler = assembly_compiler();
+    compiler.set_target(Target::from_name("x86_64-linux").unwrap());
+    compiler.set_link_mode(LinkMode::Libc);
+    let assembly = compiler.compile("main.brs", source).unwrap().m_output;
+    assert!(assembly.starts_with("global main\nmain:\n"));
+    assert!(assembly.contains("extern printf") && assembly.contains("call printf wrt ..plt"));
+    assert!(assembly.contains("string0: db 37, 100, 10, 0"));
+
+    // C functions are only called once declared
+    assert!(compiler.compile("main.brs", "printf(1)\nexit(0)").is_err());
+    compiler.apply_args(&["--link=freestanding".to_string()]).unwrap();
+    assert!(compiler.compile("main.brs", "exit(0)").unwrap().m_output.contains("_start"));
+    let diagnostics = compiler.compile("main.brs", source).unwrap_err();
+    assert_eq!(diagnostics[0].m_code, Some("E0056"));
+    assert!(compiler.apply_args(&["--link=static".to_string()]).is_err());
+}
+
+#[test]
+fn test_ast_round_trip(){
+    let source = "let x = (1 + 2) * 3\n{\nexit(x)\n}";
+    let mut compiler = assembly_compiler();
+    let assembly = compiler.compile("main.brs", source).unwrap().m_output;
+
+    compiler.set_output_kind(OutputKind::AstJson);
+    let json = compiler.compile("main.brs", source).unwrap().m_output;
+    compiler.set_output_kind(OutputKind::AstSexpr);
+    assert!(compiler.compile("main.brs", source).unwrap().m_output.starts_with("(program @0:0..31"));
+
+    // Feeding the dump back gives the same code, without the source line markers
+    compiler.set_output_kind(OutputKind::Assembly);
+    let from_json = compiler.compile_ast("main.json", &json).unwrap().m_output;
+    let without_markers = |assembly: &str| assembly.lines().filter(|line| !line.contains("main.brs:")).collect::<Vec<_>>().join("\n");
+    assert_eq!(without_markers(&assembly), from_json.lines().collect::<Vec<_>>().join("\n"));
+    assert!(compiler.compile_ast("main.json", "{}").is_err());
+}
+
+#[test]
+fn test_ast_errors(){
+    let mut compiler = assembly_compiler();
+    let json = r#"{"kind": "Program", "stmts": [{"kind": "Exit", "expr": {"kind": "Variable", "name": "x"}}]}"#;
+    let diagnostics = compiler.compile_ast("main.json", json).unwrap_err();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].m_code, Some("E0040"));
+    assert!(diagnostics[0].m_message.starts_with("main.json: "));
+    assert!(diagnostics[0].m_span.is_none());
+}
\ No newline at end of file
@@ -29,11 +29,11 @@ fn test_successful_compilation(){
     fs::create_dir_all(output_folder).expect("Failed to create output folder");
     
     let source_code = r#"
-    x = ((3+5)*2 + (12//4))%7+(18//(6-3))*(2**3-4) + 10
-    y = true
-    z = true && false
+    let x = ((3+5)*2 + (12//4))%7+(18//(6-3))*(2**3-4) + 10
+    let y = true
+    let z = true && false
     {
-        x = 0
+        let x = 0
         exit(x)
     }
     exit(x)